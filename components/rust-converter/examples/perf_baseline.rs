@@ -1,20 +1,101 @@
+use std::alloc::{GlobalAlloc, Layout, System};
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::process::{self, Command};
 use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
 use std::time::Instant;
 
+use nginx_markdown_converter::compression::{self, CompressionEncoding};
 use nginx_markdown_converter::converter::{
     ConversionContext, ConversionOptions, MarkdownConverter, MarkdownFlavor,
 };
 use nginx_markdown_converter::etag_generator::ETagGenerator;
 use nginx_markdown_converter::ffi::{
     markdown_convert, markdown_converter_free, markdown_converter_new, markdown_result_free,
-    MarkdownConverterHandle, MarkdownOptions, MarkdownResult, ERROR_SUCCESS,
+    MarkdownOptions, MarkdownResult, ERROR_SUCCESS, MARKDOWN_ABI_VERSION, RENDERER_DEFAULT,
 };
 use nginx_markdown_converter::parser::parse_html_with_charset;
 use nginx_markdown_converter::token_estimator::TokenEstimator;
 
+// ============================================================================
+// Counting global allocator (--track-allocations)
+// ============================================================================
+//
+// There's no Cargo manifest in this workspace to hang a feature flag off
+// of, so the allocator is gated at runtime instead: a single `Relaxed`
+// atomic load per alloc/dealloc call when tracking is off, which is cheap
+// enough not to perturb the timing-only modes above. Only
+// `--track-allocations` flips it on and pays for the read-modify-write
+// atomics that make per-stage accounting possible.
+
+static TRACKING_ENABLED: AtomicBool = AtomicBool::new(false);
+static LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+static TOTAL_ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() && TRACKING_ENABLED.load(Ordering::Relaxed) {
+            TOTAL_ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+            let live = LIVE_BYTES.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK_BYTES.fetch_max(live, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        if TRACKING_ENABLED.load(Ordering::Relaxed) {
+            LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Bytes allocated and peak live-byte increase observed during one stage.
+#[derive(Default, Clone, Copy)]
+struct StageAlloc {
+    bytes_allocated_avg: usize,
+    peak_delta_bytes: usize,
+}
+
+/// Per-stage allocation accounting for one `run_breakdown` run, populated
+/// only when `--track-allocations` is passed.
+#[derive(Default, Clone)]
+struct AllocBreakdown {
+    parse: StageAlloc,
+    convert: StageAlloc,
+    etag: StageAlloc,
+    token: StageAlloc,
+    compress: StageAlloc,
+}
+
+/// Snapshot the allocator counters before a stage runs. Pair with
+/// [`end_stage_allocation`] to get that stage's `StageAlloc`.
+fn begin_stage_allocation() -> (usize, usize) {
+    let live_at_start = LIVE_BYTES.load(Ordering::Relaxed);
+    let total_at_start = TOTAL_ALLOCATED_BYTES.load(Ordering::Relaxed);
+    PEAK_BYTES.store(live_at_start, Ordering::Relaxed);
+    (live_at_start, total_at_start)
+}
+
+fn end_stage_allocation(live_at_start: usize, total_at_start: usize) -> StageAlloc {
+    let total_now = TOTAL_ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let peak_now = PEAK_BYTES.load(Ordering::Relaxed);
+    StageAlloc {
+        bytes_allocated_avg: total_now.saturating_sub(total_at_start),
+        peak_delta_bytes: peak_now.saturating_sub(live_at_start),
+    }
+}
+
 #[derive(Clone, Copy)]
 struct RunConfig {
     warmup: usize,
@@ -44,6 +125,8 @@ struct FfiSummary {
     html_bytes: usize,
     markdown_bytes_avg: usize,
     token_estimate_avg: u32,
+    compressed_bytes: usize,
+    compression_ratio: f64,
 }
 
 #[derive(Default, Clone)]
@@ -52,7 +135,11 @@ struct BreakdownSummary {
     convert_ms: f64,
     etag_ms: f64,
     token_ms: f64,
+    compress_ms: f64,
     total_ms: f64,
+    /// Per-stage allocation accounting, present only when `run_breakdown`
+    /// was called with `track_allocations: true`.
+    alloc: Option<AllocBreakdown>,
 }
 
 fn percentile_ms(sorted: &[f64], p: f64) -> f64 {
@@ -148,22 +235,45 @@ fn build_samples() -> Vec<Sample> {
     ]
 }
 
-fn run_ffi_baseline(sample: &Sample, cfg: RunConfig) -> FfiSummary {
+fn run_ffi_baseline(name: &str, html: &[u8], cfg: RunConfig) -> FfiSummary {
     let content_type = b"text/html; charset=UTF-8";
     let options = MarkdownOptions {
+        abi_version: MARKDOWN_ABI_VERSION,
         flavor: 0,
         timeout_ms: 5000,
+        max_input_bytes: 0,
+        max_output_bytes: 0,
+        max_decompressed_bytes: 0,
         generate_etag: 1,
         estimate_tokens: 1,
         front_matter: 0,
+        preserve_tables: 1,
+        generate_toc: 0,
+        heading_anchors: 0,
+        renderer: RENDERER_DEFAULT,
+        heading_offset: 0,
+        extensions: 0,
         content_type: content_type.as_ptr(),
         content_type_len: content_type.len(),
         base_url: ptr::null(),
         base_url_len: 0,
+        accept_encoding: ptr::null(),
+        accept_encoding_len: 0,
+        input_charset: ptr::null(),
+        input_charset_len: 0,
+        content_encoding: ptr::null(),
+        content_encoding_len: 0,
     };
 
-    let handle: *mut MarkdownConverterHandle = markdown_converter_new();
-    assert!(!handle.is_null(), "failed to create FFI converter handle");
+    let gzip_accept_encoding = b"gzip";
+    let gzip_options = MarkdownOptions {
+        accept_encoding: gzip_accept_encoding.as_ptr(),
+        accept_encoding_len: gzip_accept_encoding.len(),
+        ..options
+    };
+
+    let handle: u64 = markdown_converter_new();
+    assert_ne!(handle, 0, "failed to create FFI converter handle");
 
     let mut durations = Vec::with_capacity(cfg.iterations);
     let mut markdown_len_sum = 0usize;
@@ -176,28 +286,28 @@ fn run_ffi_baseline(sample: &Sample, cfg: RunConfig) -> FfiSummary {
             markdown_len: 0,
             etag: ptr::null_mut(),
             etag_len: 0,
+            toc: ptr::null_mut(),
+            toc_len: 0,
             token_estimate: 0,
+            had_lossy_decode: 0,
             error_code: 0,
             error_message: ptr::null_mut(),
             error_len: 0,
+            content_encoding: ptr::null_mut(),
+            content_encoding_len: 0,
+            uncompressed_len: 0,
         };
 
         let start = Instant::now();
         unsafe {
-            markdown_convert(
-                handle,
-                sample.html.as_ptr(),
-                sample.html.len(),
-                &options,
-                &mut result,
-            );
+            markdown_convert(handle, html.as_ptr(), html.len(), &options, &mut result);
         }
         let elapsed = start.elapsed().as_secs_f64();
 
         assert_eq!(
             result.error_code, ERROR_SUCCESS,
             "ffi conversion failed for {} with code {}",
-            sample.name, result.error_code
+            name, result.error_code
         );
 
         if i >= cfg.warmup {
@@ -209,18 +319,58 @@ fn run_ffi_baseline(sample: &Sample, cfg: RunConfig) -> FfiSummary {
         unsafe { markdown_result_free(&mut result) };
     }
 
-    unsafe { markdown_converter_free(handle) };
+    let mut gzip_result = MarkdownResult {
+        markdown: ptr::null_mut(),
+        markdown_len: 0,
+        etag: ptr::null_mut(),
+        etag_len: 0,
+        toc: ptr::null_mut(),
+        toc_len: 0,
+        token_estimate: 0,
+        had_lossy_decode: 0,
+        error_code: 0,
+        error_message: ptr::null_mut(),
+        error_len: 0,
+        content_encoding: ptr::null_mut(),
+        content_encoding_len: 0,
+        uncompressed_len: 0,
+    };
+    unsafe {
+        markdown_convert(
+            handle,
+            html.as_ptr(),
+            html.len(),
+            &gzip_options,
+            &mut gzip_result,
+        );
+    }
+    assert_eq!(
+        gzip_result.error_code, ERROR_SUCCESS,
+        "gzip-negotiated conversion failed for {name} with code {}",
+        gzip_result.error_code
+    );
+    let compressed_bytes = gzip_result.markdown_len;
+    let compression_ratio = if compressed_bytes == 0 {
+        0.0
+    } else {
+        gzip_result.uncompressed_len as f64 / compressed_bytes as f64
+    };
+    unsafe { markdown_result_free(&mut gzip_result) };
+
+    markdown_converter_free(handle);
 
-    let stats = summarize(&durations, sample.html.len());
+    let stats = summarize(&durations, html.len());
     FfiSummary {
         stats,
-        html_bytes: sample.html.len(),
+        html_bytes: html.len(),
         markdown_bytes_avg: markdown_len_sum / cfg.iterations.max(1),
         token_estimate_avg: (token_sum / cfg.iterations.max(1) as u64) as u32,
+        compressed_bytes,
+        compression_ratio,
     }
 }
 
-fn run_breakdown(sample: &Sample, iterations: usize) -> BreakdownSummary {
+fn run_breakdown(html: &[u8], iterations: usize, track_allocations: bool) -> BreakdownSummary {
     let converter = MarkdownConverter::with_options(ConversionOptions {
         flavor: MarkdownFlavor::CommonMark,
         include_front_matter: false,
@@ -229,6 +379,7 @@ fn run_breakdown(sample: &Sample, iterations: usize) -> BreakdownSummary {
         preserve_tables: true,
         base_url: None,
         resolve_relative_urls: false,
+        ..Default::default()
     });
     let etag = ETagGenerator::new();
     let token = TokenEstimator::new();
@@ -237,51 +388,139 @@ fn run_breakdown(sample: &Sample, iterations: usize) -> BreakdownSummary {
     let mut convert = 0.0;
     let mut etag_gen = 0.0;
     let mut token_est = 0.0;
+    let mut compress = 0.0;
     let mut total = 0.0;
 
+    let mut parse_bytes = 0usize;
+    let mut parse_peak = 0usize;
+    let mut convert_bytes = 0usize;
+    let mut convert_peak = 0usize;
+    let mut etag_bytes = 0usize;
+    let mut etag_peak = 0usize;
+    let mut token_bytes = 0usize;
+    let mut token_peak = 0usize;
+    let mut compress_bytes = 0usize;
+    let mut compress_peak = 0usize;
+
+    if track_allocations {
+        TRACKING_ENABLED.store(true, Ordering::Relaxed);
+    }
+
     for _ in 0..iterations {
+        let parse_start = track_allocations.then(begin_stage_allocation);
         let t0 = Instant::now();
-        let dom = parse_html_with_charset(&sample.html, Some("text/html; charset=UTF-8"))
+        let dom = parse_html_with_charset(html, Some("text/html; charset=UTF-8"))
             .expect("parse_html_with_charset failed");
         let t1 = Instant::now();
+        if let Some((live0, total0)) = parse_start {
+            let s = end_stage_allocation(live0, total0);
+            parse_bytes += s.bytes_allocated_avg;
+            parse_peak = parse_peak.max(s.peak_delta_bytes);
+        }
 
+        let convert_start = track_allocations.then(begin_stage_allocation);
         let mut ctx = ConversionContext::new(std::time::Duration::from_millis(5000));
         let markdown = converter
             .convert_with_context(&dom, &mut ctx)
             .expect("convert_with_context failed");
         let t2 = Instant::now();
+        if let Some((live0, total0)) = convert_start {
+            let s = end_stage_allocation(live0, total0);
+            convert_bytes += s.bytes_allocated_avg;
+            convert_peak = convert_peak.max(s.peak_delta_bytes);
+        }
 
+        let etag_start = track_allocations.then(begin_stage_allocation);
         let _etag = etag.generate(markdown.as_bytes());
         let t3 = Instant::now();
+        if let Some((live0, total0)) = etag_start {
+            let s = end_stage_allocation(live0, total0);
+            etag_bytes += s.bytes_allocated_avg;
+            etag_peak = etag_peak.max(s.peak_delta_bytes);
+        }
 
+        let token_start = track_allocations.then(begin_stage_allocation);
         let _tokens = token.estimate(&markdown);
         let t4 = Instant::now();
+        if let Some((live0, total0)) = token_start {
+            let s = end_stage_allocation(live0, total0);
+            token_bytes += s.bytes_allocated_avg;
+            token_peak = token_peak.max(s.peak_delta_bytes);
+        }
+
+        // ETag is computed from the uncompressed Markdown above, matching
+        // FR-06.4 (see `compression` module docs), so timing compression
+        // last here mirrors the order a real response pipeline would use.
+        let compress_start = track_allocations.then(begin_stage_allocation);
+        let _compressed = compression::compress(markdown.as_bytes(), CompressionEncoding::Gzip)
+            .expect("gzip compression failed");
+        let t5 = Instant::now();
+        if let Some((live0, total0)) = compress_start {
+            let s = end_stage_allocation(live0, total0);
+            compress_bytes += s.bytes_allocated_avg;
+            compress_peak = compress_peak.max(s.peak_delta_bytes);
+        }
 
         parse += (t1 - t0).as_secs_f64();
         convert += (t2 - t1).as_secs_f64();
         etag_gen += (t3 - t2).as_secs_f64();
         token_est += (t4 - t3).as_secs_f64();
-        total += (t4 - t0).as_secs_f64();
+        compress += (t5 - t4).as_secs_f64();
+        total += (t5 - t0).as_secs_f64();
+    }
+
+    if track_allocations {
+        TRACKING_ENABLED.store(false, Ordering::Relaxed);
     }
 
     let n = iterations as f64;
+    let iterations_nonzero = iterations.max(1);
+    let alloc = track_allocations.then(|| AllocBreakdown {
+        parse: StageAlloc {
+            bytes_allocated_avg: parse_bytes / iterations_nonzero,
+            peak_delta_bytes: parse_peak,
+        },
+        convert: StageAlloc {
+            bytes_allocated_avg: convert_bytes / iterations_nonzero,
+            peak_delta_bytes: convert_peak,
+        },
+        etag: StageAlloc {
+            bytes_allocated_avg: etag_bytes / iterations_nonzero,
+            peak_delta_bytes: etag_peak,
+        },
+        token: StageAlloc {
+            bytes_allocated_avg: token_bytes / iterations_nonzero,
+            peak_delta_bytes: token_peak,
+        },
+        compress: StageAlloc {
+            bytes_allocated_avg: compress_bytes / iterations_nonzero,
+            peak_delta_bytes: compress_peak,
+        },
+    });
+
     BreakdownSummary {
         parse_ms: parse * 1000.0 / n,
         convert_ms: convert * 1000.0 / n,
         etag_ms: etag_gen * 1000.0 / n,
         token_ms: token_est * 1000.0 / n,
+        compress_ms: compress * 1000.0 / n,
         total_ms: total * 1000.0 / n,
+        alloc,
     }
 }
 
 fn print_ffi_table(results: &[(Sample, FfiSummary)]) {
     println!("# FFI Baseline (local, release build)");
     println!();
-    println!("| Sample | HTML bytes | Markdown bytes (avg) | Tokens (avg) | Avg ms | P50 ms | P95 ms | P99 ms | Req/s | Input MB/s |");
-    println!("|--------|------------|----------------------|--------------|--------|--------|--------|--------|-------|------------|");
+    println!(
+        "| Sample | HTML bytes | Markdown bytes (avg) | Tokens (avg) | Avg ms | P50 ms | P95 ms | P99 ms | Req/s | Input MB/s | Gzip bytes | Gzip ratio |"
+    );
+    println!(
+        "|--------|------------|----------------------|--------------|--------|--------|--------|--------|-------|------------|------------|------------|"
+    );
     for (s, r) in results {
         println!(
-            "| {} ({}) | {} | {} | {} | {:.3} | {:.3} | {:.3} | {:.3} | {:.1} | {:.2} |",
+            "| {} ({}) | {} | {} | {} | {:.3} | {:.3} | {:.3} | {:.3} | {:.1} | {:.2} | {} | {:.2}x |",
             s.name,
             s.target_label,
             r.html_bytes,
@@ -292,41 +531,891 @@ fn print_ffi_table(results: &[(Sample, FfiSummary)]) {
             r.stats.p95_ms,
             r.stats.p99_ms,
             r.stats.req_per_s,
-            r.stats.input_mb_per_s
+            r.stats.input_mb_per_s,
+            r.compressed_bytes,
+            r.compression_ratio
         );
     }
     println!();
 }
 
-fn print_breakdown(sample: &Sample, b: &BreakdownSummary, ffi_avg_ms: f64) {
-    let known = b.parse_ms + b.convert_ms + b.etag_ms + b.token_ms;
+fn print_breakdown(name: &str, target_label: &str, b: &BreakdownSummary, ffi_avg_ms: f64) {
+    let known = b.parse_ms + b.convert_ms + b.etag_ms + b.token_ms + b.compress_ms;
     let ffi_overhead = (ffi_avg_ms - known).max(0.0);
-    println!(
-        "## Stage Breakdown ({}, {})",
-        sample.name, sample.target_label
-    );
+    println!("## Stage Breakdown ({name}, {target_label})");
+    println!();
+
+    let stages = [
+        (
+            "parse_html_with_charset",
+            b.parse_ms,
+            b.alloc.as_ref().map(|a| a.parse),
+        ),
+        (
+            "convert_with_context",
+            b.convert_ms,
+            b.alloc.as_ref().map(|a| a.convert),
+        ),
+        ("etag.generate", b.etag_ms, b.alloc.as_ref().map(|a| a.etag)),
+        (
+            "token_estimate",
+            b.token_ms,
+            b.alloc.as_ref().map(|a| a.token),
+        ),
+        (
+            "compression::compress (gzip)",
+            b.compress_ms,
+            b.alloc.as_ref().map(|a| a.compress),
+        ),
+    ];
+
+    if b.alloc.is_some() {
+        println!("| Stage | Avg ms | Share (direct stage timing) | Bytes/iter | Peak Δ bytes |");
+        println!("|-------|--------|------------------------------|------------|--------------|");
+        for (name, v, alloc) in stages {
+            let share = if b.total_ms > 0.0 {
+                v / b.total_ms * 100.0
+            } else {
+                0.0
+            };
+            let alloc = alloc.unwrap_or_default();
+            println!(
+                "| {} | {:.3} | {:.1}% | {} | {} |",
+                name, v, share, alloc.bytes_allocated_avg, alloc.peak_delta_bytes
+            );
+        }
+        println!("| direct total | {:.3} | 100.0% | - | - |", b.total_ms);
+        println!("| ffi end-to-end avg | {:.3} | - | - | - |", ffi_avg_ms);
+        println!(
+            "| inferred ffi/runtime overhead | {:.3} | - | - | - |",
+            ffi_overhead
+        );
+    } else {
+        println!("| Stage | Avg ms | Share (direct stage timing) |");
+        println!("|-------|--------|-----------------------------|");
+        for (name, v, _) in stages {
+            let share = if b.total_ms > 0.0 {
+                v / b.total_ms * 100.0
+            } else {
+                0.0
+            };
+            println!("| {} | {:.3} | {:.1}% |", name, v, share);
+        }
+        println!("| direct total | {:.3} | 100.0% |", b.total_ms);
+        println!("| ffi end-to-end avg | {:.3} | - |", ffi_avg_ms);
+        println!(
+            "| inferred ffi/runtime overhead | {:.3} | - |",
+            ffi_overhead
+        );
+    }
     println!();
-    println!("| Stage | Avg ms | Share (direct stage timing) |");
-    println!("|-------|--------|-----------------------------|");
-    for (name, v) in [
-        ("parse_html_with_charset", b.parse_ms),
-        ("convert_with_context", b.convert_ms),
-        ("etag.generate", b.etag_ms),
-        ("token_estimate", b.token_ms),
-    ] {
-        let share = if b.total_ms > 0.0 {
-            v / b.total_ms * 100.0
+}
+
+// ============================================================================
+// Baseline snapshots (--save-baseline / --baseline)
+// ============================================================================
+//
+// A machine-readable counterpart to `print_ffi_table`'s Markdown output, so
+// CI can catch a regression instead of a human having to eyeball a table.
+// There is no JSON crate in this workspace, so both directions (write and
+// read) are hand-rolled against the flat schema below rather than pulling in
+// a dependency for one example binary.
+
+/// One sample's `FfiSummary`/`Stats` fields, plus the inputs needed to match
+/// it against a later run by `name`.
+#[derive(Clone)]
+struct SampleSnapshot {
+    name: String,
+    html_bytes: usize,
+    markdown_bytes_avg: usize,
+    token_estimate_avg: u32,
+    avg_ms: f64,
+    p50_ms: f64,
+    p95_ms: f64,
+    p99_ms: f64,
+    req_per_s: f64,
+    input_mb_per_s: f64,
+}
+
+struct BaselineSnapshot {
+    git_commit: String,
+    toolchain: String,
+    samples: Vec<SampleSnapshot>,
+}
+
+fn snapshot_from_results(results: &[(Sample, FfiSummary)]) -> BaselineSnapshot {
+    BaselineSnapshot {
+        git_commit: git_commit_tag(),
+        toolchain: toolchain_tag(),
+        samples: results
+            .iter()
+            .map(|(s, r)| SampleSnapshot {
+                name: s.name.to_string(),
+                html_bytes: r.html_bytes,
+                markdown_bytes_avg: r.markdown_bytes_avg,
+                token_estimate_avg: r.token_estimate_avg,
+                avg_ms: r.stats.avg_ms,
+                p50_ms: r.stats.p50_ms,
+                p95_ms: r.stats.p95_ms,
+                p99_ms: r.stats.p99_ms,
+                req_per_s: r.stats.req_per_s,
+                input_mb_per_s: r.stats.input_mb_per_s,
+            })
+            .collect(),
+    }
+}
+
+/// Short commit hash of the current `HEAD`, or `"unknown"` if `git` is
+/// unavailable (e.g. a source tarball with no `.git` directory).
+fn git_commit_tag() -> String {
+    Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .current_dir(repo_root())
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// `rustc --version` output, or `"unknown"` if `rustc` cannot be invoked.
+fn toolchain_tag() -> String {
+    Command::new("rustc")
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn json_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn snapshot_to_json(snapshot: &BaselineSnapshot) -> String {
+    let mut out = String::new();
+    out.push_str("{\n");
+    out.push_str(&format!(
+        "  \"git_commit\": \"{}\",\n",
+        json_escape(&snapshot.git_commit)
+    ));
+    out.push_str(&format!(
+        "  \"toolchain\": \"{}\",\n",
+        json_escape(&snapshot.toolchain)
+    ));
+    out.push_str("  \"samples\": [\n");
+    for (i, s) in snapshot.samples.iter().enumerate() {
+        out.push_str("    {\n");
+        out.push_str(&format!("      \"name\": \"{}\",\n", json_escape(&s.name)));
+        out.push_str(&format!("      \"html_bytes\": {},\n", s.html_bytes));
+        out.push_str(&format!(
+            "      \"markdown_bytes_avg\": {},\n",
+            s.markdown_bytes_avg
+        ));
+        out.push_str(&format!(
+            "      \"token_estimate_avg\": {},\n",
+            s.token_estimate_avg
+        ));
+        out.push_str(&format!("      \"avg_ms\": {},\n", s.avg_ms));
+        out.push_str(&format!("      \"p50_ms\": {},\n", s.p50_ms));
+        out.push_str(&format!("      \"p95_ms\": {},\n", s.p95_ms));
+        out.push_str(&format!("      \"p99_ms\": {},\n", s.p99_ms));
+        out.push_str(&format!("      \"req_per_s\": {},\n", s.req_per_s));
+        out.push_str(&format!("      \"input_mb_per_s\": {}\n", s.input_mb_per_s));
+        out.push_str(if i + 1 == snapshot.samples.len() {
+            "    }\n"
         } else {
+            "    },\n"
+        });
+    }
+    out.push_str("  ]\n");
+    out.push_str("}\n");
+    out
+}
+
+fn save_baseline(path: &Path, snapshot: &BaselineSnapshot) {
+    fs::write(path, snapshot_to_json(snapshot))
+        .unwrap_or_else(|e| panic!("failed to write baseline to {}: {e}", path.display()));
+    println!("Saved baseline to {}", path.display());
+}
+
+/// Tiny hand-rolled JSON value, just enough to read back what
+/// [`snapshot_to_json`] writes - not a general-purpose parser.
+enum JsonValue {
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    fn get(&self, key: &str) -> &JsonValue {
+        match self {
+            JsonValue::Object(fields) => fields
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v)
+                .unwrap_or_else(|| panic!("missing JSON field `{key}`")),
+            _ => panic!("expected a JSON object while reading `{key}`"),
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        match self {
+            JsonValue::String(s) => s,
+            _ => panic!("expected a JSON string"),
+        }
+    }
+
+    fn as_f64(&self) -> f64 {
+        match self {
+            JsonValue::Number(n) => *n,
+            _ => panic!("expected a JSON number"),
+        }
+    }
+
+    fn as_usize(&self) -> usize {
+        self.as_f64() as usize
+    }
+
+    fn as_u32(&self) -> u32 {
+        self.as_f64() as u32
+    }
+
+    fn as_array(&self) -> &[JsonValue] {
+        match self {
+            JsonValue::Array(items) => items,
+            _ => panic!("expected a JSON array"),
+        }
+    }
+}
+
+struct JsonParser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            bytes: input.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn skip_ws(&mut self) {
+        while matches!(self.bytes.get(self.pos), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) {
+        assert_eq!(
+            self.bytes.get(self.pos),
+            Some(&byte),
+            "expected '{}' at byte {}",
+            byte as char,
+            self.pos
+        );
+        self.pos += 1;
+    }
+
+    fn parse_value(&mut self) -> JsonValue {
+        self.skip_ws();
+        match self.bytes.get(self.pos) {
+            Some(b'{') => self.parse_object(),
+            Some(b'[') => self.parse_array(),
+            Some(b'"') => JsonValue::String(self.parse_string()),
+            Some(_) => self.parse_number(),
+            None => panic!("unexpected end of JSON input"),
+        }
+    }
+
+    fn parse_object(&mut self) -> JsonValue {
+        self.expect(b'{');
+        let mut fields = Vec::new();
+        self.skip_ws();
+        if self.bytes.get(self.pos) == Some(&b'}') {
+            self.pos += 1;
+            return JsonValue::Object(fields);
+        }
+        loop {
+            self.skip_ws();
+            let key = self.parse_string();
+            self.skip_ws();
+            self.expect(b':');
+            let value = self.parse_value();
+            fields.push((key, value));
+            self.skip_ws();
+            match self.bytes.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    break;
+                }
+                other => panic!("expected ',' or '}}' in object, found {other:?}"),
+            }
+        }
+        JsonValue::Object(fields)
+    }
+
+    fn parse_array(&mut self) -> JsonValue {
+        self.expect(b'[');
+        let mut items = Vec::new();
+        self.skip_ws();
+        if self.bytes.get(self.pos) == Some(&b']') {
+            self.pos += 1;
+            return JsonValue::Array(items);
+        }
+        loop {
+            items.push(self.parse_value());
+            self.skip_ws();
+            match self.bytes.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    break;
+                }
+                other => panic!("expected ',' or ']' in array, found {other:?}"),
+            }
+        }
+        JsonValue::Array(items)
+    }
+
+    fn parse_string(&mut self) -> String {
+        self.expect(b'"');
+        let mut out = String::new();
+        loop {
+            match self.bytes.get(self.pos) {
+                Some(b'"') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    match self.bytes.get(self.pos) {
+                        Some(b'n') => out.push('\n'),
+                        Some(b'"') => out.push('"'),
+                        Some(b'\\') => out.push('\\'),
+                        Some(b'u') => {
+                            let hex = std::str::from_utf8(&self.bytes[self.pos + 1..self.pos + 5])
+                                .expect("valid \\u escape");
+                            let code = u32::from_str_radix(hex, 16).expect("valid \\u hex digits");
+                            out.push(char::from_u32(code).unwrap_or('\u{FFFD}'));
+                            self.pos += 4;
+                        }
+                        other => panic!("unsupported escape: {other:?}"),
+                    }
+                    self.pos += 1;
+                }
+                Some(&b) => {
+                    out.push(b as char);
+                    self.pos += 1;
+                }
+                None => panic!("unterminated JSON string"),
+            }
+        }
+        out
+    }
+
+    fn parse_number(&mut self) -> JsonValue {
+        let start = self.pos;
+        while matches!(
+            self.bytes.get(self.pos),
+            Some(b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E')
+        ) {
+            self.pos += 1;
+        }
+        let text = std::str::from_utf8(&self.bytes[start..self.pos]).expect("valid UTF-8 number");
+        JsonValue::Number(
+            text.parse()
+                .unwrap_or_else(|_| panic!("invalid number: {text}")),
+        )
+    }
+}
+
+fn load_baseline(path: &Path) -> BaselineSnapshot {
+    let text = fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("failed to read baseline from {}: {e}", path.display()));
+    let root = JsonParser::new(&text).parse_value();
+    BaselineSnapshot {
+        git_commit: root.get("git_commit").as_str().to_string(),
+        toolchain: root.get("toolchain").as_str().to_string(),
+        samples: root
+            .get("samples")
+            .as_array()
+            .iter()
+            .map(|s| SampleSnapshot {
+                name: s.get("name").as_str().to_string(),
+                html_bytes: s.get("html_bytes").as_usize(),
+                markdown_bytes_avg: s.get("markdown_bytes_avg").as_usize(),
+                token_estimate_avg: s.get("token_estimate_avg").as_u32(),
+                avg_ms: s.get("avg_ms").as_f64(),
+                p50_ms: s.get("p50_ms").as_f64(),
+                p95_ms: s.get("p95_ms").as_f64(),
+                p99_ms: s.get("p99_ms").as_f64(),
+                req_per_s: s.get("req_per_s").as_f64(),
+                input_mb_per_s: s.get("input_mb_per_s").as_f64(),
+            })
+            .collect(),
+    }
+}
+
+/// Percentage change from `old` to `new` (positive = increase).
+fn pct_delta(old: f64, new: f64) -> f64 {
+    if old == 0.0 {
+        if new == 0.0 {
             0.0
+        } else {
+            f64::INFINITY
+        }
+    } else {
+        (new - old) / old * 100.0
+    }
+}
+
+/// Compare a freshly measured run against a saved baseline, print a diff
+/// table, and report whether any metric regressed beyond `threshold_pct`.
+///
+/// A latency metric (`avg_ms`/`p95_ms`/`p99_ms`) regresses when it *rises* by
+/// more than the threshold; a throughput metric (`req_per_s`/
+/// `input_mb_per_s`) regresses when it *falls* by more than the threshold.
+/// Samples present on only one side are reported, not silently dropped, and
+/// a sample missing from the new run counts as a regression since it can no
+/// longer be checked.
+fn compare_to_baseline(old: &BaselineSnapshot, new: &BaselineSnapshot, threshold_pct: f64) -> bool {
+    println!("# Baseline Comparison");
+    println!();
+    println!("Old: commit={} toolchain={}", old.git_commit, old.toolchain);
+    println!("New: commit={} toolchain={}", new.git_commit, new.toolchain);
+    println!();
+
+    let mut regressed = false;
+
+    for old_sample in &old.samples {
+        let Some(new_sample) = new.samples.iter().find(|s| s.name == old_sample.name) else {
+            println!(
+                "REGRESSION: sample `{}` present in baseline but missing from this run",
+                old_sample.name
+            );
+            regressed = true;
+            continue;
         };
-        println!("| {} | {:.3} | {:.1}% |", name, v, share);
+
+        println!("## {}", old_sample.name);
+        println!();
+        println!("| Metric | Old | New | Delta |");
+        println!("|--------|-----|-----|-------|");
+
+        for (metric, old_v, new_v, higher_is_worse) in [
+            ("avg_ms", old_sample.avg_ms, new_sample.avg_ms, true),
+            ("p95_ms", old_sample.p95_ms, new_sample.p95_ms, true),
+            ("p99_ms", old_sample.p99_ms, new_sample.p99_ms, true),
+            (
+                "req_per_s",
+                old_sample.req_per_s,
+                new_sample.req_per_s,
+                false,
+            ),
+            (
+                "input_mb_per_s",
+                old_sample.input_mb_per_s,
+                new_sample.input_mb_per_s,
+                false,
+            ),
+        ] {
+            let delta = pct_delta(old_v, new_v);
+            let signed_delta = if higher_is_worse { delta } else { -delta };
+            let is_regression = signed_delta > threshold_pct;
+            println!(
+                "| {} | {:.3} | {:.3} | {:+.1}%{} |",
+                metric,
+                old_v,
+                new_v,
+                delta,
+                if is_regression { " ⚠" } else { "" }
+            );
+            if is_regression {
+                regressed = true;
+            }
+        }
+        println!();
+    }
+
+    for new_sample in &new.samples {
+        if !old.samples.iter().any(|s| s.name == new_sample.name) {
+            println!(
+                "NOTE: sample `{}` is new, no baseline to compare against",
+                new_sample.name
+            );
+        }
     }
-    println!("| direct total | {:.3} | 100.0% |", b.total_ms);
-    println!("| ffi end-to-end avg | {:.3} | - |", ffi_avg_ms);
+
+    regressed
+}
+
+// ============================================================================
+// Corpus-directory benchmarking (--corpus <dir>)
+// ============================================================================
+//
+// `build_samples` only exercises three synthetic inputs built by repeating a
+// single seed document, which produces artificially homogeneous DOMs. This
+// mode instead walks a directory of real `.html` fixtures, benchmarks each
+// one individually, and buckets them by size so the numbers reflect the
+// diversity of real documents (tables, deep navigation, mixed charsets)
+// rather than one repeated shape.
+
+/// Number of timed iterations per corpus file. Kept low relative to
+/// `build_samples`' synthetic configs since a corpus can hold many files and
+/// each one pays its own warmup cost.
+const CORPUS_RUN_CONFIG: RunConfig = RunConfig {
+    warmup: 3,
+    iterations: 20,
+};
+
+/// Size bucket a corpus file falls into, based on raw byte length.
+fn size_bucket(bytes: usize) -> &'static str {
+    if bytes < 1024 {
+        "<1KB"
+    } else if bytes <= 50 * 1024 {
+        "1-50KB"
+    } else {
+        ">50KB"
+    }
+}
+
+/// Recursively collect `.html` files under `dir`, sorted for stable output.
+fn discover_corpus_files(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    collect_corpus_files(dir, &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+fn collect_corpus_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_dir() {
+            collect_corpus_files(&path, files)?;
+            continue;
+        }
+        if path.extension().and_then(|ext| ext.to_str()) == Some("html") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// One corpus file's benchmark result, keyed by its path relative to the
+/// corpus root so rows are stable regardless of where the corpus directory
+/// lives on disk.
+struct CorpusFileResult {
+    relative_path: String,
+    bucket: &'static str,
+    ffi: FfiSummary,
+    breakdown: BreakdownSummary,
+}
+
+fn run_corpus_mode(dir: &Path) {
+    let files = discover_corpus_files(dir)
+        .unwrap_or_else(|e| panic!("failed to walk corpus directory {}: {e}", dir.display()));
+    assert!(
+        !files.is_empty(),
+        "no .html files found under corpus directory {}",
+        dir.display()
+    );
+
+    let mut results = Vec::with_capacity(files.len());
+    for path in &files {
+        let html = read_file(path);
+        let relative_path = path
+            .strip_prefix(dir)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace(std::path::MAIN_SEPARATOR, "/");
+        let bucket = size_bucket(html.len());
+        let ffi = run_ffi_baseline(&relative_path, &html, CORPUS_RUN_CONFIG);
+        let breakdown = run_breakdown(&html, CORPUS_RUN_CONFIG.iterations, false);
+        results.push(CorpusFileResult {
+            relative_path,
+            bucket,
+            ffi,
+            breakdown,
+        });
+    }
+
+    print_corpus_table(&results);
+    print_corpus_bucket_summary(&results);
+}
+
+fn print_corpus_table(results: &[CorpusFileResult]) {
+    println!("# Corpus Baseline (per file)");
+    println!();
+    println!(
+        "| File | Bucket | HTML bytes | Markdown bytes (avg) | Avg ms | P95 ms | Req/s | Breakdown total ms |"
+    );
     println!(
-        "| inferred ffi/runtime overhead | {:.3} | - |",
-        ffi_overhead
+        "|------|--------|------------|----------------------|--------|--------|-------|---------------------|"
     );
+    for r in results {
+        println!(
+            "| {} | {} | {} | {} | {:.3} | {:.3} | {:.1} | {:.3} |",
+            r.relative_path,
+            r.bucket,
+            r.ffi.html_bytes,
+            r.ffi.markdown_bytes_avg,
+            r.ffi.stats.avg_ms,
+            r.ffi.stats.p95_ms,
+            r.ffi.stats.req_per_s,
+            r.breakdown.total_ms
+        );
+    }
+    println!();
+}
+
+fn print_corpus_bucket_summary(results: &[CorpusFileResult]) {
+    println!("# Corpus Baseline (per bucket)");
+    println!();
+    println!("| Bucket | Files | Median avg_ms | P95 avg_ms | Median req/s | P95 req/s |");
+    println!("|--------|-------|----------------|------------|---------------|-----------|");
+    for bucket in ["<1KB", "1-50KB", ">50KB"] {
+        let in_bucket: Vec<&CorpusFileResult> =
+            results.iter().filter(|r| r.bucket == bucket).collect();
+        if in_bucket.is_empty() {
+            continue;
+        }
+
+        let mut avg_ms: Vec<f64> = in_bucket.iter().map(|r| r.ffi.stats.avg_ms).collect();
+        avg_ms.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let mut req_per_s: Vec<f64> = in_bucket.iter().map(|r| r.ffi.stats.req_per_s).collect();
+        req_per_s.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        println!(
+            "| {} | {} | {:.3} | {:.3} | {:.1} | {:.1} |",
+            bucket,
+            in_bucket.len(),
+            percentile_ms(&avg_ms, 0.50),
+            percentile_ms(&avg_ms, 0.95),
+            percentile_ms(&req_per_s, 0.50),
+            percentile_ms(&req_per_s, 0.95),
+        );
+    }
+    println!();
+}
+
+// ============================================================================
+// Concurrent throughput harness (--threads N)
+// ============================================================================
+//
+// nginx runs many worker processes/threads calling into the converter
+// simultaneously; the modes above only measure single-threaded latency. This
+// mode hammers `markdown_convert` from N OS threads, either sharing one
+// converter handle or giving each thread its own, and reports aggregate
+// throughput plus how it scales against the single-thread number. Sharing a
+// handle exercises the per-slot `Mutex` in `HandleRegistry` - see its "Thread
+// Safety" doc comment - which serializes conversions rather than running
+// them in parallel, so the scaling factor there is expected to stay flat;
+// the per-thread mode is what an nginx deployment should actually look like.
+
+/// Whether concurrent threads share one converter handle or each create
+/// their own.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum HandleMode {
+    Shared,
+    PerThread,
+}
+
+/// Run one timed `markdown_convert` call and report its elapsed time and
+/// error code. The caller is responsible for freeing `handle`.
+fn run_threaded_conversion(handle: u64, html: &[u8], content_type: &'static [u8]) -> (f64, u32) {
+    let options = MarkdownOptions {
+        abi_version: MARKDOWN_ABI_VERSION,
+        flavor: 0,
+        timeout_ms: 5000,
+        max_input_bytes: 0,
+        max_output_bytes: 0,
+        max_decompressed_bytes: 0,
+        generate_etag: 1,
+        estimate_tokens: 1,
+        front_matter: 0,
+        preserve_tables: 1,
+        generate_toc: 0,
+        heading_anchors: 0,
+        renderer: RENDERER_DEFAULT,
+        heading_offset: 0,
+        extensions: 0,
+        content_type: content_type.as_ptr(),
+        content_type_len: content_type.len(),
+        base_url: ptr::null(),
+        base_url_len: 0,
+        accept_encoding: ptr::null(),
+        accept_encoding_len: 0,
+        input_charset: ptr::null(),
+        input_charset_len: 0,
+        content_encoding: ptr::null(),
+        content_encoding_len: 0,
+    };
+
+    let mut result = MarkdownResult {
+        markdown: ptr::null_mut(),
+        markdown_len: 0,
+        etag: ptr::null_mut(),
+        etag_len: 0,
+        toc: ptr::null_mut(),
+        toc_len: 0,
+        token_estimate: 0,
+        had_lossy_decode: 0,
+        error_code: 0,
+        error_message: ptr::null_mut(),
+        error_len: 0,
+        content_encoding: ptr::null_mut(),
+        content_encoding_len: 0,
+        uncompressed_len: 0,
+    };
+
+    let start = Instant::now();
+    unsafe {
+        markdown_convert(handle, html.as_ptr(), html.len(), &options, &mut result);
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+    let error_code = result.error_code;
+    unsafe { markdown_result_free(&mut result) };
+    (elapsed, error_code)
+}
+
+fn run_threads_mode(n_threads: usize, iterations_per_thread: usize, handle_mode: HandleMode) {
+    const CONTENT_TYPE: &[u8] = b"text/html; charset=UTF-8";
+    const WARMUP_PER_THREAD: usize = 10;
+
+    let samples = build_samples();
+    let sample = samples
+        .into_iter()
+        .find(|s| s.name == "medium")
+        .expect("medium sample");
+    let html = &sample.html;
+
+    let shared_handle = match handle_mode {
+        HandleMode::Shared => {
+            let handle = markdown_converter_new();
+            assert_ne!(handle, 0, "failed to create shared FFI converter handle");
+            Some(handle)
+        }
+        HandleMode::PerThread => None,
+    };
+
+    let per_thread_results: Vec<(Vec<f64>, Vec<u32>)> = thread::scope(|scope| {
+        let join_handles: Vec<_> = (0..n_threads)
+            .map(|_| {
+                scope.spawn(move || {
+                    let handle = match shared_handle {
+                        Some(handle) => handle,
+                        None => {
+                            let handle = markdown_converter_new();
+                            assert_ne!(
+                                handle, 0,
+                                "failed to create per-thread FFI converter handle"
+                            );
+                            handle
+                        }
+                    };
+
+                    let mut durations = Vec::with_capacity(iterations_per_thread);
+                    let mut error_codes = Vec::new();
+                    for i in 0..(WARMUP_PER_THREAD + iterations_per_thread) {
+                        let (elapsed, error_code) =
+                            run_threaded_conversion(handle, html, CONTENT_TYPE);
+                        if error_code != ERROR_SUCCESS {
+                            error_codes.push(error_code);
+                        }
+                        if i >= WARMUP_PER_THREAD {
+                            durations.push(elapsed);
+                        }
+                    }
+
+                    if handle_mode == HandleMode::PerThread {
+                        markdown_converter_free(handle);
+                    }
+
+                    (durations, error_codes)
+                })
+            })
+            .collect();
+
+        join_handles
+            .into_iter()
+            .map(|h| h.join().expect("benchmark thread panicked"))
+            .collect()
+    });
+
+    if let Some(handle) = shared_handle {
+        markdown_converter_free(handle);
+    }
+
+    let all_durations: Vec<f64> = per_thread_results
+        .iter()
+        .flat_map(|(d, _)| d.iter().copied())
+        .collect();
+    let all_error_codes: Vec<u32> = per_thread_results
+        .iter()
+        .flat_map(|(_, e)| e.iter().copied())
+        .collect();
+
+    assert!(
+        all_error_codes.is_empty(),
+        "concurrent markdown_convert calls returned non-success error codes: {all_error_codes:?}"
+    );
+
+    let stats = summarize(&all_durations, html.len());
+    let single_thread = run_ffi_baseline(
+        sample.name,
+        html,
+        RunConfig {
+            warmup: WARMUP_PER_THREAD,
+            iterations: iterations_per_thread,
+        },
+    );
+    let scaling_factor = if single_thread.stats.req_per_s > 0.0 {
+        stats.req_per_s / single_thread.stats.req_per_s
+    } else {
+        0.0
+    };
+
+    let handle_label = match handle_mode {
+        HandleMode::Shared => "shared handle",
+        HandleMode::PerThread => "per-thread handle",
+    };
+    println!(
+        "# Concurrent Throughput ({}, {n_threads} threads, {handle_label})",
+        sample.name
+    );
+    println!();
+    println!("| Metric | Value |");
+    println!("|--------|-------|");
+    println!("| Threads | {n_threads} |");
+    println!("| Iterations/thread | {iterations_per_thread} |");
+    println!("| Total conversions | {} |", all_durations.len());
+    println!("| Aggregate avg ms | {:.3} |", stats.avg_ms);
+    println!("| Aggregate p95 ms | {:.3} |", stats.p95_ms);
+    println!("| Aggregate req/s | {:.1} |", stats.req_per_s);
+    println!(
+        "| Single-thread req/s | {:.1} |",
+        single_thread.stats.req_per_s
+    );
+    println!("| Scaling factor (N-thread / single) | {scaling_factor:.2}x |");
     println!();
 }
 
@@ -351,7 +1440,7 @@ fn run_single_mode(name: &str) {
         },
         _ => unreachable!(),
     };
-    let result = run_ffi_baseline(&sample, cfg);
+    let result = run_ffi_baseline(sample.name, &sample.html, cfg);
     println!(
         "single_sample={} html_bytes={} avg_ms={:.3} p95_ms={:.3} req_per_s={:.1}",
         sample.name,
@@ -362,12 +1451,87 @@ fn run_single_mode(name: &str) {
     );
 }
 
+/// Default percentage threshold beyond which `compare_to_baseline` reports a
+/// regression, used when `--threshold` is not given.
+const DEFAULT_REGRESSION_THRESHOLD_PCT: f64 = 10.0;
+
+/// Default timed iterations per thread for `--threads`, used when
+/// `--iterations` is not given.
+const DEFAULT_THREAD_ITERATIONS: usize = 500;
+
 fn main() {
     let args: Vec<String> = env::args().collect();
     if args.len() == 3 && args[1] == "--single" {
         run_single_mode(&args[2]);
         return;
     }
+    if args.len() == 3 && args[1] == "--corpus" {
+        run_corpus_mode(Path::new(&args[2]));
+        return;
+    }
+    if args.len() >= 3 && args[1] == "--threads" {
+        let n_threads: usize = args[2]
+            .parse()
+            .unwrap_or_else(|e| panic!("invalid --threads value: {e}"));
+        let mut handle_mode = HandleMode::PerThread;
+        let mut iterations_per_thread = DEFAULT_THREAD_ITERATIONS;
+        let mut i = 3;
+        while i < args.len() {
+            match args[i].as_str() {
+                "--shared-handle" => handle_mode = HandleMode::Shared,
+                "--iterations" => {
+                    i += 1;
+                    iterations_per_thread = args
+                        .get(i)
+                        .unwrap_or_else(|| panic!("--iterations requires a count"))
+                        .parse()
+                        .unwrap_or_else(|e| panic!("invalid --iterations value: {e}"));
+                }
+                other => panic!("unrecognized argument: {other}"),
+            }
+            i += 1;
+        }
+        run_threads_mode(n_threads, iterations_per_thread, handle_mode);
+        return;
+    }
+
+    let mut baseline_path: Option<PathBuf> = None;
+    let mut save_baseline_path: Option<PathBuf> = None;
+    let mut threshold_pct = DEFAULT_REGRESSION_THRESHOLD_PCT;
+    let mut track_allocations = false;
+
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--baseline" => {
+                i += 1;
+                baseline_path = Some(PathBuf::from(
+                    args.get(i)
+                        .unwrap_or_else(|| panic!("--baseline requires a path")),
+                ));
+            }
+            "--save-baseline" => {
+                i += 1;
+                save_baseline_path =
+                    Some(PathBuf::from(args.get(i).unwrap_or_else(|| {
+                        panic!("--save-baseline requires a path")
+                    })));
+            }
+            "--threshold" => {
+                i += 1;
+                threshold_pct = args
+                    .get(i)
+                    .unwrap_or_else(|| panic!("--threshold requires a percentage"))
+                    .parse()
+                    .unwrap_or_else(|e| panic!("invalid --threshold value: {e}"));
+            }
+            "--track-allocations" => {
+                track_allocations = true;
+            }
+            other => panic!("unrecognized argument: {other}"),
+        }
+        i += 1;
+    }
 
     let samples = build_samples();
     let mut results = Vec::new();
@@ -388,7 +1552,7 @@ fn main() {
             },
             _ => unreachable!(),
         };
-        let summary = run_ffi_baseline(sample, cfg);
+        let summary = run_ffi_baseline(sample.name, &sample.html, cfg);
         results.push((sample.clone(), summary));
     }
 
@@ -398,11 +1562,26 @@ fn main() {
         .iter()
         .find(|s| s.name == "medium")
         .expect("medium sample");
-    let breakdown = run_breakdown(medium, 200);
+    let breakdown = run_breakdown(&medium.html, 200, track_allocations);
     let medium_ffi_avg = results
         .iter()
         .find(|(s, _)| s.name == "medium")
         .map(|(_, r)| r.stats.avg_ms)
         .expect("medium ffi result");
-    print_breakdown(medium, &breakdown, medium_ffi_avg);
+    print_breakdown(medium.name, medium.target_label, &breakdown, medium_ffi_avg);
+
+    let snapshot = snapshot_from_results(&results);
+
+    if let Some(path) = &save_baseline_path {
+        save_baseline(path, &snapshot);
+    }
+
+    if let Some(path) = &baseline_path {
+        let previous = load_baseline(path);
+        let regressed = compare_to_baseline(&previous, &snapshot, threshold_pct);
+        if regressed {
+            eprintln!("Performance regression detected (threshold: {threshold_pct:.1}%)");
+            process::exit(1);
+        }
+    }
 }
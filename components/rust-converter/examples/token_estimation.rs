@@ -2,7 +2,7 @@
 
 use nginx_markdown_converter::converter::MarkdownConverter;
 use nginx_markdown_converter::parser::parse_html;
-use nginx_markdown_converter::token_estimator::TokenEstimator;
+use nginx_markdown_converter::token_estimator::{ScriptAwareEstimator, TokenEstimator};
 
 fn main() {
     println!("=== Token Estimation Examples ===\n");
@@ -18,6 +18,9 @@ fn main() {
 
     // Example 4: Large document estimation
     example_4();
+
+    // Example 5: Script-aware estimation for mixed CJK/Latin text
+    example_5();
 }
 
 fn example_1() {
@@ -115,3 +118,24 @@ fn example_4() {
     println!("Estimated tokens: {}", tokens);
     println!("Useful for LLM context window planning!");
 }
+
+fn example_5() {
+    println!("\nExample 5: Script-aware estimation for mixed CJK/Latin text");
+
+    let bilingual = "Welcome to NGINX 欢迎使用 NGINX，一个高性能的 web 服务器。";
+    println!("Text: \"{}\"", bilingual);
+    println!("Characters: {}\n", bilingual.chars().count());
+
+    let uniform = TokenEstimator::new();
+    println!(
+        "Uniform estimator (4.0 chars/token): {} tokens",
+        uniform.estimate(bilingual)
+    );
+
+    let script_aware = ScriptAwareEstimator::new();
+    println!(
+        "Script-aware estimator (per-bucket CJK/Latin/whitespace ratios): {} tokens",
+        script_aware.estimate(bilingual)
+    );
+    println!("The uniform estimator undercounts CJK text, which tokenizes far more densely than Latin script.");
+}
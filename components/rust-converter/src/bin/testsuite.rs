@@ -0,0 +1,112 @@
+//! Corpus-driven differential testing CLI
+//!
+//! Runs every `.html`/`.expected.md` golden pair under a corpus directory
+//! through the converter, checks each output against its expected Markdown
+//! and against its CommonMark round-trip invariant, and prints a summary.
+//! Exits non-zero if any non-ignored case failed or errored.
+//!
+//! # Usage
+//!
+//! ```text
+//! testsuite [corpus-dir] [ignore-list-file]
+//! ```
+//!
+//! Defaults to `tests/fixtures/golden` and `tests/fixtures/golden/ignore_list.txt`
+//! (relative to the current directory) when arguments are omitted, matching
+//! the corpus shipped with this repo.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use nginx_markdown_converter::testsuite::{
+    CaseOutcome, assert_roundtrip_equivalent, load_ignore_list, run_golden_suite,
+};
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().collect();
+    let corpus_dir = args
+        .get(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("tests/fixtures/golden"));
+    let ignore_list_path = args
+        .get(2)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| corpus_dir.join("ignore_list.txt"));
+
+    let ignored = match load_ignore_list(&ignore_list_path) {
+        Ok(ignored) => ignored,
+        Err(err) => {
+            eprintln!(
+                "failed to read ignore list {}: {}",
+                ignore_list_path.display(),
+                err
+            );
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let report = match run_golden_suite(&corpus_dir, &ignored) {
+        Ok(report) => report,
+        Err(err) => {
+            eprintln!(
+                "failed to run golden suite in {}: {}",
+                corpus_dir.display(),
+                err
+            );
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut roundtrip_failures = Vec::new();
+    for result in &report.results {
+        let CaseOutcome::Passed = result.outcome else {
+            continue;
+        };
+        let html = match fs::read(&result.case.html_path) {
+            Ok(html) => html,
+            Err(err) => {
+                roundtrip_failures.push(format!(
+                    "{}: failed to re-read fixture: {}",
+                    result.case.relative_path, err
+                ));
+                continue;
+            }
+        };
+        let expected = match fs::read_to_string(&result.case.expected_path) {
+            Ok(expected) => expected,
+            Err(err) => {
+                roundtrip_failures.push(format!(
+                    "{}: failed to re-read expected output: {}",
+                    result.case.relative_path, err
+                ));
+                continue;
+            }
+        };
+        if let Err(reason) = assert_roundtrip_equivalent(&html, &expected) {
+            roundtrip_failures.push(format!("{}: {}", result.case.relative_path, reason));
+        }
+    }
+
+    println!("{report}");
+    for result in &report.results {
+        if let CaseOutcome::Failed { actual, expected } = &result.outcome {
+            println!("FAIL {}", result.case.relative_path);
+            println!("  expected: {expected:?}");
+            println!("  actual:   {actual:?}");
+        }
+        if let CaseOutcome::Errored(err) = &result.outcome {
+            println!("ERROR {}: {err}", result.case.relative_path);
+        }
+    }
+    for failure in &roundtrip_failures {
+        println!("ROUNDTRIP MISMATCH {failure}");
+    }
+
+    if report.is_clean() && roundtrip_failures.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
@@ -0,0 +1,195 @@
+//! In-process LRU cache for repeated identical conversions
+//!
+//! NGINX workers often serve the same response body, with the same
+//! conversion options, many times over (a popular page behind a shared
+//! upstream cache, a static asset re-fetched by multiple clients). Without a
+//! result cache, every one of those requests re-parses the HTML and
+//! re-walks the DOM from scratch. [`ConversionCache`] is an optional,
+//! fixed-capacity cache owned by a `MarkdownConverterHandle` that lets a
+//! repeat request skip straight to a previously computed Markdown string.
+//!
+//! # Keying
+//!
+//! A [`CacheKey`] is derived from the input HTML bytes plus the subset of
+//! the request ([`CacheableRequest`]) that actually changes the rendered
+//! Markdown — not, for example, `accept_encoding` (which only selects a
+//! compression codec applied after conversion) or `generate_etag`/
+//! `estimate_tokens` (which only gate whether already-deterministic
+//! derived values are returned, not what they'd be).
+//!
+//! The hash itself uses [`std::collections::hash_map::RandomState`], the
+//! same randomly-seeded SipHash construction `HashMap` uses internally: fast
+//! relative to a cryptographic hash like the BLAKE3 used for ETags, and
+//! resistant to an attacker crafting inputs that collide, since the seed is
+//! generated fresh per handle and never exposed. Because a non-cryptographic
+//! hash can still collide, [`ConversionCache::get`] additionally checks the
+//! candidate's stored input length before treating it as a hit.
+//!
+//! # Eviction
+//!
+//! Capacity is fixed at construction. On a miss that would grow the cache
+//! past capacity, the least-recently-used entry is evicted; hits and fresh
+//! inserts both move an entry to the most-recently-used position.
+
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{BuildHasher, Hash, Hasher};
+
+/// 128-bit key identifying a cached conversion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct CacheKey([u8; 16]);
+
+/// The subset of a conversion request that determines its rendered
+/// Markdown, used to derive a [`CacheKey`]
+///
+/// Kept separate from [`crate::ffi::MarkdownOptions`] so this module does
+/// not need to know the FFI struct's layout; the caller is responsible for
+/// projecting out the fields that matter.
+#[derive(Hash)]
+pub(crate) struct CacheableRequest<'a> {
+    pub flavor: u32,
+    pub extensions: u32,
+    pub front_matter: bool,
+    pub preserve_tables: bool,
+    pub generate_toc: bool,
+    pub heading_anchors: bool,
+    pub base_url: Option<&'a str>,
+    pub content_type: Option<&'a str>,
+    pub input_charset: Option<&'a str>,
+}
+
+/// A previously computed conversion, keyed by [`CacheKey`]
+#[derive(Clone)]
+pub(crate) struct CachedEntry {
+    /// Uncompressed, UTF-8 Markdown bytes
+    pub markdown: Box<[u8]>,
+    /// Present only if it was computed (i.e. `generate_etag` was set) on the
+    /// request that populated this entry; a later hit that wants an ETag
+    /// but finds `None` here computes it from `markdown` on the spot.
+    pub etag: Option<Box<[u8]>>,
+    /// Same `Some`-only-if-computed convention as `etag`, for token counts.
+    pub token_estimate: Option<u32>,
+    /// Whether decoding the source HTML to UTF-8 required replacing any
+    /// malformed byte sequence with U+FFFD, carried forward so a cache hit
+    /// reports the same value a fresh conversion of this input would.
+    pub had_lossy_decode: bool,
+    /// Length of the source HTML, re-checked on every lookup as a cheap
+    /// guard against the hasher's (non-cryptographic) collisions.
+    input_len: usize,
+}
+
+/// Hit/miss/eviction counters for a [`ConversionCache`]
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct CacheCounters {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+/// Fixed-capacity, least-recently-used cache of HTML-to-Markdown conversions
+pub(crate) struct ConversionCache {
+    hasher: RandomState,
+    capacity: usize,
+    entries: HashMap<CacheKey, CachedEntry>,
+    order: VecDeque<CacheKey>,
+    counters: CacheCounters,
+}
+
+impl ConversionCache {
+    /// Create a cache holding at most `capacity` entries
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            hasher: RandomState::new(),
+            capacity,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            counters: CacheCounters::default(),
+        }
+    }
+
+    /// Derive this handle's key for `html` under `request`
+    ///
+    /// Two 64-bit SipHash outputs, domain-separated by a leading tag byte,
+    /// are concatenated into the 128-bit key — cheaper than a cryptographic
+    /// hash while keeping the collision space comparable to the ETag
+    /// generator's truncated BLAKE3/SHA-256 digests.
+    pub(crate) fn key_for(&self, html: &[u8], request: &CacheableRequest<'_>) -> CacheKey {
+        let low = self.domain_hash(0u8, html, request);
+        let high = self.domain_hash(1u8, html, request);
+        let mut bytes = [0u8; 16];
+        bytes[..8].copy_from_slice(&low.to_le_bytes());
+        bytes[8..].copy_from_slice(&high.to_le_bytes());
+        CacheKey(bytes)
+    }
+
+    fn domain_hash(&self, domain: u8, html: &[u8], request: &CacheableRequest<'_>) -> u64 {
+        let mut hasher = self.hasher.build_hasher();
+        domain.hash(&mut hasher);
+        html.hash(&mut hasher);
+        request.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Look up `key`, verifying the candidate's stored input length matches
+    /// `input_len` before treating it as a hit
+    pub(crate) fn get(&mut self, key: &CacheKey, input_len: usize) -> Option<CachedEntry> {
+        let hit = self
+            .entries
+            .get(key)
+            .filter(|entry| entry.input_len == input_len)
+            .cloned();
+
+        if hit.is_some() {
+            self.counters.hits += 1;
+            self.touch(*key);
+        } else {
+            self.counters.misses += 1;
+        }
+
+        hit
+    }
+
+    /// Insert or replace the entry for `key`, evicting the least-recently-used
+    /// entry first if the cache is at capacity
+    pub(crate) fn insert(
+        &mut self,
+        key: CacheKey,
+        markdown: Box<[u8]>,
+        etag: Option<Box<[u8]>>,
+        token_estimate: Option<u32>,
+        had_lossy_decode: bool,
+        input_len: usize,
+    ) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+            if let Some(lru_key) = self.order.pop_front() {
+                self.entries.remove(&lru_key);
+                self.counters.evictions += 1;
+            }
+        }
+
+        self.entries.insert(
+            key,
+            CachedEntry {
+                markdown,
+                etag,
+                token_estimate,
+                had_lossy_decode,
+                input_len,
+            },
+        );
+        self.touch(key);
+    }
+
+    fn touch(&mut self, key: CacheKey) {
+        self.order.retain(|existing| *existing != key);
+        self.order.push_back(key);
+    }
+
+    pub(crate) fn counters(&self) -> CacheCounters {
+        self.counters
+    }
+}
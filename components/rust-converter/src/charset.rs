@@ -1,24 +1,63 @@
 //! Character encoding detection and handling
 //!
 //! This module implements the charset detection cascade as specified in
-//! Requirements FR-05.1, FR-05.2, and FR-05.3.
+//! Requirements FR-05.0 through FR-05.3.
 //!
 //! # Detection Cascade
 //!
-//! The charset detection follows a three-level cascade:
+//! [`detect_charset`] follows a five-level cascade:
 //!
+//! 0. **Byte-Order Mark**: A Unicode BOM at the start of `html` (FR-05.0)
 //! 1. **Content-Type Header**: Check for charset parameter in Content-Type header
-//! 2. **HTML Meta Tags**: Parse HTML for `<meta charset>` or `<meta http-equiv="Content-Type">`
-//! 3. **Default to UTF-8**: If both fail, use UTF-8 as the default encoding
+//! 2. **XML Declaration**: Check a leading `<?xml ... encoding="..."?>` for XHTML/XML content
+//! 3. **HTML Meta Tags**: Parse HTML for `<meta charset>` or `<meta http-equiv="Content-Type">`
+//! 4. **Default to UTF-8**: If none of the above match, use UTF-8 as the default encoding
+//!
+//! With the `stat_charset_detect` feature enabled, a further stage runs
+//! between 3 and 4: when none of the header, XML declaration, or a meta tag
+//! named a charset and `html` isn't valid UTF-8 on its own,
+//! [`crate::statistical_charset::detect_charset_statistically`] guesses a
+//! legacy single-byte or CJK multibyte encoding instead of defaulting
+//! straight to UTF-8.
+//!
+//! A BOM is authoritative per the HTML spec, since it is a literal marker in
+//! the bytes themselves rather than a claim made by a header or tag, so it
+//! wins over every other stage and short-circuits the rest of the cascade.
+//! Short of that, the Content-Type header intentionally outranks
+//! `<meta charset>`: it reflects what the serving origin actually sent on
+//! the wire, matching the HTML spec's own encoding-sniffing algorithm,
+//! whereas a stale or templated meta tag is the more common source of a
+//! "lying" charset in practice.
+//!
+//! [`extract_charset_from_bom`] recognizes UTF-8, UTF-16LE/BE, and
+//! UTF-32LE/BE BOMs. A leading BOM matters beyond picking the right charset
+//! label: a 16- or 32-bit encoded document can't be meta-prescanned the way
+//! [`extract_charset_from_html`] does, since its ASCII `<meta charset>` bytes
+//! are interleaved with null bytes and won't match as text. The BOM
+//! sidesteps that entirely by settling the charset before any byte-oriented
+//! scanning happens. [`resolve_input_charset`] additionally needs to know how
+//! many leading bytes the BOM occupies (to skip them before parsing), which
+//! [`detect_bom`] reports and [`extract_charset_from_bom`] does not.
+//!
+//! Whatever label a stage finds is passed through [`normalize_charset`],
+//! which validates it against `encoding_rs` and canonicalizes it to the name
+//! the Encoding Standard defines (e.g. `"ISO-8859-1"` becomes
+//! `"windows-1252"`, `"latin1"` likewise). A label `encoding_rs` doesn't
+//! recognize at all doesn't stop the cascade early: that stage is treated as
+//! if it hadn't matched, and detection falls through to the next one.
 //!
 //! # Examples
 //!
 //! ```rust
 //! use nginx_markdown_converter::charset::detect_charset;
 //!
-//! // Detect from Content-Type header
+//! // A BOM outranks everything else.
+//! let charset = detect_charset(Some("text/html; charset=ISO-8859-1"), b"\xEF\xBB\xBF<html>...</html>");
+//! assert_eq!(charset, "UTF-8");
+//!
+//! // Detect from Content-Type header; the label is canonicalized.
 //! let charset = detect_charset(Some("text/html; charset=ISO-8859-1"), b"<html>...</html>");
-//! assert_eq!(charset, "ISO-8859-1");
+//! assert_eq!(charset, "windows-1252");
 //!
 //! // Detect from HTML meta tag
 //! let html = b"<html><head><meta charset=\"UTF-8\"></head></html>";
@@ -39,19 +78,21 @@ const DEFAULT_CHARSET: &str = "UTF-8";
 /// Maximum bytes to scan for meta charset tags (first 1024 bytes)
 const META_SCAN_LIMIT: usize = 1024;
 
-/// Detect character encoding using the three-level cascade
+/// Detect character encoding using the five-level cascade
 ///
 /// This function implements the charset detection cascade specified in
-/// Requirements FR-05.1, FR-05.2, and FR-05.3:
+/// Requirements FR-05.0 through FR-05.3:
 ///
+/// 0. Check for a byte-order mark (FR-05.0)
 /// 1. Check Content-Type header charset parameter (FR-05.1)
-/// 2. Check HTML meta charset tags (FR-05.2)
-/// 3. Default to UTF-8 (FR-05.3)
+/// 2. Check an XML declaration's `encoding` attribute (for XHTML/XML content)
+/// 3. Check HTML meta charset tags (FR-05.2)
+/// 4. Default to UTF-8 (FR-05.3)
 ///
 /// # Arguments
 ///
 /// * `content_type` - Optional Content-Type header value (e.g., "text/html; charset=UTF-8")
-/// * `html` - HTML content bytes to scan for meta charset tags
+/// * `html` - Content bytes to scan for a BOM, an XML declaration, and meta charset tags
 ///
 /// # Returns
 ///
@@ -63,46 +104,491 @@ const META_SCAN_LIMIT: usize = 1024;
 /// ```rust
 /// use nginx_markdown_converter::charset::detect_charset;
 ///
-/// // Priority 1: Content-Type header
+/// // Priority 0: Byte-order mark, even over a conflicting header
+/// let charset = detect_charset(
+///     Some("text/html; charset=ISO-8859-1"),
+///     b"\xEF\xBB\xBF<html>...</html>"
+/// );
+/// assert_eq!(charset, "UTF-8");
+///
+/// // Priority 1: Content-Type header, canonicalized via `normalize_charset`
 /// let charset = detect_charset(
 ///     Some("text/html; charset=ISO-8859-1"),
 ///     b"<html>...</html>"
 /// );
-/// assert_eq!(charset, "ISO-8859-1");
+/// assert_eq!(charset, "windows-1252");
+///
+/// // Priority 2: XML declaration, for XHTML/XML served without a header charset
+/// let xml = b"<?xml version=\"1.0\" encoding=\"Shift_JIS\"?><html></html>";
+/// let charset = detect_charset(Some("application/xhtml+xml"), xml);
+/// assert_eq!(charset, "Shift_JIS");
 ///
-/// // Priority 2: HTML meta tag
+/// // Priority 3: HTML meta tag
 /// let html = b"<html><head><meta charset=\"UTF-8\"></head></html>";
 /// let charset = detect_charset(None, html);
 /// assert_eq!(charset, "UTF-8");
 ///
-/// // Priority 3: Default to UTF-8
+/// // Priority 4: Default to UTF-8
 /// let charset = detect_charset(None, b"<html><body>No charset</body></html>");
 /// assert_eq!(charset, "UTF-8");
+///
+/// // An unrecognized label at one level falls through to the next rather
+/// // than being returned verbatim.
+/// let charset = detect_charset(Some("text/html; charset=not-a-real-charset"), html);
+/// assert_eq!(charset, "UTF-8");
 /// ```
 ///
 /// # Charset Normalization
 ///
-/// The function normalizes charset names to uppercase for consistency:
+/// Every label this cascade finds is validated and canonicalized by
+/// [`normalize_charset`] rather than merely uppercased:
 /// - "utf-8" → "UTF-8"
-/// - "iso-8859-1" → "ISO-8859-1"
-/// - "windows-1252" → "WINDOWS-1252"
+/// - "iso-8859-1" → "windows-1252"
+/// - "latin1" → "windows-1252"
+/// - "shift_jis" → "Shift_JIS"
 pub fn detect_charset(content_type: Option<&str>, html: &[u8]) -> String {
+    // Level 0: Byte-order mark (FR-05.0) — authoritative per the HTML spec,
+    // so it short-circuits the rest of the cascade. The BOM bytes themselves
+    // are never handed to the meta scanner below; a document with a BOM
+    // returns here before Level 3 runs at all.
+    if let Some(charset) = extract_charset_from_bom(html)
+        && let Some(charset) = normalize_charset(charset)
+    {
+        return charset;
+    }
+
     // Level 1: Check Content-Type header charset parameter (FR-05.1)
     if let Some(ct) = content_type
         && let Some(charset) = extract_charset_from_content_type(ct)
+        && let Some(charset) = normalize_charset(&charset)
+    {
+        return charset;
+    }
+
+    // Level 2: Check an XML declaration's `encoding` attribute. This only
+    // matters for XHTML/XML content (`<?xml ... ?>` isn't valid at the start
+    // of a text/html document per the HTML parsing spec), so it's checked
+    // ahead of the HTML-specific meta tag scan rather than instead of it.
+    if let Some(charset) = extract_charset_from_xml_declaration(html)
+        && let Some(charset) = normalize_charset(&charset)
     {
-        return normalize_charset(&charset);
+        return charset;
     }
 
-    // Level 2: Check HTML meta charset tags (FR-05.2)
-    if let Some(charset) = extract_charset_from_html(html) {
-        return normalize_charset(&charset);
+    // Level 3: Check HTML meta charset tags (FR-05.2)
+    if let Some(charset) = extract_charset_from_html(html)
+        && let Some(charset) = normalize_charset(&charset)
+    {
+        return charset;
+    }
+
+    // Between levels 3 and 4: Statistical auto-detection fallback (feature-gated), only
+    // worth trying once the header/meta stages and a direct UTF-8 decode
+    // have all come up empty.
+    #[cfg(feature = "stat_charset_detect")]
+    if std::str::from_utf8(html).is_err()
+        && let Some(charset) = crate::statistical_charset::detect_charset_statistically(html)
+        && let Some(charset) = normalize_charset(charset)
+    {
+        return charset;
     }
 
-    // Level 3: Default to UTF-8 (FR-05.3)
+    // Level 4: Default to UTF-8 (FR-05.3)
     DEFAULT_CHARSET.to_string()
 }
 
+/// Detect a charset, honoring an optional caller-supplied override that
+/// bypasses [`detect_charset`]'s cascade entirely
+///
+/// Lets an operator pin a known-correct encoding for an upstream that
+/// consistently mislabels its content in headers or meta tags, mirroring
+/// the "save with custom charset" workaround a browser offers for the same
+/// problem. `override_label` is validated through [`normalize_charset`]
+/// just like every other tier in this module; an unrecognized override
+/// isn't treated as an error, it simply falls back to
+/// [`detect_charset`]'s normal cascade rather than failing the whole
+/// request over an operator typo.
+///
+/// # Returns
+///
+/// Returns the overridden charset's canonical name when `override_label` is
+/// `Some` and recognized, otherwise [`detect_charset`]'s result.
+///
+/// # Examples
+///
+/// ```rust
+/// use nginx_markdown_converter::charset::detect_charset_with_override;
+///
+/// // The override wins even over a conflicting Content-Type header.
+/// let charset = detect_charset_with_override(
+///     Some("windows-1252"),
+///     Some("text/html; charset=UTF-8"),
+///     b"<html></html>",
+/// );
+/// assert_eq!(charset, "windows-1252");
+///
+/// // An unrecognized override falls back to the normal cascade.
+/// let charset = detect_charset_with_override(
+///     Some("not-a-real-charset"),
+///     Some("text/html; charset=UTF-8"),
+///     b"<html></html>",
+/// );
+/// assert_eq!(charset, "UTF-8");
+/// ```
+pub fn detect_charset_with_override(
+    override_label: Option<&str>,
+    content_type: Option<&str>,
+    html: &[u8],
+) -> String {
+    if let Some(label) = override_label
+        && let Some(charset) = normalize_charset(label)
+    {
+        return charset;
+    }
+
+    detect_charset(content_type, html)
+}
+
+/// Detect `bytes`' charset via [`detect_charset`] and decode it to UTF-8
+///
+/// A convenience wrapper for callers outside [`crate::parser`]'s full
+/// HTML-parsing pipeline who just want decoded text plus the charset that
+/// was used, without driving [`resolve_input_charset`]'s override/BOM-skipping
+/// cascade or [`crate::parser`]'s own incremental, `encoding_rs::Decoder`-based
+/// transcoding. Only [`detect_charset`]'s cascade (BOM, Content-Type, XML
+/// declaration, `<meta charset>`, then a UTF-8 default) decides the label; an
+/// unrecognized label falls back to UTF-8, same as an unrecognized
+/// `<meta charset>` label elsewhere in this module. Malformed byte sequences
+/// under the chosen encoding are replaced with U+FFFD rather than rejected,
+/// matching the rest of this crate's lenient decoding.
+///
+/// # Returns
+///
+/// Returns `(decoded_text, charset_used)`. `charset_used` is the
+/// `encoding_rs` canonical name actually applied, which can differ from
+/// [`detect_charset`]'s label when the Encoding Standard maps it to another
+/// encoding under the hood (e.g. `"ISO-8859-1"` decodes as `"windows-1252"`).
+///
+/// # Examples
+///
+/// ```rust
+/// use nginx_markdown_converter::charset::transcode_to_utf8;
+///
+/// let (text, charset) =
+///     transcode_to_utf8(Some("text/html; charset=windows-1252"), b"Caf\xe9");
+/// assert_eq!(text, "Café");
+/// assert_eq!(charset, "windows-1252");
+///
+/// // An unrecognized label falls back to UTF-8.
+/// let (text, charset) = transcode_to_utf8(Some("text/html; charset=bogus"), b"hello");
+/// assert_eq!(text, "hello");
+/// assert_eq!(charset, "UTF-8");
+/// ```
+pub fn transcode_to_utf8(content_type: Option<&str>, bytes: &[u8]) -> (String, String) {
+    let label = detect_charset(content_type, bytes);
+    let encoding =
+        encoding_rs::Encoding::for_label(label.as_bytes()).unwrap_or(encoding_rs::UTF_8);
+    let (decoded, _had_errors) = encoding.decode_without_bom_handling(bytes);
+    (decoded.into_owned(), encoding.name().to_string())
+}
+
+/// Detect the charset implied by a Unicode byte-order mark at the start of
+/// `bytes`, without reporting how many leading bytes the BOM itself occupies
+///
+/// A thin wrapper around [`detect_bom`] for callers like [`detect_charset`]
+/// that only need the charset name, not where the document's actual content
+/// begins. See [`detect_bom`] for which BOMs are recognized and why the
+/// four-byte UTF-32 patterns are checked ahead of the two-byte UTF-16 ones.
+///
+/// # Examples
+///
+/// ```rust
+/// use nginx_markdown_converter::charset::extract_charset_from_bom;
+///
+/// assert_eq!(extract_charset_from_bom(b"\xEF\xBB\xBF<html>"), Some("UTF-8"));
+/// assert_eq!(extract_charset_from_bom(b"<html>"), None);
+/// ```
+pub fn extract_charset_from_bom(bytes: &[u8]) -> Option<&'static str> {
+    detect_bom(bytes).map(|(charset, _bom_len)| charset)
+}
+
+/// Detect a Unicode byte-order mark at the start of `bytes`
+///
+/// Recognizes the UTF-8, UTF-16LE, UTF-16BE, UTF-32LE, and UTF-32BE BOMs. A
+/// BOM is a stronger signal than any label-based sniffing, since it is a
+/// literal marker present in the bytes themselves rather than a claim made
+/// by a header or meta tag.
+///
+/// The UTF-32LE BOM (`FF FE 00 00`) shares its first two bytes with the
+/// UTF-16LE BOM (`FF FE`), so the four-byte patterns are checked first;
+/// `encoding_rs` doesn't support UTF-32 (it isn't part of the Encoding
+/// Standard browsers implement), so [`crate::parser`] decodes it directly
+/// rather than through `encoding_rs::Encoding`.
+///
+/// # Returns
+///
+/// Returns `Some((charset, bom_len))` with the implied charset name and the
+/// number of leading bytes the BOM occupies, or `None` if no recognized BOM
+/// is present.
+///
+/// # Examples
+///
+/// ```rust
+/// use nginx_markdown_converter::charset::detect_bom;
+///
+/// assert_eq!(detect_bom(b"\xEF\xBB\xBF<html>"), Some(("UTF-8", 3)));
+/// assert_eq!(detect_bom(b"<html>"), None);
+/// ```
+pub fn detect_bom(bytes: &[u8]) -> Option<(&'static str, usize)> {
+    if bytes.starts_with(&[0xFF, 0xFE, 0x00, 0x00]) {
+        return Some(("UTF-32LE", 4));
+    }
+    if bytes.starts_with(&[0x00, 0x00, 0xFE, 0xFF]) {
+        return Some(("UTF-32BE", 4));
+    }
+
+    encoding_rs::Encoding::for_bom(bytes).map(|(encoding, len)| (encoding.name(), len))
+}
+
+/// Resolve the charset to decode with, plus the BOM byte length to skip
+///
+/// Extends [`detect_charset`]'s cascade with an explicit caller-provided
+/// override and BOM sniffing, in priority order:
+///
+/// 1. `input_charset` override, when given (wins over every sniffing signal)
+/// 2. A BOM at the start of `html` (FR-05.0)
+/// 3. Content-Type header charset parameter (FR-05.1)
+/// 4. An XML declaration's `encoding` attribute
+/// 5. HTML meta charset tags (FR-05.2)
+/// 6. Statistical auto-detection, with the `stat_charset_detect` feature enabled (FR-05.4)
+/// 7. Default to UTF-8 (FR-05.3)
+///
+/// Every tier's label, including an `input_charset` override, is validated
+/// and canonicalized by [`normalize_charset`] before being returned; one
+/// `encoding_rs` doesn't recognize falls through to the next tier rather
+/// than winning by default, so an override can never make this function
+/// emit an unusable charset.
+///
+/// # Returns
+///
+/// Returns `(charset, bom_len)`. `bom_len` is always `0` unless the charset
+/// was resolved from a detected BOM, in which case it is the BOM's byte
+/// length and those leading bytes must be skipped before decoding.
+pub fn resolve_input_charset(
+    input_charset: Option<&str>,
+    content_type: Option<&str>,
+    html: &[u8],
+) -> (String, usize) {
+    if let Some(charset) = input_charset
+        && let Some(charset) = normalize_charset(charset)
+    {
+        return (charset, 0);
+    }
+
+    if let Some((charset, bom_len)) = detect_bom(html)
+        && let Some(charset) = normalize_charset(charset)
+    {
+        return (charset, bom_len);
+    }
+
+    (detect_charset(content_type, html), 0)
+}
+
+/// Resolve the charset to decode with, same priority as
+/// [`resolve_input_charset`], but also reports whether the result came from
+/// the feature-gated statistical guesser rather than a declared source, and
+/// lets a caller disable that guesser or bias it with a TLD/locale hint.
+///
+/// Everything through `<meta charset>` is identical to
+/// [`resolve_input_charset`]. The difference is the statistical stage: when
+/// `allow_statistical_detection` is `true` (and the `stat_charset_detect`
+/// feature is enabled) and `html` isn't valid UTF-8 on its own,
+/// [`crate::statistical_charset::detect_charset_statistically_with_hint`]
+/// guesses a legacy encoding using `tld_hint` to bias ambiguous cases,
+/// instead of defaulting straight to UTF-8. Passing
+/// `allow_statistical_detection: false` (or building without the feature)
+/// skips that stage entirely, matching [`resolve_input_charset`]'s UTF-8
+/// default for undeclared, non-UTF-8 input.
+///
+/// # Returns
+///
+/// Returns `(charset, bom_len, was_guessed)`. `was_guessed` is `true` only
+/// when the statistical fallback supplied the charset; it is always `false`
+/// for a declared (override/BOM/header/meta) or defaulted charset.
+///
+/// # Examples
+///
+/// ```rust
+/// use nginx_markdown_converter::charset::resolve_input_charset_with_detection;
+///
+/// // A declared charset is never reported as guessed, and is canonicalized
+/// // the same way `detect_charset` would canonicalize it.
+/// let (charset, _bom_len, was_guessed) =
+///     resolve_input_charset_with_detection(None, Some("text/html; charset=ISO-8859-1"), b"", true, None);
+/// assert_eq!(charset, "windows-1252");
+/// assert!(!was_guessed);
+/// ```
+pub fn resolve_input_charset_with_detection(
+    input_charset: Option<&str>,
+    content_type: Option<&str>,
+    html: &[u8],
+    allow_statistical_detection: bool,
+    tld_hint: Option<&str>,
+) -> (String, usize, bool) {
+    if let Some(charset) = input_charset
+        && let Some(charset) = normalize_charset(charset)
+    {
+        return (charset, 0, false);
+    }
+
+    if let Some((charset, bom_len)) = detect_bom(html)
+        && let Some(charset) = normalize_charset(charset)
+    {
+        return (charset, bom_len, false);
+    }
+
+    if let Some(ct) = content_type
+        && let Some(charset) = extract_charset_from_content_type(ct)
+        && let Some(charset) = normalize_charset(&charset)
+    {
+        return (charset, 0, false);
+    }
+
+    if let Some(charset) = extract_charset_from_xml_declaration(html)
+        && let Some(charset) = normalize_charset(&charset)
+    {
+        return (charset, 0, false);
+    }
+
+    if let Some(charset) = extract_charset_from_html(html)
+        && let Some(charset) = normalize_charset(&charset)
+    {
+        return (charset, 0, false);
+    }
+
+    #[cfg(feature = "stat_charset_detect")]
+    if allow_statistical_detection
+        && std::str::from_utf8(html).is_err()
+        && let Some(charset) =
+            crate::statistical_charset::detect_charset_statistically_with_hint(html, tld_hint)
+        && let Some(charset) = normalize_charset(charset)
+    {
+        return (charset, 0, true);
+    }
+
+    #[cfg(not(feature = "stat_charset_detect"))]
+    {
+        let _ = (allow_statistical_detection, tld_hint);
+    }
+
+    (DEFAULT_CHARSET.to_string(), 0, false)
+}
+
+/// Resolve a Windows numeric codepage to its `encoding_rs` equivalent
+///
+/// Some callers (notably anything that went through a Windows API, or a
+/// document format that stores `charset` as a numeric codepage rather than
+/// an IANA label) only have a codepage number, not a string like
+/// `"windows-1252"`. This maps the common ones to the encoding the Encoding
+/// Standard already defines for them.
+///
+/// Two codepage ranges are deliberately *not* mapped, returning `None` so
+/// the caller falls through to the rest of the charset cascade instead of
+/// decoding with the wrong encoding:
+///
+/// - Codepage 1 nominally means EBCDIC, which isn't part of the Encoding
+///   Standard and has no `encoding_rs` equivalent at all.
+/// - Codepages 2 and 3 nominally mean "7-bit ASCII" and "8-bit ASCII", but
+///   data tagged with either in practice is frequently not actually ASCII;
+///   treating them as a hard override would silently mis-decode it.
+///
+/// # Examples
+///
+/// ```rust
+/// use nginx_markdown_converter::charset::encoding_for_codepage;
+///
+/// assert_eq!(encoding_for_codepage(1252).map(|e| e.name()), Some("windows-1252"));
+/// assert_eq!(encoding_for_codepage(936).map(|e| e.name()), Some("GBK"));
+/// assert_eq!(encoding_for_codepage(1), None);
+/// assert_eq!(encoding_for_codepage(2), None);
+/// ```
+pub fn encoding_for_codepage(codepage: u32) -> Option<&'static encoding_rs::Encoding> {
+    match codepage {
+        1 => None,
+        2 | 3 => None,
+        874 => Some(encoding_rs::WINDOWS_874),
+        932 => Some(encoding_rs::SHIFT_JIS),
+        936 => Some(encoding_rs::GBK),
+        949 => Some(encoding_rs::EUC_KR),
+        950 => Some(encoding_rs::BIG5),
+        1200 => Some(encoding_rs::UTF_16LE),
+        1201 => Some(encoding_rs::UTF_16BE),
+        1250 => Some(encoding_rs::WINDOWS_1250),
+        1251 => Some(encoding_rs::WINDOWS_1251),
+        1252 => Some(encoding_rs::WINDOWS_1252),
+        1253 => Some(encoding_rs::WINDOWS_1253),
+        1254 => Some(encoding_rs::WINDOWS_1254),
+        1255 => Some(encoding_rs::WINDOWS_1255),
+        1256 => Some(encoding_rs::WINDOWS_1256),
+        1257 => Some(encoding_rs::WINDOWS_1257),
+        1258 => Some(encoding_rs::WINDOWS_1258),
+        20866 => Some(encoding_rs::KOI8_R),
+        21866 => Some(encoding_rs::KOI8_U),
+        // ISO-8859-1's label resolves to windows-1252 per the Encoding
+        // Standard (the two agree outside the C1 control range), matching
+        // `encoding_rs::Encoding::for_label("ISO-8859-1")`.
+        28591 => Some(encoding_rs::WINDOWS_1252),
+        28592 => Some(encoding_rs::ISO_8859_2),
+        28595 => Some(encoding_rs::ISO_8859_5),
+        28597 => Some(encoding_rs::ISO_8859_7),
+        28605 => Some(encoding_rs::ISO_8859_15),
+        65001 => Some(encoding_rs::UTF_8),
+        _ => None,
+    }
+}
+
+/// Resolve the charset to decode with, same cascade as [`resolve_input_charset`],
+/// but also accepting a Windows numeric codepage as an alternative to a
+/// string `input_charset` label.
+///
+/// `codepage` is folded into the same priority tier as `input_charset` via
+/// [`encoding_for_codepage`]: if both are given, the string label wins (it's
+/// the more explicit, human-auditable signal); if only `codepage` is given
+/// and it resolves to a known encoding, it overrides BOM/header/meta
+/// sniffing exactly like `input_charset` would. An unmapped codepage (see
+/// [`encoding_for_codepage`]) is treated as if it hadn't been supplied at
+/// all, falling through to [`resolve_input_charset`]'s normal cascade.
+///
+/// # Returns
+///
+/// Returns `(charset, bom_len)`, same shape as [`resolve_input_charset`].
+///
+/// # Examples
+///
+/// ```rust
+/// use nginx_markdown_converter::charset::resolve_input_charset_with_codepage;
+///
+/// let html = b"<html><head><meta charset=\"UTF-8\"></head></html>";
+/// let (charset, bom_len) = resolve_input_charset_with_codepage(None, Some(936), None, html);
+/// assert_eq!(charset, "GBK");
+/// assert_eq!(bom_len, 0);
+/// ```
+pub fn resolve_input_charset_with_codepage(
+    input_charset: Option<&str>,
+    codepage: Option<u32>,
+    content_type: Option<&str>,
+    html: &[u8],
+) -> (String, usize) {
+    if input_charset.is_none()
+        && let Some(encoding) = codepage.and_then(encoding_for_codepage)
+        && let Some(charset) = normalize_charset(encoding.name())
+    {
+        return (charset, 0);
+    }
+
+    resolve_input_charset(input_charset, content_type, html)
+}
+
 /// Extract charset from Content-Type header
 ///
 /// Parses the Content-Type header for a charset parameter.
@@ -156,9 +642,74 @@ pub fn extract_charset_from_content_type(content_type: &str) -> Option<String> {
         .map(|m| m.as_str().to_string())
 }
 
+/// Extract the `encoding` attribute from a leading XML declaration
+///
+/// Matches `<?xml version="1.0" encoding="..."?>` within the first
+/// [`META_SCAN_LIMIT`] bytes of `bytes`, the same window [`extract_charset_from_html`]
+/// scans. This is the XHTML/XML counterpart to that HTML-specific meta-tag
+/// scan: a document served as `application/xhtml+xml` or `text/xml` declares
+/// its encoding in the XML prolog rather than (or in addition to) an HTML
+/// `<meta charset>` tag.
+///
+/// # Arguments
+///
+/// * `bytes` - Document content bytes to scan for a leading XML declaration
+///
+/// # Returns
+///
+/// Returns `Some(charset)` if an `encoding` attribute is found, `None`
+/// otherwise.
+///
+/// # Examples
+///
+/// ```rust
+/// use nginx_markdown_converter::charset::extract_charset_from_xml_declaration;
+///
+/// assert_eq!(
+///     extract_charset_from_xml_declaration(b"<?xml version=\"1.0\" encoding=\"Shift_JIS\"?><html/>"),
+///     Some("Shift_JIS".to_string())
+/// );
+///
+/// assert_eq!(
+///     extract_charset_from_xml_declaration(b"<html><body>No declaration</body></html>"),
+///     None
+/// );
+/// ```
+pub fn extract_charset_from_xml_declaration(bytes: &[u8]) -> Option<String> {
+    static XML_ENCODING_REGEX: OnceLock<Option<Regex>> = OnceLock::new();
+    let regex = XML_ENCODING_REGEX.get_or_init(|| {
+        Regex::new(r#"(?i)<\?xml\s+[^>]*encoding\s*=\s*["']([^"']+)["']"#).ok()
+    });
+    let regex = regex.as_ref()?;
+
+    let limit = std::cmp::min(bytes.len(), META_SCAN_LIMIT);
+    let text = String::from_utf8_lossy(&bytes[..limit]);
+
+    regex
+        .captures(&text)
+        .and_then(|caps| caps.get(1))
+        .map(|m| m.as_str().to_string())
+}
+
 /// Extract charset from HTML meta tags
 ///
-/// Scans the HTML content for charset declarations in meta tags.
+/// Runs the WHATWG "prescan a byte stream to determine its encoding"
+/// algorithm over the first 1024 bytes: `<!-- -->` comments are skipped as a
+/// unit, `<meta>` tags are parsed attribute-by-attribute, every other tag's
+/// attributes are consumed and ignored, and a bare `<` that doesn't form a
+/// recognized construct is just another text byte. This makes the scan
+/// immune to a charset-looking string that happens to appear inside a
+/// comment, an unrelated attribute value, or running text, which a plain
+/// substring/regex search over the same bytes can't distinguish.
+///
+/// The scan walks `<meta>` tags in source order and returns the *first*
+/// one that supplies a recognized charset, whichever syntax it uses — a
+/// `<meta http-equiv="Content-Type">` earlier in the document wins over a
+/// `<meta charset>` later in it, matching how a browser's prescan would
+/// read the same markup. A `<meta>` that doesn't resolve to a supported
+/// encoding (an unrecognized label, or a `content` attribute with no
+/// matching `http-equiv` pragma) is skipped rather than ending the scan, so
+/// a later tag still gets a chance.
 ///
 /// # Supported Formats
 ///
@@ -171,7 +722,8 @@ pub fn extract_charset_from_content_type(content_type: &str) -> Option<String> {
 ///
 /// # Returns
 ///
-/// Returns `Some(charset)` if found, `None` otherwise.
+/// Returns `Some(charset)` if a `<meta>` tag supplies a recognized charset
+/// label (per [`encoding_rs::Encoding::for_label`]), `None` otherwise.
 ///
 /// # Performance
 ///
@@ -196,67 +748,319 @@ pub fn extract_charset_from_content_type(content_type: &str) -> Option<String> {
 /// assert_eq!(extract_charset_from_html(html), None);
 /// ```
 pub fn extract_charset_from_html(html: &[u8]) -> Option<String> {
-    // Only scan the first META_SCAN_LIMIT bytes for performance
-    let scan_limit = std::cmp::min(html.len(), META_SCAN_LIMIT);
-    let html_prefix = &html[..scan_limit];
+    prescan_byte_stream(html)
+}
+
+/// An attribute, the end of a tag, or end-of-input, as returned by [`get_attribute`]
+enum AttrOutcome {
+    Attribute { name: String, value: Vec<u8> },
+    TagEnd,
+    Eof,
+}
 
-    // Convert to string for regex matching (lossy conversion is OK for meta tag detection)
-    let html_str = String::from_utf8_lossy(html_prefix);
+fn is_tag_whitespace(b: u8) -> bool {
+    matches!(b, b'\t' | b'\n' | 0x0C | b'\r' | b' ')
+}
 
-    // Try HTML5 meta charset format first
-    static HTML5_REGEX: OnceLock<Option<Regex>> = OnceLock::new();
-    let html5_regex =
-        HTML5_REGEX.get_or_init(|| Regex::new(r#"(?i)<meta\s+charset\s*=\s*"?([^";>\s]+)"?"#).ok());
-    let html5_regex = html5_regex.as_ref()?;
+fn starts_with_ci(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.len() >= needle.len() && haystack[..needle.len()].eq_ignore_ascii_case(needle)
+}
 
-    if let Some(caps) = html5_regex.captures(&html_str)
-        && let Some(m) = caps.get(1)
-    {
-        return Some(m.as_str().to_string());
+fn find_subslice(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// WHATWG "get an attribute" step: consume one name/value pair (or the tag's
+/// closing `>`, or end-of-input) starting at `*pos`, advancing `*pos` past it
+fn get_attribute(bytes: &[u8], pos: &mut usize) -> AttrOutcome {
+    while *pos < bytes.len() && (is_tag_whitespace(bytes[*pos]) || bytes[*pos] == b'/') {
+        *pos += 1;
+    }
+    if *pos >= bytes.len() {
+        return AttrOutcome::Eof;
+    }
+    if bytes[*pos] == b'>' {
+        *pos += 1;
+        return AttrOutcome::TagEnd;
     }
 
-    // Try HTML4 meta http-equiv format
-    static HTML4_REGEX: OnceLock<Option<Regex>> = OnceLock::new();
-    let html4_regex = HTML4_REGEX.get_or_init(|| {
-        Regex::new(
-            r#"(?i)<meta\s+http-equiv\s*=\s*"?Content-Type"?\s+content\s*=\s*"?[^">]*charset\s*=\s*([^";>\s]+)"?"#,
-        )
-        .ok()
-    });
-    let html4_regex = html4_regex.as_ref()?;
+    let name_start = *pos;
+    while *pos < bytes.len() {
+        let b = bytes[*pos];
+        if is_tag_whitespace(b) || b == b'/' || b == b'>' || b == b'=' {
+            break;
+        }
+        *pos += 1;
+    }
+    let name = String::from_utf8_lossy(&bytes[name_start..*pos]).to_ascii_lowercase();
+
+    while *pos < bytes.len() && is_tag_whitespace(bytes[*pos]) {
+        *pos += 1;
+    }
+
+    if *pos >= bytes.len() || bytes[*pos] != b'=' {
+        return AttrOutcome::Attribute {
+            name,
+            value: Vec::new(),
+        };
+    }
+    *pos += 1;
+    while *pos < bytes.len() && is_tag_whitespace(bytes[*pos]) {
+        *pos += 1;
+    }
+    if *pos >= bytes.len() {
+        return AttrOutcome::Attribute {
+            name,
+            value: Vec::new(),
+        };
+    }
+
+    let value = if bytes[*pos] == b'"' || bytes[*pos] == b'\'' {
+        let quote = bytes[*pos];
+        *pos += 1;
+        let value_start = *pos;
+        while *pos < bytes.len() && bytes[*pos] != quote {
+            *pos += 1;
+        }
+        let value = bytes[value_start..*pos].to_vec();
+        if *pos < bytes.len() {
+            *pos += 1; // consume the closing quote
+        }
+        value
+    } else {
+        let value_start = *pos;
+        while *pos < bytes.len() && !is_tag_whitespace(bytes[*pos]) && bytes[*pos] != b'>' {
+            *pos += 1;
+        }
+        bytes[value_start..*pos].to_vec()
+    };
+
+    AttrOutcome::Attribute { name, value }
+}
+
+/// Consume and discard a non-`<meta>` tag's attribute list, up to its `>` or end-of-input
+fn skip_tag_attributes(bytes: &[u8], pos: &mut usize) {
+    loop {
+        match get_attribute(bytes, pos) {
+            AttrOutcome::Attribute { .. } => continue,
+            AttrOutcome::TagEnd | AttrOutcome::Eof => return,
+        }
+    }
+}
+
+/// WHATWG "algorithm for extracting a character encoding from a meta element":
+/// find a `charset=VALUE` token inside a `content` attribute's value
+fn extract_encoding_from_meta_content(value: &[u8]) -> Option<String> {
+    let charset_pos = value
+        .windows(7)
+        .position(|w| w.eq_ignore_ascii_case(b"charset"))?;
+    let mut pos = charset_pos + 7;
 
-    if let Some(caps) = html4_regex.captures(&html_str)
-        && let Some(m) = caps.get(1)
+    while pos < value.len() && is_tag_whitespace(value[pos]) {
+        pos += 1;
+    }
+    if pos >= value.len() || value[pos] != b'=' {
+        return None;
+    }
+    pos += 1;
+    while pos < value.len() && is_tag_whitespace(value[pos]) {
+        pos += 1;
+    }
+    if pos >= value.len() {
+        return None;
+    }
+
+    let label = if value[pos] == b'"' || value[pos] == b'\'' {
+        let quote = value[pos];
+        pos += 1;
+        let start = pos;
+        while pos < value.len() && value[pos] != quote {
+            pos += 1;
+        }
+        &value[start..pos]
+    } else {
+        let start = pos;
+        while pos < value.len() && !is_tag_whitespace(value[pos]) && value[pos] != b';' {
+            pos += 1;
+        }
+        &value[start..pos]
+    };
+
+    if label.is_empty() {
+        None
+    } else {
+        Some(String::from_utf8_lossy(label).to_string())
+    }
+}
+
+/// Map a raw meta-declared label to its spec-mandated override, matching the
+/// WHATWG prescan algorithm's final step before returning a charset
+///
+/// `x-user-defined` maps to `windows-1252` (the two agree on every byte
+/// value it actually maps), and any spelling of UTF-16 (bare `"UTF-16"` as
+/// well as the explicit `"UTF-16BE"`/`"UTF-16LE"`) maps to UTF-8: per the
+/// spec, a document that declares itself UTF-16 but doesn't have a BOM to
+/// back it up is far more likely mislabeled ASCII/UTF-8 than an actual
+/// 16-bit encoded document, so treating the declaration at face value would
+/// decode the whole thing into garbage.
+fn apply_encoding_label_overrides(label: &str) -> String {
+    if label.eq_ignore_ascii_case("x-user-defined") {
+        return "windows-1252".to_string();
+    }
+    if label.eq_ignore_ascii_case("UTF-16BE")
+        || label.eq_ignore_ascii_case("UTF-16LE")
+        || label.eq_ignore_ascii_case("UTF-16")
     {
-        return Some(m.as_str().to_string());
+        return "UTF-8".to_string();
+    }
+    label.to_string()
+}
+
+/// WHATWG "prescan a byte stream to determine its encoding", over the first
+/// [`META_SCAN_LIMIT`] bytes of `html`
+fn prescan_byte_stream(html: &[u8]) -> Option<String> {
+    let limit = std::cmp::min(html.len(), META_SCAN_LIMIT);
+    let bytes = &html[..limit];
+    let mut pos = 0usize;
+
+    while pos < bytes.len() {
+        if starts_with_ci(&bytes[pos..], b"<!--") {
+            pos = match find_subslice(&bytes[pos + 4..], b"-->") {
+                Some(offset) => pos + 4 + offset + 3,
+                None => bytes.len(),
+            };
+            continue;
+        }
+
+        if starts_with_ci(&bytes[pos..], b"<meta")
+            && bytes
+                .get(pos + 5)
+                .is_some_and(|&b| is_tag_whitespace(b) || b == b'/' || b == b'>')
+        {
+            pos += 5;
+            let mut got_pragma = false;
+            let mut need_pragma: Option<bool> = None;
+            let mut charset: Option<String> = None;
+
+            loop {
+                match get_attribute(bytes, &mut pos) {
+                    AttrOutcome::TagEnd | AttrOutcome::Eof => break,
+                    AttrOutcome::Attribute { name, value } => match name.as_str() {
+                        "http-equiv" => {
+                            if value.eq_ignore_ascii_case(b"content-type") {
+                                got_pragma = true;
+                            }
+                        }
+                        "content" => {
+                            if charset.is_none()
+                                && let Some(extracted) = extract_encoding_from_meta_content(&value)
+                            {
+                                charset = Some(extracted);
+                                need_pragma = Some(true);
+                            }
+                        }
+                        "charset" => {
+                            charset = Some(String::from_utf8_lossy(&value).to_string());
+                            need_pragma = Some(false);
+                        }
+                        _ => {}
+                    },
+                }
+            }
+
+            let pragma_satisfied = !need_pragma.unwrap_or(true) || got_pragma;
+            if let Some(label) = charset
+                && pragma_satisfied
+                && encoding_rs::Encoding::for_label(label.as_bytes()).is_some()
+            {
+                return Some(apply_encoding_label_overrides(&label));
+            }
+
+            continue;
+        }
+
+        if bytes[pos] == b'<'
+            && bytes
+                .get(pos + 1)
+                .is_some_and(|&b| b.is_ascii_alphabetic())
+        {
+            pos += 1;
+            while pos < bytes.len() && !is_tag_whitespace(bytes[pos]) && bytes[pos] != b'>' {
+                pos += 1;
+            }
+            skip_tag_attributes(bytes, &mut pos);
+            continue;
+        }
+
+        if starts_with_ci(&bytes[pos..], b"</")
+            && bytes
+                .get(pos + 2)
+                .is_some_and(|&b| b.is_ascii_alphabetic())
+        {
+            pos += 2;
+            while pos < bytes.len() && !is_tag_whitespace(bytes[pos]) && bytes[pos] != b'>' {
+                pos += 1;
+            }
+            skip_tag_attributes(bytes, &mut pos);
+            continue;
+        }
+
+        if bytes[pos] == b'<'
+            && bytes
+                .get(pos + 1)
+                .is_some_and(|&b| b == b'!' || b == b'/' || b == b'?')
+        {
+            pos = match find_subslice(&bytes[pos..], b">") {
+                Some(offset) => pos + offset + 1,
+                None => bytes.len(),
+            };
+            continue;
+        }
+
+        pos += 1;
     }
 
     None
 }
 
-/// Normalize charset name to uppercase
+/// Validate a detected/declared charset label and canonicalize it to the
+/// name `encoding_rs` uses for it
 ///
-/// Converts charset names to uppercase for consistency.
+/// Unlike a plain uppercase pass, this resolves aliases ("latin1",
+/// "csISOLatin1", "utf8", ...) through
+/// [`encoding_rs::Encoding::for_label_no_replacement`] to the single
+/// canonical name the Encoding Standard defines for them (e.g.
+/// "windows-1252", "Shift_JIS"), so every charset a caller like
+/// [`detect_charset`] hands back is guaranteed to be decodable. The
+/// `_no_replacement` variant is used instead of
+/// [`encoding_rs::Encoding::for_label`] so a handful of labels reserved for
+/// security-sensitive contexts (e.g. "replacement", "iso-2022-kr") aren't
+/// silently accepted here.
 ///
 /// # Arguments
 ///
-/// * `charset` - Charset name to normalize
+/// * `charset` - Charset label to validate and canonicalize
 ///
 /// # Returns
 ///
-/// Returns the normalized charset name in uppercase.
+/// Returns `Some(canonical_name)` when `charset` names a recognized
+/// encoding, or `None` when it doesn't, so a cascade like
+/// [`detect_charset`]'s can fall through to its next level rather than
+/// emitting an unusable charset.
 ///
 /// # Examples
 ///
 /// ```rust
 /// use nginx_markdown_converter::charset::normalize_charset;
 ///
-/// assert_eq!(normalize_charset("utf-8"), "UTF-8");
-/// assert_eq!(normalize_charset("ISO-8859-1"), "ISO-8859-1");
-/// assert_eq!(normalize_charset("windows-1252"), "WINDOWS-1252");
+/// assert_eq!(normalize_charset("utf-8"), Some("UTF-8".to_string()));
+/// assert_eq!(normalize_charset("latin1"), Some("windows-1252".to_string()));
+/// assert_eq!(normalize_charset("csISOLatin1"), Some("windows-1252".to_string()));
+/// assert_eq!(normalize_charset("not-a-real-charset"), None);
 /// ```
-pub fn normalize_charset(charset: &str) -> String {
-    charset.to_uppercase()
+pub fn normalize_charset(charset: &str) -> Option<String> {
+    encoding_rs::Encoding::for_label_no_replacement(charset.as_bytes())
+        .map(|encoding| encoding.name().to_string())
 }
 
 #[cfg(test)]
@@ -334,6 +1138,56 @@ mod tests {
         assert_eq!(extract_charset_from_content_type(""), None);
     }
 
+    // ============================================================================
+    // Unit Tests for XML Declaration Charset Extraction
+    // ============================================================================
+
+    #[test]
+    fn test_extract_charset_from_xml_declaration_basic() {
+        let xml = b"<?xml version=\"1.0\" encoding=\"UTF-8\"?><html/>";
+        assert_eq!(
+            extract_charset_from_xml_declaration(xml),
+            Some("UTF-8".to_string())
+        );
+    }
+
+    #[test]
+    fn test_extract_charset_from_xml_declaration_single_quotes() {
+        let xml = b"<?xml version='1.0' encoding='Shift_JIS'?><html/>";
+        assert_eq!(
+            extract_charset_from_xml_declaration(xml),
+            Some("Shift_JIS".to_string())
+        );
+    }
+
+    #[test]
+    fn test_extract_charset_from_xml_declaration_encoding_before_version() {
+        let xml = b"<?xml encoding=\"GB2312\" version=\"1.0\"?><html/>";
+        assert_eq!(
+            extract_charset_from_xml_declaration(xml),
+            Some("GB2312".to_string())
+        );
+    }
+
+    #[test]
+    fn test_extract_charset_from_xml_declaration_no_declaration() {
+        let html = b"<html><body>No declaration</body></html>";
+        assert_eq!(extract_charset_from_xml_declaration(html), None);
+    }
+
+    #[test]
+    fn test_extract_charset_from_xml_declaration_no_encoding_attribute() {
+        let xml = b"<?xml version=\"1.0\"?><html/>";
+        assert_eq!(extract_charset_from_xml_declaration(xml), None);
+    }
+
+    #[test]
+    fn test_extract_charset_from_xml_declaration_beyond_scan_limit() {
+        let mut xml = vec![b' '; META_SCAN_LIMIT + 100];
+        xml.extend_from_slice(b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>");
+        assert_eq!(extract_charset_from_xml_declaration(&xml), None);
+    }
+
     // ============================================================================
     // Unit Tests for HTML Meta Charset Extraction
     // ============================================================================
@@ -400,10 +1254,109 @@ mod tests {
         assert_eq!(extract_charset_from_html(&html), None);
     }
 
+    #[test]
+    fn test_extract_charset_from_html_ignores_charset_looking_text_in_comment() {
+        // A substring search would be fooled by this; the prescan state
+        // machine skips comments as a unit.
+        let html = b"<!-- <meta charset=\"ISO-8859-1\"> --><meta charset=\"UTF-8\">";
+        assert_eq!(extract_charset_from_html(html), Some("UTF-8".to_string()));
+    }
+
+    #[test]
+    fn test_extract_charset_from_html_ignores_charset_in_unrelated_attribute() {
+        // An `alt` attribute that happens to contain "charset=..." must not
+        // be mistaken for a meta declaration.
+        let html = b"<img alt=\"charset=ISO-8859-1\"><meta charset=\"UTF-8\">";
+        assert_eq!(extract_charset_from_html(html), Some("UTF-8".to_string()));
+    }
+
+    #[test]
+    fn test_extract_charset_from_html_http_equiv_after_content() {
+        // Attribute order shouldn't matter: http-equiv after content is just
+        // as valid as the conventional content-after-http-equiv order.
+        let html = b"<meta content=\"text/html; charset=ISO-8859-1\" http-equiv=\"Content-Type\">";
+        assert_eq!(
+            extract_charset_from_html(html),
+            Some("ISO-8859-1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_extract_charset_from_html_content_without_http_equiv_is_ignored() {
+        // A `content` attribute naming a charset, without a matching
+        // `http-equiv="Content-Type"` on the same tag, doesn't count.
+        let html = b"<meta name=\"description\" content=\"charset=ISO-8859-1\"><meta charset=\"UTF-8\">";
+        assert_eq!(extract_charset_from_html(html), Some("UTF-8".to_string()));
+    }
+
+    #[test]
+    fn test_extract_charset_from_html_http_equiv_meta_wins_when_it_comes_first() {
+        // Document order decides, not syntax: an earlier http-equiv pragma
+        // wins over a later HTML5-style meta charset.
+        let html = b"<meta http-equiv=\"Content-Type\" content=\"text/html; charset=ISO-8859-1\"><meta charset=\"UTF-8\">";
+        assert_eq!(
+            extract_charset_from_html(html),
+            Some("ISO-8859-1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_extract_charset_from_html_utf16_label_overridden_to_utf8() {
+        // A bare "UTF-16" declaration (no BOM to back it up) is far more
+        // likely mislabeled ASCII/UTF-8 than an actual 16-bit document.
+        let html = b"<meta charset=\"UTF-16\">";
+        assert_eq!(extract_charset_from_html(html), Some("UTF-8".to_string()));
+    }
+
+    #[test]
+    fn test_extract_charset_from_html_x_user_defined_overridden_to_windows_1252() {
+        let html = b"<meta charset=\"x-user-defined\">";
+        assert_eq!(
+            extract_charset_from_html(html),
+            Some("windows-1252".to_string())
+        );
+    }
+
+    #[test]
+    fn test_extract_charset_from_html_unrecognized_label_is_skipped() {
+        // An unrecognized charset label doesn't satisfy `for_label`, so the
+        // scan continues past this tag to find the next declaration.
+        let html = b"<meta charset=\"not-a-real-charset\"><meta charset=\"UTF-8\">";
+        assert_eq!(extract_charset_from_html(html), Some("UTF-8".to_string()));
+    }
+
+    #[test]
+    fn test_extract_charset_from_html_skips_unrelated_tags() {
+        let html = b"<html><head><title>Test</title><script>var x = 1;</script><meta charset=\"UTF-8\"></head></html>";
+        assert_eq!(extract_charset_from_html(html), Some("UTF-8".to_string()));
+    }
+
+    #[test]
+    fn test_extract_charset_from_html_bogus_comment_is_skipped() {
+        // `<!doctype ...>` isn't a real comment but is skipped the same way
+        // up to its closing `>`.
+        let html = b"<!doctype html><meta charset=\"UTF-8\">";
+        assert_eq!(extract_charset_from_html(html), Some("UTF-8".to_string()));
+    }
+
     // ============================================================================
     // Unit Tests for Charset Detection Cascade
     // ============================================================================
 
+    #[test]
+    fn test_detect_charset_bom_wins_over_content_type_and_meta() {
+        let html = b"\xEF\xBB\xBF<html><head><meta charset=\"ISO-8859-1\"></head></html>";
+        let charset = detect_charset(Some("text/html; charset=GBK"), html);
+        assert_eq!(charset, "UTF-8");
+    }
+
+    #[test]
+    fn test_detect_charset_bom_utf16le_wins_over_content_type() {
+        let html = b"\xFF\xFE<html>";
+        let charset = detect_charset(Some("text/html; charset=UTF-8"), html);
+        assert_eq!(charset, "UTF-16LE");
+    }
+
     #[test]
     fn test_detect_charset_priority_content_type() {
         // Content-Type should take priority over HTML meta tag
@@ -417,7 +1370,22 @@ mod tests {
         // Should use HTML meta tag when Content-Type has no charset
         let html = b"<html><head><meta charset=\"ISO-8859-1\"></head></html>";
         let charset = detect_charset(Some("text/html"), html);
-        assert_eq!(charset, "ISO-8859-1");
+        // ISO-8859-1 resolves to windows-1252 under the Encoding Standard.
+        assert_eq!(charset, "windows-1252");
+    }
+
+    #[test]
+    fn test_detect_charset_xml_declaration_wins_over_meta_tag() {
+        let xml = b"<?xml version=\"1.0\" encoding=\"Shift_JIS\"?><html><head><meta charset=\"UTF-8\"></head></html>";
+        let charset = detect_charset(Some("application/xhtml+xml"), xml);
+        assert_eq!(charset, "Shift_JIS");
+    }
+
+    #[test]
+    fn test_detect_charset_content_type_wins_over_xml_declaration() {
+        let xml = b"<?xml version=\"1.0\" encoding=\"Shift_JIS\"?><html/>";
+        let charset = detect_charset(Some("application/xhtml+xml; charset=UTF-8"), xml);
+        assert_eq!(charset, "UTF-8");
     }
 
     #[test]
@@ -428,6 +1396,26 @@ mod tests {
         assert_eq!(charset, "UTF-8");
     }
 
+    #[cfg(feature = "stat_charset_detect")]
+    #[test]
+    fn test_detect_charset_falls_back_to_statistical_detection() {
+        // No Content-Type charset, no meta tag, and "Caf\xe9" isn't valid
+        // UTF-8, so the statistical stage should win over the UTF-8 default.
+        let html = b"<html><head><title>No charset</title></head><body>Caf\xe9</body></html>";
+        let charset = detect_charset(None, html);
+        assert_eq!(charset, "windows-1252");
+    }
+
+    #[cfg(feature = "stat_charset_detect")]
+    #[test]
+    fn test_detect_charset_skips_statistical_detection_for_valid_utf8() {
+        // Valid UTF-8 with no charset info should still just default to
+        // UTF-8, not get routed through the statistical fallback.
+        let html = "<html><body>Caf\u{e9}</body></html>".as_bytes();
+        let charset = detect_charset(None, html);
+        assert_eq!(charset, "UTF-8");
+    }
+
     #[test]
     fn test_detect_charset_normalization() {
         // Should normalize charset to uppercase
@@ -445,52 +1433,355 @@ mod tests {
 
     #[test]
     fn test_detect_charset_various_charsets() {
-        // Test various charset names
+        // Labels are canonicalized to the name the Encoding Standard defines
+        // for them, which isn't always a plain uppercase of the input label
+        // (e.g. "ISO-8859-1" maps to "windows-1252", "GB2312" to "GBK").
         let charsets = vec![
-            "UTF-8",
-            "ISO-8859-1",
-            "ISO-8859-15",
-            "windows-1252",
-            "GB2312",
-            "Big5",
-            "Shift_JIS",
-            "EUC-KR",
+            ("UTF-8", "UTF-8"),
+            ("ISO-8859-1", "windows-1252"),
+            ("ISO-8859-15", "ISO-8859-15"),
+            ("windows-1252", "windows-1252"),
+            ("GB2312", "GBK"),
+            ("Big5", "Big5"),
+            ("Shift_JIS", "Shift_JIS"),
+            ("EUC-KR", "EUC-KR"),
         ];
 
-        for cs in charsets {
+        for (cs, expected) in charsets {
             let content_type = format!("text/html; charset={}", cs);
             let detected = detect_charset(Some(&content_type), b"");
-            assert_eq!(detected, cs.to_uppercase());
+            assert_eq!(detected, expected);
         }
     }
 
+    // ============================================================================
+    // Unit Tests for detect_charset_with_override
+    // ============================================================================
+
+    #[test]
+    fn test_detect_charset_with_override_wins_over_content_type_and_meta() {
+        let html = b"<html><head><meta charset=\"UTF-8\"></head></html>";
+        let charset =
+            detect_charset_with_override(Some("windows-1252"), Some("text/html; charset=UTF-8"), html);
+        assert_eq!(charset, "windows-1252");
+    }
+
+    #[test]
+    fn test_detect_charset_with_override_canonicalizes_the_label() {
+        let charset = detect_charset_with_override(Some("latin1"), None, b"");
+        assert_eq!(charset, "windows-1252");
+    }
+
+    #[test]
+    fn test_detect_charset_with_override_unrecognized_label_falls_back_to_cascade() {
+        let html = b"<html><head><meta charset=\"ISO-8859-1\"></head></html>";
+        let charset = detect_charset_with_override(Some("not-a-real-charset"), None, html);
+        assert_eq!(charset, "windows-1252");
+    }
+
+    #[test]
+    fn test_detect_charset_with_override_none_falls_back_to_cascade() {
+        let charset = detect_charset_with_override(None, Some("text/html; charset=UTF-8"), b"");
+        assert_eq!(charset, "UTF-8");
+    }
+
+    // ============================================================================
+    // Unit Tests for transcode_to_utf8
+    // ============================================================================
+
+    #[test]
+    fn test_transcode_to_utf8_decodes_with_content_type_charset() {
+        let (encoded, _, _) = encoding_rs::WINDOWS_1252.encode("Café au lait");
+        let (text, charset) =
+            transcode_to_utf8(Some("text/html; charset=windows-1252"), &encoded);
+        assert_eq!(text, "Café au lait");
+        assert_eq!(charset, "windows-1252");
+    }
+
+    #[test]
+    fn test_transcode_to_utf8_falls_back_to_html_meta() {
+        let html = b"<html><head><meta charset=\"ISO-8859-1\"></head><body>Caf\xe9</body></html>";
+        let (text, charset) = transcode_to_utf8(None, html);
+        assert!(text.contains("Café"));
+        // ISO-8859-1 resolves to windows-1252 under the Encoding Standard.
+        assert_eq!(charset, "windows-1252");
+    }
+
+    #[test]
+    fn test_transcode_to_utf8_unrecognized_label_falls_back_to_utf8() {
+        let (text, charset) = transcode_to_utf8(Some("text/html; charset=not-a-charset"), b"hello");
+        assert_eq!(text, "hello");
+        assert_eq!(charset, "UTF-8");
+    }
+
+    #[test]
+    fn test_transcode_to_utf8_replaces_malformed_bytes() {
+        // 0x81 is unmapped in windows-1252, so it must be replaced rather
+        // than rejected.
+        let (text, charset) =
+            transcode_to_utf8(Some("text/html; charset=windows-1252"), b"Hello \x81 World");
+        assert!(text.contains('\u{FFFD}'));
+        assert_eq!(charset, "windows-1252");
+    }
+
     // ============================================================================
     // Unit Tests for Charset Normalization
     // ============================================================================
 
     #[test]
     fn test_normalize_charset_lowercase() {
-        assert_eq!(normalize_charset("utf-8"), "UTF-8");
+        assert_eq!(normalize_charset("utf-8"), Some("UTF-8".to_string()));
     }
 
     #[test]
     fn test_normalize_charset_uppercase() {
-        assert_eq!(normalize_charset("UTF-8"), "UTF-8");
+        assert_eq!(normalize_charset("UTF-8"), Some("UTF-8".to_string()));
     }
 
     #[test]
     fn test_normalize_charset_mixed_case() {
-        assert_eq!(normalize_charset("Utf-8"), "UTF-8");
+        assert_eq!(normalize_charset("Utf-8"), Some("UTF-8".to_string()));
     }
 
     #[test]
     fn test_normalize_charset_iso() {
-        assert_eq!(normalize_charset("iso-8859-1"), "ISO-8859-1");
+        // ISO-8859-1 resolves to windows-1252 under the Encoding Standard.
+        assert_eq!(normalize_charset("iso-8859-1"), Some("windows-1252".to_string()));
     }
 
     #[test]
     fn test_normalize_charset_windows() {
-        assert_eq!(normalize_charset("windows-1252"), "WINDOWS-1252");
+        assert_eq!(normalize_charset("windows-1252"), Some("windows-1252".to_string()));
+    }
+
+    #[test]
+    fn test_normalize_charset_unrecognized_label_returns_none() {
+        assert_eq!(normalize_charset("not-a-real-charset"), None);
+    }
+
+    // ============================================================================
+    // Unit Tests for BOM Detection
+    // ============================================================================
+
+    #[test]
+    fn test_extract_charset_from_bom_utf8() {
+        assert_eq!(extract_charset_from_bom(b"\xEF\xBB\xBF<html>"), Some("UTF-8"));
+    }
+
+    #[test]
+    fn test_extract_charset_from_bom_utf32le_not_shadowed_by_utf16le() {
+        assert_eq!(
+            extract_charset_from_bom(b"\xFF\xFE\x00\x00<html>"),
+            Some("UTF-32LE")
+        );
+    }
+
+    #[test]
+    fn test_extract_charset_from_bom_none() {
+        assert_eq!(extract_charset_from_bom(b"<html>"), None);
+    }
+
+    #[test]
+    fn test_detect_bom_utf8() {
+        assert_eq!(detect_bom(b"\xEF\xBB\xBF<html>"), Some(("UTF-8", 3)));
+    }
+
+    #[test]
+    fn test_detect_bom_utf16le() {
+        assert_eq!(detect_bom(b"\xFF\xFE<html>"), Some(("UTF-16LE", 2)));
+    }
+
+    #[test]
+    fn test_detect_bom_utf16be() {
+        assert_eq!(detect_bom(b"\xFE\xFF<html>"), Some(("UTF-16BE", 2)));
+    }
+
+    #[test]
+    fn test_detect_bom_utf32le() {
+        assert_eq!(
+            detect_bom(b"\xFF\xFE\x00\x00<html>"),
+            Some(("UTF-32LE", 4))
+        );
+    }
+
+    #[test]
+    fn test_detect_bom_utf32be() {
+        assert_eq!(
+            detect_bom(b"\x00\x00\xFE\xFF<html>"),
+            Some(("UTF-32BE", 4))
+        );
+    }
+
+    #[test]
+    fn test_detect_bom_utf32le_not_shadowed_by_utf16le() {
+        // The UTF-32LE BOM's first two bytes are the UTF-16LE BOM; the
+        // longer, more specific match must win.
+        assert_ne!(detect_bom(b"\xFF\xFE\x00\x00"), Some(("UTF-16LE", 2)));
+    }
+
+    #[test]
+    fn test_detect_bom_none() {
+        assert_eq!(detect_bom(b"<html>"), None);
+    }
+
+    #[test]
+    fn test_detect_bom_empty() {
+        assert_eq!(detect_bom(b""), None);
+    }
+
+    // ============================================================================
+    // Unit Tests for Input Charset Resolution Cascade
+    // ============================================================================
+
+    #[test]
+    fn test_resolve_input_charset_override_wins_over_everything() {
+        let html = b"\xEF\xBB\xBF<html><head><meta charset=\"ISO-8859-1\"></head></html>";
+        let (charset, bom_len) =
+            resolve_input_charset(Some("windows-1252"), Some("text/html; charset=UTF-8"), html);
+        assert_eq!(charset, "windows-1252");
+        assert_eq!(bom_len, 0);
+    }
+
+    #[test]
+    fn test_resolve_input_charset_bom_wins_over_content_type_and_meta() {
+        let html = b"\xEF\xBB\xBF<html><head><meta charset=\"ISO-8859-1\"></head></html>";
+        let (charset, bom_len) = resolve_input_charset(None, Some("text/html; charset=GBK"), html);
+        assert_eq!(charset, "UTF-8");
+        assert_eq!(bom_len, 3);
+    }
+
+    #[test]
+    fn test_resolve_input_charset_falls_back_to_existing_cascade() {
+        let html = b"<html><head><meta charset=\"ISO-8859-1\"></head></html>";
+        let (charset, bom_len) = resolve_input_charset(None, None, html);
+        assert_eq!(charset, "windows-1252");
+        assert_eq!(bom_len, 0);
+    }
+
+    // ============================================================================
+    // Unit Tests for resolve_input_charset_with_detection
+    // ============================================================================
+
+    #[test]
+    fn test_resolve_input_charset_with_detection_override_is_never_guessed() {
+        let html = b"<html><head><meta charset=\"ISO-8859-1\"></head></html>";
+        let (charset, bom_len, was_guessed) =
+            resolve_input_charset_with_detection(Some("windows-1252"), None, html, true, None);
+        assert_eq!(charset, "windows-1252");
+        assert_eq!(bom_len, 0);
+        assert!(!was_guessed);
+    }
+
+    #[test]
+    fn test_resolve_input_charset_with_detection_meta_is_never_guessed() {
+        let html = b"<html><head><meta charset=\"ISO-8859-1\"></head></html>";
+        let (charset, _bom_len, was_guessed) =
+            resolve_input_charset_with_detection(None, None, html, true, None);
+        assert_eq!(charset, "windows-1252");
+        assert!(!was_guessed);
+    }
+
+    #[test]
+    fn test_resolve_input_charset_with_detection_disabled_defaults_to_utf8() {
+        let (encoded, _, _) = encoding_rs::WINDOWS_1252.encode("Caf\u{e9} au lait");
+        let html = format!("<html><body>{}</body></html>", String::from_utf8_lossy(&encoded));
+        let (charset, _bom_len, was_guessed) =
+            resolve_input_charset_with_detection(None, None, html.as_bytes(), false, None);
+        assert_eq!(charset, "UTF-8");
+        assert!(!was_guessed);
+    }
+
+    #[cfg(feature = "stat_charset_detect")]
+    #[test]
+    fn test_resolve_input_charset_with_detection_guesses_when_enabled() {
+        let mut html = b"<html><body>Caf".to_vec();
+        html.extend_from_slice(&encoding_rs::WINDOWS_1252.encode("\u{e9}").0);
+        html.extend_from_slice(b" au lait</body></html>");
+        let (charset, _bom_len, was_guessed) =
+            resolve_input_charset_with_detection(None, None, &html, true, None);
+        assert_eq!(charset, "windows-1252");
+        assert!(was_guessed);
+    }
+
+    #[cfg(feature = "stat_charset_detect")]
+    #[test]
+    fn test_resolve_input_charset_with_detection_honors_tld_hint() {
+        let (encoded, _, _) =
+            encoding_rs::SHIFT_JIS.encode("\u{3053}\u{3093}\u{306b}\u{3061}\u{306f}");
+        let mut html = b"<html><body>".to_vec();
+        html.extend_from_slice(&encoded);
+        html.extend_from_slice(b"</body></html>");
+        let (charset, _bom_len, was_guessed) =
+            resolve_input_charset_with_detection(None, None, &html, true, Some("jp"));
+        assert_eq!(charset, "Shift_JIS");
+        assert!(was_guessed);
+    }
+
+    // ============================================================================
+    // Unit Tests for Windows Codepage Resolution
+    // ============================================================================
+
+    #[test]
+    fn test_encoding_for_codepage_known_values() {
+        assert_eq!(
+            encoding_for_codepage(1252).map(|e| e.name()),
+            Some("windows-1252")
+        );
+        assert_eq!(encoding_for_codepage(936).map(|e| e.name()), Some("GBK"));
+        assert_eq!(
+            encoding_for_codepage(932).map(|e| e.name()),
+            Some("Shift_JIS")
+        );
+        assert_eq!(encoding_for_codepage(65001).map(|e| e.name()), Some("UTF-8"));
+    }
+
+    #[test]
+    fn test_encoding_for_codepage_ebcdic_is_unmapped() {
+        assert_eq!(encoding_for_codepage(1), None);
+    }
+
+    #[test]
+    fn test_encoding_for_codepage_nominal_ascii_codepages_are_unmapped() {
+        assert_eq!(encoding_for_codepage(2), None);
+        assert_eq!(encoding_for_codepage(3), None);
+    }
+
+    #[test]
+    fn test_encoding_for_codepage_unknown_value_is_none() {
+        assert_eq!(encoding_for_codepage(424242), None);
+    }
+
+    #[test]
+    fn test_resolve_input_charset_with_codepage_overrides_meta() {
+        let html = b"<html><head><meta charset=\"UTF-8\"></head></html>";
+        let (charset, bom_len) =
+            resolve_input_charset_with_codepage(None, Some(936), None, html);
+        assert_eq!(charset, "GBK");
+        assert_eq!(bom_len, 0);
+    }
+
+    #[test]
+    fn test_resolve_input_charset_with_codepage_label_wins_over_codepage() {
+        let html = b"<html><body>Hello</body></html>";
+        let (charset, _bom_len) =
+            resolve_input_charset_with_codepage(Some("windows-1251"), Some(936), None, html);
+        assert_eq!(charset, "windows-1251");
+    }
+
+    #[test]
+    fn test_resolve_input_charset_with_codepage_unmapped_falls_through_to_cascade() {
+        let html = b"<html><head><meta charset=\"ISO-8859-1\"></head></html>";
+        let (charset, bom_len) = resolve_input_charset_with_codepage(None, Some(1), None, html);
+        assert_eq!(charset, "windows-1252");
+        assert_eq!(bom_len, 0);
+    }
+
+    #[test]
+    fn test_resolve_input_charset_with_codepage_none_falls_through_to_cascade() {
+        let html = b"<html><head><meta charset=\"ISO-8859-1\"></head></html>";
+        let (charset, bom_len) = resolve_input_charset_with_codepage(None, None, None, html);
+        assert_eq!(charset, "windows-1252");
+        assert_eq!(bom_len, 0);
     }
 
     // ============================================================================
@@ -502,23 +1793,37 @@ mod tests {
         /// Validates: FR-05.1, FR-05.2, FR-05.3
         #[test]
         fn prop_detect_charset_content_type_has_priority_over_html_meta(
-            header_charset in prop::sample::select(vec!["utf-8", "iso-8859-1", "windows-1252", "shift_jis", "gb2312"]),
+            header in prop::sample::select(vec![
+                ("utf-8", "UTF-8"),
+                ("iso-8859-1", "windows-1252"),
+                ("windows-1252", "windows-1252"),
+                ("shift_jis", "Shift_JIS"),
+                ("gb2312", "GBK"),
+            ]),
             meta_charset in prop::sample::select(vec!["UTF-8", "ISO-8859-1", "WINDOWS-1252", "SHIFT_JIS", "GB2312"]),
         ) {
-            prop_assume!(header_charset.to_uppercase() != meta_charset.to_uppercase());
+            let (header_charset, header_canonical) = header;
+            prop_assume!(header_canonical != meta_charset.to_uppercase());
 
             let content_type = format!("text/html; charset={header_charset}");
             let html = format!(r#"<html><head><meta charset="{meta_charset}"></head><body>x</body></html>"#);
 
             let detected = detect_charset(Some(&content_type), html.as_bytes());
-            prop_assert_eq!(detected, header_charset.to_uppercase());
+            prop_assert_eq!(detected, header_canonical);
         }
 
         #[test]
         fn prop_detect_charset_falls_back_to_html_meta_when_header_has_no_charset(
-            meta_charset in prop::sample::select(vec!["utf-8", "iso-8859-1", "windows-1252", "shift_jis", "big5"]),
+            meta in prop::sample::select(vec![
+                ("utf-8", "UTF-8"),
+                ("iso-8859-1", "windows-1252"),
+                ("windows-1252", "windows-1252"),
+                ("shift_jis", "Shift_JIS"),
+                ("big5", "Big5"),
+            ]),
             use_html4_syntax in any::<bool>(),
         ) {
+            let (meta_charset, meta_canonical) = meta;
             let html = if use_html4_syntax {
                 format!(
                     r#"<html><head><meta http-equiv="Content-Type" content="text/html; charset={}"></head></html>"#,
@@ -529,7 +1834,7 @@ mod tests {
             };
 
             let detected = detect_charset(Some("text/html"), html.as_bytes());
-            prop_assert_eq!(detected, meta_charset.to_uppercase());
+            prop_assert_eq!(detected, meta_canonical);
         }
     }
 }
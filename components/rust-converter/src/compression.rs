@@ -0,0 +1,264 @@
+//! Output compression for HTTP delivery
+//!
+//! NGINX upstreams often prefer receiving a pre-compressed body so the edge
+//! doesn't have to recompress it on every request. This module negotiates a
+//! compression encoding from an `Accept-Encoding`-style preference string and
+//! compresses Markdown output accordingly.
+//!
+//! # Requirements
+//!
+//! - **FR-06.4**: ETag must remain stable across encodings — callers must
+//!   compute the ETag from the uncompressed Markdown before calling
+//!   [`compress`], never from the compressed bytes.
+
+use crate::error::ConversionError;
+
+/// Supported compression encodings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionEncoding {
+    /// No compression; body is sent as-is
+    Identity,
+    /// gzip (RFC 1952), via `flate2`
+    Gzip,
+    /// Brotli, via the `brotli` crate
+    Brotli,
+    /// Zstandard, via the `zstd` crate
+    Zstd,
+}
+
+impl CompressionEncoding {
+    /// `Content-Encoding` header value for this variant
+    ///
+    /// Returns an empty string for [`Self::Identity`], matching the FFI
+    /// convention that an absent/empty optional string field means "not set".
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Identity => "",
+            Self::Gzip => "gzip",
+            Self::Brotli => "br",
+            Self::Zstd => "zstd",
+        }
+    }
+}
+
+/// Negotiate the best supported encoding from an `Accept-Encoding`-style value
+///
+/// Parses a comma-separated list of tokens with optional `;q=` weights (e.g.
+/// `"br;q=0.9, gzip;q=0.8, *;q=0"`), ignoring `identity` and any unsupported
+/// token. Ties in weight are broken in favor of the better compression ratio:
+/// Brotli, then Zstandard, then gzip. An empty or all-unsupported preference
+/// yields [`CompressionEncoding::Identity`].
+///
+/// # Examples
+///
+/// ```
+/// use nginx_markdown_converter::compression::{negotiate_encoding, CompressionEncoding};
+///
+/// assert_eq!(negotiate_encoding("gzip, br"), CompressionEncoding::Brotli);
+/// assert_eq!(negotiate_encoding("gzip;q=1.0, br;q=0.5"), CompressionEncoding::Gzip);
+/// assert_eq!(negotiate_encoding(""), CompressionEncoding::Identity);
+/// ```
+pub fn negotiate_encoding(accept_encoding: &str) -> CompressionEncoding {
+    let mut best = CompressionEncoding::Identity;
+    let mut best_quality = 0.0f32;
+
+    for token in accept_encoding.split(',') {
+        let token = token.trim();
+        if token.is_empty() {
+            continue;
+        }
+
+        let mut segments = token.split(';');
+        let name = segments.next().unwrap_or("").trim().to_ascii_lowercase();
+        let quality = segments
+            .find_map(|segment| segment.trim().strip_prefix("q="))
+            .and_then(|value| value.parse::<f32>().ok())
+            .unwrap_or(1.0);
+
+        if quality <= 0.0 {
+            continue;
+        }
+
+        let Some(encoding) = (match name.as_str() {
+            "gzip" => Some(CompressionEncoding::Gzip),
+            "br" => Some(CompressionEncoding::Brotli),
+            "zstd" => Some(CompressionEncoding::Zstd),
+            _ => None,
+        }) else {
+            continue;
+        };
+
+        if quality > best_quality
+            || (quality == best_quality && compression_ratio_rank(encoding) > compression_ratio_rank(best))
+        {
+            best = encoding;
+            best_quality = quality;
+        }
+    }
+
+    best
+}
+
+/// Relative preference among encodings of equal requested quality, highest first
+fn compression_ratio_rank(encoding: CompressionEncoding) -> u8 {
+    match encoding {
+        CompressionEncoding::Identity => 0,
+        CompressionEncoding::Gzip => 1,
+        CompressionEncoding::Zstd => 2,
+        CompressionEncoding::Brotli => 3,
+    }
+}
+
+/// Compress `data` with the given encoding
+///
+/// [`CompressionEncoding::Identity`] returns an owned copy of `data`
+/// unchanged.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InternalError`] if the underlying compressor
+/// fails (out-of-memory conditions; malformed input is not possible since
+/// compressors operate on arbitrary bytes).
+pub fn compress(data: &[u8], encoding: CompressionEncoding) -> Result<Vec<u8>, ConversionError> {
+    Ok(match encoding {
+        CompressionEncoding::Identity => data.to_vec(),
+        CompressionEncoding::Gzip => compress_gzip(data)?,
+        CompressionEncoding::Brotli => compress_brotli(data)?,
+        CompressionEncoding::Zstd => compress_zstd(data)?,
+    })
+}
+
+fn compress_gzip(data: &[u8]) -> Result<Vec<u8>, crate::error::EncoderError> {
+    use std::io::Write;
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data).map_err(crate::error::EncoderError::IoError)?;
+    encoder.finish().map_err(crate::error::EncoderError::IoError)
+}
+
+fn compress_brotli(data: &[u8]) -> Result<Vec<u8>, crate::error::EncoderError> {
+    use std::io::Write;
+
+    let mut output = Vec::new();
+    {
+        const BUFFER_SIZE: usize = 4096;
+        const QUALITY: u32 = 5;
+        const LG_WINDOW_SIZE: u32 = 22;
+        let mut writer = brotli::CompressorWriter::new(&mut output, BUFFER_SIZE, QUALITY, LG_WINDOW_SIZE);
+        writer
+            .write_all(data)
+            .map_err(crate::error::EncoderError::IoError)?;
+    }
+    Ok(output)
+}
+
+fn compress_zstd(data: &[u8]) -> Result<Vec<u8>, crate::error::EncoderError> {
+    const DEFAULT_LEVEL: i32 = 0;
+    zstd::stream::encode_all(data, DEFAULT_LEVEL).map_err(crate::error::EncoderError::IoError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_negotiate_encoding_prefers_higher_quality() {
+        assert_eq!(
+            negotiate_encoding("gzip;q=1.0, br;q=0.5"),
+            CompressionEncoding::Gzip
+        );
+    }
+
+    #[test]
+    fn test_negotiate_encoding_prefers_brotli_on_tie() {
+        assert_eq!(negotiate_encoding("gzip, br, zstd"), CompressionEncoding::Brotli);
+    }
+
+    #[test]
+    fn test_negotiate_encoding_ignores_zero_quality() {
+        assert_eq!(negotiate_encoding("br;q=0, gzip"), CompressionEncoding::Gzip);
+    }
+
+    #[test]
+    fn test_negotiate_encoding_ignores_unsupported_tokens() {
+        assert_eq!(negotiate_encoding("compress, identity, sdch"), CompressionEncoding::Identity);
+    }
+
+    #[test]
+    fn test_negotiate_encoding_empty_is_identity() {
+        assert_eq!(negotiate_encoding(""), CompressionEncoding::Identity);
+    }
+
+    #[test]
+    fn test_compress_identity_returns_input_unchanged() {
+        let data = b"hello world";
+        let compressed = compress(data, CompressionEncoding::Identity).expect("compression failed");
+        assert_eq!(compressed, data);
+    }
+
+    #[test]
+    fn test_compress_gzip_roundtrips() {
+        let data = b"The quick brown fox jumps over the lazy dog. ".repeat(20);
+        let compressed = compress(&data, CompressionEncoding::Gzip).expect("compression failed");
+        assert_ne!(compressed, data);
+
+        let mut decoder = flate2::read::GzDecoder::new(compressed.as_slice());
+        let mut decompressed = Vec::new();
+        std::io::Read::read_to_end(&mut decoder, &mut decompressed).expect("decompression failed");
+        assert_eq!(decompressed, data);
+    }
+
+    #[test]
+    fn test_compress_brotli_roundtrips() {
+        let data = b"The quick brown fox jumps over the lazy dog. ".repeat(20);
+        let compressed = compress(&data, CompressionEncoding::Brotli).expect("compression failed");
+        assert_ne!(compressed, data);
+
+        let mut decompressed = Vec::new();
+        brotli::BrotliDecompress(&mut compressed.as_slice(), &mut decompressed)
+            .expect("decompression failed");
+        assert_eq!(decompressed, data);
+    }
+
+    #[test]
+    fn test_compress_zstd_roundtrips() {
+        let data = b"The quick brown fox jumps over the lazy dog. ".repeat(20);
+        let compressed = compress(&data, CompressionEncoding::Zstd).expect("compression failed");
+        assert_ne!(compressed, data);
+
+        let decompressed = zstd::stream::decode_all(compressed.as_slice()).expect("decompression failed");
+        assert_eq!(decompressed, data);
+    }
+
+    #[test]
+    fn test_compress_empty_input() {
+        for encoding in [
+            CompressionEncoding::Identity,
+            CompressionEncoding::Gzip,
+            CompressionEncoding::Brotli,
+            CompressionEncoding::Zstd,
+        ] {
+            let compressed = compress(b"", encoding).expect("compression failed");
+            assert!(!compressed.is_empty() || encoding == CompressionEncoding::Identity);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn prop_gzip_roundtrip(data in prop::collection::vec(any::<u8>(), 0..4096)) {
+            let compressed = compress(&data, CompressionEncoding::Gzip).expect("compression failed");
+            let mut decoder = flate2::read::GzDecoder::new(compressed.as_slice());
+            let mut decompressed = Vec::new();
+            std::io::Read::read_to_end(&mut decoder, &mut decompressed).expect("decompression failed");
+            prop_assert_eq!(decompressed, data);
+        }
+
+        #[test]
+        fn prop_zstd_roundtrip(data in prop::collection::vec(any::<u8>(), 0..4096)) {
+            let compressed = compress(&data, CompressionEncoding::Zstd).expect("compression failed");
+            let decompressed = zstd::stream::decode_all(compressed.as_slice()).expect("decompression failed");
+            prop_assert_eq!(decompressed, data);
+        }
+    }
+}
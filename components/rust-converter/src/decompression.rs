@@ -0,0 +1,270 @@
+//! Inbound `Content-Encoding` decompression
+//!
+//! As an NGINX module sitting in front of upstream HTML, the bytes handed to
+//! [`crate::parser::parse_html_with_options`] are frequently compressed
+//! (`Content-Encoding: gzip`, `br`, `deflate`, or `zstd`) by the origin
+//! server. This module undoes that before the charset cascade ever sees the
+//! bytes, using the same `flate2`/`brotli`/`zstd` crates
+//! [`crate::compression`] already depends on for the opposite (outbound)
+//! direction.
+//!
+//! # Decompression Bomb Protection
+//!
+//! Decompression is bounded by a caller-supplied byte cap; exceeding it
+//! aborts with [`ConversionError::MemoryLimit`] rather than continuing to
+//! inflate an attacker-controlled payload, the same error `max_output_bytes`
+//! reports (the input-size caps, `max_input_bytes` and incremental
+//! buffering, report [`ConversionError::InputTooLarge`] instead).
+
+use crate::error::ConversionError;
+use std::borrow::Cow;
+use std::io::{Read, Write};
+
+/// Decompress `body` according to a `Content-Encoding` header value
+///
+/// `content_encoding` may list multiple comma-separated tokens (e.g.
+/// `"gzip, br"`), naming encodings in the order they were applied on the
+/// wire per RFC 9110 §8.4.1; they are undone in reverse order, the same
+/// way a stack of wrapping is unwrapped from the outside in. A `None` or
+/// empty header, or the `identity` token, returns `body` unchanged as a
+/// borrowed [`Cow`] with no allocation.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::EncodingError`] if a token names an
+/// unrecognized encoding or a compressed stream is malformed.
+/// Returns [`ConversionError::MemoryLimit`] if decompressing a token would
+/// produce more than `max_decompressed_size` bytes.
+pub fn decompress_body<'a>(
+    body: &'a [u8],
+    content_encoding: Option<&str>,
+    max_decompressed_size: usize,
+) -> Result<Cow<'a, [u8]>, ConversionError> {
+    let tokens: Vec<&str> = content_encoding
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|token| !token.is_empty() && !token.eq_ignore_ascii_case("identity"))
+        .collect();
+
+    if tokens.is_empty() {
+        return Ok(Cow::Borrowed(body));
+    }
+
+    let mut current = body.to_vec();
+    for token in tokens.iter().rev() {
+        current = decompress_one(&current, token, max_decompressed_size)?;
+    }
+
+    Ok(Cow::Owned(current))
+}
+
+fn decompress_one(
+    data: &[u8],
+    token: &str,
+    max_decompressed_size: usize,
+) -> Result<Vec<u8>, ConversionError> {
+    match token.to_ascii_lowercase().as_str() {
+        "gzip" | "x-gzip" => {
+            read_bounded(flate2::read::GzDecoder::new(data), max_decompressed_size, "gzip")
+        }
+        "deflate" => read_bounded(
+            flate2::read::ZlibDecoder::new(data),
+            max_decompressed_size,
+            "deflate",
+        ),
+        "br" => decompress_brotli(data, max_decompressed_size),
+        "zstd" => decompress_zstd(data, max_decompressed_size),
+        other => Err(ConversionError::EncodingError(format!(
+            "unsupported Content-Encoding token: {other}"
+        ))),
+    }
+}
+
+/// Read `reader` to completion, capped at `max_decompressed_size` bytes
+///
+/// Wrapping the reader in [`Read::take`] with one byte of headroom lets a
+/// stream that fits exactly at the cap succeed, while any stream that would
+/// produce more is detected (its output is longer than the cap) without
+/// ever buffering more than `max_decompressed_size + 1` bytes.
+fn read_bounded(
+    reader: impl Read,
+    max_decompressed_size: usize,
+    label: &str,
+) -> Result<Vec<u8>, ConversionError> {
+    let mut output = Vec::new();
+    reader
+        .take(max_decompressed_size as u64 + 1)
+        .read_to_end(&mut output)
+        .map_err(|e| ConversionError::EncodingError(format!("{label} decompression failed: {e}")))?;
+
+    if output.len() > max_decompressed_size {
+        return Err(ConversionError::MemoryLimit {
+            used_bytes: output.len(),
+            limit_bytes: max_decompressed_size,
+        });
+    }
+
+    Ok(output)
+}
+
+/// Writer that errors as soon as writing `data` would exceed `max` bytes
+///
+/// Used to bound [`brotli::BrotliDecompress`], which writes directly to a
+/// [`Write`] sink rather than implementing [`Read`] like the gzip/zstd
+/// decoders, so it can't be bounded with [`Read::take`].
+struct BoundedWriter<'a> {
+    buf: &'a mut Vec<u8>,
+    max: usize,
+    exceeded: bool,
+}
+
+impl Write for BoundedWriter<'_> {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        if self.buf.len() + data.len() > self.max {
+            self.exceeded = true;
+            return Err(std::io::Error::other("decompressed size exceeds limit"));
+        }
+        self.buf.extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn decompress_brotli(data: &[u8], max_decompressed_size: usize) -> Result<Vec<u8>, ConversionError> {
+    let mut output = Vec::new();
+    let result = {
+        let mut writer = BoundedWriter {
+            buf: &mut output,
+            max: max_decompressed_size,
+            exceeded: false,
+        };
+        let mut reader = data;
+        let result = brotli::BrotliDecompress(&mut reader, &mut writer);
+        if writer.exceeded {
+            return Err(ConversionError::MemoryLimit {
+                used_bytes: writer.buf.len(),
+                limit_bytes: max_decompressed_size,
+            });
+        }
+        result
+    };
+    result.map_err(|e| ConversionError::EncodingError(format!("brotli decompression failed: {e}")))?;
+    Ok(output)
+}
+
+fn decompress_zstd(data: &[u8], max_decompressed_size: usize) -> Result<Vec<u8>, ConversionError> {
+    let decoder = zstd::stream::Decoder::new(data)
+        .map_err(|e| ConversionError::EncodingError(format!("zstd decompression failed: {e}")))?;
+    read_bounded(decoder, max_decompressed_size, "zstd")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compression::{compress, CompressionEncoding};
+
+    const CAP: usize = 1024 * 1024;
+
+    #[test]
+    fn test_decompress_body_no_header_is_unchanged() {
+        let data = b"plain bytes";
+        let result = decompress_body(data, None, CAP).expect("decompression failed");
+        assert_eq!(result.as_ref(), data);
+        assert!(matches!(result, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_decompress_body_identity_is_unchanged() {
+        let data = b"plain bytes";
+        let result = decompress_body(data, Some("identity"), CAP).expect("decompression failed");
+        assert_eq!(result.as_ref(), data);
+    }
+
+    #[test]
+    fn test_decompress_body_gzip_roundtrips() {
+        let data = b"The quick brown fox jumps over the lazy dog. ".repeat(20);
+        let compressed = compress(&data, CompressionEncoding::Gzip).expect("compression failed");
+        let result =
+            decompress_body(&compressed, Some("gzip"), CAP).expect("decompression failed");
+        assert_eq!(result.as_ref(), data.as_slice());
+    }
+
+    #[test]
+    fn test_decompress_body_brotli_roundtrips() {
+        let data = b"The quick brown fox jumps over the lazy dog. ".repeat(20);
+        let compressed = compress(&data, CompressionEncoding::Brotli).expect("compression failed");
+        let result = decompress_body(&compressed, Some("br"), CAP).expect("decompression failed");
+        assert_eq!(result.as_ref(), data.as_slice());
+    }
+
+    #[test]
+    fn test_decompress_body_zstd_roundtrips() {
+        let data = b"The quick brown fox jumps over the lazy dog. ".repeat(20);
+        let compressed = compress(&data, CompressionEncoding::Zstd).expect("compression failed");
+        let result = decompress_body(&compressed, Some("zstd"), CAP).expect("decompression failed");
+        assert_eq!(result.as_ref(), data.as_slice());
+    }
+
+    #[test]
+    fn test_decompress_body_deflate_roundtrips() {
+        use std::io::Write as _;
+
+        let data = b"The quick brown fox jumps over the lazy dog. ".repeat(20);
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&data).expect("deflate compression failed");
+        let compressed = encoder.finish().expect("deflate compression failed");
+
+        let result =
+            decompress_body(&compressed, Some("deflate"), CAP).expect("decompression failed");
+        assert_eq!(result.as_ref(), data.as_slice());
+    }
+
+    #[test]
+    fn test_decompress_body_unwraps_tokens_in_reverse_order() {
+        // "gzip, br" means the origin applied gzip first, then brotli on top
+        // of that, so undoing it must decode brotli first, then gzip.
+        let data = b"The quick brown fox jumps over the lazy dog. ".repeat(20);
+        let gzipped = compress(&data, CompressionEncoding::Gzip).expect("compression failed");
+        let doubly_compressed =
+            compress(&gzipped, CompressionEncoding::Brotli).expect("compression failed");
+
+        let result = decompress_body(&doubly_compressed, Some("gzip, br"), CAP)
+            .expect("decompression failed");
+        assert_eq!(result.as_ref(), data.as_slice());
+    }
+
+    #[test]
+    fn test_decompress_body_unsupported_encoding_is_encoding_error() {
+        let result = decompress_body(b"whatever", Some("compress"), CAP);
+        assert!(matches!(result, Err(ConversionError::EncodingError(_))));
+    }
+
+    #[test]
+    fn test_decompress_body_malformed_gzip_is_encoding_error() {
+        let result = decompress_body(b"not actually gzip", Some("gzip"), CAP);
+        assert!(matches!(result, Err(ConversionError::EncodingError(_))));
+    }
+
+    #[test]
+    fn test_decompress_body_over_cap_is_memory_limit() {
+        let data = b"The quick brown fox jumps over the lazy dog. ".repeat(1000);
+        let compressed = compress(&data, CompressionEncoding::Gzip).expect("compression failed");
+
+        let result = decompress_body(&compressed, Some("gzip"), 16);
+        assert!(matches!(result, Err(ConversionError::MemoryLimit { .. })));
+    }
+
+    #[test]
+    fn test_decompress_body_brotli_over_cap_is_memory_limit() {
+        let data = b"The quick brown fox jumps over the lazy dog. ".repeat(1000);
+        let compressed = compress(&data, CompressionEncoding::Brotli).expect("compression failed");
+
+        let result = decompress_body(&compressed, Some("br"), 16);
+        assert!(matches!(result, Err(ConversionError::MemoryLimit { .. })));
+    }
+}
@@ -0,0 +1,192 @@
+//! Unicode emoji <-> GitHub/CommonMark shortcode normalization
+//!
+//! Mirrors Zola's `render_emoji` option: [`crate::converter::ConversionOptions::emoji_shortcodes`]
+//! rewrites Unicode emoji found in prose text to their `:shortcode:` form
+//! (e.g. `😄` -> `:smile:`), and [`crate::converter::ConversionOptions::emoji_unicode`]
+//! does the reverse. Both are useful for agent pipelines that want Markdown
+//! output stable across platforms/fonts that render emoji glyphs
+//! differently, or across tokenizers that count a multi-codepoint emoji
+//! sequence inconsistently.
+//!
+//! [`EMOJI_TABLE`] is hand-curated rather than generated from the full
+//! Unicode CLDR annotation data (this crate has no code generation step),
+//! so it covers common single-codepoint emoji plus a few ZWJ (Zero Width
+//! Joiner) sequences, not the entire emoji repertoire. An emoji missing from
+//! the table is left untouched by [`emoji_to_shortcode`], and an unknown
+//! `:shortcode:` token is left untouched by [`shortcode_to_emoji`].
+//!
+//! Entries are ordered longest-sequence-first so [`emoji_to_shortcode`]
+//! matches a multi-codepoint ZWJ sequence (e.g. the family emoji) before it
+//! would otherwise match one of the sequence's individual component emoji
+//! (e.g. a lone "woman" emoji), which would otherwise split the sequence and
+//! leave the joiner codepoints as unmapped text.
+
+/// `(emoji, shortcode)` pairs, longest `emoji` first so matching never
+/// splits a ZWJ sequence into its component emoji
+const EMOJI_TABLE: &[(&str, &str)] = &[
+    // ZWJ sequences (checked first - longest match wins)
+    ("👨\u{200D}👩\u{200D}👧", "family_man_woman_girl"),
+    ("❤️\u{200D}🔥", "heart_on_fire"),
+    ("🏳️\u{200D}🌈", "rainbow_flag"),
+    // Common single-codepoint emoji
+    ("😄", "smile"),
+    ("😃", "smiley"),
+    ("😀", "grinning"),
+    ("😁", "grin"),
+    ("😆", "laughing"),
+    ("😅", "sweat_smile"),
+    ("😂", "joy"),
+    ("🙂", "slightly_smiling_face"),
+    ("😉", "wink"),
+    ("😊", "blush"),
+    ("😍", "heart_eyes"),
+    ("😘", "kissing_heart"),
+    ("😢", "cry"),
+    ("😭", "sob"),
+    ("😠", "angry"),
+    ("😱", "scream"),
+    ("😕", "confused"),
+    ("😐", "neutral_face"),
+    ("😏", "smirk"),
+    ("🤔", "thinking"),
+    ("👍", "thumbsup"),
+    ("👎", "thumbsdown"),
+    ("👏", "clap"),
+    ("🙏", "pray"),
+    ("👋", "wave"),
+    ("💪", "muscle"),
+    ("👉", "point_right"),
+    ("👈", "point_left"),
+    ("🙌", "raised_hands"),
+    ("👀", "eyes"),
+    ("❤️", "heart"),
+    ("💯", "100"),
+    ("🔥", "fire"),
+    ("✨", "sparkles"),
+    ("🎉", "tada"),
+    ("🚀", "rocket"),
+    ("💥", "boom"),
+    ("⚠️", "warning"),
+    ("❌", "x"),
+    ("✅", "white_check_mark"),
+    ("✔️", "heavy_check_mark"),
+    ("❓", "question"),
+    ("💡", "bulb"),
+    ("📝", "memo"),
+    ("🐛", "bug"),
+    ("🚧", "construction"),
+    ("🚨", "rotating_light"),
+    ("📦", "package"),
+    ("⚙️", "gear"),
+    ("🔒", "lock"),
+    ("🔑", "key"),
+    ("📧", "email"),
+    ("📞", "phone"),
+    ("📅", "calendar"),
+    ("⭐", "star"),
+];
+
+/// Replace every Unicode emoji in `text` found in [`EMOJI_TABLE`] with its
+/// `:shortcode:` form, leaving unrecognized emoji and ordinary text
+/// untouched
+pub fn emoji_to_shortcode(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    'outer: while !rest.is_empty() {
+        for (emoji, shortcode) in EMOJI_TABLE {
+            if let Some(stripped) = rest.strip_prefix(emoji) {
+                out.push(':');
+                out.push_str(shortcode);
+                out.push(':');
+                rest = stripped;
+                continue 'outer;
+            }
+        }
+
+        let ch = rest.chars().next().expect("rest is non-empty");
+        out.push(ch);
+        rest = &rest[ch.len_utf8()..];
+    }
+
+    out
+}
+
+/// Replace every `:shortcode:` token in `text` found in [`EMOJI_TABLE`] with
+/// its Unicode emoji, leaving unrecognized tokens (and anything that isn't a
+/// well-formed `:[a-z0-9_+-]+:` token, such as a bare clock time) untouched
+pub fn shortcode_to_emoji(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let chars: Vec<char> = text.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        if chars[i] == ':' {
+            if let Some(end) = find_shortcode_end(&chars, i) {
+                let token: String = chars[i + 1..end].iter().collect();
+                if let Some((emoji, _)) = EMOJI_TABLE.iter().find(|(_, sc)| *sc == token) {
+                    out.push_str(emoji);
+                    i = end + 1;
+                    continue;
+                }
+            }
+        }
+
+        out.push(chars[i]);
+        i += 1;
+    }
+
+    out
+}
+
+/// Index of the closing `:` of a candidate shortcode token starting at
+/// `chars[start]` (which must itself be `:`), or `None` if the run of
+/// `[a-z0-9_+-]` characters following `start` never reaches a closing `:`
+fn find_shortcode_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut j = start + 1;
+    while j < chars.len() {
+        match chars[j] {
+            ':' if j > start + 1 => return Some(j),
+            c if c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '+' | '-') => {
+                j += 1;
+            }
+            _ => return None,
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_emoji_to_shortcode() {
+        assert_eq!(emoji_to_shortcode("I am 😄 today"), "I am :smile: today");
+    }
+
+    #[test]
+    fn test_unmapped_emoji_left_untouched() {
+        let text = "rare emoji: 🫠";
+        assert_eq!(emoji_to_shortcode(text), text);
+    }
+
+    #[test]
+    fn test_zwj_sequence_matched_before_component_emoji() {
+        let family = "👨\u{200D}👩\u{200D}👧";
+        assert_eq!(emoji_to_shortcode(family), ":family_man_woman_girl:");
+    }
+
+    #[test]
+    fn test_shortcode_to_emoji_round_trips() {
+        let shortcode = shortcode_to_emoji("ship it :rocket:");
+        assert_eq!(shortcode, "ship it 🚀");
+        assert_eq!(emoji_to_shortcode(&shortcode), "ship it :rocket:");
+    }
+
+    #[test]
+    fn test_unknown_shortcode_left_untouched() {
+        let text = "10:30 is the meeting time, not :not_a_real_emoji:";
+        assert_eq!(shortcode_to_emoji(text), text);
+    }
+}
@@ -6,44 +6,418 @@ use std::fmt;
 #[derive(Debug)]
 pub enum ConversionError {
     /// HTML parsing failed
-    ParseError(String),
+    ParseError {
+        /// What went wrong
+        message: String,
+        /// Byte offset into the source HTML where parsing failed, when known
+        offset: Option<usize>,
+        /// 1-indexed line number corresponding to `offset`, when known
+        line: Option<u32>,
+        /// 1-indexed column number corresponding to `offset`, when known
+        col: Option<u32>,
+    },
     /// Character encoding error
     EncodingError(String),
     /// Conversion timeout exceeded
-    Timeout,
-    /// Memory limit exceeded
-    MemoryLimit,
+    Timeout {
+        /// How long the conversion had been running when the timeout fired
+        elapsed_ms: u64,
+        /// The configured `timeout_ms` budget that was exceeded
+        limit_ms: u64,
+    },
+    /// Memory limit exceeded (output size or decompressed size)
+    MemoryLimit {
+        /// The size that tripped the limit (bytes produced/written so far)
+        used_bytes: usize,
+        /// The configured cap (`max_output_bytes`/`max_decompressed_bytes`)
+        /// that was exceeded
+        limit_bytes: usize,
+    },
     /// Invalid input data
+    ///
+    /// Kept as a plain message rather than gaining the same `offset`/`line`/
+    /// `col` fields as [`Self::ParseError`]: it's constructed at ~20 call
+    /// sites across `ffi`, `parser`, and `testsuite` (NULL handles, bad
+    /// encoding overrides, malformed FFI arguments) that have no source
+    /// HTML position to report in the first place, and is matched by
+    /// existing `matches!(.., ConversionError::InvalidInput(_))` assertions
+    /// that a struct-variant change would break. [`Self::invalid_input_at`]
+    /// gives a caller that *does* have a byte offset a harmonized way to
+    /// include it without widening the variant.
     InvalidInput(String),
     /// Internal error
     InternalError(String),
+    /// A caller-supplied encoding label isn't recognized by `encoding_rs`
+    UnknownEncoding(String),
+    /// The HTML input (or, for streaming, the accumulated buffer) exceeds
+    /// `max_input_bytes`. Split out from [`ConversionError::MemoryLimit`] so
+    /// a caller can distinguish "the body itself was too big" - a cheap,
+    /// predictable rejection an operator can act on before spending any of
+    /// `timeout_ms` - from an output- or decompression-size limit being hit
+    /// partway through conversion.
+    InputTooLarge,
+    /// Bytes that were expected to be valid UTF-8 weren't, with the
+    /// original [`std::str::Utf8Error`] preserved as the [`source()`](
+    /// std::error::Error::source) so a caller can inspect `valid_up_to()`
+    /// instead of parsing it back out of a formatted message
+    Utf8Error(std::str::Utf8Error),
+    /// An I/O operation failed, with the original [`std::io::Error`] preserved as the [`source()`](
+    /// std::error::Error::source)
+    IoError(std::io::Error),
 }
 
 impl ConversionError {
+    /// Build a [`Self::ParseError`] with no position information, for
+    /// callers that can't pinpoint where in the source HTML parsing failed
+    pub fn parse_error(message: impl Into<String>) -> Self {
+        ConversionError::ParseError {
+            message: message.into(),
+            offset: None,
+            line: None,
+            col: None,
+        }
+    }
+
+    /// Build a [`Self::ParseError`] with a byte offset and the 1-indexed
+    /// line/column it corresponds to, as reported by the html5ever
+    /// tokenizer
+    pub fn parse_error_at(message: impl Into<String>, offset: usize, line: u32, col: u32) -> Self {
+        ConversionError::ParseError {
+            message: message.into(),
+            offset: Some(offset),
+            line: Some(line),
+            col: Some(col),
+        }
+    }
+
+    /// Build a [`Self::InvalidInput`] that names the byte offset into the
+    /// source HTML where the problem was found, formatted in the same
+    /// `context: message` style as [`Self::ParseError`]'s `Display` output
+    pub fn invalid_input_at(message: impl Into<String>, offset: usize) -> Self {
+        ConversionError::InvalidInput(format!("at byte {offset}: {}", message.into()))
+    }
+
     /// Get numeric error code for FFI
     pub fn code(&self) -> u32 {
         match self {
-            ConversionError::ParseError(_) => 1,
+            ConversionError::ParseError { .. } => 1,
             ConversionError::EncodingError(_) => 2,
-            ConversionError::Timeout => 3,
-            ConversionError::MemoryLimit => 4,
+            ConversionError::Timeout { .. } => 3,
+            ConversionError::MemoryLimit { .. } => 4,
             ConversionError::InvalidInput(_) => 5,
+            ConversionError::UnknownEncoding(_) => 6,
+            ConversionError::InputTooLarge => 8,
+            // Both new causes are encoding/IO-flavored failures, so they
+            // share the existing `EncodingError` code rather than minting a
+            // new one that FFI callers would need to start handling.
+            ConversionError::Utf8Error(_) | ConversionError::IoError(_) => 2,
             ConversionError::InternalError(_) => 99,
         }
     }
+
+    /// Coarse error class, for an FFI caller (the nginx module) to pick an
+    /// HTTP status without hardcoding every [`Self::code`] value: client
+    /// input errors map to `400`, resource limits to `503`, and internal
+    /// errors to `500`
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            ConversionError::ParseError { .. }
+            | ConversionError::EncodingError(_)
+            | ConversionError::InvalidInput(_)
+            | ConversionError::UnknownEncoding(_)
+            | ConversionError::Utf8Error(_) => ErrorCategory::ClientInput,
+            ConversionError::Timeout { .. }
+            | ConversionError::MemoryLimit { .. }
+            | ConversionError::InputTooLarge => ErrorCategory::ResourceLimit,
+            ConversionError::InternalError(_) | ConversionError::IoError(_) => {
+                ErrorCategory::Internal
+            }
+        }
+    }
+
+    /// Whether retrying the same conversion (unchanged) has a reasonable
+    /// chance of succeeding: a [`Self::Timeout`] may simply need a larger
+    /// `timeout_ms`, and transient resource exhaustion
+    /// ([`Self::MemoryLimit`], [`Self::IoError`]) may clear on its own.
+    /// Parse/input errors are deterministic - retrying without changing the
+    /// input or options will fail the same way every time.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ConversionError::Timeout { .. }
+                | ConversionError::MemoryLimit { .. }
+                | ConversionError::IoError(_)
+        )
+    }
+
+    /// Coarse operational severity, for log-level selection: a client input
+    /// error is routine and expected in normal operation, a resource limit
+    /// is worth a warning since it may indicate undersized budgets, and an
+    /// internal error always warrants investigation
+    pub fn severity(&self) -> ErrorSeverity {
+        match self.category() {
+            ErrorCategory::ClientInput => ErrorSeverity::Info,
+            ErrorCategory::ResourceLimit => ErrorSeverity::Warning,
+            ErrorCategory::Internal => ErrorSeverity::Error,
+        }
+    }
+}
+
+/// Coarse operational severity for a [`ConversionError`], independent of its
+/// [`ErrorCategory`] - see [`ConversionError::severity`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ErrorSeverity {
+    /// Routine, expected in normal operation
+    Info,
+    /// Worth a warning; may indicate undersized budgets
+    Warning,
+    /// Always warrants investigation
+    Error,
+}
+
+/// Coarse class a [`ConversionError`] falls into, for mapping to an HTTP
+/// status without an FFI caller needing to know every numeric [`ConversionError::code`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCategory {
+    /// The request itself was malformed or unsupported (bad HTML, unknown
+    /// encoding, NULL/invalid arguments) - not retryable as-is; maps to
+    /// an HTTP `400`
+    ClientInput,
+    /// A configured timeout or size cap was hit - may succeed on retry
+    /// with a larger budget; maps to an HTTP `503`
+    ResourceLimit,
+    /// An unexpected internal condition (including a caught panic); maps
+    /// to an HTTP `500`
+    Internal,
 }
 
 impl fmt::Display for ConversionError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            ConversionError::ParseError(msg) => write!(f, "Parse error: {}", msg),
+            ConversionError::ParseError { message, line, col, offset } => match (line, col) {
+                (Some(line), Some(col)) => write!(f, "parse error at {line}:{col}: {message}"),
+                _ => match offset {
+                    Some(offset) => write!(f, "parse error at byte {offset}: {message}"),
+                    None => write!(f, "Parse error: {}", message),
+                },
+            },
             ConversionError::EncodingError(msg) => write!(f, "Encoding error: {}", msg),
-            ConversionError::Timeout => write!(f, "Conversion timeout exceeded"),
-            ConversionError::MemoryLimit => write!(f, "Memory limit exceeded"),
+            ConversionError::Timeout { elapsed_ms, limit_ms } => {
+                write!(f, "Conversion timeout exceeded ({elapsed_ms}ms > {limit_ms}ms)")
+            }
+            ConversionError::MemoryLimit { used_bytes, limit_bytes } => {
+                write!(f, "Memory limit exceeded ({used_bytes} bytes > {limit_bytes} bytes)")
+            }
             ConversionError::InvalidInput(msg) => write!(f, "Invalid input: {}", msg),
             ConversionError::InternalError(msg) => write!(f, "Internal error: {}", msg),
+            ConversionError::UnknownEncoding(label) => {
+                write!(f, "Unknown encoding label: {}", label)
+            }
+            ConversionError::InputTooLarge => write!(f, "Input exceeds max_input_bytes"),
+            ConversionError::Utf8Error(e) => write!(f, "Encoding error: {}", e),
+            ConversionError::IoError(e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConversionError::Utf8Error(e) => Some(e),
+            ConversionError::IoError(e) => Some(e),
+            _ => None,
         }
     }
 }
 
-impl std::error::Error for ConversionError {}
+impl From<std::str::Utf8Error> for ConversionError {
+    fn from(e: std::str::Utf8Error) -> Self {
+        ConversionError::Utf8Error(e)
+    }
+}
+
+impl From<std::string::FromUtf8Error> for ConversionError {
+    fn from(e: std::string::FromUtf8Error) -> Self {
+        ConversionError::Utf8Error(e.utf8_error())
+    }
+}
+
+impl From<std::io::Error> for ConversionError {
+    fn from(e: std::io::Error) -> Self {
+        ConversionError::IoError(e)
+    }
+}
+
+// Per-subsystem error sets
+//
+// `ConversionError` is the crate's single public error type, but stuffing
+// every subsystem's failures into one flat enum pushes unrelated code down
+// the `InternalError(String)` catch-all. These narrower enums let a
+// subsystem's internal helpers return only the variants they can actually
+// produce, then coerce into `ConversionError` via `From` wherever `?` is
+// used in a function that returns the public error type - so the public API
+// still surfaces a single `ConversionError`, while exhaustive `match`es
+// inside a subsystem stay honest about what that subsystem can fail with.
+// [`LimitError`] (the timeout/output-budget guard in
+// [`crate::converter::ConversionContext`]) and [`EncoderError`]
+// ([`crate::compression::compress`]) are wired up below; [`ParserError`] and
+// [`SanitizerError`] are published now so `parser`/`security` can adopt the
+// same pattern incrementally without another round of public-enum churn.
+
+/// Errors the conversion-budget guard ([`crate::converter::ConversionContext`]'s
+/// timeout and output-size checkpoints) can produce
+#[derive(Debug)]
+pub enum LimitError {
+    /// Conversion timeout exceeded
+    Timeout {
+        /// How long the conversion had been running when the timeout fired
+        elapsed_ms: u64,
+        /// The configured `timeout_ms` budget that was exceeded
+        limit_ms: u64,
+    },
+    /// Memory limit exceeded (output size)
+    MemoryLimit {
+        /// The size that tripped the limit (bytes produced/written so far)
+        used_bytes: usize,
+        /// The configured `max_output_bytes` cap that was exceeded
+        limit_bytes: usize,
+    },
+}
+
+impl fmt::Display for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitError::Timeout { elapsed_ms, limit_ms } => {
+                write!(f, "Conversion timeout exceeded ({elapsed_ms}ms > {limit_ms}ms)")
+            }
+            LimitError::MemoryLimit { used_bytes, limit_bytes } => {
+                write!(f, "Memory limit exceeded ({used_bytes} bytes > {limit_bytes} bytes)")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LimitError {}
+
+impl From<LimitError> for ConversionError {
+    fn from(e: LimitError) -> Self {
+        match e {
+            LimitError::Timeout { elapsed_ms, limit_ms } => {
+                ConversionError::Timeout { elapsed_ms, limit_ms }
+            }
+            LimitError::MemoryLimit { used_bytes, limit_bytes } => {
+                ConversionError::MemoryLimit { used_bytes, limit_bytes }
+            }
+        }
+    }
+}
+
+/// Errors [`crate::compression::compress`] and its per-encoding helpers can
+/// produce
+#[derive(Debug)]
+pub enum EncoderError {
+    /// The underlying compressor's writer reported an I/O failure (e.g. an
+    /// out-of-memory condition); malformed input is not possible since
+    /// compressors operate on arbitrary bytes
+    IoError(std::io::Error),
+}
+
+impl fmt::Display for EncoderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncoderError::IoError(e) => write!(f, "compression failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for EncoderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EncoderError::IoError(e) => Some(e),
+        }
+    }
+}
+
+impl From<EncoderError> for ConversionError {
+    fn from(e: EncoderError) -> Self {
+        match e {
+            EncoderError::IoError(e) => ConversionError::InternalError(e.to_string()),
+        }
+    }
+}
+
+/// Errors the HTML parsing subsystem ([`crate::parser`]) can produce
+///
+/// Not yet returned by `parser`'s public functions - those keep returning
+/// `ConversionError` directly today so their existing doc examples and
+/// variant-matching tests don't need to change - but published so internal
+/// parsing helpers can start adopting it.
+#[derive(Debug)]
+pub enum ParserError {
+    /// Invalid input data
+    InvalidInput(String),
+    /// A caller-supplied encoding label isn't recognized by `encoding_rs`
+    UnknownEncoding(String),
+    /// Bytes that were expected to be valid UTF-8 weren't
+    Utf8Error(std::str::Utf8Error),
+}
+
+impl fmt::Display for ParserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParserError::InvalidInput(msg) => write!(f, "Invalid input: {}", msg),
+            ParserError::UnknownEncoding(label) => {
+                write!(f, "Unknown encoding label: {}", label)
+            }
+            ParserError::Utf8Error(e) => write!(f, "Encoding error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ParserError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParserError::Utf8Error(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParserError> for ConversionError {
+    fn from(e: ParserError) -> Self {
+        match e {
+            ParserError::InvalidInput(msg) => ConversionError::InvalidInput(msg),
+            ParserError::UnknownEncoding(label) => ConversionError::UnknownEncoding(label),
+            ParserError::Utf8Error(e) => ConversionError::Utf8Error(e),
+        }
+    }
+}
+
+/// Errors the sanitization subsystem ([`crate::security`]) can produce
+///
+/// Not yet returned anywhere - `security`'s policy checks currently report
+/// decisions via `bool`/`Option` rather than `Result` - but published
+/// alongside its sibling error sets for when that changes.
+#[derive(Debug)]
+pub enum SanitizerError {
+    /// Input rejected by a sanitization policy
+    InvalidInput(String),
+}
+
+impl fmt::Display for SanitizerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SanitizerError::InvalidInput(msg) => write!(f, "Invalid input: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for SanitizerError {}
+
+impl From<SanitizerError> for ConversionError {
+    fn from(e: SanitizerError) -> Self {
+        match e {
+            SanitizerError::InvalidInput(msg) => ConversionError::InvalidInput(msg),
+        }
+    }
+}
@@ -1,22 +1,49 @@
-//! ETag generation using BLAKE3 hashing
+//! ETag generation using configurable hash algorithms
 //!
-//! This module provides ETag generation for HTTP caching using the BLAKE3 hash algorithm.
-//! ETags are used to identify specific versions of resources and enable efficient caching
-//! and conditional requests.
+//! This module provides ETag generation for HTTP caching using a configurable
+//! hash algorithm. ETags are used to identify specific versions of resources
+//! and enable efficient caching and conditional requests.
 //!
 //! # Algorithm
 //!
-//! 1. Hash the Markdown output bytes using BLAKE3
-//! 2. Take the first 128 bits (16 bytes) of the hash
-//! 3. Encode as hexadecimal string
-//! 4. Wrap in double quotes per HTTP specification (RFC 9110)
+//! 1. Hash the Markdown output bytes using the configured [`HashAlgorithm`]
+//! 2. Encode the (possibly truncated) digest as a hexadecimal string
+//! 3. Wrap in double quotes per HTTP specification (RFC 9110), or prefix with
+//!    `W/` for a weak validator
 //!
-//! # Why BLAKE3?
+//! # Choosing an Algorithm
 //!
-//! - **Fast**: Significantly faster than MD5, SHA-1, SHA-2
-//! - **Secure**: Cryptographically secure (though not required for ETags)
-//! - **Deterministic**: Same input always produces same output
-//! - **Collision-resistant**: Extremely unlikely to produce same hash for different content
+//! - [`HashAlgorithm::Blake3Truncated128`] (the default): fast and
+//!   collision-resistant for cache validation, producing a 32-hex-char ETag.
+//! - [`HashAlgorithm::Sha256Truncated128`]: same 32-hex-char length, for
+//!   deployments standardizing on SHA-2.
+//! - [`HashAlgorithm::Sha256Full`]: the full 256-bit digest (64 hex chars),
+//!   for deployments that want stronger collision resistance than a 128-bit
+//!   tag and don't mind a longer validator.
+//! - [`HashAlgorithm::Sha512Truncated128`] / [`HashAlgorithm::Sha512Full`]:
+//!   same trade-offs as the SHA-256 pair, for deployments standardizing on
+//!   SHA-512 instead (e.g. to match a reverse proxy or test harness that
+//!   already speaks SHA-2-family validators at that width).
+//!
+//! # Configurable Tag Length
+//!
+//! Each [`HashAlgorithm`] variant has a default truncation length (128 bits
+//! for the `*Truncated128` variants, the complete digest for the `*Full`
+//! ones). Operators with very large caches who want stronger collision
+//! guarantees without switching algorithms can override this directly with
+//! [`ETagGenerator::with_bytes`], or request the complete digest with
+//! [`ETagGenerator::full`].
+//!
+//! # Semantically Significant Metadata
+//!
+//! By default the ETag is a pure function of the Markdown bytes, so two
+//! responses that differ only in content negotiation (e.g. a different
+//! `Content-Type` or charset) can collide. When that distinction matters,
+//! register the relevant fields with
+//! [`ETagGenerator::with_context_field`]; their values are folded into the
+//! hash input ahead of the Markdown bytes, and the generator automatically
+//! switches to weak validators, since a tag derived from representation
+//! metadata can't guarantee byte-for-byte identity.
 //!
 //! # HTTP Specification Compliance
 //!
@@ -24,6 +51,24 @@
 //! - Strong ETags: `"<hex-string>"` (quoted)
 //! - Weak ETags: `W/"<hex-string>"` (prefixed with W/)
 //!
+//! # Streaming / Incremental Computation
+//!
+//! [`ETagGenerator::generate`] takes the whole document at once, which means
+//! buffering it in memory. For a pipeline that produces Markdown in chunks
+//! (e.g. `markdown_convert_streaming`'s fragment callback), use
+//! [`ETagGenerator::incremental`] instead: feed each chunk to
+//! [`IncrementalEtag::update`] as it is produced, then call
+//! [`IncrementalEtag::finish`] once the document is complete. This keeps
+//! ETag computation O(1) in extra memory, since only the running hash
+//! state is held, not the accumulated bytes.
+//!
+//! # Consuming Incoming ETags
+//!
+//! [`ETagGenerator`] only produces ETags. To compare a client-supplied
+//! `If-None-Match` header against one, parse it with [`EntityTag`] and
+//! check [`EntityTag::weak_eq`], or use the [`is_not_modified`] helper
+//! directly to decide whether a `304 Not Modified` response is warranted.
+//!
 //! # Requirements
 //!
 //! - **FR-04.5**: Generate ETag for Markdown variant
@@ -48,21 +93,290 @@
 //! assert_eq!(etag, etag2);
 //! ```
 
-use blake3;
+use sha2::{Digest, Sha256, Sha512};
+
+/// Hash algorithm used to compute an ETag's validator bytes
+///
+/// Every variant is deterministic (identical input always yields an
+/// identical digest), which is the property [`ETagGenerator`] relies on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    /// BLAKE3, truncated to the first 128 bits (32 hex characters). This is
+    /// the historical default: significantly faster than SHA-2 while still
+    /// cryptographically collision-resistant.
+    Blake3Truncated128,
+    /// SHA-256, truncated to the first 128 bits (32 hex characters), for
+    /// deployments that want a SHA-2-family validator at the same length as
+    /// the default.
+    Sha256Truncated128,
+    /// The full 256-bit SHA-256 digest (64 hex characters), for stronger
+    /// collision resistance than the 128-bit truncations.
+    Sha256Full,
+    /// SHA-512, truncated to the first 128 bits (32 hex characters), for
+    /// deployments standardizing on SHA-512 instead of SHA-256.
+    Sha512Truncated128,
+    /// The full 512-bit SHA-512 digest (128 hex characters), for deployments
+    /// that want the strongest available collision resistance and don't
+    /// mind the longer validator.
+    Sha512Full,
+}
+
+impl HashAlgorithm {
+    /// Compute this algorithm's complete, untruncated digest bytes for
+    /// `markdown`
+    ///
+    /// Truncation is applied separately by [`ETagGenerator`] (see
+    /// [`ETagGenerator::with_bytes`]), not by this method, so that the
+    /// truncation length can be configured independently of which hash
+    /// function produced the digest.
+    fn full_digest(self, markdown: &[u8]) -> Vec<u8> {
+        match self {
+            HashAlgorithm::Blake3Truncated128 => blake3::hash(markdown).as_bytes().to_vec(),
+            HashAlgorithm::Sha256Truncated128 | HashAlgorithm::Sha256Full => {
+                Sha256::digest(markdown).to_vec()
+            }
+            HashAlgorithm::Sha512Truncated128 | HashAlgorithm::Sha512Full => {
+                Sha512::digest(markdown).to_vec()
+            }
+        }
+    }
+
+    /// Length in bytes of this algorithm's complete, untruncated digest
+    fn full_digest_len(self) -> usize {
+        match self {
+            HashAlgorithm::Blake3Truncated128
+            | HashAlgorithm::Sha256Truncated128
+            | HashAlgorithm::Sha256Full => 32,
+            HashAlgorithm::Sha512Truncated128 | HashAlgorithm::Sha512Full => 64,
+        }
+    }
+
+    /// This variant's truncation length in bytes, absent an explicit
+    /// [`ETagGenerator::with_bytes`]/[`ETagGenerator::full`] override
+    fn default_truncate_bytes(self) -> usize {
+        match self {
+            HashAlgorithm::Blake3Truncated128
+            | HashAlgorithm::Sha256Truncated128
+            | HashAlgorithm::Sha512Truncated128 => 16,
+            HashAlgorithm::Sha256Full | HashAlgorithm::Sha512Full => self.full_digest_len(),
+        }
+    }
+
+    /// Length in hex characters of this algorithm's encoded digest, absent
+    /// an explicit [`ETagGenerator::with_bytes`]/[`ETagGenerator::full`]
+    /// override
+    ///
+    /// Exposed so tests (and callers validating a configured generator's
+    /// output) don't need to hardcode a length that depends on the chosen
+    /// algorithm. Callers that configure [`ETagGenerator::with_bytes`] or
+    /// [`ETagGenerator::full`] should use [`ETagGenerator::hex_len`]
+    /// instead, which accounts for the override.
+    pub fn hex_len(self) -> usize {
+        match self {
+            HashAlgorithm::Blake3Truncated128
+            | HashAlgorithm::Sha256Truncated128
+            | HashAlgorithm::Sha512Truncated128 => 32,
+            HashAlgorithm::Sha256Full => 64,
+            HashAlgorithm::Sha512Full => 128,
+        }
+    }
+}
+
+impl Default for HashAlgorithm {
+    fn default() -> Self {
+        HashAlgorithm::Blake3Truncated128
+    }
+}
 
-/// ETag generator using BLAKE3 hash
-pub struct ETagGenerator;
+/// ETag generator with a configurable hash algorithm and validator strength
+pub struct ETagGenerator {
+    algorithm: HashAlgorithm,
+    weak: bool,
+    /// `"name=value"` entries folded (each followed by a NUL) into the hash
+    /// input ahead of the Markdown bytes; see [`Self::with_context_field`].
+    context_fields: Vec<String>,
+    /// Explicit truncation length in bytes, overriding the algorithm's
+    /// default; see [`Self::with_bytes`].
+    truncate_bytes: Option<usize>,
+}
 
 impl ETagGenerator {
-    /// Create a new ETag generator
+    /// Create a new ETag generator using the default algorithm
+    /// ([`HashAlgorithm::Blake3Truncated128`]) and strong validators
     pub fn new() -> Self {
-        Self
+        Self {
+            algorithm: HashAlgorithm::default(),
+            weak: false,
+            context_fields: Vec::new(),
+            truncate_bytes: None,
+        }
+    }
+
+    /// Create a new ETag generator using a specific hash algorithm
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nginx_markdown_converter::etag_generator::{ETagGenerator, HashAlgorithm};
+    ///
+    /// let generator = ETagGenerator::with_algorithm(HashAlgorithm::Sha256Full);
+    /// let etag = generator.generate(b"# Hello World");
+    /// assert_eq!(etag.len(), 66); // 64 hex chars + 2 quotes
+    /// ```
+    pub fn with_algorithm(algorithm: HashAlgorithm) -> Self {
+        Self {
+            algorithm,
+            weak: false,
+            context_fields: Vec::new(),
+            truncate_bytes: None,
+        }
     }
 
-    /// Generate ETag from Markdown bytes
+    /// Override the number of digest bytes encoded into the ETag
+    ///
+    /// Clamped to a sane range: at least 1 byte, and at most the configured
+    /// [`HashAlgorithm`]'s complete digest length (truncating beyond the
+    /// underlying hash's output would be meaningless). Operators with very
+    /// large caches who want stronger collision guarantees than the
+    /// algorithm's default truncation can opt into a longer validator this
+    /// way, without switching to a different [`HashAlgorithm`] variant. See
+    /// [`Self::full`] for a shorthand requesting the complete digest.
+    ///
+    /// # Examples
     ///
-    /// Uses BLAKE3 hash (first 128 bits) formatted as quoted hex string
-    /// per HTTP specification.
+    /// ```
+    /// use nginx_markdown_converter::etag_generator::ETagGenerator;
+    ///
+    /// let generator = ETagGenerator::new().with_bytes(24);
+    /// let etag = generator.generate(b"# Hello World");
+    /// assert_eq!(etag.len(), 24 * 2 + 2); // 24 bytes -> 48 hex chars + 2 quotes
+    /// assert_eq!(etag.len(), generator.hex_len() + 2);
+    /// ```
+    pub fn with_bytes(mut self, bytes: usize) -> Self {
+        self.truncate_bytes = Some(bytes.max(1));
+        self
+    }
+
+    /// Request the complete, untruncated digest of the configured
+    /// [`HashAlgorithm`] (e.g. the full 256-bit BLAKE3 digest for the
+    /// default algorithm)
+    ///
+    /// Equivalent to `with_bytes(usize::MAX)`, since [`Self::with_bytes`]
+    /// clamps to the algorithm's full digest length.
+    pub fn full(self) -> Self {
+        self.with_bytes(usize::MAX)
+    }
+
+    /// Number of digest bytes this generator's configuration actually
+    /// encodes into an ETag, accounting for any [`Self::with_bytes`]/
+    /// [`Self::full`] override
+    fn effective_bytes(&self) -> usize {
+        let configured = self
+            .truncate_bytes
+            .unwrap_or_else(|| self.algorithm.default_truncate_bytes());
+        configured.min(self.algorithm.full_digest_len())
+    }
+
+    /// Hex-character length of the ETag digest this generator is configured
+    /// to produce, accounting for any [`Self::with_bytes`]/[`Self::full`]
+    /// override
+    ///
+    /// Exposed so tests (and callers validating a configured generator's
+    /// output) don't need to hardcode a length.
+    pub fn hex_len(&self) -> usize {
+        self.effective_bytes() * 2
+    }
+
+    /// Set whether [`Self::generate`] emits weak validators (`W/"..."`)
+    /// instead of strong ones (`"..."`)
+    ///
+    /// Weak ETags indicate semantic equivalence rather than byte-for-byte
+    /// identity, which is appropriate when the caller's cache key tolerates
+    /// insignificant differences (e.g. whitespace-only re-renders).
+    pub fn with_weak_validator(mut self, weak: bool) -> Self {
+        self.weak = weak;
+        self
+    }
+
+    /// Register a semantically significant field (e.g. `Content-Type`,
+    /// charset, converter version/options) whose value is folded into the
+    /// hash input alongside the Markdown bytes
+    ///
+    /// Two responses with identical Markdown bytes but different values for
+    /// a registered field produce different ETags, preventing collisions
+    /// across content negotiation. Call repeatedly to register more than one
+    /// field; fields are folded in registration order.
+    ///
+    /// A generator with one or more context fields always emits weak
+    /// validators (`W/"..."`) from [`Self::generate`], regardless of
+    /// [`Self::with_weak_validator`]: a tag derived from representation
+    /// metadata rather than exact body bytes can't guarantee byte-for-byte
+    /// identity, so treating it as strong would be misleading.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nginx_markdown_converter::etag_generator::ETagGenerator;
+    ///
+    /// let generator = ETagGenerator::new()
+    ///     .with_context_field("content-type", "text/markdown")
+    ///     .with_context_field("charset", "UTF-8");
+    /// let etag = generator.generate(b"# Hello World");
+    /// assert!(etag.starts_with("W/\"")); // context fields force a weak validator
+    ///
+    /// // Same bytes, different charset: different ETag.
+    /// let other_charset = ETagGenerator::new()
+    ///     .with_context_field("content-type", "text/markdown")
+    ///     .with_context_field("charset", "windows-1252");
+    /// assert_ne!(etag, other_charset.generate(b"# Hello World"));
+    /// ```
+    pub fn with_context_field(mut self, name: &str, value: &str) -> Self {
+        self.context_fields.push(format!("{name}={value}"));
+        self
+    }
+
+    /// Whether [`Self::generate`] should emit a weak validator: either
+    /// explicitly requested via [`Self::with_weak_validator`], or implied by
+    /// one or more registered [`Self::with_context_field`] entries
+    fn emits_weak(&self) -> bool {
+        self.weak || !self.context_fields.is_empty()
+    }
+
+    /// Bytes for every registered context field, each followed by a NUL
+    /// separator, in registration order
+    ///
+    /// Empty when no context fields are registered, so callers with no
+    /// context fields hash exactly the Markdown bytes with no overhead.
+    fn context_prefix(&self) -> Vec<u8> {
+        let mut prefix = Vec::new();
+        for field in &self.context_fields {
+            prefix.extend_from_slice(field.as_bytes());
+            prefix.push(0);
+        }
+        prefix
+    }
+
+    /// Hex-encode this generator's configured digest of `markdown`, preceded
+    /// by any registered context fields and truncated to
+    /// [`Self::effective_bytes`]
+    fn hash_hex(&self, markdown: &[u8]) -> String {
+        let full = if self.context_fields.is_empty() {
+            self.algorithm.full_digest(markdown)
+        } else {
+            let mut input = self.context_prefix();
+            input.extend_from_slice(markdown);
+            self.algorithm.full_digest(&input)
+        };
+
+        hex::encode(&full[..self.effective_bytes()])
+    }
+
+    /// Generate an ETag from Markdown bytes
+    ///
+    /// Uses the configured [`HashAlgorithm`], formatted as a quoted hex
+    /// string per HTTP specification. Emits a weak validator (`W/"..."`)
+    /// instead when [`Self::with_weak_validator`] was set to `true`, or when
+    /// any [`Self::with_context_field`] is registered.
     ///
     /// # Arguments
     ///
@@ -70,7 +384,7 @@ impl ETagGenerator {
     ///
     /// # Returns
     ///
-    /// ETag string in format: "hexhexhex..."
+    /// ETag string in format: `"hexhexhex..."` (or `W/"hexhexhex..."`)
     ///
     /// # Example
     ///
@@ -83,20 +397,56 @@ impl ETagGenerator {
     /// assert!(etag.ends_with('"'));
     /// ```
     pub fn generate(&self, markdown: &[u8]) -> String {
-        let hash = blake3::hash(markdown);
-        let hash_bytes = hash.as_bytes();
-
-        // Use first 16 bytes (128 bits) for ETag
-        // Format as quoted hex string per HTTP spec
-        format!("\"{}\"", hex::encode(&hash_bytes[..16]))
+        let hex = self.hash_hex(markdown);
+        if self.emits_weak() {
+            format!("W/\"{hex}\"")
+        } else {
+            format!("\"{hex}\"")
+        }
     }
 
-    /// Generate weak ETag (W/"...")
+    /// Generate a weak ETag (`W/"..."`) regardless of [`Self::with_weak_validator`]
     ///
     /// Weak ETags indicate semantic equivalence rather than byte-for-byte
-    /// identity. Currently not used but provided for future extensibility.
+    /// identity. Kept as an explicit, always-weak shorthand alongside the
+    /// configurable [`Self::generate`] for callers that only ever want a weak
+    /// validator from this generator.
     pub fn generate_weak(&self, markdown: &[u8]) -> String {
-        format!("W/{}", self.generate(markdown))
+        format!("W/\"{}\"", self.hash_hex(markdown))
+    }
+
+    /// Start an [`IncrementalEtag`] using this generator's configured
+    /// algorithm and validator strength
+    ///
+    /// For callers that produce Markdown in chunks (e.g.
+    /// `markdown_convert_streaming`'s fragment callback) and want to avoid
+    /// buffering the whole document just to compute an ETag afterwards:
+    /// feed each fragment to [`IncrementalEtag::update`] as it is produced,
+    /// then call [`IncrementalEtag::finish`] once the document is complete.
+    /// Produces the same ETag [`Self::generate`] would for the concatenation
+    /// of every fed chunk. Any registered [`Self::with_context_field`]
+    /// entries are folded in up front, before the first fed chunk.
+    pub fn incremental(&self) -> IncrementalEtag {
+        let mut incremental = IncrementalEtag {
+            weak: self.emits_weak(),
+            truncate_bytes: self.effective_bytes(),
+            state: match self.algorithm {
+                HashAlgorithm::Blake3Truncated128 => {
+                    IncrementalHashState::Blake3(Box::new(blake3::Hasher::new()))
+                }
+                HashAlgorithm::Sha256Truncated128 | HashAlgorithm::Sha256Full => {
+                    IncrementalHashState::Sha256(Box::new(Sha256::new()))
+                }
+                HashAlgorithm::Sha512Truncated128 | HashAlgorithm::Sha512Full => {
+                    IncrementalHashState::Sha512(Box::new(Sha512::new()))
+                }
+            },
+        };
+
+        if !self.context_fields.is_empty() {
+            incremental.update(&self.context_prefix());
+        }
+        incremental
     }
 }
 
@@ -106,56 +456,392 @@ impl Default for ETagGenerator {
     }
 }
 
+/// A single parsed entity tag, as found in an `ETag` or `If-None-Match`
+/// header value (RFC 9110 §8.8.3)
+///
+/// Supports the `*` wildcard (matches any entity tag) in addition to
+/// ordinary `"opaque"` / `W/"opaque"` tags.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntityTag {
+    /// `*`: matches any entity tag
+    Any,
+    /// An ordinary entity tag, with its weakness and opaque string
+    Tag {
+        /// `true` for a `W/"..."` weak validator, `false` for `"..."`
+        weak: bool,
+        /// The quoted string's contents, with the surrounding quotes removed
+        opaque: String,
+    },
+}
+
+impl EntityTag {
+    /// Parse a single entity tag from `value`
+    ///
+    /// Accepts an optional leading `W/` weak indicator, a double-quoted
+    /// opaque tag (e.g. `"abc123"` or `W/"abc123"`), or the `*` wildcard.
+    /// Surrounding whitespace is ignored. Returns `None` if `value` is
+    /// neither a well-formed quoted entity tag nor `*`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nginx_markdown_converter::etag_generator::EntityTag;
+    ///
+    /// let strong = EntityTag::parse(r#""abc123""#).unwrap();
+    /// assert!(!strong.is_weak());
+    ///
+    /// let weak = EntityTag::parse(r#"W/"abc123""#).unwrap();
+    /// assert!(weak.is_weak());
+    ///
+    /// assert_eq!(EntityTag::parse("*"), Some(EntityTag::Any));
+    /// assert_eq!(EntityTag::parse("abc123"), None); // missing quotes
+    /// ```
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if value == "*" {
+            return Some(EntityTag::Any);
+        }
+
+        let (weak, quoted) = match value.strip_prefix("W/") {
+            Some(rest) => (true, rest),
+            None => (false, value),
+        };
+
+        let opaque = quoted.strip_prefix('"')?.strip_suffix('"')?;
+        Some(EntityTag::Tag {
+            weak,
+            opaque: opaque.to_string(),
+        })
+    }
+
+    /// Parse a comma-separated list of entity tags, as found in an
+    /// `If-None-Match` (or `If-Match`) header value
+    ///
+    /// Entries that fail to parse are skipped rather than aborting the
+    /// whole list, since a single malformed entry shouldn't prevent
+    /// matching against the well-formed ones the client also sent.
+    pub fn parse_list(value: &str) -> Vec<Self> {
+        value
+            .split(',')
+            .filter_map(|entry| EntityTag::parse(entry))
+            .collect()
+    }
+
+    /// Whether this tag is a weak validator (`W/"..."`)
+    ///
+    /// [`EntityTag::Any`] is never weak.
+    pub fn is_weak(&self) -> bool {
+        matches!(self, EntityTag::Tag { weak: true, .. })
+    }
+
+    /// Strong comparison (RFC 9110 §8.8.3.2): `true` only if neither tag is
+    /// weak and their opaque strings are byte-for-byte equal
+    ///
+    /// Appropriate for range requests and other uses that require
+    /// byte-identical representations.
+    pub fn strong_eq(&self, other: &EntityTag) -> bool {
+        match (self, other) {
+            (EntityTag::Any, EntityTag::Any) => true,
+            (
+                EntityTag::Tag {
+                    weak: false,
+                    opaque: a,
+                },
+                EntityTag::Tag {
+                    weak: false,
+                    opaque: b,
+                },
+            ) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Weak comparison (RFC 9110 §8.8.3.2): ignores the weak flag of either
+    /// tag and compares only the opaque strings
+    ///
+    /// Appropriate for `GET`/`HEAD` conditional requests (`If-None-Match`),
+    /// where semantic equivalence is enough to justify a `304 Not Modified`.
+    pub fn weak_eq(&self, other: &EntityTag) -> bool {
+        match (self, other) {
+            (EntityTag::Any, EntityTag::Any) => true,
+            (EntityTag::Tag { opaque: a, .. }, EntityTag::Tag { opaque: b, .. }) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// Decide whether a `GET`/`HEAD` request carrying an `If-None-Match` header
+/// can be answered with `304 Not Modified`
+///
+/// `if_none_match` is the raw header value (a comma-separated list of entity
+/// tags, or `*`). `generated_etag` is the ETag this module just produced for
+/// the current Markdown output (e.g. via [`ETagGenerator::generate`]).
+///
+/// Per RFC 9110 §13.1.2, `If-None-Match` evaluation for `GET`/`HEAD` uses
+/// weak comparison, so a match is reported whenever any list entry is
+/// weakly equal to `generated_etag`, regardless of either tag's weak flag.
+/// `*` matches unconditionally, since its meaning is "any representation of
+/// this resource."
+///
+/// # Examples
+///
+/// ```
+/// use nginx_markdown_converter::etag_generator::is_not_modified;
+///
+/// assert!(is_not_modified(r#""abc123""#, r#""abc123""#));
+/// assert!(is_not_modified(r#"W/"abc123""#, r#""abc123""#)); // weak comparison
+/// assert!(is_not_modified("*", r#""abc123""#));
+/// assert!(!is_not_modified(r#""abc123""#, r#""xyz789""#));
+/// ```
+pub fn is_not_modified(if_none_match: &str, generated_etag: &str) -> bool {
+    let Some(generated) = EntityTag::parse(generated_etag) else {
+        return false;
+    };
+
+    EntityTag::parse_list(if_none_match)
+        .iter()
+        .any(|candidate| *candidate == EntityTag::Any || candidate.weak_eq(&generated))
+}
+
+enum IncrementalHashState {
+    Blake3(Box<blake3::Hasher>),
+    Sha256(Box<Sha256>),
+    Sha512(Box<Sha512>),
+}
+
+/// Streaming counterpart to [`ETagGenerator::generate`], built via
+/// [`ETagGenerator::incremental`]
+///
+/// See [`ETagGenerator::incremental`] for when to reach for this instead of
+/// the one-shot [`ETagGenerator::generate`].
+pub struct IncrementalEtag {
+    weak: bool,
+    /// Number of leading digest bytes to keep, mirroring the generator's
+    /// [`ETagGenerator::effective_bytes`] at the time [`ETagGenerator::incremental`]
+    /// was called.
+    truncate_bytes: usize,
+    state: IncrementalHashState,
+}
+
+impl IncrementalEtag {
+    /// Feed the next chunk of Markdown bytes into the running hash
+    ///
+    /// Chunks may be any length; the result does not depend on how the full
+    /// document was split across calls.
+    pub fn update(&mut self, chunk: &[u8]) {
+        match &mut self.state {
+            IncrementalHashState::Blake3(hasher) => {
+                hasher.update(chunk);
+            }
+            IncrementalHashState::Sha256(hasher) => {
+                hasher.update(chunk);
+            }
+            IncrementalHashState::Sha512(hasher) => {
+                hasher.update(chunk);
+            }
+        }
+    }
+
+    /// Finalize the hash over every chunk fed so far, formatted the same
+    /// way [`ETagGenerator::generate`] would format it
+    pub fn finish(self) -> String {
+        let full = match self.state {
+            IncrementalHashState::Blake3(hasher) => hasher.finalize().as_bytes().to_vec(),
+            IncrementalHashState::Sha256(hasher) => hasher.finalize().to_vec(),
+            IncrementalHashState::Sha512(hasher) => hasher.finalize().to_vec(),
+        };
+        let hex = hex::encode(&full[..self.truncate_bytes.min(full.len())]);
+        if self.weak {
+            format!("W/\"{hex}\"")
+        } else {
+            format!("\"{hex}\"")
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use proptest::prelude::*;
 
+    /// All algorithms a generalized format-compliance test should cover
+    const ALL_ALGORITHMS: &[HashAlgorithm] = &[
+        HashAlgorithm::Blake3Truncated128,
+        HashAlgorithm::Sha256Truncated128,
+        HashAlgorithm::Sha256Full,
+        HashAlgorithm::Sha512Truncated128,
+        HashAlgorithm::Sha512Full,
+    ];
+
     #[test]
     fn test_etag_format() {
-        let generator = ETagGenerator::new();
-        let etag = generator.generate(b"test content");
+        for algorithm in ALL_ALGORITHMS {
+            let generator = ETagGenerator::with_algorithm(*algorithm);
+            let etag = generator.generate(b"test content");
 
-        // Should be quoted
-        assert!(etag.starts_with('"'));
-        assert!(etag.ends_with('"'));
+            // Should be quoted
+            assert!(etag.starts_with('"'));
+            assert!(etag.ends_with('"'));
 
-        // Should be hex (32 chars + 2 quotes = 34 total)
-        assert_eq!(etag.len(), 34);
+            // Should be hex, with a length determined by the algorithm
+            assert_eq!(etag.len(), algorithm.hex_len() + 2);
+        }
     }
 
     #[test]
     fn test_etag_consistency() {
-        let generator = ETagGenerator::new();
-        let content = b"consistent content";
+        for algorithm in ALL_ALGORITHMS {
+            let generator = ETagGenerator::with_algorithm(*algorithm);
+            let content = b"consistent content";
 
-        let etag1 = generator.generate(content);
-        let etag2 = generator.generate(content);
+            let etag1 = generator.generate(content);
+            let etag2 = generator.generate(content);
 
-        // Same content should produce same ETag
-        assert_eq!(etag1, etag2);
+            // Same content should produce same ETag
+            assert_eq!(etag1, etag2);
+        }
     }
 
     #[test]
     fn test_etag_uniqueness() {
-        let generator = ETagGenerator::new();
+        for algorithm in ALL_ALGORITHMS {
+            let generator = ETagGenerator::with_algorithm(*algorithm);
 
-        let etag1 = generator.generate(b"content 1");
-        let etag2 = generator.generate(b"content 2");
+            let etag1 = generator.generate(b"content 1");
+            let etag2 = generator.generate(b"content 2");
 
-        // Different content should produce different ETags
-        assert_ne!(etag1, etag2);
+            // Different content should produce different ETags
+            assert_ne!(etag1, etag2);
+        }
     }
 
     #[test]
     fn test_etag_empty_content() {
+        for algorithm in ALL_ALGORITHMS {
+            let generator = ETagGenerator::with_algorithm(*algorithm);
+            let etag = generator.generate(b"");
+
+            // Should still produce valid ETag for empty content
+            assert!(etag.starts_with('"'));
+            assert!(etag.ends_with('"'));
+            assert_eq!(etag.len(), algorithm.hex_len() + 2);
+        }
+    }
+
+    #[test]
+    fn test_incremental_etag_matches_one_shot_for_single_chunk() {
+        for algorithm in ALL_ALGORITHMS {
+            let generator = ETagGenerator::with_algorithm(*algorithm);
+            let content = b"single chunk content";
+
+            let mut incremental = generator.incremental();
+            incremental.update(content);
+
+            assert_eq!(incremental.finish(), generator.generate(content));
+        }
+    }
+
+    #[test]
+    fn test_incremental_etag_matches_one_shot_across_chunk_boundaries() {
+        for algorithm in ALL_ALGORITHMS {
+            let generator = ETagGenerator::with_algorithm(*algorithm);
+            let whole = b"first fragmentsecond fragmentthird fragment";
+
+            let mut incremental = generator.incremental();
+            incremental.update(b"first fragment");
+            incremental.update(b"second fragment");
+            incremental.update(b"third fragment");
+
+            assert_eq!(incremental.finish(), generator.generate(whole));
+        }
+    }
+
+    #[test]
+    fn test_incremental_etag_respects_weak_validator() {
+        let generator = ETagGenerator::new().with_weak_validator(true);
+        let mut incremental = generator.incremental();
+        incremental.update(b"content");
+
+        let etag = incremental.finish();
+        assert!(etag.starts_with("W/\""));
+        assert_eq!(etag, generator.generate(b"content"));
+    }
+
+    #[test]
+    fn test_context_field_forces_weak_validator() {
+        let generator = ETagGenerator::new().with_context_field("charset", "UTF-8");
+        let etag = generator.generate(b"content");
+        assert!(etag.starts_with("W/\""));
+    }
+
+    #[test]
+    fn test_context_field_changes_etag_for_identical_bytes() {
+        let utf8 = ETagGenerator::new().with_context_field("charset", "UTF-8");
+        let latin1 = ETagGenerator::new().with_context_field("charset", "windows-1252");
+
+        assert_ne!(utf8.generate(b"content"), latin1.generate(b"content"));
+    }
+
+    #[test]
+    fn test_context_field_differs_from_plain_generator_for_same_bytes() {
+        let plain = ETagGenerator::new();
+        let with_context = ETagGenerator::new().with_context_field("charset", "UTF-8");
+
+        assert_ne!(plain.generate(b"content"), with_context.generate(b"content"));
+    }
+
+    #[test]
+    fn test_multiple_context_fields_are_order_sensitive() {
+        let a = ETagGenerator::new()
+            .with_context_field("content-type", "text/markdown")
+            .with_context_field("charset", "UTF-8");
+        let b = ETagGenerator::new()
+            .with_context_field("charset", "UTF-8")
+            .with_context_field("content-type", "text/markdown");
+
+        assert_ne!(a.generate(b"content"), b.generate(b"content"));
+    }
+
+    #[test]
+    fn test_no_context_fields_matches_plain_generate() {
         let generator = ETagGenerator::new();
-        let etag = generator.generate(b"");
+        assert_eq!(
+            generator.generate(b"content"),
+            ETagGenerator::new().generate(b"content")
+        );
+        assert!(!generator.generate(b"content").starts_with("W/"));
+    }
 
-        // Should still produce valid ETag for empty content
-        assert!(etag.starts_with('"'));
-        assert!(etag.ends_with('"'));
-        assert_eq!(etag.len(), 34);
+    #[test]
+    fn test_context_fields_are_consistent_across_calls() {
+        let generator = ETagGenerator::new().with_context_field("charset", "UTF-8");
+        assert_eq!(generator.generate(b"content"), generator.generate(b"content"));
+    }
+
+    #[test]
+    fn test_incremental_etag_honors_context_fields() {
+        let generator = ETagGenerator::new().with_context_field("charset", "UTF-8");
+        let mut incremental = generator.incremental();
+        incremental.update(b"content");
+
+        assert_eq!(incremental.finish(), generator.generate(b"content"));
+    }
+
+    #[test]
+    fn test_incremental_etag_matches_one_shot_over_many_small_chunks() {
+        // Simulates a streaming pipeline feeding small fragments one at a
+        // time rather than buffering the whole document.
+        for algorithm in ALL_ALGORITHMS {
+            let generator = ETagGenerator::with_algorithm(*algorithm);
+            let whole: Vec<u8> = (0..2000u32).map(|b| (b % 256) as u8).collect();
+
+            let mut incremental = generator.incremental();
+            for chunk in whole.chunks(7) {
+                incremental.update(chunk);
+            }
+
+            assert_eq!(incremental.finish(), generator.generate(&whole));
+        }
     }
 
     #[test]
@@ -197,16 +883,18 @@ mod tests {
 
     #[test]
     fn test_etag_hex_characters() {
-        let generator = ETagGenerator::new();
-        let etag = generator.generate(b"test");
+        for algorithm in ALL_ALGORITHMS {
+            let generator = ETagGenerator::with_algorithm(*algorithm);
+            let etag = generator.generate(b"test");
 
-        // Remove quotes and verify all characters are valid hex
-        let hex_part = &etag[1..etag.len() - 1];
-        assert!(hex_part.chars().all(|c| c.is_ascii_hexdigit()));
+            // Remove quotes and verify all characters are valid hex
+            let hex_part = &etag[1..etag.len() - 1];
+            assert!(hex_part.chars().all(|c| c.is_ascii_hexdigit()));
+        }
     }
 
     #[test]
-    fn test_etag_128_bits() {
+    fn test_etag_128_bits_by_default() {
         let generator = ETagGenerator::new();
         let etag = generator.generate(b"test");
 
@@ -225,6 +913,308 @@ mod tests {
         assert!(weak_etag.ends_with('"'));
     }
 
+    #[test]
+    fn test_with_weak_validator_makes_generate_emit_weak_form() {
+        let strong = ETagGenerator::new();
+        let weak = ETagGenerator::new().with_weak_validator(true);
+
+        let strong_etag = strong.generate(b"test content");
+        let weak_etag = weak.generate(b"test content");
+
+        assert!(!strong_etag.starts_with("W/"));
+        assert!(weak_etag.starts_with("W/\""));
+        assert!(weak_etag.ends_with('"'));
+
+        // Both still carry the same digest, just a different wrapper.
+        assert_eq!(weak_etag, format!("W/{strong_etag}"));
+    }
+
+    #[test]
+    fn test_sha256_truncated_128_produces_32_hex_chars() {
+        let generator = ETagGenerator::with_algorithm(HashAlgorithm::Sha256Truncated128);
+        let etag = generator.generate(b"test content");
+
+        assert_eq!(etag.len(), 34);
+    }
+
+    #[test]
+    fn test_sha256_full_produces_64_hex_chars() {
+        let generator = ETagGenerator::with_algorithm(HashAlgorithm::Sha256Full);
+        let etag = generator.generate(b"test content");
+
+        assert_eq!(etag.len(), 66);
+    }
+
+    #[test]
+    fn test_different_algorithms_produce_different_etags_for_same_content() {
+        let content = b"same content, different algorithm";
+        let blake3 =
+            ETagGenerator::with_algorithm(HashAlgorithm::Blake3Truncated128).generate(content);
+        let sha256_truncated =
+            ETagGenerator::with_algorithm(HashAlgorithm::Sha256Truncated128).generate(content);
+        let sha256_full =
+            ETagGenerator::with_algorithm(HashAlgorithm::Sha256Full).generate(content);
+
+        assert_ne!(blake3, sha256_truncated);
+        assert_ne!(blake3, sha256_full);
+        assert_ne!(sha256_truncated, sha256_full);
+    }
+
+    #[test]
+    fn test_sha512_truncated_128_produces_32_hex_chars() {
+        let generator = ETagGenerator::with_algorithm(HashAlgorithm::Sha512Truncated128);
+        let etag = generator.generate(b"test content");
+
+        assert_eq!(etag.len(), 34);
+    }
+
+    #[test]
+    fn test_sha512_full_produces_128_hex_chars() {
+        let generator = ETagGenerator::with_algorithm(HashAlgorithm::Sha512Full);
+        let etag = generator.generate(b"test content");
+
+        assert_eq!(etag.len(), 130);
+    }
+
+    #[test]
+    fn test_sha512_differs_from_sha256_at_the_same_truncation() {
+        let content = b"same content, different SHA-2 width";
+        let sha256 =
+            ETagGenerator::with_algorithm(HashAlgorithm::Sha256Truncated128).generate(content);
+        let sha512 =
+            ETagGenerator::with_algorithm(HashAlgorithm::Sha512Truncated128).generate(content);
+
+        assert_ne!(sha256, sha512);
+    }
+
+    #[test]
+    fn test_with_bytes_overrides_default_truncation_length() {
+        let generator = ETagGenerator::new().with_bytes(24);
+        let etag = generator.generate(b"test content");
+
+        assert_eq!(etag.len(), generator.hex_len() + 2);
+        assert_eq!(generator.hex_len(), 48);
+    }
+
+    #[test]
+    fn test_with_bytes_clamps_to_the_algorithm_full_digest_length() {
+        let generator = ETagGenerator::with_algorithm(HashAlgorithm::Sha256Truncated128)
+            .with_bytes(1_000_000);
+        assert_eq!(generator.hex_len(), 64); // clamped to SHA-256's 32-byte digest
+    }
+
+    #[test]
+    fn test_with_bytes_clamps_to_at_least_one_byte() {
+        let generator = ETagGenerator::new().with_bytes(0);
+        assert_eq!(generator.hex_len(), 2);
+    }
+
+    #[test]
+    fn test_full_requests_the_complete_digest() {
+        let generator = ETagGenerator::new().full();
+        assert_eq!(generator.hex_len(), 64); // full 256-bit BLAKE3 digest
+
+        let sha512 = ETagGenerator::with_algorithm(HashAlgorithm::Sha512Truncated128).full();
+        assert_eq!(sha512.hex_len(), 128); // full 512-bit SHA-512 digest
+    }
+
+    #[test]
+    fn test_full_and_default_truncated_etags_differ() {
+        let truncated = ETagGenerator::new();
+        let full = ETagGenerator::new().full();
+
+        let content = b"same content, different tag length";
+        assert_ne!(truncated.generate(content), full.generate(content));
+    }
+
+    #[test]
+    fn test_incremental_etag_honors_with_bytes_override() {
+        let generator = ETagGenerator::new().with_bytes(24);
+        let mut incremental = generator.incremental();
+        incremental.update(b"content");
+
+        assert_eq!(incremental.finish(), generator.generate(b"content"));
+    }
+
+    #[test]
+    fn test_entity_tag_parse_strong() {
+        let tag = EntityTag::parse(r#""abc123""#).unwrap();
+        assert_eq!(
+            tag,
+            EntityTag::Tag {
+                weak: false,
+                opaque: "abc123".to_string()
+            }
+        );
+        assert!(!tag.is_weak());
+    }
+
+    #[test]
+    fn test_entity_tag_parse_weak() {
+        let tag = EntityTag::parse(r#"W/"abc123""#).unwrap();
+        assert_eq!(
+            tag,
+            EntityTag::Tag {
+                weak: true,
+                opaque: "abc123".to_string()
+            }
+        );
+        assert!(tag.is_weak());
+    }
+
+    #[test]
+    fn test_entity_tag_parse_wildcard() {
+        assert_eq!(EntityTag::parse("*"), Some(EntityTag::Any));
+    }
+
+    #[test]
+    fn test_entity_tag_parse_trims_surrounding_whitespace() {
+        assert_eq!(
+            EntityTag::parse(r#"  "abc123"  "#),
+            EntityTag::parse(r#""abc123""#)
+        );
+    }
+
+    #[test]
+    fn test_entity_tag_parse_rejects_unquoted_value() {
+        assert_eq!(EntityTag::parse("abc123"), None);
+    }
+
+    #[test]
+    fn test_entity_tag_parse_rejects_empty_value() {
+        assert_eq!(EntityTag::parse(""), None);
+    }
+
+    #[test]
+    fn test_entity_tag_parse_list() {
+        let tags = EntityTag::parse_list(r#""a", W/"b", *"#);
+        assert_eq!(
+            tags,
+            vec![
+                EntityTag::Tag {
+                    weak: false,
+                    opaque: "a".to_string()
+                },
+                EntityTag::Tag {
+                    weak: true,
+                    opaque: "b".to_string()
+                },
+                EntityTag::Any,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_entity_tag_parse_list_skips_malformed_entries() {
+        let tags = EntityTag::parse_list(r#""a", not-quoted, "b""#);
+        assert_eq!(
+            tags,
+            vec![
+                EntityTag::Tag {
+                    weak: false,
+                    opaque: "a".to_string()
+                },
+                EntityTag::Tag {
+                    weak: false,
+                    opaque: "b".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_entity_tag_strong_eq_requires_both_strong_and_equal_opaque() {
+        let a = EntityTag::parse(r#""abc""#).unwrap();
+        let b = EntityTag::parse(r#""abc""#).unwrap();
+        let weak_a = EntityTag::parse(r#"W/"abc""#).unwrap();
+
+        assert!(a.strong_eq(&b));
+        assert!(!a.strong_eq(&weak_a));
+        assert!(!weak_a.strong_eq(&weak_a.clone()));
+    }
+
+    #[test]
+    fn test_entity_tag_strong_eq_requires_equal_opaque_strings() {
+        let a = EntityTag::parse(r#""abc""#).unwrap();
+        let c = EntityTag::parse(r#""xyz""#).unwrap();
+        assert!(!a.strong_eq(&c));
+    }
+
+    #[test]
+    fn test_entity_tag_weak_eq_ignores_weak_flag() {
+        let strong = EntityTag::parse(r#""abc""#).unwrap();
+        let weak = EntityTag::parse(r#"W/"abc""#).unwrap();
+
+        assert!(strong.weak_eq(&weak));
+        assert!(weak.weak_eq(&strong));
+        assert!(weak.weak_eq(&weak.clone()));
+    }
+
+    #[test]
+    fn test_entity_tag_weak_eq_still_requires_equal_opaque_strings() {
+        let a = EntityTag::parse(r#""abc""#).unwrap();
+        let c = EntityTag::parse(r#""xyz""#).unwrap();
+        assert!(!a.weak_eq(&c));
+    }
+
+    #[test]
+    fn test_entity_tag_any_only_matches_any() {
+        let any = EntityTag::Any;
+        let tag = EntityTag::parse(r#""abc""#).unwrap();
+
+        assert!(any.strong_eq(&EntityTag::Any));
+        assert!(any.weak_eq(&EntityTag::Any));
+        assert!(!any.strong_eq(&tag));
+        assert!(!any.weak_eq(&tag));
+    }
+
+    #[test]
+    fn test_is_not_modified_matches_identical_etag() {
+        let generator = ETagGenerator::new();
+        let etag = generator.generate(b"# Hello World");
+
+        assert!(is_not_modified(&etag, &etag));
+    }
+
+    #[test]
+    fn test_is_not_modified_uses_weak_comparison() {
+        let generator = ETagGenerator::new();
+        let strong = generator.generate(b"content");
+        let weak = format!("W/{strong}");
+
+        assert!(is_not_modified(&weak, &strong));
+    }
+
+    #[test]
+    fn test_is_not_modified_matches_any_entry_in_a_list() {
+        let generator = ETagGenerator::new();
+        let etag = generator.generate(b"content");
+        let if_none_match = format!(r#""stale-tag", {etag}, "other-tag""#);
+
+        assert!(is_not_modified(&if_none_match, &etag));
+    }
+
+    #[test]
+    fn test_is_not_modified_wildcard_always_matches() {
+        let generator = ETagGenerator::new();
+        let etag = generator.generate(b"content");
+
+        assert!(is_not_modified("*", &etag));
+    }
+
+    #[test]
+    fn test_is_not_modified_false_when_nothing_matches() {
+        let generator = ETagGenerator::new();
+        let etag = generator.generate(b"content");
+
+        assert!(!is_not_modified(r#""stale-tag""#, &etag));
+    }
+
+    #[test]
+    fn test_is_not_modified_false_for_unparseable_generated_etag() {
+        assert!(!is_not_modified(r#""abc""#, "not-a-valid-etag"));
+    }
+
     proptest! {
         /// Property 12: ETag Consistency
         /// Validates: FR-06.4
@@ -256,5 +1246,41 @@ mod tests {
             // Truncated 128-bit BLAKE3 collisions are cryptographically negligible.
             prop_assert_ne!(etag_a, etag_b, "Different variant bytes should produce different ETags");
         }
+
+        /// Same property as above, generalized across every supported
+        /// algorithm, since determinism must hold regardless of which one is
+        /// configured.
+        #[test]
+        fn prop_etag_consistency_across_algorithms(
+            markdown in prop::collection::vec(any::<u8>(), 0..512),
+            algorithm_index in 0usize..ALL_ALGORITHMS.len(),
+        ) {
+            let algorithm = ALL_ALGORITHMS[algorithm_index];
+            let generator = ETagGenerator::with_algorithm(algorithm);
+
+            let etag1 = generator.generate(&markdown);
+            let etag2 = generator.generate(&markdown);
+
+            prop_assert_eq!(&etag1, &etag2, "Identical input must produce identical ETag for {:?}", algorithm);
+            prop_assert_eq!(etag1.len(), algorithm.hex_len() + 2);
+        }
+
+        /// An `If-None-Match` list built from a generator's own ETag must
+        /// always report a match, regardless of how many unrelated tags
+        /// surround it in the list.
+        #[test]
+        fn prop_is_not_modified_matches_own_etag_anywhere_in_list(
+            markdown in prop::collection::vec(any::<u8>(), 0..512),
+            decoys in prop::collection::vec("[a-z0-9]{1,16}", 0..5),
+        ) {
+            let generator = ETagGenerator::new();
+            let etag = generator.generate(&markdown);
+
+            let mut entries: Vec<String> = decoys.iter().map(|d| format!("\"{d}\"")).collect();
+            entries.push(etag.clone());
+            let if_none_match = entries.join(", ");
+
+            prop_assert!(is_not_modified(&if_none_match, &etag));
+        }
     }
 }
@@ -61,9 +61,15 @@
 //! let mut result = MarkdownResult {
 //!     markdown: markdown_ptr,
 //!     markdown_len,
+//!     uncompressed_len: markdown_len,
+//!     content_encoding: ptr::null_mut(),
+//!     content_encoding_len: 0,
 //!     etag: ptr::null_mut(),
 //!     etag_len: 0,
+//!     toc: ptr::null_mut(),
+//!     toc_len: 0,
 //!     token_estimate: 0,
+//!     had_lossy_decode: 0,
 //!     error_code: 0,
 //!     error_message: ptr::null_mut(),
 //!     error_len: 0,
@@ -107,20 +113,73 @@
 //!
 //! ## Thread Safety
 //!
-//! - `MarkdownConverterHandle` is NOT thread-safe
-//! - Each NGINX worker should have its own converter instance
-//! - Concurrent calls to `markdown_convert()` on the same handle are unsafe
+//! - `MarkdownConverterHandle` is reachable only through an opaque `u64`
+//!   handle backed by a process-wide registry (see
+//!   [`MarkdownConverterHandle`]); concurrent `markdown_convert()` calls on
+//!   the same handle are serialized rather than unsafe, though still not
+//!   parallel
+//! - Each NGINX worker should still have its own converter instance for
+//!   throughput
 //! - Multiple converter instances can be used concurrently
+//!
+//! ## ABI Versioning
+//!
+//! [`MarkdownOptions`] and [`MarkdownResult`] are `#[repr(C)]` structs shared
+//! with the NGINX C module; a silently reordered or added field would corrupt
+//! the boundary with no diagnostic. The C module should call
+//! [`markdown_abi_version`] once at load time and refuse to start if it
+//! doesn't match the version it was compiled against. Each struct's size is
+//! additionally pinned at compile time via `static_assert_size!`, so a
+//! layout-changing edit fails the Rust build itself.
+//!
+//! ## Incremental Conversion
+//!
+//! `markdown_convert()` requires the whole HTML body in one contiguous
+//! slice. `markdown_stream_begin()`/`markdown_stream_push()`/
+//! `markdown_stream_finish()`/`markdown_stream_free()` let a body filter
+//! feed a document as it arrives in a chain of buffers instead. See
+//! [`crate::incremental`] for how output is derived incrementally.
+//!
+//! `markdown_stream_push()` does not return a dedicated status enum; its
+//! three possible outcomes are carried by the existing `error_code`/
+//! `markdown_len` fields instead, the same way every other conversion
+//! result is reported: a nonzero return is `Error`, a zero return with
+//! `result->markdown_len > 0` is `Complete` (for this chunk - more output
+//! may still follow), and a zero return with `result->markdown_len == 0` is
+//! `Partial` (the chunk boundary split a tag, entity reference, or
+//! multi-byte UTF-8 sequence, and nothing new has closed yet).
+//!
+//! ## Diagnostic Logging
+//!
+//! A conversion can succeed while still hiding operationally interesting
+//! facts - lossy charset decoding, an input that was rejected for exceeding
+//! `max_input_bytes` - behind a single pass/fail result. `markdown_set_log_callback()`
+//! registers a per-handle, leveled (`LOG_LEVEL_*`) callback that `convert_inner`
+//! and friends invoke with structured diagnostic records as they occur, so
+//! NGINX can forward them into its own error log. The callback is optional
+//! (a NULL callback disables logging, the default) and every invocation is
+//! wrapped in `catch_unwind`, so a misbehaving C callback cannot unwind into
+//! Rust.
 
 use std::panic;
 use std::ptr;
 use std::slice;
 use std::time::Duration;
 
-use crate::converter::{ConversionContext, ConversionOptions, MarkdownConverter, MarkdownFlavor};
-use crate::error::ConversionError;
+use std::ffi::c_void;
+use std::ops::ControlFlow;
+
+use crate::cache::{CacheableRequest, ConversionCache};
+use crate::compression::{self, CompressionEncoding};
+use crate::converter::{
+    ConversionContext, ConversionOptions, FrontMatterFormat, MarkdownConverter, MarkdownFlavor,
+    StreamCapacity, StreamOutcome,
+};
+use crate::decompression::decompress_body;
+use crate::error::{ConversionError, ErrorCategory};
 use crate::etag_generator::ETagGenerator;
-use crate::parser::parse_html_with_charset;
+use crate::incremental::IncrementalConverter;
+use crate::parser::parse_html_with_charset_detection;
 use crate::token_estimator::TokenEstimator;
 
 // ============================================================================
@@ -145,9 +204,238 @@ pub const ERROR_MEMORY_LIMIT: u32 = 4;
 /// Invalid input data (NULL pointers, invalid parameters)
 pub const ERROR_INVALID_INPUT: u32 = 5;
 
-/// Internal error (unexpected condition, panic caught)
+/// `force_encoding`/`input_charset` named an encoding `encoding_rs` doesn't
+/// recognize (see [`crate::error::ConversionError::UnknownEncoding`]).
+/// Already returned by [`crate::parser::parse_html_with_forced_encoding`]
+/// and friends; named here so FFI callers can branch on it without string
+/// matching `error_message`.
+pub const ERROR_UNKNOWN_ENCODING: u32 = 6;
+
+/// A Rust panic was caught at the FFI boundary via `catch_unwind` and
+/// reported as an ordinary error instead of unwinding into C code
+pub const ERROR_PANIC: u32 = 7;
+
+/// The HTML input (or, for `markdown_stream_push()`, the accumulated
+/// buffer) exceeds `max_input_bytes`. Checked before any parsing work, so
+/// this is returned cheaply instead of spending the `timeout_ms` budget on
+/// an oversized body. Distinct from `ERROR_MEMORY_LIMIT`, which covers
+/// `max_output_bytes` and `max_decompressed_bytes` instead.
+pub const ERROR_INPUT_TOO_LARGE: u32 = 8;
+
+/// Internal error (unexpected condition other than a caught panic, which
+/// has its own `ERROR_PANIC` code)
 pub const ERROR_INTERNAL: u32 = 99;
 
+// ============================================================================
+// Error Category Constants
+//
+// Coarse class for `markdown_last_error_category()`, matching
+// `ErrorCategory`. A caller that doesn't want to hardcode every `ERROR_*`
+// code above can branch on these three instead to pick an HTTP status.
+// ============================================================================
+
+/// [`ErrorCategory::ClientInput`] - maps to an HTTP `400`
+pub const ERROR_CATEGORY_CLIENT_INPUT: u32 = 0;
+
+/// [`ErrorCategory::ResourceLimit`] - maps to an HTTP `503`
+pub const ERROR_CATEGORY_RESOURCE_LIMIT: u32 = 1;
+
+/// [`ErrorCategory::Internal`] - maps to an HTTP `500`
+pub const ERROR_CATEGORY_INTERNAL: u32 = 2;
+
+fn category_code(category: ErrorCategory) -> u32 {
+    match category {
+        ErrorCategory::ClientInput => ERROR_CATEGORY_CLIENT_INPUT,
+        ErrorCategory::ResourceLimit => ERROR_CATEGORY_RESOURCE_LIMIT,
+        ErrorCategory::Internal => ERROR_CATEGORY_INTERNAL,
+    }
+}
+
+// ============================================================================
+// Markdown Flavor
+// ============================================================================
+//
+// Values for `MarkdownOptions::flavor`. An unrecognized value is rejected
+// with `ERROR_INVALID_INPUT` rather than silently falling back to
+// `FLAVOR_COMMONMARK`, since a caller passing an out-of-range value almost
+// certainly built against a newer flavor this library doesn't implement yet.
+
+/// CommonMark: the well-specified baseline, no GFM extensions.
+pub const FLAVOR_COMMONMARK: u32 = 0;
+/// GitHub Flavored Markdown: adds pipe tables, strikethrough, task lists, and
+/// autolinks on top of CommonMark, each still gated by its own `extensions`
+/// bit or `preserve_tables`/`heading_anchors` field.
+pub const FLAVOR_GFM: u32 = 1;
+
+// ============================================================================
+// Rendering Backend
+// ============================================================================
+//
+// Values for `MarkdownOptions::renderer`, which selects the conversion
+// *engine* independently of `flavor` (which only selects which Markdown
+// dialect that engine emits). An unrecognized value is rejected with
+// `ERROR_INVALID_INPUT`, for the same reason an unrecognized `flavor` is.
+
+/// The only backend this build implements: the single-pass DOM-walking
+/// converter in [`crate::converter`]. Kept as `0` so a caller built against
+/// an older header - where this byte was unused padding, always zero - keeps
+/// selecting the same behavior it always has.
+pub const RENDERER_DEFAULT: u8 = 0;
+
+// ============================================================================
+// Log Levels
+// ============================================================================
+//
+// Severities for a diagnostic record passed to a callback registered via
+// `markdown_set_log_callback()`. Ordered most to least severe, matching the
+// usual C logging library convention (lower numeric value = more severe), so
+// a caller's `level_filter` is a simple `<=` comparison: passing
+// `LOG_LEVEL_WARN` as the filter delivers ERROR and WARN records but not INFO
+// or DEBUG.
+
+/// A non-fatal condition the caller likely wants surfaced (e.g. truncation at
+/// a configured memory limit, reached while the conversion still otherwise
+/// succeeds or is retried).
+pub const LOG_LEVEL_ERROR: u32 = 0;
+/// A condition worth noting but unlikely to need action (e.g. lossy charset
+/// decoding, an unsupported element dropped).
+pub const LOG_LEVEL_WARN: u32 = 1;
+/// Routine operational detail (e.g. which cascade step selected the charset).
+pub const LOG_LEVEL_INFO: u32 = 2;
+/// Verbose detail useful only when actively debugging a conversion.
+pub const LOG_LEVEL_DEBUG: u32 = 3;
+
+// ============================================================================
+// Markdown Extension Flags
+// ============================================================================
+//
+// Bits for `MarkdownOptions::extensions`, each toggling one Markdown
+// extension independently of `flavor`. `flavor` only seeds which bits a
+// caller that doesn't set `extensions` explicitly would reasonably want by
+// default (GFM implies `EXT_TABLES | EXT_STRIKETHROUGH | EXT_TASK_LISTS |
+// EXT_AUTOLINK`); the bits themselves are what the renderer actually checks.
+// `EXT_TABLES` and `EXT_TASK_LISTS` emit GFM-only syntax (pipe tables, `- [
+// ]` items) and have no effect outside `MarkdownFlavor::GitHubFlavoredMarkdown`.
+// `EXT_STRIKETHROUGH`, `EXT_AUTOLINK`, and `EXT_SMART_PUNCTUATION` are plain
+// inline substitutions that apply under either flavor.
+
+/// Convert `<table>` elements to GFM pipe tables. GFM flavor only.
+pub const EXT_TABLES: u32 = 1 << 0;
+/// Render `<del>`/`<s>` as `~~text~~`.
+pub const EXT_STRIKETHROUGH: u32 = 1 << 1;
+/// Render a list item containing a leading `<input type="checkbox">` as a
+/// GFM task list item (`- [ ]`/`- [x]`). GFM flavor only.
+pub const EXT_TASK_LISTS: u32 = 1 << 2;
+/// Render a link whose text is identical to its href as a bare autolink
+/// (`<https://example.com>`) instead of `[text](href)`.
+pub const EXT_AUTOLINK: u32 = 1 << 3;
+/// Recognize footnote markup and emit `[^n]` references and definitions.
+/// Not yet implemented by the renderer; reserved for a future release.
+pub const EXT_FOOTNOTES: u32 = 1 << 4;
+/// Transform straight quotes, `--`/`---`, and `...` into curly quotes, en/em
+/// dashes, and an ellipsis. See [`crate::converter::ConversionOptions::smart_punctuation`].
+pub const EXT_SMART_PUNCTUATION: u32 = 1 << 5;
+/// Detect a fenced code block's language from its `<code>` element's class
+/// or `data-lang`/`data-language` attribute and emit it after the opening
+/// fence. Applies under either flavor. See
+/// [`crate::converter::ConversionOptions::preserve_code_language`].
+pub const EXT_PRESERVE_CODE_LANGUAGE: u32 = 1 << 6;
+/// Fold typographic Unicode punctuation (curly quotes, em/en dashes,
+/// ellipsis, non-breaking spaces) into stable ASCII forms. Applies under
+/// either flavor. See
+/// [`crate::converter::ConversionOptions::normalize_punctuation`].
+pub const EXT_NORMALIZE_PUNCTUATION: u32 = 1 << 7;
+/// Isolate the primary article body (Mozilla/arc90 Readability scoring)
+/// before conversion, discarding navigation, sidebars, ads, and other
+/// boilerplate. See
+/// [`crate::converter::ConversionOptions::readability_mode`].
+pub const EXT_READABILITY_MODE: u32 = 1 << 8;
+/// When no BOM, Content-Type charset, or `<meta charset>` declares an
+/// encoding and the input isn't valid UTF-8 on its own, guess a legacy
+/// single-byte or CJK multibyte encoding statistically instead of
+/// defaulting straight to UTF-8. See
+/// [`crate::parser::parse_html_with_charset_detection`]. Requires the
+/// crate's `stat_charset_detect` feature; without it, this bit has no
+/// effect and undeclared non-UTF-8 input still decodes as UTF-8 with U+FFFD
+/// replacement, same as when the bit is unset.
+pub const EXT_STATISTICAL_CHARSET_DETECTION: u32 = 1 << 9;
+/// Splice the generated table of contents into `markdown` itself rather
+/// than leaving it to the separate `MarkdownResult::toc` output: into a
+/// `<!-- toc -->` placeholder line if present, otherwise after any front
+/// matter block. No-op if the document has no headings. See
+/// [`crate::converter::ConversionOptions::inline_toc`].
+pub const EXT_INLINE_TOC: u32 = 1 << 10;
+/// Rewrite Unicode emoji in prose text to `:shortcode:` form (e.g. `😄`
+/// becomes `:smile:`). See
+/// [`crate::converter::ConversionOptions::emoji_shortcodes`].
+pub const EXT_EMOJI_SHORTCODES: u32 = 1 << 11;
+/// The inverse of `EXT_EMOJI_SHORTCODES`: expand `:shortcode:` tokens back
+/// into Unicode emoji. See
+/// [`crate::converter::ConversionOptions::emoji_unicode`].
+pub const EXT_EMOJI_UNICODE: u32 = 1 << 12;
+/// Render `<ins>` as raw inline HTML (`<ins>text</ins>`) instead of
+/// unwrapping it to plain text. Applies under either flavor, same as
+/// `EXT_STRIKETHROUGH`. See
+/// [`crate::converter::ConversionOptions::underline`].
+pub const EXT_UNDERLINE: u32 = 1 << 13;
+/// Emit reference-style links/images (`[text][1]`, `![alt][2]`) with the
+/// URLs collected into a trailing `[1]: https://…` definitions block,
+/// instead of inline `[text](url)`/`![alt](url)`. Applies under either
+/// flavor. See
+/// [`crate::converter::ConversionOptions::reference_style_links`].
+pub const EXT_REFERENCE_LINKS: u32 = 1 << 14;
+/// Pad every GFM table column to a uniform width instead of ragged pipes.
+/// GFM flavor only, and only takes effect alongside `EXT_TABLES`/
+/// `preserve_tables`. See
+/// [`crate::converter::ConversionOptions::pretty_tables`].
+pub const EXT_PRETTY_TABLES: u32 = 1 << 15;
+/// Append extra fenced-code info-string attributes (rustdoc-style flags and
+/// Pandoc-style `{.attr}` hints) after the language resolved by
+/// `EXT_PRESERVE_CODE_LANGUAGE`. No effect unless that bit is also set. See
+/// [`crate::converter::ConversionOptions::preserve_code_attributes`].
+pub const EXT_PRESERVE_CODE_ATTRIBUTES: u32 = 1 << 16;
+
+// ============================================================================
+// ABI Version & Layout Guards
+// ============================================================================
+
+/// ABI version for the C-facing FFI surface
+///
+/// Bump this whenever a `static_assert_size!` size below changes, or when any
+/// other breaking change is made to [`MarkdownOptions`], [`MarkdownResult`],
+/// or the exported function signatures. The C module should call
+/// [`markdown_abi_version`] once at load time and refuse to start on a
+/// mismatch, rather than silently running against a struct layout it wasn't
+/// built for.
+pub const MARKDOWN_ABI_VERSION: u32 = 11;
+
+/// Return the ABI version this build was compiled with
+///
+/// The C module calls this at load time and compares the result against its
+/// own compiled-in expectation, failing fast on a mismatch instead of
+/// corrupting the FFI boundary.
+///
+/// # Safety
+///
+/// This function has no preconditions; it is safe to call at any time.
+#[unsafe(no_mangle)]
+pub extern "C" fn markdown_abi_version() -> u32 {
+    MARKDOWN_ABI_VERSION
+}
+
+/// Assert at compile time that a `#[repr(C)]` FFI struct has not silently
+/// changed size (e.g. via a reordered, added, or removed field)
+///
+/// Expands to a `size_of` check evaluated in a const context, so a mismatch
+/// is a compile error naming the expected and actual sizes, e.g.
+/// `expected \`[(); 64]\`, found \`[(); 72]\``. [`MARKDOWN_ABI_VERSION`] must
+/// be bumped whenever an asserted size legitimately changes.
+macro_rules! static_assert_size {
+    ($ty:ty, $size:expr) => {
+        const _: [(); $size] = [(); ::core::mem::size_of::<$ty>()];
+    };
+}
+
 // ============================================================================
 // FFI Data Structures
 // ============================================================================
@@ -165,15 +453,40 @@ pub const ERROR_INTERNAL: u32 = 99;
 ///
 /// # Field Descriptions
 ///
+/// - `abi_version`: Must equal [`MARKDOWN_ABI_VERSION`] for the `.so` being
+///   called
+///   - A mismatch (older or newer) fails with `ERROR_INVALID_INPUT` before
+///     any other field is read, since a header generated for a different
+///     ABI version may not agree with this build on struct layout
+///   - Call `markdown_abi_version()` to read the running `.so`'s version
+///
 /// - `flavor`: Markdown output format
-///   - 0 = CommonMark (default, well-specified baseline)
-///   - 1 = GitHub Flavored Markdown (GFM, adds tables, task lists, etc.)
+///   - [`FLAVOR_COMMONMARK`] (0) = CommonMark (default, well-specified baseline)
+///   - [`FLAVOR_GFM`] (1) = GitHub Flavored Markdown (GFM, adds tables, task lists, etc.)
+///   - Any other value fails with `ERROR_INVALID_INPUT`
 ///
 /// - `timeout_ms`: Maximum conversion time in milliseconds
 ///   - 0 = no timeout (not recommended)
 ///   - Typical value: 5000 (5 seconds)
 ///   - Cooperative timeout (checks periodically, doesn't spawn threads)
 ///
+/// - `max_input_bytes`: Maximum size of the (possibly chunked) HTML input, in bytes
+///   - 0 = no limit (not recommended for untrusted input)
+///   - Checked before parsing begins; exceeding it fails with
+///     `ERROR_INPUT_TOO_LARGE` rather than allocating space for the input
+///
+/// - `max_output_bytes`: Maximum size the rendered Markdown output may grow to, in bytes
+///   - 0 = no limit (not recommended for untrusted input)
+///   - Checked cooperatively during traversal, the same way `timeout_ms` is;
+///     exceeding it fails with `ERROR_MEMORY_LIMIT` instead of growing the
+///     output buffer without bound
+///
+/// - `max_decompressed_bytes`: Cap on the size `content_encoding` decompresses to, in bytes
+///   - 0 = no limit (not recommended for untrusted input)
+///   - Checked while inflating `content_encoding`-compressed input, before
+///     `max_input_bytes` ever sees the inflated bytes; exceeding it fails
+///     with `ERROR_MEMORY_LIMIT`, guarding against decompression-bomb input
+///
 /// - `generate_etag`: Whether to generate ETag for caching
 ///   - 0 = no ETag generation (faster)
 ///   - 1 = generate ETag via BLAKE3 hash of output
@@ -186,6 +499,12 @@ pub const ERROR_INTERNAL: u32 = 99;
 ///   - 0 = no front matter
 ///   - 1 = include front matter (title, description, etc.)
 ///
+/// - `preserve_tables`: Whether to convert `<table>` elements to GFM pipe
+///   tables (GFM flavor only; ignored under CommonMark, where tables are
+///   always flattened to plain text)
+///   - 0 = flatten tables to plain text
+///   - 1 = convert to GFM pipe tables
+///
 /// - `content_type`: Optional Content-Type header value for charset detection
 ///   - Pointer to UTF-8 string (e.g., "text/html; charset=UTF-8")
 ///   - NULL if not available
@@ -205,35 +524,96 @@ pub const ERROR_INTERNAL: u32 = 99;
 /// - `base_url_len`: Length of base_url string in bytes
 ///   - 0 if base_url is NULL
 ///
+/// - `input_charset`: Optional explicit charset label (e.g. "ISO-8859-1")
+///   - Pointer to UTF-8 string
+///   - NULL if not available
+///   - If pointer is NULL, `input_charset_len` must be 0
+///   - Overrides BOM sniffing, the Content-Type header, and `<meta charset>`
+///     detection; use when the caller already knows the true encoding
+///   - When this is NULL, `charset::resolve_input_charset_with_detection`
+///     still runs the full cascade (BOM, then `content_type`'s `charset=`
+///     parameter, then a `<meta charset>` scan of the first 1KB) before
+///     falling back to UTF-8, so Latin-1/Windows-1252/GBK/Shift-JIS and
+///     similar non-UTF-8 documents are transcoded automatically
+///   - Decoding never fails: bytes that are invalid under the resolved
+///     charset, or a charset label `encoding_rs` doesn't recognize, are
+///     replaced with U+FFFD rather than rejected, and the substitution is
+///     reported back via `result->had_lossy_decode` instead of an error
+///     code — real upstreams frequently omit an honest charset signal
+///     entirely, and the pipeline favors producing output over failing
+///     the whole conversion on untrustworthy metadata
+///
+/// - `input_charset_len`: Length of input_charset string in bytes
+///   - 0 if input_charset is NULL
+///
+/// - `content_encoding`: Optional `Content-Encoding` header value (e.g. `"gzip"`, `"br"`, `"deflate"`, `"zstd"`, or a comma-separated list)
+///   - Pointer to UTF-8 string
+///   - NULL if the input isn't compressed
+///   - If pointer is NULL, `content_encoding_len` must be 0
+///   - Decompressed (tokens undone in reverse order) before charset
+///     detection and parsing; an unsupported token fails with
+///     `ERROR_ENCODING`
+///
+/// - `content_encoding_len`: Length of content_encoding string in bytes
+///   - 0 if content_encoding is NULL
+///
 /// # Example Usage (C)
 ///
 /// ```c
 /// // Without Content-Type or base_url
 /// markdown_options_t options = {
+///     .abi_version = MARKDOWN_ABI_VERSION, // Must match the running .so
 ///     .flavor = 0,              // CommonMark
 ///     .timeout_ms = 5000,       // 5 second timeout
+///     .max_input_bytes = 0,     // No input size limit
+///     .max_output_bytes = 0,    // No output size limit
+///     .max_decompressed_bytes = 0, // No decompressed size limit
 ///     .generate_etag = 1,       // Generate ETag
 ///     .estimate_tokens = 1,     // Estimate tokens
 ///     .front_matter = 0,        // No front matter
+///     .preserve_tables = 1,     // Convert <table> to GFM pipe tables
+///     .generate_toc = 0,        // No table of contents
+///     .heading_anchors = 0,     // No heading anchor ids
+///     .extensions = 0,          // No fine-grained extensions
 ///     .content_type = NULL,     // No Content-Type
 ///     .content_type_len = 0,
 ///     .base_url = NULL,         // No base URL
-///     .base_url_len = 0
+///     .base_url_len = 0,
+///     .accept_encoding = NULL,
+///     .accept_encoding_len = 0,
+///     .input_charset = NULL,    // Let detection decide
+///     .input_charset_len = 0,
+///     .content_encoding = NULL, // Input isn't compressed
+///     .content_encoding_len = 0
 /// };
 ///
 /// // With Content-Type and base_url
 /// const char *ct = "text/html; charset=UTF-8";
 /// const char *base = "https://example.com/page";
 /// markdown_options_t options = {
+///     .abi_version = MARKDOWN_ABI_VERSION,
 ///     .flavor = 0,
 ///     .timeout_ms = 5000,
+///     .max_input_bytes = 10485760,  // 10 MiB
+///     .max_output_bytes = 10485760, // 10 MiB
+///     .max_decompressed_bytes = 52428800, // 50 MiB
 ///     .generate_etag = 1,
 ///     .estimate_tokens = 1,
 ///     .front_matter = 0,
+///     .preserve_tables = 1,
+///     .generate_toc = 0,
+///     .heading_anchors = 0,
+///     .extensions = EXT_STRIKETHROUGH | EXT_AUTOLINK,
 ///     .content_type = (const uint8_t*)ct,
 ///     .content_type_len = strlen(ct),
 ///     .base_url = (const uint8_t*)base,
-///     .base_url_len = strlen(base)
+///     .base_url_len = strlen(base),
+///     .accept_encoding = NULL,
+///     .accept_encoding_len = 0,
+///     .input_charset = NULL,
+///     .input_charset_len = 0,
+///     .content_encoding = NULL,
+///     .content_encoding_len = 0
 /// };
 /// ```
 ///
@@ -243,16 +623,69 @@ pub const ERROR_INTERNAL: u32 = 99;
 /// plain data types with no pointers or complex ownership.
 #[repr(C)]
 pub struct MarkdownOptions {
-    /// Markdown flavor: 0=CommonMark, 1=GFM
+    /// ABI version this struct was built against; must equal
+    /// [`MARKDOWN_ABI_VERSION`] exactly. A C module should set this to the
+    /// `MARKDOWN_ABI_VERSION` its header was generated from (not necessarily
+    /// a literal constant, so a mismatch between an old `.so` and a newer
+    /// header - or vice versa - is caught here rather than reading fields
+    /// the caller's struct never had). `markdown_convert()` and friends
+    /// reject a mismatch with `ERROR_INVALID_INPUT` instead of guessing how
+    /// to interpret a layout they were not built for.
+    pub abi_version: u32,
+    /// Markdown flavor: [`FLAVOR_COMMONMARK`] (0) or [`FLAVOR_GFM`] (1); any
+    /// other value is rejected with `ERROR_INVALID_INPUT`
     pub flavor: u32,
     /// Conversion timeout in milliseconds (0=no timeout)
     pub timeout_ms: u32,
+    /// Maximum size of the HTML input in bytes (0=no limit). Checked before
+    /// parsing; exceeding it returns `ERROR_INPUT_TOO_LARGE`.
+    pub max_input_bytes: u32,
+    /// Maximum size the rendered Markdown output may grow to, in bytes
+    /// (0=no limit). Checked cooperatively during traversal, the same way
+    /// `timeout_ms` is; exceeding it returns `ERROR_MEMORY_LIMIT`.
+    pub max_output_bytes: u32,
+    /// Cap on the size `content_encoding` decompresses to, in bytes
+    /// (0=no limit). Checked while inflating, before `max_input_bytes` ever
+    /// sees the inflated bytes; exceeding it returns `ERROR_MEMORY_LIMIT`,
+    /// guarding against decompression-bomb input.
+    pub max_decompressed_bytes: u32,
     /// Generate ETag: 0=no, 1=yes
     pub generate_etag: u8,
     /// Estimate tokens: 0=no, 1=yes
     pub estimate_tokens: u8,
     /// Include YAML front matter: 0=no, 1=yes
     pub front_matter: u8,
+    /// Convert `<table>` elements to GFM pipe tables (GFM flavor only):
+    /// 0=no (tables are flattened to plain text), 1=yes
+    pub preserve_tables: u8,
+    /// Build a table of contents from the document's headings, returned via
+    /// `MarkdownResult::toc`: 0=no, 1=yes
+    pub generate_toc: u8,
+    /// Levels to shift every heading down by (0-5, clamped at h6) when
+    /// computing `MarkdownResult::toc`'s nesting; any other value is
+    /// rejected with `ERROR_INVALID_INPUT`. Does not change the `#` count in
+    /// the returned Markdown itself, only the TOC's indentation - useful
+    /// when a caller splices a converted fragment under an existing heading
+    /// hierarchy and wants the TOC to match where it lands.
+    pub heading_offset: u8,
+    /// Attach a stable slug anchor to each heading (CommonMark flavor only;
+    /// GFM renders these implicitly): 0=no, 1=yes
+    pub heading_anchors: u8,
+    /// Conversion engine: [`RENDERER_DEFAULT`] (0), the only backend this
+    /// build implements; any other value is rejected with
+    /// `ERROR_INVALID_INPUT`. Independent of `flavor`, which only selects
+    /// the Markdown dialect a renderer emits.
+    pub renderer: u8,
+    /// Fine-grained Markdown extension bitfield: OR together `EXT_TABLES`,
+    /// `EXT_STRIKETHROUGH`, `EXT_TASK_LISTS`, `EXT_AUTOLINK`,
+    /// `EXT_FOOTNOTES`, `EXT_SMART_PUNCTUATION`, `EXT_PRESERVE_CODE_LANGUAGE`,
+    /// `EXT_NORMALIZE_PUNCTUATION`, `EXT_READABILITY_MODE`, `EXT_INLINE_TOC`,
+    /// `EXT_EMOJI_SHORTCODES`, `EXT_EMOJI_UNICODE`, `EXT_UNDERLINE`,
+    /// `EXT_REFERENCE_LINKS`, `EXT_PRETTY_TABLES`, and
+    /// `EXT_PRESERVE_CODE_ATTRIBUTES`.
+    /// `preserve_tables` above is equivalent to `EXT_TABLES` and is kept for
+    /// existing callers; either one enables GFM tables.
+    pub extensions: u32,
     /// Content-Type header value for charset detection (UTF-8 bytes, can be NULL)
     pub content_type: *const u8,
     /// Length of content_type in bytes (0 if NULL)
@@ -261,8 +694,40 @@ pub struct MarkdownOptions {
     pub base_url: *const u8,
     /// Length of base_url in bytes (0 if NULL)
     pub base_url_len: usize,
+    /// `Accept-Encoding`-style compression preference (e.g. `"br, gzip;q=0.8"`,
+    /// UTF-8 bytes, can be NULL). Negotiated via
+    /// [`crate::compression::negotiate_encoding`]; absent or unrecognized
+    /// preferences fall back to uncompressed output.
+    pub accept_encoding: *const u8,
+    /// Length of accept_encoding in bytes (0 if NULL)
+    pub accept_encoding_len: usize,
+    /// Explicit charset label overriding BOM/header/meta detection (UTF-8
+    /// bytes, can be NULL)
+    pub input_charset: *const u8,
+    /// Length of input_charset in bytes (0 if NULL)
+    pub input_charset_len: usize,
+    /// `Content-Encoding` header value of the input (e.g. `"gzip"`, `"br"`,
+    /// `"deflate"`, `"zstd"`, or a comma-separated list; UTF-8 bytes, can be
+    /// NULL). Decompressed via [`crate::decompression::decompress_body`]
+    /// (tokens undone in reverse order) before charset detection and
+    /// parsing; an unsupported token returns `ERROR_ENCODING`.
+    pub content_encoding: *const u8,
+    /// Length of content_encoding in bytes (0 if NULL)
+    pub content_encoding_len: usize,
 }
 
+// Layout guard: a reordered or added/removed field changes this size, which
+// must be caught here rather than silently corrupting the C boundary. Pinned
+// to 64-bit pointer width since the byte count below assumes 8-byte pointers.
+//
+// `renderer` and `heading_offset` each landed inside a padding byte that
+// already followed `heading_anchors`, so this total is still 120 even
+// though two fields were added since it was first pinned - bump
+// `MARKDOWN_ABI_VERSION` for changes like these, not just for changes that
+// move this number.
+#[cfg(target_pointer_width = "64")]
+static_assert_size!(MarkdownOptions, 120);
+
 /// Conversion result returned from Rust to C
 ///
 /// This structure contains the output of HTML to Markdown conversion,
@@ -284,7 +749,8 @@ pub struct MarkdownOptions {
 /// # Memory Ownership
 ///
 /// **Rust owns all allocated memory:**
-/// - On success, `markdown` and `etag` point to Rust-allocated memory
+/// - On success, `markdown`, `etag`, `toc`, and `content_encoding` point to
+///   Rust-allocated memory
 /// - C receives pointers but does NOT own the memory
 /// - C MUST call `markdown_result_free()` exactly once to deallocate
 /// - After calling free, all pointers become invalid
@@ -294,11 +760,27 @@ pub struct MarkdownOptions {
 ///
 /// ## Output Fields (valid on success)
 ///
-/// - `markdown`: Pointer to UTF-8 Markdown output bytes
-/// - `markdown_len`: Byte length of Markdown output
-/// - `etag`: Pointer to UTF-8 ETag string (NULL if not generated)
+/// - `markdown`: Pointer to the Markdown output bytes. Compressed per the
+///   negotiated `content_encoding` when one was requested via
+///   `accept_encoding`; raw UTF-8 Markdown otherwise.
+/// - `markdown_len`: Byte length of `markdown` as returned (i.e. the
+///   compressed length when `content_encoding` is non-empty) — use this for
+///   `Content-Length`
+/// - `uncompressed_len`: Byte length of the Markdown before compression
+///   (equal to `markdown_len` when no compression was applied)
+/// - `content_encoding`: `Content-Encoding` label (e.g. `"gzip"`, `"br"`,
+///   `"zstd"`), UTF-8 bytes, NULL if the output is uncompressed
+/// - `content_encoding_len`: Byte length of `content_encoding` (0 if NULL)
+/// - `etag`: Pointer to UTF-8 ETag string (NULL if not generated). Computed
+///   over the *uncompressed* Markdown, so it is stable across encodings.
 /// - `etag_len`: Byte length of ETag (0 if NULL)
+/// - `toc`: Pointer to a generated table of contents (UTF-8 bytes, NULL
+///   unless `MarkdownOptions::generate_toc` was set and the document had at
+///   least one heading)
+/// - `toc_len`: Byte length of `toc` (0 if NULL)
 /// - `token_estimate`: Estimated token count for LLM context windows
+/// - `had_lossy_decode`: `1` if a malformed byte sequence in the input was
+///   replaced with U+FFFD while transcoding to UTF-8, `0` otherwise
 ///
 /// ## Error Fields (valid on error)
 ///
@@ -309,11 +791,18 @@ pub struct MarkdownOptions {
 /// # State Invariants
 ///
 /// **Success State (error_code == 0):**
-/// - `markdown` is non-NULL and points to valid UTF-8 bytes
+/// - `markdown` is non-NULL and points to valid bytes
 /// - `markdown_len` > 0
+/// - `uncompressed_len` > 0
+/// - `content_encoding` is NULL (and `content_encoding_len` is 0) unless a
+///   supported encoding was negotiated from `accept_encoding`
 /// - `etag` may be NULL or point to valid UTF-8 bytes
 /// - `etag_len` matches etag content (0 if etag is NULL)
+/// - `toc` may be NULL or point to valid UTF-8 bytes
+/// - `toc_len` matches toc content (0 if toc is NULL)
 /// - `token_estimate` contains estimated count (or 0 if not requested)
+/// - `had_lossy_decode` is `1` or `0` depending on whether transcoding
+///   replaced any bytes
 /// - `error_message` is NULL
 /// - `error_len` is 0
 ///
@@ -322,7 +811,13 @@ pub struct MarkdownOptions {
 /// - `markdown_len` is 0
 /// - `etag` is NULL
 /// - `etag_len` is 0
+/// - `toc` is NULL
+/// - `toc_len` is 0
+/// - `content_encoding` is NULL
+/// - `content_encoding_len` is 0
+/// - `uncompressed_len` is 0
 /// - `token_estimate` is 0
+/// - `had_lossy_decode` is 0
 /// - `error_message` is non-NULL and points to valid UTF-8 bytes
 /// - `error_len` > 0
 ///
@@ -364,26 +859,60 @@ pub struct MarkdownOptions {
 /// - Do not mix Rust and C memory allocators
 #[repr(C)]
 pub struct MarkdownResult {
-    /// Output Markdown (UTF-8 bytes, NOT NUL-terminated)
+    /// Output Markdown bytes, NOT NUL-terminated. Compressed per
+    /// `content_encoding` when a supported encoding was negotiated, raw
+    /// UTF-8 Markdown otherwise.
     /// NULL on error, non-NULL on success
     pub markdown: *mut u8,
 
-    /// Length of markdown in bytes (NOT including NUL)
+    /// Length of markdown in bytes as returned (the compressed length when
+    /// `content_encoding` is set). Use for `Content-Length`.
     /// 0 on error, >0 on success
     pub markdown_len: usize,
 
+    /// Byte length of the Markdown before compression (equal to
+    /// `markdown_len` when the output is uncompressed)
+    /// 0 on error, >0 on success
+    pub uncompressed_len: usize,
+
+    /// `Content-Encoding` label for `markdown` (UTF-8 bytes, e.g. `"gzip"`,
+    /// `"br"`, `"zstd"`), NULL if the output is uncompressed
+    pub content_encoding: *mut u8,
+
+    /// Length of content_encoding in bytes
+    /// 0 if content_encoding is NULL
+    pub content_encoding_len: usize,
+
     /// ETag string (UTF-8 bytes, optional, NULL if not generated)
-    /// NULL if not requested or on error
+    /// NULL if not requested or on error. Computed over the uncompressed
+    /// Markdown, so it stays stable across encodings.
     pub etag: *mut u8,
 
     /// Length of etag in bytes
     /// 0 if etag is NULL
     pub etag_len: usize,
 
+    /// Generated table of contents (UTF-8 bytes, optional, NULL if not
+    /// requested via `MarkdownOptions::generate_toc` or if the document has
+    /// no headings). A nested bulleted Markdown list, not inlined into
+    /// `markdown`; see [`crate::converter::MarkdownConverter::generate_toc`].
+    pub toc: *mut u8,
+
+    /// Length of toc in bytes
+    /// 0 if toc is NULL
+    pub toc_len: usize,
+
     /// Estimated token count for LLM context windows
     /// 0 if not requested or on error
     pub token_estimate: u32,
 
+    /// `1` if decoding the input HTML to UTF-8 replaced any malformed byte
+    /// sequence with U+FFFD (the Unicode replacement character) under the
+    /// detected/explicit charset, `0` if decoding was clean or on error.
+    /// Surfacing this lets a caller log or reject suspiciously mislabeled
+    /// upstream content without having to re-sniff the charset itself.
+    pub had_lossy_decode: u8,
+
     /// Error code: 0=success, non-zero=error (see ERROR_* constants)
     pub error_code: u32,
 
@@ -396,29 +925,61 @@ pub struct MarkdownResult {
     pub error_len: usize,
 }
 
-/// Opaque handle to Rust converter instance
+// Layout guard: see the comment on `MarkdownOptions`'s static_assert_size!.
+#[cfg(target_pointer_width = "64")]
+static_assert_size!(MarkdownResult, 104);
+
+/// Hit/miss/eviction counters for a converter's result cache
+///
+/// Populated by `markdown_converter_cache_stats()`. All fields are `0` for a
+/// handle created by `markdown_converter_new()`, since caching is disabled.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CacheStats {
+    /// Conversions served from the cache
+    pub hits: u64,
+    /// Conversions that required a full parse and render
+    pub misses: u64,
+    /// Entries discarded to make room for a new one under a full cache
+    pub evictions: u64,
+}
+
+#[cfg(target_pointer_width = "64")]
+static_assert_size!(CacheStats, 24);
+
+/// Converter instance, reachable only through an opaque [`u64`] handle
 ///
-/// This is an opaque type that hides the internal Rust implementation
-/// from C code. C code receives a pointer to this type but cannot
-/// access its internals.
+/// C code never sees a pointer to this type. Instead, `markdown_converter_new()`
+/// returns an integer handle that indexes into [`HANDLE_REGISTRY`]; every other
+/// function taking a `handle: u64` parameter looks it up there via
+/// [`with_converter`]. This indirection is what lets use-after-free,
+/// double-free, and concurrent same-handle use be reported as
+/// `ERROR_INVALID_INPUT` instead of triggering undefined behavior - see the
+/// "Thread Safety" section below.
 ///
 /// # Lifecycle
 ///
 /// 1. Create: `markdown_converter_new()` returns a handle
 /// 2. Use: Pass handle to `markdown_convert()` for conversions
-/// 3. Destroy: `markdown_converter_free()` deallocates the handle
+/// 3. Destroy: `markdown_converter_free()` releases the handle's slot
 ///
 /// # Thread Safety
 ///
-/// **NOT thread-safe** - Each NGINX worker should have its own instance.
-/// Do not share handles across threads or concurrent requests.
+/// The registry itself is safe to use from multiple threads: each slot is
+/// guarded by its own `Mutex`, so concurrent `markdown_convert()` calls on the
+/// same handle are serialized rather than racing, and a handle freed on one
+/// thread while in use on another is caught by the generation check rather
+/// than dereferencing freed memory. That said, serialized is not
+/// parallel - a converter instance does not process two conversions at once,
+/// so sharing one handle across threads only buys safety, not throughput.
+/// Each NGINX worker should still prefer its own converter instance.
 ///
 /// # Example Usage (C)
 ///
 /// ```c
 /// // Create converter
-/// markdown_converter_t *converter = markdown_converter_new();
-/// if (converter == NULL) {
+/// uint64_t converter = markdown_converter_new();
+/// if (converter == 0) {
 ///     // Handle allocation failure
 ///     return;
 /// }
@@ -438,40 +999,384 @@ pub struct MarkdownResult {
 pub struct MarkdownConverterHandle {
     etag_generator: ETagGenerator,
     token_estimator: TokenEstimator,
+    cache: Option<ConversionCache>,
+    log_sink: Option<LogSink>,
+}
+
+/// Diagnostic logging callback registered via `markdown_set_log_callback()`
+///
+/// Invoked with a leveled (`LOG_LEVEL_*`), UTF-8 message describing a
+/// non-fatal condition encountered during conversion - the message is valid
+/// only for the duration of the call. `user_data` is passed through
+/// unchanged from registration.
+pub type MarkdownLogCallback =
+    unsafe extern "C" fn(level: u32, msg: *const u8, msg_len: usize, user_data: *mut c_void);
+
+/// Wraps a `*mut c_void` to make it `Send`/`Sync` so [`LogSink`] can live
+/// inside [`MarkdownConverterHandle`], which is stored in a `Mutex` behind
+/// [`HANDLE_REGISTRY`]. Sound because the registry's mutex already
+/// serializes every access to the pointer, matching the FFI contract that
+/// `user_data` is only ever touched from inside a `sink` callback invocation.
+struct SendPtr(*mut c_void);
+
+// SAFETY: see the doc comment above; access is always serialized by the
+// handle's `Mutex`.
+unsafe impl Send for SendPtr {}
+unsafe impl Sync for SendPtr {}
+
+/// A registered diagnostic log callback plus the state needed to invoke it
+struct LogSink {
+    /// Minimum severity (by `LOG_LEVEL_*` ordering) to deliver; a record more
+    /// verbose than this (numerically greater) is dropped before the
+    /// callback is invoked.
+    level_filter: u32,
+    callback: MarkdownLogCallback,
+    user_data: SendPtr,
+}
+
+impl LogSink {
+    /// Invoke the callback with `msg` at `level`, unless `level` is more
+    /// verbose than `level_filter`. Guards the call with `catch_unwind` so a
+    /// panicking C callback cannot unwind across the FFI boundary; a caught
+    /// panic is silently dropped rather than propagated, since a logging
+    /// callback misbehaving should never fail the conversion it's reporting
+    /// on.
+    fn log(&self, level: u32, msg: &str) {
+        if level > self.level_filter {
+            return;
+        }
+
+        let callback = self.callback;
+        let user_data = self.user_data.0;
+        let bytes = msg.as_bytes();
+        let _ = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            // SAFETY: `callback` and `user_data` were supplied by the caller
+            // per the FFI contract of `markdown_set_log_callback`; `bytes` is
+            // valid for the duration of this call only.
+            unsafe { callback(level, bytes.as_ptr(), bytes.len(), user_data) }
+        }));
+    }
+}
+
+// ============================================================================
+// Converter Handle Registry
+// ============================================================================
+//
+// `markdown_converter_new()` used to hand C a raw `Box::into_raw` pointer,
+// which made use-after-free, double-free, and concurrent same-handle use all
+// undefined behavior - the classic C handle hazards. Replacing the pointer
+// with an opaque `u64` that indexes a process-wide registry turns all three
+// into an ordinary, reportable `ERROR_INVALID_INPUT`: freeing bumps the
+// slot's generation, so a stale handle's generation check simply fails
+// instead of touching reclaimed memory, and the per-slot `Mutex` serializes
+// concurrent callers instead of racing them.
+
+use std::sync::{Mutex, RwLock};
+
+/// Number of low bits of a handle that encode the registry slot index; the
+/// remaining high bits encode the slot's generation.
+const HANDLE_INDEX_BITS: u32 = 32;
+
+/// Pack a slot `index` and `generation` into the `u64` handle C code holds.
+///
+/// `handle == 0` is reserved as always-invalid (mirroring the NULL-pointer
+/// idiom this registry replaces), so `generation` must never be `0` -
+/// [`HandleRegistry::insert`] enforces this.
+fn encode_handle(index: usize, generation: u32) -> u64 {
+    (u64::from(generation) << HANDLE_INDEX_BITS) | index as u64
+}
+
+/// Unpack a handle into `(index, generation)`, or `None` for handle `0`.
+fn decode_handle(handle: u64) -> Option<(usize, u32)> {
+    if handle == 0 {
+        return None;
+    }
+
+    let index = (handle & u64::from(u32::MAX)) as usize;
+    let generation = (handle >> HANDLE_INDEX_BITS) as u32;
+    Some((index, generation))
+}
+
+struct HandleSlot {
+    generation: u32,
+    value: Option<Mutex<MarkdownConverterHandle>>,
+}
+
+struct HandleRegistry {
+    slots: Vec<HandleSlot>,
+    free_list: Vec<usize>,
+}
+
+impl HandleRegistry {
+    const fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            free_list: Vec::new(),
+        }
+    }
+
+    /// Store `value` in a free slot (or a newly grown one) and return its
+    /// handle.
+    fn insert(&mut self, value: MarkdownConverterHandle) -> u64 {
+        let index = self.free_list.pop().unwrap_or(self.slots.len());
+        if index == self.slots.len() {
+            self.slots.push(HandleSlot {
+                generation: 0,
+                value: None,
+            });
+        }
+
+        let slot = &mut self.slots[index];
+        // Non-zero so `decode_handle` never mistakes a live handle for `0`.
+        slot.generation = slot.generation.wrapping_add(1).max(1);
+        slot.value = Some(Mutex::new(value));
+        encode_handle(index, slot.generation)
+    }
+
+    /// Remove the slot named by `handle`, returning whether it was live.
+    ///
+    /// Bumps the generation even on removal, so a handle copy that outlives
+    /// this call (e.g. held by another thread) fails its generation check
+    /// rather than resolving to whatever reuses this slot next.
+    fn remove(&mut self, handle: u64) -> bool {
+        let Some((index, generation)) = decode_handle(handle) else {
+            return false;
+        };
+        let Some(slot) = self.slots.get_mut(index) else {
+            return false;
+        };
+        if slot.generation != generation || slot.value.is_none() {
+            return false;
+        }
+
+        slot.value = None;
+        slot.generation = slot.generation.wrapping_add(1).max(1);
+        self.free_list.push(index);
+        true
+    }
+}
+
+/// Process-wide table of live converter instances, indexed by the `u64`
+/// handles returned from `markdown_converter_new()`/`markdown_converter_new_cached()`.
+static HANDLE_REGISTRY: RwLock<HandleRegistry> = RwLock::new(HandleRegistry::new());
+
+/// Look up `handle`, lock its converter, and run `f` on it.
+///
+/// Returns `ERROR_INVALID_INPUT` (wrapped in `ConversionError::InvalidInput`)
+/// for handle `0`, an unrecognized index, a stale generation (freed or
+/// reused), or a vacant slot. A poisoned lock (from a panic during a prior
+/// call on this handle) is recovered rather than propagated, matching this
+/// module's existing stance that every FFI entry point already isolates a
+/// single call's panic via `catch_unwind`.
+fn with_converter<R>(
+    handle: u64,
+    f: impl FnOnce(&mut MarkdownConverterHandle) -> R,
+) -> Result<R, ConversionError> {
+    let (index, generation) = decode_handle(handle)
+        .ok_or_else(|| ConversionError::InvalidInput("Converter handle is invalid".to_string()))?;
+
+    let registry = HANDLE_REGISTRY
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let slot = registry
+        .slots
+        .get(index)
+        .filter(|slot| slot.generation == generation)
+        .ok_or_else(|| ConversionError::InvalidInput("Converter handle is invalid".to_string()))?;
+    let converter_lock = slot
+        .value
+        .as_ref()
+        .ok_or_else(|| ConversionError::InvalidInput("Converter handle is invalid".to_string()))?;
+
+    let mut converter = converter_lock
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    Ok(f(&mut converter))
+}
+
+/// Confirm `handle` currently names a live converter, without using it.
+///
+/// Used by the streaming functions, which only need proof the handle is
+/// valid and never read or mutate the converter it names.
+fn validate_handle(handle: u64) -> Result<(), ConversionError> {
+    with_converter(handle, |_| ())
 }
 
 struct ConversionOutput {
     markdown: Box<[u8]>,
+    uncompressed_len: usize,
+    content_encoding: CompressionEncoding,
     etag: Option<Box<[u8]>>,
+    toc: Option<Box<[u8]>>,
     token_estimate: u32,
+    had_lossy_decode: bool,
+}
+
+/// Opaque handle to an in-progress incremental conversion
+///
+/// Created by `markdown_stream_begin()` for a single HTML body delivered as a
+/// sequence of buffers (e.g. an NGINX body filter chain), fed chunks via
+/// `markdown_stream_push()`, and finalized with exactly one
+/// `markdown_stream_finish()` call before being released with
+/// `markdown_stream_free()`. See [`crate::incremental`] for how output is
+/// derived incrementally from the crate's whole-document parser and
+/// converter.
+///
+/// Like [`MarkdownConverterHandle`], this is NOT thread-safe: one stream per
+/// in-flight response body, used from a single thread at a time.
+pub struct MarkdownStream {
+    inner: IncrementalConverter,
+    etag_generator: ETagGenerator,
+    token_estimator: TokenEstimator,
+    generate_etag: bool,
+    estimate_tokens: bool,
+    generate_toc: bool,
+    finished: bool,
 }
 
 fn reset_result(result: &mut MarkdownResult) {
     result.markdown = ptr::null_mut();
     result.markdown_len = 0;
+    result.uncompressed_len = 0;
+    result.content_encoding = ptr::null_mut();
+    result.content_encoding_len = 0;
     result.etag = ptr::null_mut();
     result.etag_len = 0;
+    result.toc = ptr::null_mut();
+    result.toc_len = 0;
     result.token_estimate = 0;
+    result.had_lossy_decode = 0;
     result.error_code = ERROR_SUCCESS;
     result.error_message = ptr::null_mut();
     result.error_len = 0;
 }
 
-fn set_error_result(result: &mut MarkdownResult, error_code: u32, error_message: String) {
+fn set_error_result(
+    result: &mut MarkdownResult,
+    error_code: u32,
+    category: ErrorCategory,
+    error_message: String,
+) {
+    set_last_error(error_code, category, error_message.clone());
     let error_bytes = error_message.into_bytes().into_boxed_slice();
     result.error_code = error_code;
     result.error_len = error_bytes.len();
     result.error_message = Box::into_raw(error_bytes) as *mut u8;
 }
 
+// ============================================================================
+// errno-style last-error retrieval
+//
+// `MarkdownResult::error_code`/`error_message` already carry full detail for
+// every call that takes a `result: *mut MarkdownResult` out-param, but
+// `markdown_stream_begin()` has no such channel - it can only return NULL on
+// failure. These accessors mirror libc's `errno`/`strerror()` pattern so the
+// nginx module can recover the last error on the calling thread regardless
+// of which function failed.
+// ============================================================================
+
+struct LastError {
+    code: u32,
+    category: ErrorCategory,
+    message: String,
+}
+
+thread_local! {
+    static LAST_ERROR: std::cell::RefCell<Option<LastError>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+/// Record `code`/`category`/`message` as the calling thread's last error,
+/// for later retrieval via `markdown_last_error_code()` and friends
+fn set_last_error(code: u32, category: ErrorCategory, message: String) {
+    LAST_ERROR.with(|cell| {
+        *cell.borrow_mut() = Some(LastError {
+            code,
+            category,
+            message,
+        });
+    });
+}
+
+/// The calling thread's last recorded error code, or `ERROR_SUCCESS` if none
+/// has been recorded yet
+///
+/// # Safety
+///
+/// No pointers are dereferenced; safe to call from any thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn markdown_last_error_code() -> u32 {
+    LAST_ERROR.with(|cell| cell.borrow().as_ref().map_or(ERROR_SUCCESS, |e| e.code))
+}
+
+/// The calling thread's last recorded error category (one of the
+/// `ERROR_CATEGORY_*` constants), or `ERROR_CATEGORY_CLIENT_INPUT` if none
+/// has been recorded yet
+///
+/// # Safety
+///
+/// No pointers are dereferenced; safe to call from any thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn markdown_last_error_category() -> u32 {
+    LAST_ERROR.with(|cell| {
+        cell.borrow()
+            .as_ref()
+            .map_or(ERROR_CATEGORY_CLIENT_INPUT, |e| category_code(e.category))
+    })
+}
+
+/// Copy the calling thread's last recorded error message into `buf`
+///
+/// Copies at most `len` bytes (truncating, never writing a NUL terminator -
+/// `markdown`/`etag`/`error_message` elsewhere in this FFI are never
+/// NUL-terminated C strings either) and always returns the *full* message
+/// length, so a caller can detect truncation by comparing the return value
+/// against `len` and retry with a bigger buffer. Passing `buf = NULL` or
+/// `len = 0` returns the length without copying, for size discovery.
+///
+/// # Safety
+///
+/// `buf`, if non-NULL, must be valid for writes of `len` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn markdown_last_error_message(buf: *mut u8, len: usize) -> usize {
+    LAST_ERROR.with(|cell| {
+        let borrowed = cell.borrow();
+        let Some(last_error) = borrowed.as_ref() else {
+            return 0;
+        };
+        let message_bytes = last_error.message.as_bytes();
+        if !buf.is_null() && len > 0 {
+            let copy_len = message_bytes.len().min(len);
+            // SAFETY: `buf` is valid for writes of `len` bytes per this
+            // function's safety contract, and `copy_len <= len`.
+            unsafe {
+                ptr::copy_nonoverlapping(message_bytes.as_ptr(), buf, copy_len);
+            }
+        }
+        message_bytes.len()
+    })
+}
+
 fn set_success_result(result: &mut MarkdownResult, output: ConversionOutput) {
     result.markdown_len = output.markdown.len();
     result.markdown = Box::into_raw(output.markdown) as *mut u8;
+    result.uncompressed_len = output.uncompressed_len;
     result.token_estimate = output.token_estimate;
+    result.had_lossy_decode = u8::from(output.had_lossy_decode);
     result.error_code = ERROR_SUCCESS;
     result.error_message = ptr::null_mut();
     result.error_len = 0;
 
+    let encoding_label = output.content_encoding.label();
+    if encoding_label.is_empty() {
+        result.content_encoding = ptr::null_mut();
+        result.content_encoding_len = 0;
+    } else {
+        let encoding_bytes = encoding_label.as_bytes().to_vec().into_boxed_slice();
+        result.content_encoding_len = encoding_bytes.len();
+        result.content_encoding = Box::into_raw(encoding_bytes) as *mut u8;
+    }
+
     if let Some(etag_bytes) = output.etag {
         result.etag_len = etag_bytes.len();
         result.etag = Box::into_raw(etag_bytes) as *mut u8;
@@ -479,6 +1384,14 @@ fn set_success_result(result: &mut MarkdownResult, output: ConversionOutput) {
         result.etag = ptr::null_mut();
         result.etag_len = 0;
     }
+
+    if let Some(toc_bytes) = output.toc {
+        result.toc_len = toc_bytes.len();
+        result.toc = Box::into_raw(toc_bytes) as *mut u8;
+    } else {
+        result.toc = ptr::null_mut();
+        result.toc_len = 0;
+    }
 }
 
 fn required_ref<'a, T>(ptr: *const T, name: &str) -> Result<&'a T, ConversionError> {
@@ -493,6 +1406,18 @@ fn required_ref<'a, T>(ptr: *const T, name: &str) -> Result<&'a T, ConversionErr
     Ok(unsafe { &*ptr })
 }
 
+fn required_mut_ref<'a, T>(ptr: *mut T, name: &str) -> Result<&'a mut T, ConversionError> {
+    if ptr.is_null() {
+        return Err(ConversionError::InvalidInput(format!(
+            "{name} pointer is NULL"
+        )));
+    }
+
+    // SAFETY: Caller provided a non-NULL pointer and accepts FFI contract
+    // that this points to a valid, properly aligned, uniquely-owned value.
+    Ok(unsafe { &mut *ptr })
+}
+
 fn required_bytes<'a>(ptr: *const u8, len: usize, name: &str) -> Result<&'a [u8], ConversionError> {
     if len == 0 {
         return Ok(&[]);
@@ -530,11 +1455,134 @@ fn optional_utf8<'a>(
     Ok(std::str::from_utf8(bytes).ok())
 }
 
+/// Reject a [`MarkdownOptions`] built against a different ABI version
+///
+/// A mismatch means the caller's header was generated from a different
+/// [`MARKDOWN_ABI_VERSION`] than this build, so fields beyond whatever the
+/// two versions have in common cannot be trusted to mean what this build
+/// expects - rejecting outright is safer than guessing.
+fn validate_abi_version(options_ref: &MarkdownOptions) -> Result<(), ConversionError> {
+    if options_ref.abi_version != MARKDOWN_ABI_VERSION {
+        return Err(ConversionError::InvalidInput(format!(
+            "abi_version mismatch: options built for {}, library is {}",
+            options_ref.abi_version, MARKDOWN_ABI_VERSION
+        )));
+    }
+    Ok(())
+}
+
+/// Reject a [`MarkdownOptions`] built with an unrecognized `flavor`
+///
+/// `flavor` only has two valid values ([`FLAVOR_COMMONMARK`], [`FLAVOR_GFM`]);
+/// anything else is rejected rather than silently treated as CommonMark,
+/// since an out-of-range value usually means the caller meant a flavor this
+/// build doesn't implement.
+fn validate_flavor(options_ref: &MarkdownOptions) -> Result<(), ConversionError> {
+    match options_ref.flavor {
+        FLAVOR_COMMONMARK | FLAVOR_GFM => Ok(()),
+        other => Err(ConversionError::InvalidInput(format!(
+            "unrecognized flavor: {other}"
+        ))),
+    }
+}
+
+/// Reject a [`MarkdownOptions`] built with an unrecognized `renderer`
+///
+/// [`RENDERER_DEFAULT`] is the only backend this build implements; anything
+/// else is rejected rather than silently falling back to it, for the same
+/// reason an out-of-range `flavor` is rejected in [`validate_flavor`].
+fn validate_renderer(options_ref: &MarkdownOptions) -> Result<(), ConversionError> {
+    match options_ref.renderer {
+        RENDERER_DEFAULT => Ok(()),
+        other => Err(ConversionError::InvalidInput(format!(
+            "unrecognized renderer: {other}"
+        ))),
+    }
+}
+
+/// Reject a [`MarkdownOptions`] built with a `heading_offset` that would
+/// shift a heading past h6
+fn validate_heading_offset(options_ref: &MarkdownOptions) -> Result<(), ConversionError> {
+    match options_ref.heading_offset {
+        0..=5 => Ok(()),
+        other => Err(ConversionError::InvalidInput(format!(
+            "heading_offset out of range (0-5): {other}"
+        ))),
+    }
+}
+
+/// Build [`ConversionOptions`] from the C-facing [`MarkdownOptions`], shared
+/// by `convert_inner`, `convert_streaming_inner`, and `markdown_stream_begin`
+fn build_conversion_options(
+    options_ref: &MarkdownOptions,
+    base_url: Option<String>,
+) -> ConversionOptions {
+    let flavor = match options_ref.flavor {
+        FLAVOR_GFM => MarkdownFlavor::GitHubFlavoredMarkdown,
+        _ => MarkdownFlavor::CommonMark,
+    };
+
+    let resolve_relative_urls = base_url.is_some();
+    let extensions = options_ref.extensions;
+    ConversionOptions {
+        flavor,
+        include_front_matter: options_ref.front_matter != 0,
+        extract_metadata: options_ref.front_matter != 0,
+        simplify_navigation: true,
+        // `preserve_tables` is kept for backward compatibility; either it or
+        // `EXT_TABLES` enables GFM tables.
+        preserve_tables: options_ref.preserve_tables != 0 || extensions & EXT_TABLES != 0,
+        base_url,
+        resolve_relative_urls,
+        sanitization_policy: None,
+        // The FFI boundary does not yet expose a format selector, so the
+        // C-facing `front_matter` flag keeps producing the historical YAML
+        // output until a dedicated option field is added.
+        front_matter_format: FrontMatterFormat::Yaml,
+        // The FFI boundary does not yet expose domain filtering or
+        // configurable metadata field mapping.
+        blocked_domains: Vec::new(),
+        allowed_domains: None,
+        smart_punctuation: extensions & EXT_SMART_PUNCTUATION != 0,
+        metadata_fields: Vec::new(),
+        heading_anchors: options_ref.heading_anchors != 0,
+        generate_toc: options_ref.generate_toc != 0,
+        heading_offset: options_ref.heading_offset,
+        inline_toc: extensions & EXT_INLINE_TOC != 0,
+        emoji_shortcodes: extensions & EXT_EMOJI_SHORTCODES != 0,
+        emoji_unicode: extensions & EXT_EMOJI_UNICODE != 0,
+        strikethrough: extensions & EXT_STRIKETHROUGH != 0,
+        task_lists: extensions & EXT_TASK_LISTS != 0,
+        underline: extensions & EXT_UNDERLINE != 0,
+        autolink: extensions & EXT_AUTOLINK != 0,
+        footnotes: extensions & EXT_FOOTNOTES != 0,
+        preserve_code_language: extensions & EXT_PRESERVE_CODE_LANGUAGE != 0,
+        normalize_punctuation: extensions & EXT_NORMALIZE_PUNCTUATION != 0,
+        readability_mode: extensions & EXT_READABILITY_MODE != 0,
+        reference_style_links: extensions & EXT_REFERENCE_LINKS != 0,
+        pretty_tables: extensions & EXT_PRETTY_TABLES != 0,
+        preserve_code_attributes: extensions & EXT_PRESERVE_CODE_ATTRIBUTES != 0,
+        // The FFI boundary does not yet expose bold/italic delimiter
+        // selection; callers get the CommonMark-default `**`/`*` markers.
+        strong_style: '*',
+        emphasis_char: '*',
+        // Spread defaults for options the FFI boundary does not yet expose
+        // a field for, so newly added `ConversionOptions` fields don't
+        // require touching this builder.
+        ..Default::default()
+    }
+}
+
 fn convert_inner(
-    handle_ref: &MarkdownConverterHandle,
+    handle_ref: &mut MarkdownConverterHandle,
     html_slice: &[u8],
     options_ref: &MarkdownOptions,
 ) -> Result<ConversionOutput, ConversionError> {
+    validate_abi_version(options_ref)?;
+    validate_flavor(options_ref)?;
+    validate_renderer(options_ref)?;
+    validate_heading_offset(options_ref)?;
+
     let content_type_str = optional_utf8(
         options_ref.content_type,
         options_ref.content_type_len,
@@ -542,9 +1590,53 @@ fn convert_inner(
     )?;
     let base_url_str = optional_utf8(options_ref.base_url, options_ref.base_url_len, "base_url")?
         .map(ToOwned::to_owned);
+    let accept_encoding_str = optional_utf8(
+        options_ref.accept_encoding,
+        options_ref.accept_encoding_len,
+        "accept_encoding",
+    )?;
+    let input_charset_str = optional_utf8(
+        options_ref.input_charset,
+        options_ref.input_charset_len,
+        "input_charset",
+    )?;
+    let request_content_encoding_str = optional_utf8(
+        options_ref.content_encoding,
+        options_ref.content_encoding_len,
+        "content_encoding",
+    )?;
+    let content_encoding = accept_encoding_str
+        .map(compression::negotiate_encoding)
+        .unwrap_or(CompressionEncoding::Identity);
+
+    let max_decompressed_bytes = options_ref.max_decompressed_bytes;
+    let decompressed = decompress_body(
+        html_slice,
+        request_content_encoding_str,
+        if max_decompressed_bytes > 0 {
+            max_decompressed_bytes as usize
+        } else {
+            usize::MAX
+        },
+    )?;
+    let html_slice = decompressed.as_ref();
+
+    let max_input_bytes = options_ref.max_input_bytes;
+    if max_input_bytes > 0 && html_slice.len() as u64 > u64::from(max_input_bytes) {
+        if let Some(log_sink) = &handle_ref.log_sink {
+            log_sink.log(
+                LOG_LEVEL_ERROR,
+                &format!(
+                    "input of {} bytes exceeds max_input_bytes of {max_input_bytes}",
+                    html_slice.len()
+                ),
+            );
+        }
+        return Err(ConversionError::InputTooLarge);
+    }
 
     if html_slice.is_empty() {
-        let markdown_bytes = Box::<[u8]>::default();
+        let uncompressed = Box::<[u8]>::default();
         let token_estimate = if options_ref.estimate_tokens != 0 {
             handle_ref.token_estimator.estimate("")
         } else {
@@ -555,7 +1647,7 @@ fn convert_inner(
             Some(
                 handle_ref
                     .etag_generator
-                    .generate(markdown_bytes.as_ref())
+                    .generate(uncompressed.as_ref())
                     .into_bytes()
                     .into_boxed_slice(),
             )
@@ -563,83 +1655,502 @@ fn convert_inner(
             None
         };
 
+        let uncompressed_len = uncompressed.len();
+        let markdown_bytes =
+            compression::compress(uncompressed.as_ref(), content_encoding)?.into_boxed_slice();
+
         return Ok(ConversionOutput {
             markdown: markdown_bytes,
+            uncompressed_len,
+            content_encoding,
             etag: etag_bytes,
+            toc: None,
             token_estimate,
+            had_lossy_decode: false,
         });
     }
 
-    // Parse HTML with charset detection cascade (FR-05.1, FR-05.2, FR-05.3)
-    let dom = parse_html_with_charset(html_slice, content_type_str)?;
-
-    // Create conversion context with timeout
-    let timeout_ms = options_ref.timeout_ms;
-    let timeout_duration = if timeout_ms > 0 {
-        Duration::from_millis(timeout_ms as u64)
-    } else {
-        Duration::ZERO // No timeout
+    // A cache hit skips parsing and rendering entirely; a miss populates the
+    // cache (if enabled) with the freshly rendered Markdown so a later
+    // identical request can skip it. See `crate::cache` for what is and
+    // isn't part of the key.
+    let cache_request = CacheableRequest {
+        flavor: options_ref.flavor,
+        extensions: options_ref.extensions,
+        front_matter: options_ref.front_matter != 0,
+        preserve_tables: options_ref.preserve_tables != 0,
+        generate_toc: options_ref.generate_toc != 0,
+        heading_anchors: options_ref.heading_anchors != 0,
+        base_url: base_url_str.as_deref(),
+        content_type: content_type_str,
+        input_charset: input_charset_str,
+    };
+    let cache_key = handle_ref
+        .cache
+        .as_ref()
+        .map(|cache| cache.key_for(html_slice, &cache_request));
+    let cached_entry = match &cache_key {
+        Some(key) => handle_ref
+            .cache
+            .as_mut()
+            .and_then(|cache| cache.get(key, html_slice.len())),
+        None => None,
     };
-    let mut ctx = ConversionContext::new(timeout_duration);
 
-    // Check timeout after parsing
-    ctx.check_timeout()?;
+    let (uncompressed, etag_bytes, token_estimate, toc_bytes, had_lossy_decode) =
+        if let Some(entry) = cached_entry {
+            let markdown_str = std::str::from_utf8(&entry.markdown)?;
 
-    // Build conversion options
-    let flavor = match options_ref.flavor {
-        1 => MarkdownFlavor::GitHubFlavoredMarkdown,
-        _ => MarkdownFlavor::CommonMark,
-    };
+            let token_estimate = if options_ref.estimate_tokens != 0 {
+                entry
+                    .token_estimate
+                    .unwrap_or_else(|| handle_ref.token_estimator.estimate(markdown_str))
+            } else {
+                0
+            };
+            let etag_bytes = if options_ref.generate_etag != 0 {
+                Some(entry.etag.clone().unwrap_or_else(|| {
+                    handle_ref
+                        .etag_generator
+                        .generate(&entry.markdown)
+                        .into_bytes()
+                        .into_boxed_slice()
+                }))
+            } else {
+                None
+            };
+            let toc_bytes = if options_ref.generate_toc != 0 {
+                let conv_options = build_conversion_options(options_ref, base_url_str);
+                MarkdownConverter::with_options(conv_options)
+                    .generate_toc(markdown_str)
+                    .map(|toc| toc.into_bytes().into_boxed_slice())
+            } else {
+                None
+            };
 
-    let resolve_relative_urls = base_url_str.is_some();
-    let conv_options = ConversionOptions {
-        flavor,
-        include_front_matter: options_ref.front_matter != 0,
-        extract_metadata: options_ref.front_matter != 0,
-        simplify_navigation: true,
-        preserve_tables: true,
-        base_url: base_url_str,
-        resolve_relative_urls,
-    };
+            (
+                entry.markdown,
+                etag_bytes,
+                token_estimate,
+                toc_bytes,
+                entry.had_lossy_decode,
+            )
+        } else {
+            // Parse HTML with charset detection cascade (FR-05.1, FR-05.2, FR-05.3),
+            // plus the statistical fallback stage when the caller opted in via
+            // `EXT_STATISTICAL_CHARSET_DETECTION`.
+            let (dom, _detected_charset, had_lossy_decode, _was_guessed) =
+                parse_html_with_charset_detection(
+                    html_slice,
+                    content_type_str,
+                    input_charset_str,
+                    options_ref.extensions & EXT_STATISTICAL_CHARSET_DETECTION != 0,
+                    None,
+                )?;
+            if had_lossy_decode {
+                if let Some(log_sink) = &handle_ref.log_sink {
+                    log_sink.log(
+                        LOG_LEVEL_WARN,
+                        "malformed byte sequence replaced with U+FFFD while decoding input charset",
+                    );
+                }
+            }
 
-    // Create converter and perform conversion with timeout support
-    let converter = MarkdownConverter::with_options(conv_options);
-    let markdown = converter.convert_with_context(&dom, &mut ctx)?;
+            // Create conversion context with timeout
+            let timeout_ms = options_ref.timeout_ms;
+            let timeout_duration = if timeout_ms > 0 {
+                Duration::from_millis(timeout_ms as u64)
+            } else {
+                Duration::ZERO // No timeout
+            };
+            let mut ctx = ConversionContext::new(timeout_duration)
+                .with_max_output_bytes(u64::from(options_ref.max_output_bytes));
 
-    // Estimate tokens while Markdown is still in String form (avoids reconstructing
-    // a UTF-8 view from raw bytes after allocation).
-    let token_estimate = if options_ref.estimate_tokens != 0 {
-        handle_ref.token_estimator.estimate(&markdown)
-    } else {
-        0
-    };
+            // Check timeout after parsing
+            ctx.check_timeout()?;
 
-    let markdown_bytes = markdown.into_bytes().into_boxed_slice();
-    let etag_bytes = if options_ref.generate_etag != 0 {
-        Some(
-            handle_ref
-                .etag_generator
-                .generate(markdown_bytes.as_ref())
-                .into_bytes()
-                .into_boxed_slice(),
-        )
-    } else {
-        None
-    };
+            // Build conversion options
+            let conv_options = build_conversion_options(options_ref, base_url_str);
+
+            // Create converter and perform conversion with timeout support
+            let converter = MarkdownConverter::with_options(conv_options);
+            let markdown = converter.convert_with_context(&dom, &mut ctx).inspect_err(|e| {
+                if let Some(log_sink) = &handle_ref.log_sink {
+                    let level = match e {
+                        ConversionError::Timeout { .. } | ConversionError::MemoryLimit { .. } => {
+                            LOG_LEVEL_ERROR
+                        }
+                        _ => LOG_LEVEL_WARN,
+                    };
+                    log_sink.log(level, &format!("conversion did not complete: {e}"));
+                }
+            })?;
+
+            // Estimate tokens while Markdown is still in String form (avoids reconstructing
+            // a UTF-8 view from raw bytes after allocation).
+            let token_estimate = if options_ref.estimate_tokens != 0 {
+                handle_ref.token_estimator.estimate(&markdown)
+            } else {
+                0
+            };
+            let toc_bytes = if options_ref.generate_toc != 0 {
+                converter
+                    .generate_toc(&markdown)
+                    .map(|toc| toc.into_bytes().into_boxed_slice())
+            } else {
+                None
+            };
+
+            let uncompressed = markdown.into_bytes().into_boxed_slice();
+            // ETag is computed over the uncompressed Markdown so it stays stable
+            // across encodings (FR-06.4).
+            let etag_bytes = if options_ref.generate_etag != 0 {
+                Some(
+                    handle_ref
+                        .etag_generator
+                        .generate(uncompressed.as_ref())
+                        .into_bytes()
+                        .into_boxed_slice(),
+                )
+            } else {
+                None
+            };
+
+            if let (Some(key), Some(cache)) = (cache_key, handle_ref.cache.as_mut()) {
+                let cached_token_estimate =
+                    (options_ref.estimate_tokens != 0).then_some(token_estimate);
+                cache.insert(
+                    key,
+                    uncompressed.clone(),
+                    etag_bytes.clone(),
+                    cached_token_estimate,
+                    had_lossy_decode,
+                    html_slice.len(),
+                );
+            }
+
+            (
+                uncompressed,
+                etag_bytes,
+                token_estimate,
+                toc_bytes,
+                had_lossy_decode,
+            )
+        };
+
+    let uncompressed_len = uncompressed.len();
+    let markdown_bytes =
+        compression::compress(uncompressed.as_ref(), content_encoding)?.into_boxed_slice();
 
     Ok(ConversionOutput {
         markdown: markdown_bytes,
+        uncompressed_len,
+        content_encoding,
         etag: etag_bytes,
+        toc: toc_bytes,
         token_estimate,
+        had_lossy_decode,
     })
 }
 
-fn free_buffer(ptr_field: &mut *mut u8, len_field: &mut usize) {
-    if (*ptr_field).is_null() {
-        return;
-    }
+/// Callback invoked with each Markdown fragment during streaming conversion
+///
+/// Returns `0` to request more fragments, or any non-zero value to request
+/// that traversal stop (e.g. the client disconnected or the consumer's
+/// buffer is full). `chunk` is a UTF-8 byte slice valid only for the
+/// duration of the call; `user_data` is passed through unchanged from
+/// `markdown_convert_streaming()`.
+pub type MarkdownStreamSink =
+    unsafe extern "C" fn(chunk: *const u8, chunk_len: usize, user_data: *mut c_void) -> i32;
 
-    let raw = ptr::slice_from_raw_parts_mut(*ptr_field, *len_field);
+/// Callback invoked exactly once after a `markdown_convert_streaming()` call
+/// finishes traversing the whole document, carrying the outputs that can
+/// only be finalized once every fragment has been seen
+///
+/// `etag` is NULL (with `etag_len` `0`) unless `options->generate_etag` was
+/// set; `token_estimate` is `0` unless `options->estimate_tokens` was set.
+/// Both are valid only for the duration of the call. Not invoked at all if
+/// `sink` or `capacity` asked traversal to stop before the end - in that
+/// case the document was never fully seen, so there is nothing valid to
+/// report.
+pub type MarkdownStreamCompletion = unsafe extern "C" fn(
+    etag: *const u8,
+    etag_len: usize,
+    token_estimate: u32,
+    user_data: *mut c_void,
+);
+
+/// Invoke `completion` (if any) with `etag_bytes`/`token_estimate`, guarding
+/// the call with `catch_unwind` so a panicking C callback cannot unwind
+/// across the FFI boundary - mirrors [`LogSink::log`]'s handling of the
+/// diagnostic callback.
+fn invoke_stream_completion(
+    completion: Option<MarkdownStreamCompletion>,
+    etag_bytes: Option<&[u8]>,
+    token_estimate: u32,
+    user_data: *mut c_void,
+) {
+    let Some(completion) = completion else {
+        return;
+    };
+    let (etag_ptr, etag_len) = match etag_bytes {
+        Some(bytes) => (bytes.as_ptr(), bytes.len()),
+        None => (ptr::null(), 0),
+    };
+    let _ = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        // SAFETY: `completion` and `user_data` are supplied by the caller per
+        // the FFI contract of `markdown_convert_streaming`; `etag_ptr` is
+        // valid for the duration of this call only.
+        unsafe { completion(etag_ptr, etag_len, token_estimate, user_data) }
+    }));
+}
+
+fn convert_streaming_inner(
+    html_slice: &[u8],
+    options_ref: &MarkdownOptions,
+    capacity_ref: &StreamCapacity,
+    sink: MarkdownStreamSink,
+    completion: Option<MarkdownStreamCompletion>,
+    user_data: *mut c_void,
+) -> Result<(), ConversionError> {
+    validate_abi_version(options_ref)?;
+    validate_flavor(options_ref)?;
+    validate_renderer(options_ref)?;
+    validate_heading_offset(options_ref)?;
+
+    if html_slice.is_empty() {
+        let etag_bytes = (options_ref.generate_etag != 0)
+            .then(|| ETagGenerator::new().generate(&[]).into_bytes());
+        let token_estimate = if options_ref.estimate_tokens != 0 {
+            TokenEstimator::new().estimate_from_char_count(0)
+        } else {
+            0
+        };
+        invoke_stream_completion(completion, etag_bytes.as_deref(), token_estimate, user_data);
+        return Ok(());
+    }
+
+    let content_encoding_str = optional_utf8(
+        options_ref.content_encoding,
+        options_ref.content_encoding_len,
+        "content_encoding",
+    )?;
+    let max_decompressed_bytes = options_ref.max_decompressed_bytes;
+    let decompressed = decompress_body(
+        html_slice,
+        content_encoding_str,
+        if max_decompressed_bytes > 0 {
+            max_decompressed_bytes as usize
+        } else {
+            usize::MAX
+        },
+    )?;
+    let html_slice = decompressed.as_ref();
+
+    let max_input_bytes = options_ref.max_input_bytes;
+    if max_input_bytes > 0 && html_slice.len() as u64 > u64::from(max_input_bytes) {
+        return Err(ConversionError::InputTooLarge);
+    }
+
+    let content_type_str = optional_utf8(
+        options_ref.content_type,
+        options_ref.content_type_len,
+        "content_type",
+    )?;
+    let base_url_str = optional_utf8(options_ref.base_url, options_ref.base_url_len, "base_url")?
+        .map(ToOwned::to_owned);
+    let input_charset_str = optional_utf8(
+        options_ref.input_charset,
+        options_ref.input_charset_len,
+        "input_charset",
+    )?;
+
+    // `markdown_convert_streaming` has no `MarkdownResult` to report
+    // `had_lossy_decode`/the detected charset on, so both are discarded
+    // here; a caller who needs them should use `markdown_convert` instead.
+    let (dom, _detected_charset, _had_lossy_decode, _was_guessed) = parse_html_with_charset_detection(
+        html_slice,
+        content_type_str,
+        input_charset_str,
+        options_ref.extensions & EXT_STATISTICAL_CHARSET_DETECTION != 0,
+        None,
+    )?;
+
+    let timeout_ms = options_ref.timeout_ms;
+    let timeout_duration = if timeout_ms > 0 {
+        Duration::from_millis(timeout_ms as u64)
+    } else {
+        Duration::ZERO
+    };
+    let mut ctx = ConversionContext::new(timeout_duration)
+        .with_max_output_bytes(u64::from(options_ref.max_output_bytes));
+    ctx.check_timeout()?;
+
+    let conv_options = build_conversion_options(options_ref, base_url_str);
+
+    let mut incremental_etag = (options_ref.generate_etag != 0).then(|| ETagGenerator::new().incremental());
+    let estimate_tokens = options_ref.estimate_tokens != 0;
+    let mut char_count = 0u64;
+
+    let converter = MarkdownConverter::with_options(conv_options);
+    let outcome = converter.convert_streaming(&dom, &mut ctx, capacity_ref, |fragment| {
+        let bytes = fragment.as_bytes();
+        if let Some(incremental_etag) = incremental_etag.as_mut() {
+            incremental_etag.update(bytes);
+        }
+        if estimate_tokens {
+            char_count += fragment.chars().count() as u64;
+        }
+        // SAFETY: `sink` and `user_data` are supplied by the caller per the
+        // FFI contract of `markdown_convert_streaming`; `bytes` is valid for
+        // the duration of this call only.
+        let stop_requested = unsafe { sink(bytes.as_ptr(), bytes.len(), user_data) } != 0;
+        if stop_requested {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    })?;
+
+    if outcome == StreamOutcome::Completed {
+        let etag_bytes = incremental_etag.map(|incremental| incremental.finish().into_bytes());
+        let token_estimate = if estimate_tokens {
+            TokenEstimator::new().estimate_from_char_count(char_count)
+        } else {
+            0
+        };
+        invoke_stream_completion(completion, etag_bytes.as_deref(), token_estimate, user_data);
+    }
+
+    Ok(())
+}
+
+fn stream_begin_inner(options_ref: &MarkdownOptions) -> Result<MarkdownStream, ConversionError> {
+    validate_abi_version(options_ref)?;
+    validate_flavor(options_ref)?;
+    validate_renderer(options_ref)?;
+    validate_heading_offset(options_ref)?;
+
+    let content_type = optional_utf8(
+        options_ref.content_type,
+        options_ref.content_type_len,
+        "content_type",
+    )?
+    .map(ToOwned::to_owned);
+    let base_url_str = optional_utf8(options_ref.base_url, options_ref.base_url_len, "base_url")?
+        .map(ToOwned::to_owned);
+    let input_charset = optional_utf8(
+        options_ref.input_charset,
+        options_ref.input_charset_len,
+        "input_charset",
+    )?
+    .map(ToOwned::to_owned);
+
+    let timeout_ms = options_ref.timeout_ms;
+    let timeout = if timeout_ms > 0 {
+        Duration::from_millis(timeout_ms as u64)
+    } else {
+        Duration::ZERO
+    };
+
+    let conv_options = build_conversion_options(options_ref, base_url_str);
+
+    Ok(MarkdownStream {
+        inner: IncrementalConverter::new(
+            conv_options,
+            content_type,
+            input_charset,
+            timeout,
+            u64::from(options_ref.max_input_bytes),
+            u64::from(options_ref.max_output_bytes),
+        ),
+        etag_generator: ETagGenerator::new(),
+        token_estimator: TokenEstimator::new(),
+        generate_etag: options_ref.generate_etag != 0,
+        estimate_tokens: options_ref.estimate_tokens != 0,
+        generate_toc: options_ref.generate_toc != 0,
+        finished: false,
+    })
+}
+
+fn stream_push_inner(
+    stream_ref: &mut MarkdownStream,
+    chunk: &[u8],
+) -> Result<ConversionOutput, ConversionError> {
+    if stream_ref.finished {
+        return Err(ConversionError::InvalidInput(
+            "markdown_stream_push called after markdown_stream_finish".to_string(),
+        ));
+    }
+
+    let markdown = stream_ref.inner.push(chunk)?;
+    Ok(ConversionOutput {
+        uncompressed_len: markdown.len(),
+        markdown: markdown.into_bytes().into_boxed_slice(),
+        content_encoding: CompressionEncoding::Identity,
+        etag: None,
+        toc: None,
+        token_estimate: 0,
+        had_lossy_decode: stream_ref.inner.had_lossy_decode(),
+    })
+}
+
+fn stream_finish_inner(
+    stream_ref: &mut MarkdownStream,
+) -> Result<ConversionOutput, ConversionError> {
+    if stream_ref.finished {
+        return Err(ConversionError::InvalidInput(
+            "markdown_stream_finish already called on this stream".to_string(),
+        ));
+    }
+
+    let tail = stream_ref.inner.finish()?;
+    stream_ref.finished = true;
+
+    // Unlike `push`, ETag/token estimation run here, over the complete
+    // output accumulated across every push, not just this final fragment.
+    let full_markdown = stream_ref.inner.full_markdown();
+    let token_estimate = if stream_ref.estimate_tokens {
+        stream_ref.token_estimator.estimate(full_markdown)
+    } else {
+        0
+    };
+    let etag = if stream_ref.generate_etag {
+        Some(
+            stream_ref
+                .etag_generator
+                .generate(full_markdown.as_bytes())
+                .into_bytes()
+                .into_boxed_slice(),
+        )
+    } else {
+        None
+    };
+    let toc = if stream_ref.generate_toc {
+        MarkdownConverter::with_options(stream_ref.inner.options().clone())
+            .generate_toc(full_markdown)
+            .map(|toc| toc.into_bytes().into_boxed_slice())
+    } else {
+        None
+    };
+
+    Ok(ConversionOutput {
+        uncompressed_len: tail.len(),
+        markdown: tail.into_bytes().into_boxed_slice(),
+        content_encoding: CompressionEncoding::Identity,
+        etag,
+        toc,
+        token_estimate,
+        had_lossy_decode: stream_ref.inner.had_lossy_decode(),
+    })
+}
+
+fn free_buffer(ptr_field: &mut *mut u8, len_field: &mut usize) {
+    if (*ptr_field).is_null() {
+        return;
+    }
+
+    let raw = ptr::slice_from_raw_parts_mut(*ptr_field, *len_field);
     // SAFETY: `raw` was allocated by `Box<[u8]>` via `Box::into_raw`.
     let _ = unsafe { Box::from_raw(raw) };
     *ptr_field = ptr::null_mut();
@@ -657,25 +2168,26 @@ fn free_buffer(ptr_field: &mut *mut u8, len_field: &mut usize) {
 ///
 /// # Returns
 ///
-/// - Non-NULL pointer to `MarkdownConverterHandle` on success
-/// - NULL on allocation failure
+/// - A non-zero handle on success, to be passed to `markdown_convert()` and
+///   friends
+/// - `0` if a panic occurred during initialization
 ///
 /// # Memory Management
 ///
-/// The returned handle is owned by the caller and must be freed by calling
-/// `markdown_converter_free()` exactly once when no longer needed.
+/// The returned handle is owned by the caller and must be released by
+/// calling `markdown_converter_free()` exactly once when no longer needed.
 ///
 /// # Thread Safety
 ///
 /// This function is thread-safe. Multiple threads can create their own
-/// converter instances concurrently. However, the returned handle is NOT
-/// thread-safe and should not be shared across threads.
+/// converter instances concurrently. See [`MarkdownConverterHandle`] for the
+/// thread-safety characteristics of the returned handle itself.
 ///
 /// # Example (C)
 ///
 /// ```c
-/// markdown_converter_t *converter = markdown_converter_new();
-/// if (converter == NULL) {
+/// uint64_t converter = markdown_converter_new();
+/// if (converter == 0) {
 ///     fprintf(stderr, "Failed to create converter\n");
 ///     return -1;
 /// }
@@ -688,26 +2200,75 @@ fn free_buffer(ptr_field: &mut *mut u8, len_field: &mut usize) {
 /// This function is safe to call from C code. It performs no pointer
 /// dereferencing and handles all allocation failures gracefully.
 #[unsafe(no_mangle)]
-pub extern "C" fn markdown_converter_new() -> *mut MarkdownConverterHandle {
+pub extern "C" fn markdown_converter_new() -> u64 {
     // Catch any panics to prevent unwinding into C code
     let result = panic::catch_unwind(|| {
         let handle = MarkdownConverterHandle {
             etag_generator: ETagGenerator::new(),
             token_estimator: TokenEstimator::new(),
+            cache: None,
+            log_sink: None,
         };
 
-        // Allocate on heap and return raw pointer
-        Box::into_raw(Box::new(handle))
+        let mut registry = HANDLE_REGISTRY
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        registry.insert(handle)
     });
 
-    match result {
-        Ok(ptr) => ptr,
-        Err(_) => {
-            // Panic occurred during initialization
-            // Return NULL to indicate failure
-            ptr::null_mut()
-        }
-    }
+    // A panic during initialization leaves no handle to return; `0` signals
+    // failure the same way a NULL pointer used to.
+    result.unwrap_or(0)
+}
+
+/// Create a new converter instance with a result cache enabled
+///
+/// Identical to `markdown_converter_new()`, except `markdown_convert()` on
+/// the returned handle first checks a fixed-capacity, least-recently-used
+/// cache keyed on the input HTML and the subset of `options` that affects
+/// rendered output (see [`crate::cache`]). A repeat conversion with the same
+/// HTML and equivalent options skips parsing and rendering entirely.
+///
+/// `generate_etag` and `estimate_tokens` are honored correctly on a cache
+/// hit even if the entry was populated by a request with those flags unset
+/// — they are computed from the cached Markdown on demand rather than
+/// cached themselves unconditionally.
+///
+/// Not used by `markdown_convert_streaming()` or the `markdown_stream_*`
+/// functions, which have no notion of a complete input to key on until
+/// `markdown_stream_finish()`.
+///
+/// # Parameters
+///
+/// - `capacity`: Maximum number of distinct conversions to retain. `0`
+///   disables caching, equivalent to `markdown_converter_new()`.
+///
+/// # Returns
+///
+/// A non-zero handle on success, `0` if a panic occurred during
+/// initialization.
+///
+/// # Safety
+///
+/// This function is safe to call from C code. It performs no pointer
+/// dereferencing and handles all allocation failures gracefully.
+#[unsafe(no_mangle)]
+pub extern "C" fn markdown_converter_new_cached(capacity: usize) -> u64 {
+    let result = panic::catch_unwind(|| {
+        let handle = MarkdownConverterHandle {
+            etag_generator: ETagGenerator::new(),
+            token_estimator: TokenEstimator::new(),
+            cache: Some(ConversionCache::new(capacity)),
+            log_sink: None,
+        };
+
+        let mut registry = HANDLE_REGISTRY
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        registry.insert(handle)
+    });
+
+    result.unwrap_or(0)
 }
 
 /// Perform HTML to Markdown conversion
@@ -717,25 +2278,34 @@ pub extern "C" fn markdown_converter_new() -> *mut MarkdownConverterHandle {
 ///
 /// # Parameters
 ///
-/// - `handle`: Pointer to converter instance from `markdown_converter_new()`
-///   - Must be non-NULL
-///   - Must be a valid handle (not freed)
-///   - Must not be used concurrently from multiple threads
+/// - `handle`: Handle from `markdown_converter_new()`
+///   - Must be non-zero
+///   - Must be a handle that has not yet been freed - a stale or freed
+///     handle is reported as `ERROR_INVALID_INPUT`, not undefined behavior
 ///
 /// - `html`: Pointer to HTML input bytes
 ///   - Must be non-NULL when `html_len > 0`
 ///   - Must point to valid memory of at least `html_len` bytes
 ///   - May be NULL when `html_len == 0`
-///   - Should be valid UTF-8 (invalid UTF-8 will cause encoding error)
+///   - Need not be UTF-8: `options->input_charset`, a BOM, the Content-Type
+///     header, or a `<meta charset>` tag (in that priority order) select the
+///     encoding to transcode from, defaulting to UTF-8. Decoding never
+///     fails; malformed bytes become U+FFFD and are reported via
+///     `result->had_lossy_decode`, not `ERROR_ENCODING`.
 ///   - Content is not modified (read-only)
 ///
 /// - `html_len`: Length of HTML input in bytes
 ///   - Must accurately reflect the size of the html buffer
 ///   - Can be 0 (will result in empty output)
+///   - Checked against `options->max_input_bytes` before parsing begins;
+///     exceeding it is reported as `ERROR_INPUT_TOO_LARGE` rather than
+///     allocating space for the input
 ///
 /// - `options`: Pointer to conversion options
 ///   - Must be non-NULL
 ///   - Must point to valid `MarkdownOptions` struct
+///   - `options->abi_version` must equal `MARKDOWN_ABI_VERSION`; a mismatch
+///     fails with `ERROR_INVALID_INPUT` before any other field is read
 ///   - Content is not modified (read-only)
 ///
 /// - `result`: Pointer to result structure to populate
@@ -752,6 +2322,8 @@ pub extern "C" fn markdown_converter_new() -> *mut MarkdownConverterHandle {
 /// - `result->markdown_len` contains byte length
 /// - `result->etag` may be set if requested
 /// - `result->token_estimate` may be set if requested
+/// - `result->had_lossy_decode` is `1` if any input byte sequence was
+///   replaced with U+FFFD while transcoding to UTF-8, `0` otherwise
 /// - `result->error_message` is NULL
 ///
 /// **On Error:**
@@ -766,8 +2338,11 @@ pub extern "C" fn markdown_converter_new() -> *mut MarkdownConverterHandle {
 /// - `ERROR_PARSE` (1): HTML parsing failed
 /// - `ERROR_ENCODING` (2): Character encoding error
 /// - `ERROR_TIMEOUT` (3): Conversion exceeded timeout
-/// - `ERROR_MEMORY_LIMIT` (4): Memory limit exceeded
-/// - `ERROR_INTERNAL` (99): Internal error or panic caught
+/// - `ERROR_MEMORY_LIMIT` (4): `max_output_bytes` or `max_decompressed_bytes` exceeded
+/// - `ERROR_UNKNOWN_ENCODING` (6): `input_charset` named an unrecognized encoding
+/// - `ERROR_PANIC` (7): A Rust panic was caught at the FFI boundary
+/// - `ERROR_INPUT_TOO_LARGE` (8): `max_input_bytes` exceeded
+/// - `ERROR_INTERNAL` (99): Unexpected internal condition
 ///
 /// # Memory Management
 ///
@@ -784,18 +2359,18 @@ pub extern "C" fn markdown_converter_new() -> *mut MarkdownConverterHandle {
 /// # Panic Safety
 ///
 /// This function uses `catch_unwind` to prevent Rust panics from unwinding
-/// into C code. Any panic is caught and converted to `ERROR_INTERNAL`.
+/// into C code. Any panic is caught and converted to `ERROR_PANIC`.
 ///
 /// # Thread Safety
 ///
-/// This function is NOT thread-safe with respect to the same `handle`.
-/// Do not call this function concurrently on the same handle from multiple
-/// threads. Each thread should have its own converter instance.
+/// Concurrent calls on the same `handle` are serialized, not undefined
+/// behavior - see [`MarkdownConverterHandle`]. Each thread should still have
+/// its own converter instance for throughput.
 ///
 /// # Example (C)
 ///
 /// ```c
-/// markdown_converter_t *converter = markdown_converter_new();
+/// uint64_t converter = markdown_converter_new();
 /// markdown_options_t options = {
 ///     .flavor = 0,
 ///     .timeout_ms = 5000,
@@ -822,23 +2397,27 @@ pub extern "C" fn markdown_converter_new() -> *mut MarkdownConverterHandle {
 /// # Safety
 ///
 /// **Pointer Validation:**
-/// - All pointers are validated for NULL before dereferencing
-/// - NULL pointers result in `ERROR_INVALID_INPUT`
-/// - Invalid (non-NULL but bad) pointers cause undefined behavior
+/// - `html`, `options`, and `result` pointers are validated for NULL before
+///   dereferencing; NULL results in `ERROR_INVALID_INPUT`
+/// - `handle` is validated against the registry, not dereferenced directly -
+///   `0`, a freed handle, or a stale generation all report
+///   `ERROR_INVALID_INPUT` rather than being undefined behavior
+/// - Invalid (non-NULL but bad) `html`/`options`/`result` pointers cause
+///   undefined behavior
 ///
 /// **Memory Safety:**
 /// - No buffer overflows (uses Rust's bounds checking)
-/// - No use-after-free (ownership model prevents it)
-/// - No double-free (memory freed exactly once via result_free)
+/// - No use-after-free and no double-free for `handle` (registry
+///   generation check); `result` must still be freed exactly once via
+///   `markdown_result_free()`
 ///
 /// **Undefined Behavior:**
-/// - Passing invalid (non-NULL but bad) pointers is undefined behavior
-/// - Using a freed handle is undefined behavior
-/// - Concurrent calls on same handle is undefined behavior
+/// - Passing an invalid (non-NULL but bad) `html`/`options`/`result` pointer
+///   is undefined behavior
 /// - Not calling `markdown_result_free()` causes memory leak (not UB)
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn markdown_convert(
-    handle: *mut MarkdownConverterHandle,
+    handle: u64,
     html: *const u8,
     html_len: usize,
     options: *const MarkdownOptions,
@@ -856,22 +2435,24 @@ pub unsafe extern "C" fn markdown_convert(
 
     // Catch any panics to prevent unwinding into C code.
     let panic_result = panic::catch_unwind(|| -> Result<ConversionOutput, ConversionError> {
-        let handle_ref = required_ref(handle.cast_const(), "Converter handle")?;
         let options_ref = required_ref(options, "Options")?;
         let html_slice = required_bytes(html, html_len, "HTML")?;
-        convert_inner(handle_ref, html_slice, options_ref)
+        with_converter(handle, |handle_ref| {
+            convert_inner(handle_ref, html_slice, options_ref)
+        })?
     });
 
     // Handle panic or error.
     match panic_result {
         Ok(Ok(output)) => set_success_result(result_ref, output),
         Ok(Err(e)) => {
-            set_error_result(result_ref, e.code(), e.to_string());
+            set_error_result(result_ref, e.code(), e.category(), e.to_string());
         }
         Err(_) => {
             set_error_result(
                 result_ref,
-                ERROR_INTERNAL,
+                ERROR_PANIC,
+                ErrorCategory::Internal,
                 "Internal panic during conversion".to_string(),
             );
         }
@@ -963,36 +2544,40 @@ pub unsafe extern "C" fn markdown_result_free(result: *mut MarkdownResult) {
     // SAFETY: `result` was validated as non-NULL above.
     let result_ref = unsafe { &mut *result };
     free_buffer(&mut result_ref.markdown, &mut result_ref.markdown_len);
+    free_buffer(
+        &mut result_ref.content_encoding,
+        &mut result_ref.content_encoding_len,
+    );
     free_buffer(&mut result_ref.etag, &mut result_ref.etag_len);
+    free_buffer(&mut result_ref.toc, &mut result_ref.toc_len);
     // NOTE: We do NOT use CString::from_raw() because error_message
     // is NOT a NUL-terminated C string - it's UTF-8 bytes with length.
     free_buffer(&mut result_ref.error_message, &mut result_ref.error_len);
+    result_ref.uncompressed_len = 0;
     result_ref.token_estimate = 0;
     result_ref.error_code = 0;
 }
 
 /// Destroy converter instance
 ///
-/// Deallocates a converter instance created by `markdown_converter_new()`.
-/// This function must be called exactly once when the converter is no longer
-/// needed to prevent memory leaks.
+/// Releases the registry slot for a converter instance created by
+/// `markdown_converter_new()`. This function must be called exactly once
+/// when the converter is no longer needed to prevent memory leaks.
 ///
 /// # Parameters
 ///
-/// - `handle`: Pointer to converter instance
-///   - Must be non-NULL
-///   - Must be a valid handle from `markdown_converter_new()`
-///   - Must not have been freed previously
-///   - Must not be in use by concurrent operations
+/// - `handle`: Handle to converter instance
+///   - `0` and already-freed handles are a no-op
 ///
 /// # Behavior
 ///
 /// This function:
-/// 1. Deallocates the converter instance
-/// 2. Invalidates the handle pointer
+/// 1. Drops the converter instance
+/// 2. Invalidates the handle (the slot's generation advances, so the
+///    numeric handle value will never resolve to a live converter again)
 ///
-/// After calling this function, the handle pointer becomes invalid and
-/// must not be used for any further operations.
+/// After calling this function, the handle becomes invalid and must not be
+/// used for any further operations.
 ///
 /// # Lifecycle
 ///
@@ -1001,16 +2586,10 @@ pub unsafe extern "C" fn markdown_result_free(result: *mut MarkdownResult) {
 /// 2. Use: `markdown_convert()` (can be called multiple times)
 /// 3. Destroy: `markdown_converter_free()` (call once)
 ///
-/// # Memory Management
-///
-/// **CRITICAL:** This function uses Rust's memory allocator to free memory.
-/// Do NOT call C's `free()` on the handle - it will cause undefined
-/// behavior due to allocator mismatch.
-///
 /// # Example (C)
 ///
 /// ```c
-/// markdown_converter_t *converter = markdown_converter_new();
+/// uint64_t converter = markdown_converter_new();
 ///
 /// // Use converter for multiple conversions
 /// markdown_result_t result1, result2;
@@ -1024,32 +2603,636 @@ pub unsafe extern "C" fn markdown_result_free(result: *mut MarkdownResult) {
 /// // Destroy converter
 /// markdown_converter_free(converter);
 ///
-/// // After free, converter pointer is invalid
-/// // Do not use converter for any further operations
+/// // After free, the handle is invalid
+/// // Do not use it for any further operations
 /// ```
 ///
+/// # Panics
+///
+/// Does not panic; a poisoned registry lock (left by a panic during a prior
+/// call) is recovered rather than propagated.
+///
+/// # Double-Free and Use-After-Free
+///
+/// Freeing an already-freed handle, or using a handle after it was freed, is
+/// no longer undefined behavior: both are caught by the handle's generation
+/// check and reported (or, for this function, silently ignored as a no-op)
+/// rather than touching reclaimed memory.
+#[unsafe(no_mangle)]
+pub extern "C" fn markdown_converter_free(handle: u64) {
+    let mut registry = HANDLE_REGISTRY
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    registry.remove(handle);
+}
+
+/// Read a converter's result cache hit/miss/eviction counters
+///
+/// # Parameters
+///
+/// - `handle`: Handle to converter instance; must be non-zero and live
+/// - `stats`: Pointer to a `CacheStats` to populate; must be non-NULL
+///
+/// # Returns
+///
+/// `ERROR_SUCCESS` on success, including for a handle created by
+/// `markdown_converter_new()` (without caching), in which case `stats` is
+/// zeroed. `ERROR_INVALID_INPUT` if `handle` is zero, stale, or freed, or if
+/// `stats` is NULL.
+///
 /// # Safety
 ///
-/// **Pointer Validation:**
-/// - NULL handle is handled gracefully (no-op)
+/// `stats` must be non-NULL and point to a valid `CacheStats`. `handle` is
+/// looked up through the registry rather than dereferenced directly, so
+/// concurrent calls on the same handle are serialized, not undefined
+/// behavior.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn markdown_converter_cache_stats(
+    handle: u64,
+    stats: *mut CacheStats,
+) -> u32 {
+    let panic_result = panic::catch_unwind(|| -> Result<(), ConversionError> {
+        let stats_ref = required_mut_ref(stats, "Cache stats")?;
+        let counters = with_converter(handle, |handle_ref| {
+            handle_ref
+                .cache
+                .as_ref()
+                .map(ConversionCache::counters)
+                .unwrap_or_default()
+        })?;
+        stats_ref.hits = counters.hits;
+        stats_ref.misses = counters.misses;
+        stats_ref.evictions = counters.evictions;
+        Ok(())
+    });
+
+    match panic_result {
+        Ok(Ok(())) => ERROR_SUCCESS,
+        Ok(Err(e)) => e.code(),
+        Err(_) => ERROR_PANIC,
+    }
+}
+
+/// Register (or clear) a diagnostic logging callback for a converter handle
 ///
-/// **Memory Safety:**
-/// - Uses Rust's `Box::from_raw()` to reconstruct and drop allocation
-/// - Prevents double-free (caller responsibility to call only once)
+/// `markdown_convert()` and friends invoke `callback` with leveled
+/// (`LOG_LEVEL_*`) records for non-fatal conditions encountered during
+/// conversion on this handle - lossy charset decoding, truncation at a
+/// configured memory limit, and similar - instead of only a final pass/fail
+/// result. The host (e.g. NGINX) can forward these into its own error log.
+///
+/// # Parameters
+///
+/// - `handle`: Handle from `markdown_converter_new()`; must be non-zero and
+///   live
+/// - `level_filter`: Maximum severity (by `LOG_LEVEL_*` ordering, lower is
+///   more severe) to deliver; a record numerically greater than this is
+///   dropped before `callback` is invoked. Pass `LOG_LEVEL_DEBUG` to receive
+///   everything.
+/// - `callback`: `NULL` disables logging (the default for a freshly created
+///   handle); otherwise invoked for each record
+/// - `user_data`: Opaque pointer passed through unchanged to every
+///   `callback` invocation
+///
+/// # Returns
+///
+/// `ERROR_SUCCESS` on success, `ERROR_INVALID_INPUT` if `handle` is zero,
+/// stale, or freed.
+///
+/// # Panic Safety
+///
+/// Every `callback` invocation is individually wrapped in `catch_unwind`, so
+/// a panicking callback cannot unwind into Rust or fail the conversion it is
+/// reporting on.
+///
+/// # Safety
+///
+/// `callback`, if non-NULL, must be a valid function pointer safely callable
+/// with the arguments described above for as long as it remains registered
+/// on this handle (until cleared or the handle is freed). `user_data` is not
+/// dereferenced by this library and is the caller's responsibility to keep
+/// valid for that same lifetime.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn markdown_set_log_callback(
+    handle: u64,
+    level_filter: u32,
+    callback: Option<MarkdownLogCallback>,
+    user_data: *mut c_void,
+) -> u32 {
+    let panic_result = panic::catch_unwind(|| -> Result<(), ConversionError> {
+        with_converter(handle, |handle_ref| {
+            handle_ref.log_sink = callback.map(|callback| LogSink {
+                level_filter,
+                callback,
+                user_data: SendPtr(user_data),
+            });
+        })
+    });
+
+    match panic_result {
+        Ok(Ok(())) => ERROR_SUCCESS,
+        Ok(Err(e)) => e.code(),
+        Err(_) => ERROR_PANIC,
+    }
+}
+
+/// Create a new streaming backpressure capacity signal
+///
+/// Allocates a [`StreamCapacity`] the consumer updates via
+/// `markdown_stream_capacity_set()` to report how many bytes it can
+/// currently accept. Pass `usize::MAX` (or any very large value) for
+/// effectively unbounded output.
+///
+/// # Returns
+///
+/// Non-NULL pointer on success, NULL on allocation failure.
+///
+/// # Memory Management
+///
+/// The returned pointer is owned by the caller and must be freed by calling
+/// `markdown_stream_capacity_free()` exactly once.
+///
+/// # Safety
+///
+/// This function is safe to call from C code. It performs no pointer
+/// dereferencing.
+#[unsafe(no_mangle)]
+pub extern "C" fn markdown_stream_capacity_new(initial: usize) -> *mut StreamCapacity {
+    let result = panic::catch_unwind(|| Box::into_raw(Box::new(StreamCapacity::new(initial))));
+
+    result.unwrap_or(ptr::null_mut())
+}
+
+/// Update the currently available streaming capacity
+///
+/// # Parameters
+///
+/// - `capacity`: Pointer from `markdown_stream_capacity_new()`; NULL is a no-op
+/// - `value`: New available capacity in bytes; `0` signals backpressure and
+///   pauses the next streaming checkpoint
+///
+/// # Safety
+///
+/// `capacity`, if non-NULL, must point to a live value returned by
+/// `markdown_stream_capacity_new()` that has not yet been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn markdown_stream_capacity_set(capacity: *mut StreamCapacity, value: usize) {
+    if capacity.is_null() {
+        return;
+    }
+
+    // SAFETY: `capacity` was validated as non-NULL above.
+    unsafe { &*capacity }.set(value);
+}
+
+/// Destroy a streaming capacity signal
+///
+/// # Safety
+///
+/// `capacity`, if non-NULL, must point to a live value returned by
+/// `markdown_stream_capacity_new()` that has not been freed already. Using
+/// the pointer after this call is undefined behavior.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn markdown_stream_capacity_free(capacity: *mut StreamCapacity) {
+    if capacity.is_null() {
+        return;
+    }
+
+    // SAFETY: `capacity` was validated as non-NULL above and was originally
+    // created by `Box::into_raw` in `markdown_stream_capacity_new`.
+    unsafe { drop(Box::from_raw(capacity)) };
+}
+
+/// Perform HTML to Markdown conversion, streaming fragments to a callback
+///
+/// Like `markdown_convert()`, but instead of returning one large buffer,
+/// invokes `sink` with Markdown fragments as they are produced during DOM
+/// traversal. This lets an NGINX output filter forward data to the client
+/// incrementally instead of buffering the full document, and lets the
+/// consumer apply backpressure via `capacity`.
+///
+/// # Parameters
+///
+/// - `handle`: Handle from `markdown_converter_new()`; must be non-zero and
+///   live. Only checked for existence - this function does not read or
+///   mutate the converter it names.
+/// - `html`: Pointer to HTML input bytes; must be non-NULL when `html_len > 0`
+/// - `html_len`: Length of HTML input in bytes
+/// - `options`: Pointer to conversion options; must be non-NULL
+///   - `generate_etag` and `estimate_tokens`, if set, are honored by
+///     `completion` rather than by a `MarkdownResult` - this function has
+///     none - since both can only be finalized once every fragment has been
+///     produced
+/// - `capacity`: Pointer from `markdown_stream_capacity_new()`; must be non-NULL
+/// - `sink`: Callback invoked with each fragment; must be non-NULL. Return
+///   `0` to continue or non-zero to stop traversal early.
+/// - `completion`: Optional callback invoked exactly once, after the last
+///   `sink` call, with the ETag/token estimate `options` requested; NULL
+///   disables it. Not invoked if `sink` or `capacity` stopped traversal
+///   before the end.
+/// - `user_data`: Opaque pointer passed through unchanged to every `sink`
+///   call and to `completion`
+///
+/// # Returns
+///
+/// An `ERROR_*` code (see `markdown_convert()`'s documentation for the full
+/// list): `ERROR_SUCCESS` on success (including when `sink` or `capacity`
+/// asked traversal to stop early — that is a normal, not an error,
+/// termination), or the appropriate error code otherwise.
+///
+/// # Behavior
+///
+/// Fragments are produced depth-first and flushed at the same checkpoints
+/// used for cooperative timeout detection elsewhere (every 100 DOM nodes),
+/// so no single `sink` call represents an unbounded slice of the document.
+/// The ETag (if requested) is computed incrementally over fragments as they
+/// are produced, via [`crate::etag_generator::ETagGenerator::incremental`],
+/// so reporting it through `completion` costs no extra buffering of the
+/// document; the token estimate is likewise a running character count.
+///
+/// # Panic Safety
+///
+/// This function uses `catch_unwind` to prevent Rust panics — including
+/// panics unwinding out of `sink` or `completion` — from unwinding into C
+/// code. Any panic from `markdown_convert_streaming` itself is converted to
+/// `ERROR_PANIC`; a panic caught from inside `completion` is silently
+/// dropped instead, the same way a misbehaving `markdown_set_log_callback`
+/// callback is, since a broken completion report should not be treated as a
+/// failure of the conversion it is reporting on.
+///
+/// # Thread Safety
+///
+/// Unlike `markdown_convert()`, this function never reads or mutates the
+/// state behind `handle`, so concurrent calls sharing a handle with other
+/// operations are fine; the handle is only checked for existence.
+///
+/// # Example (C)
+///
+/// ```c
+/// static int on_fragment(const uint8_t *chunk, size_t chunk_len, void *user_data) {
+///     send_to_client(user_data, chunk, chunk_len);
+///     return client_disconnected(user_data) ? 1 : 0;
+/// }
+///
+/// static void on_complete(const uint8_t *etag, size_t etag_len, uint32_t token_estimate, void *user_data) {
+///     if (etag != NULL) {
+///         set_etag_header(user_data, etag, etag_len);
+///     }
+///     log_tokens(user_data, token_estimate);
+/// }
+///
+/// markdown_stream_capacity_t *capacity = markdown_stream_capacity_new(SIZE_MAX);
+/// uint32_t error = markdown_convert_streaming(
+///     converter, html, html_len, &options, capacity, on_fragment, on_complete, conn);
+/// if (error != 0) {
+///     log_error(error);
+/// }
+/// markdown_stream_capacity_free(capacity);
+/// ```
+///
+/// # Safety
+///
+/// **Pointer Validation:**
+/// - `html`, `options`, `capacity`, and `sink` are validated for NULL before
+///   dereferencing; NULL results in `ERROR_INVALID_INPUT`
+/// - `handle` of `0`, stale, or freed also results in `ERROR_INVALID_INPUT`
+/// - `completion` may be NULL, which simply disables the completion report
 ///
 /// **Undefined Behavior:**
-/// - Passing invalid (non-NULL but bad) pointer is undefined behavior
-/// - Freeing a handle that wasn't created by `markdown_converter_new()` is UB
-/// - Using handle after free is undefined behavior
-/// - Freeing handle while conversion is in progress is undefined behavior
-/// - Mixing Rust and C allocators (calling C `free()`) is UB
+/// - Passing invalid (non-NULL but bad) pointers is undefined behavior
+/// - `sink` and `completion` must be safely callable with the given
+///   `user_data` for the duration of this call
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn markdown_convert_streaming(
+    handle: u64,
+    html: *const u8,
+    html_len: usize,
+    options: *const MarkdownOptions,
+    capacity: *mut StreamCapacity,
+    sink: Option<MarkdownStreamSink>,
+    completion: Option<MarkdownStreamCompletion>,
+    user_data: *mut c_void,
+) -> u32 {
+    let panic_result = panic::catch_unwind(|| -> Result<(), ConversionError> {
+        validate_handle(handle)?;
+        let options_ref = required_ref(options, "Options")?;
+        let capacity_ref = required_ref(capacity.cast_const(), "Capacity")?;
+        let sink = sink.ok_or_else(|| {
+            ConversionError::InvalidInput("Sink callback pointer is NULL".to_string())
+        })?;
+        let html_slice = required_bytes(html, html_len, "HTML")?;
+        convert_streaming_inner(
+            html_slice,
+            options_ref,
+            capacity_ref,
+            sink,
+            completion,
+            user_data,
+        )
+    });
+
+    match panic_result {
+        Ok(Ok(())) => ERROR_SUCCESS,
+        Ok(Err(e)) => e.code(),
+        Err(_) => ERROR_PANIC,
+    }
+}
+
+/// Begin an incremental HTML-to-Markdown conversion stream
+///
+/// Unlike `markdown_convert()`, which requires the entire HTML body in one
+/// contiguous slice, this lets an NGINX body filter feed the document as it
+/// arrives in a chain of buffers. See [`crate::incremental`] for how output
+/// is derived from a growing buffer rather than a true byte-level streaming
+/// parser.
+///
+/// # Parameters
+///
+/// - `handle`: Handle from `markdown_converter_new()`; must be non-zero and
+///   live. Only checked for existence - the returned stream carries its own
+///   `ETagGenerator`/`TokenEstimator`, independent of the converter instance.
+/// - `options`: Pointer to conversion options; must be non-NULL
+///   - `generate_etag` and `estimate_tokens` are honored, but only applied
+///     once, by `markdown_stream_finish()`, over the complete output
+///   - `content_type`/`input_charset`/`base_url` are resolved once, here, and
+///     used for every `markdown_stream_push()` call on the returned stream
+///   - `max_input_bytes` is checked on every `markdown_stream_push()` call
+///     against the buffer accumulated so far, failing with
+///     `ERROR_INPUT_TOO_LARGE`; `max_output_bytes` is checked the same way
+///     `timeout_ms` is, during each push's re-render of the buffered
+///     prefix, failing with `ERROR_MEMORY_LIMIT`.
+///
+/// # Returns
+///
+/// Non-NULL pointer to a `MarkdownStream` on success, NULL on invalid input
+/// (including an `abi_version` mismatch - see `markdown_convert()`'s
+/// documentation) or allocation failure. Unlike the other `markdown_*`
+/// functions, there is no `result` out-param to carry a message on this
+/// path, so call `markdown_last_error_code()`/`markdown_last_error_message()`
+/// to find out why.
+///
+/// # Memory Management
+///
+/// The returned stream is owned by the caller and must be freed by calling
+/// `markdown_stream_free()` exactly once.
+///
+/// # Thread Safety
+///
+/// Like `markdown_convert()`, the returned stream is NOT thread-safe and
+/// must not be used concurrently from multiple threads.
+///
+/// # Example (C)
+///
+/// ```c
+/// markdown_stream_t *stream = markdown_stream_begin(converter, &options);
+/// if (stream == NULL) {
+///     // Handle invalid options or allocation failure
+/// }
+/// ```
+///
+/// # Safety
+///
+/// `options` must be non-NULL and point to a valid, properly initialized
+/// value; it is read-only and not retained by reference - its fields are
+/// copied or cloned during this call, so it may be freed or reused
+/// immediately after this function returns. `handle` of `0`, stale, or
+/// freed results in a NULL return rather than undefined behavior.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn markdown_stream_begin(
+    handle: u64,
+    options: *const MarkdownOptions,
+) -> *mut MarkdownStream {
+    let panic_result = panic::catch_unwind(|| -> Result<MarkdownStream, ConversionError> {
+        validate_handle(handle)?;
+        let options_ref = required_ref(options, "Options")?;
+        stream_begin_inner(options_ref)
+    });
+
+    match panic_result {
+        Ok(Ok(stream)) => Box::into_raw(Box::new(stream)),
+        Ok(Err(e)) => {
+            set_last_error(e.code(), e.category(), e.to_string());
+            ptr::null_mut()
+        }
+        Err(_) => {
+            set_last_error(
+                ERROR_PANIC,
+                ErrorCategory::Internal,
+                "Internal panic during stream begin".to_string(),
+            );
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Feed a chunk of HTML to a stream, writing newly available Markdown into `result`
+///
+/// Call this for each buffer as it is delivered. A tag or entity reference
+/// split across the chunk boundary is held back internally and completed by
+/// a later `markdown_stream_push()` or `markdown_stream_finish()` call, so
+/// `result->markdown_len` may legitimately be `0` with `result->error_code`
+/// still `0`.
+///
+/// `result->etag` and `result->token_estimate` are never populated here —
+/// see `markdown_stream_finish()`. `result->had_lossy_decode`, by contrast,
+/// is updated on every call, since it reflects the charset decode rather
+/// than the accumulated output.
+///
+/// # Parameters
+///
+/// - `stream`: Pointer from `markdown_stream_begin()`; must be non-NULL and not yet finished
+/// - `chunk`: Pointer to HTML input bytes; must be non-NULL when `chunk_len > 0`
+/// - `chunk_len`: Length of `chunk` in bytes; can be `0`
+/// - `result`: Pointer to result structure to populate; must be non-NULL;
+///   caller must call `markdown_result_free()` after use
+///
+/// # Returns
+///
+/// An `ERROR_*` code (see `markdown_convert()`'s documentation for the full
+/// list). Calling this after `markdown_stream_finish()` returns
+/// `ERROR_INVALID_INPUT`. There is no separate `Complete`/`Partial` status:
+/// a `0` return with `result->markdown_len > 0` means this chunk closed
+/// enough of the document to produce new output, and a `0` return with
+/// `result->markdown_len == 0` means the chunk boundary landed inside a
+/// tag, entity reference, or multi-byte UTF-8 sequence that is now held in
+/// the stream's internal buffer - not rejected - awaiting completion by a
+/// later `markdown_stream_push()` or `markdown_stream_finish()` call.
+///
+/// # Panic Safety
+///
+/// This function uses `catch_unwind` to prevent Rust panics from unwinding
+/// into C code. Any panic is converted to `ERROR_PANIC`.
+///
+/// # Example (C)
+///
+/// ```c
+/// markdown_result_t result;
+/// uint32_t error = markdown_stream_push(stream, chunk, chunk_len, &result);
+/// if (error == 0 && result.markdown_len > 0) {
+///     send_to_client(result.markdown, result.markdown_len);
+/// }
+/// markdown_result_free(&result);
+/// ```
+///
+/// # Safety
+///
+/// `stream` and `result` must be non-NULL and point to valid values; `chunk`
+/// must be readable for `chunk_len` bytes. `stream` must not be used
+/// concurrently from multiple threads.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn markdown_stream_push(
+    stream: *mut MarkdownStream,
+    chunk: *const u8,
+    chunk_len: usize,
+    result: *mut MarkdownResult,
+) -> u32 {
+    if result.is_null() {
+        return ERROR_INVALID_INPUT;
+    }
+
+    // SAFETY: `result` was validated as non-NULL above.
+    let result_ref = unsafe { &mut *result };
+    reset_result(result_ref);
+
+    // `MarkdownStream`'s options may carry a `dyn TextCleaner`, which isn't
+    // `RefUnwindSafe`, so the `&mut MarkdownStream` dereferenced inside this
+    // closure isn't inferred unwind-safe. That's fine here: a panic caught
+    // below is reported as `ERROR_PANIC` and the stream is not read again
+    // before the caller decides whether to keep pushing or to free it, so
+    // no caller ever observes a partially-mutated `TextCleaner`.
+    let panic_result = panic::catch_unwind(panic::AssertUnwindSafe(
+        || -> Result<ConversionOutput, ConversionError> {
+            let stream_ref = required_mut_ref(stream, "Stream")?;
+            let chunk_slice = required_bytes(chunk, chunk_len, "Chunk")?;
+            stream_push_inner(stream_ref, chunk_slice)
+        },
+    ));
+
+    match panic_result {
+        Ok(Ok(output)) => {
+            set_success_result(result_ref, output);
+            ERROR_SUCCESS
+        }
+        Ok(Err(e)) => {
+            set_error_result(result_ref, e.code(), e.category(), e.to_string());
+            e.code()
+        }
+        Err(_) => {
+            set_error_result(
+                result_ref,
+                ERROR_PANIC,
+                ErrorCategory::Internal,
+                "Internal panic during stream push".to_string(),
+            );
+            ERROR_PANIC
+        }
+    }
+}
+
+/// Flush a stream's tail and compute its ETag/token estimate
+///
+/// Call this exactly once, after the final chunk has been pushed, to emit
+/// any Markdown still pending for elements left open at the end of the
+/// document (html5ever tolerates a truncated document as end-of-input).
+/// Unlike `markdown_stream_push()`, `result->etag` and
+/// `result->token_estimate` are populated here (if requested via
+/// `markdown_stream_begin()`'s options), computed over the complete output
+/// accumulated across every push, not just the fragment this call returns.
+///
+/// # Parameters
+///
+/// - `stream`: Pointer from `markdown_stream_begin()`; must be non-NULL and not already finished
+/// - `result`: Pointer to result structure to populate; must be non-NULL;
+///   caller must call `markdown_result_free()` after use
+///
+/// # Returns
+///
+/// An `ERROR_*` code (see `markdown_convert()`'s documentation for the full
+/// list). Calling this more than once on the same stream returns
+/// `ERROR_INVALID_INPUT`.
+///
+/// # Panic Safety
+///
+/// This function uses `catch_unwind` to prevent Rust panics from unwinding
+/// into C code. Any panic is converted to `ERROR_PANIC`.
+///
+/// # Example (C)
+///
+/// ```c
+/// markdown_result_t result;
+/// uint32_t error = markdown_stream_finish(stream, &result);
+/// if (error == 0) {
+///     if (result.markdown_len > 0) {
+///         send_to_client(result.markdown, result.markdown_len);
+///     }
+///     send_trailer_etag(result.etag, result.etag_len);
+/// }
+/// markdown_result_free(&result);
+/// markdown_stream_free(stream);
+/// ```
+///
+/// # Safety
+///
+/// `stream` and `result` must be non-NULL and point to valid values. `stream`
+/// must not be used concurrently from multiple threads.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn markdown_stream_finish(
+    stream: *mut MarkdownStream,
+    result: *mut MarkdownResult,
+) -> u32 {
+    if result.is_null() {
+        return ERROR_INVALID_INPUT;
+    }
+
+    // SAFETY: `result` was validated as non-NULL above.
+    let result_ref = unsafe { &mut *result };
+    reset_result(result_ref);
+
+    // See the matching comment in `markdown_stream_push` for why asserting
+    // unwind safety here is sound: a caught panic is reported as
+    // `ERROR_PANIC` and this stream is not read again afterward.
+    let panic_result = panic::catch_unwind(panic::AssertUnwindSafe(
+        || -> Result<ConversionOutput, ConversionError> {
+            let stream_ref = required_mut_ref(stream, "Stream")?;
+            stream_finish_inner(stream_ref)
+        },
+    ));
+
+    match panic_result {
+        Ok(Ok(output)) => {
+            set_success_result(result_ref, output);
+            ERROR_SUCCESS
+        }
+        Ok(Err(e)) => {
+            set_error_result(result_ref, e.code(), e.category(), e.to_string());
+            e.code()
+        }
+        Err(_) => {
+            set_error_result(
+                result_ref,
+                ERROR_PANIC,
+                ErrorCategory::Internal,
+                "Internal panic during stream finish".to_string(),
+            );
+            ERROR_PANIC
+        }
+    }
+}
+
+/// Destroy an incremental conversion stream
+///
+/// # Safety
+///
+/// `stream`, if non-NULL, must point to a live value returned by
+/// `markdown_stream_begin()` that has not been freed already. Using the
+/// pointer after this call is undefined behavior.
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn markdown_converter_free(handle: *mut MarkdownConverterHandle) {
-    if handle.is_null() {
+pub unsafe extern "C" fn markdown_stream_free(stream: *mut MarkdownStream) {
+    if stream.is_null() {
         return;
     }
 
-    // SAFETY: `handle` was validated as non-NULL above and was originally
-    // created by `Box::into_raw` in `markdown_converter_new`.
-    unsafe { drop(Box::from_raw(handle)) };
+    // SAFETY: `stream` was validated as non-NULL above and was originally
+    // created by `Box::into_raw` in `markdown_stream_begin`.
+    unsafe { drop(Box::from_raw(stream)) };
 }
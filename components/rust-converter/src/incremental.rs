@@ -0,0 +1,185 @@
+//! Incremental HTML-to-Markdown conversion for chunked NGINX response bodies
+//!
+//! NGINX delivers a response body as a chain of buffers through its filter
+//! modules rather than as one contiguous allocation. [`parser::parse_html_with_options`]
+//! and [`MarkdownConverter`], like the rest of this crate, only operate on a
+//! complete, in-memory HTML document, so a caller that wants to avoid
+//! buffering the whole body itself needs a wrapper that accepts bytes as they
+//! arrive and returns Markdown as it becomes available. [`IncrementalConverter`]
+//! is that wrapper.
+//!
+//! # Approach
+//!
+//! This is a buffering approximation of incremental conversion, not a
+//! byte-level streaming tokenizer. Every [`IncrementalConverter::push`] call
+//! re-parses and re-converts the accumulated input from the start, then
+//! returns only the Markdown suffix that was not already returned by a
+//! previous call. [`crate::token_converter`] is the dedicated follow-up this
+//! once pointed at: it drives html5ever's lower-level `Tokenizer`/`TokenSink`
+//! traits directly and emits Markdown as tokens arrive, without ever
+//! re-walking earlier output or building a DOM — at the cost of handling a
+//! narrower set of elements (see its module documentation). For the response
+//! sizes NGINX filter modules typically handle, this module's simpler
+//! re-parsing approach is usually good enough; reach for
+//! [`crate::token_converter`] when peak memory on very large documents
+//! matters more than that breadth of coverage.
+//!
+//! Pushed bytes are only parsed up to the last complete `>` byte found so
+//! far; anything after it may be a tag or entity reference split across a
+//! chunk boundary, so it is held in the internal buffer until a later push
+//! completes it. [`IncrementalConverter::finish`] parses whatever remains,
+//! including any still-open elements, since html5ever tolerates a truncated
+//! document as end-of-input.
+
+use std::time::Duration;
+
+use crate::converter::{ConversionContext, ConversionOptions, MarkdownConverter};
+use crate::error::ConversionError;
+use crate::parser::parse_html_with_options;
+
+/// Converts HTML pushed in chunks to Markdown, emitting output incrementally
+///
+/// See the [module documentation](self) for how incremental output is
+/// derived from the crate's whole-document parser and converter.
+pub struct IncrementalConverter {
+    options: ConversionOptions,
+    content_type: Option<String>,
+    input_charset: Option<String>,
+    timeout: Duration,
+    max_input_bytes: u64,
+    max_output_bytes: u64,
+    buffer: Vec<u8>,
+    markdown_so_far: String,
+    had_lossy_decode: bool,
+}
+
+impl IncrementalConverter {
+    /// Create a new incremental converter
+    ///
+    /// `content_type` and `input_charset` mirror the same-named
+    /// `MarkdownOptions` fields and are resolved once, at construction, since
+    /// a response's charset does not change mid-stream. `max_input_bytes` and
+    /// `max_output_bytes` mirror the same-named `MarkdownOptions` fields (`0`
+    /// means unlimited) and are checked against the accumulated buffer and
+    /// rendered output on every `push`/`finish` call.
+    pub fn new(
+        options: ConversionOptions,
+        content_type: Option<String>,
+        input_charset: Option<String>,
+        timeout: Duration,
+        max_input_bytes: u64,
+        max_output_bytes: u64,
+    ) -> Self {
+        Self {
+            options,
+            content_type,
+            input_charset,
+            timeout,
+            max_input_bytes,
+            max_output_bytes,
+            buffer: Vec::new(),
+            markdown_so_far: String::new(),
+            had_lossy_decode: false,
+        }
+    }
+
+    /// Feed a chunk of HTML, returning the Markdown that has newly become
+    /// available for elements closed by the bytes seen so far
+    ///
+    /// Returns an empty string when nothing new has closed yet, e.g. when a
+    /// chunk boundary splits a tag or entity reference.
+    ///
+    /// Returns `Err(ConversionError::InputTooLarge)` if accumulating `chunk`
+    /// would push the buffered input past `max_input_bytes`, checked before
+    /// the chunk is appended so the buffer never grows past the cap.
+    pub fn push(&mut self, chunk: &[u8]) -> Result<String, ConversionError> {
+        if self.max_input_bytes > 0
+            && (self.buffer.len() + chunk.len()) as u64 > self.max_input_bytes
+        {
+            return Err(ConversionError::InputTooLarge);
+        }
+        self.buffer.extend_from_slice(chunk);
+        let Some(boundary) = self.last_safe_boundary() else {
+            return Ok(String::new());
+        };
+        self.convert_prefix(boundary)
+    }
+
+    /// Flush the remainder of the buffer, including any still-open elements
+    ///
+    /// Call this exactly once, after the final chunk has been pushed. Only
+    /// the final, unreturned Markdown suffix is returned here — use
+    /// [`IncrementalConverter::full_markdown`] afterwards for ETag/token
+    /// estimation, which must see the complete output rather than just this
+    /// last fragment.
+    pub fn finish(&mut self) -> Result<String, ConversionError> {
+        let remaining = self.buffer.len();
+        self.convert_prefix(remaining)
+    }
+
+    /// The complete Markdown produced from all bytes converted so far
+    ///
+    /// After [`IncrementalConverter::finish`] has been called, this is the
+    /// full document output — the same string a single whole-document
+    /// `markdown_convert()` call would have produced.
+    pub fn full_markdown(&self) -> &str {
+        &self.markdown_so_far
+    }
+
+    /// The options this stream was created with, e.g. for building a
+    /// throwaway [`MarkdownConverter`] to post-process the complete output
+    /// once [`IncrementalConverter::finish`] has been called.
+    pub fn options(&self) -> &ConversionOptions {
+        &self.options
+    }
+
+    /// Whether any byte sequence seen so far was not valid under the
+    /// detected/explicit charset and was replaced with U+FFFD
+    ///
+    /// Reflects the most recent [`IncrementalConverter::push`]/
+    /// [`IncrementalConverter::finish`] call, which always re-decodes the
+    /// buffer from the start, so this covers every byte seen so far, not
+    /// just the latest chunk.
+    pub fn had_lossy_decode(&self) -> bool {
+        self.had_lossy_decode
+    }
+
+    /// Byte offset one past the last complete tag boundary (`>`) seen in the
+    /// buffer so far. Bytes after it may be a partial tag or entity
+    /// reference split across a chunk boundary and are held back until a
+    /// later push or `finish` completes them.
+    fn last_safe_boundary(&self) -> Option<usize> {
+        self.buffer
+            .iter()
+            .rposition(|&b| b == b'>')
+            .map(|idx| idx + 1)
+    }
+
+    fn convert_prefix(&mut self, len: usize) -> Result<String, ConversionError> {
+        if len == 0 {
+            return Ok(String::new());
+        }
+
+        let (dom, had_lossy_decode) = parse_html_with_options(
+            &self.buffer[..len],
+            self.content_type.as_deref(),
+            self.input_charset.as_deref(),
+        )?;
+        self.had_lossy_decode = had_lossy_decode;
+
+        let mut ctx =
+            ConversionContext::new(self.timeout).with_max_output_bytes(self.max_output_bytes);
+        ctx.check_timeout()?;
+
+        let converter = MarkdownConverter::with_options(self.options.clone());
+        let markdown = converter.convert_with_context(&dom, &mut ctx)?;
+
+        let new_tail = markdown
+            .get(self.markdown_so_far.len()..)
+            .map(ToOwned::to_owned)
+            .unwrap_or_default();
+        self.markdown_so_far = markdown;
+
+        Ok(new_tail)
+    }
+}
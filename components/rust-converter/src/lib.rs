@@ -9,11 +9,21 @@
 //! - `ffi`: C-compatible FFI interface for NGINX integration
 //! - `parser`: HTML5 parsing using html5ever
 //! - `converter`: Markdown generation from DOM tree
+//! - `emoji`: Unicode emoji <-> `:shortcode:` normalization
 //! - `charset`: Character encoding detection and handling
 //! - `metadata`: Page metadata extraction
+//! - `readability`: Mozilla/arc90-style main-content extraction
 //! - `token_estimator`: Token count estimation for LLMs
 //! - `etag_generator`: ETag generation using BLAKE3
+//! - `incremental`: Chunked/streaming HTML input support for NGINX body filters
+//! - `token_converter`: DOM-free HTML-to-Markdown conversion via html5ever's
+//!   `TokenSink`, for O(nesting depth) memory on very large documents
 //! - `security`: Input validation and sanitization
+//! - `compression`: Optional gzip/Brotli/Zstandard output compression
+//! - `cache`: Optional in-process LRU cache of repeated conversions
+//! - `testsuite`: Corpus-driven golden-file and round-trip regression testing
+//! - `statistical_charset` (`stat_charset_detect` feature): Best-effort
+//!   charset guessing when no charset information is available at all
 //!
 //! # Safety
 //!
@@ -22,14 +32,26 @@
 //! provided cleanup functions.
 
 // Module declarations
+pub mod cache;
 pub mod charset;
+pub mod compression;
 pub mod converter;
+pub mod decompression;
+pub mod emoji;
 pub mod error;
 pub mod etag_generator;
 pub mod ffi;
+pub mod incremental;
 pub mod metadata;
 pub mod parser;
+pub mod readability;
 pub mod security;
+#[cfg(feature = "stat_charset_detect")]
+pub mod statistical_charset;
+pub mod svg;
+pub mod testsuite;
+pub mod text_cleaner;
+pub mod token_converter;
 pub mod token_estimator;
 
 // Re-export main types for convenience
@@ -5,8 +5,10 @@
 //!
 //! - Title extraction from `<title>` tag and Open Graph tags
 //! - Description extraction from meta tags
-//! - URL, image, author, and published date extraction
+//! - URL, image, video, favicon, theme color, author, and published date extraction
 //! - URL resolution for relative links and images
+//! - Configurable extraction of additional `<meta>`/`<link>` tags into a
+//!   front matter key via [`MetadataRule`]
 //!
 //! # URL Resolution Strategy
 //!
@@ -36,6 +38,8 @@
 use crate::error::ConversionError;
 use markup5ever_rcdom::{Handle, NodeData, RcDom};
 use std::cell::Ref;
+use std::collections::HashMap;
+use std::rc::Rc;
 
 /// Page metadata extracted from HTML
 #[derive(Debug, Default, Clone, PartialEq)]
@@ -48,10 +52,40 @@ pub struct PageMetadata {
     pub url: Option<String>,
     /// Page image
     pub image: Option<String>,
+    /// Page video (from `og:video`/`og:video:url`)
+    pub video: Option<String>,
+    /// Favicon (from `<link rel="icon">`/`shortcut icon`, falling back to
+    /// `/favicon.ico` if neither is present)
+    pub favicon: Option<String>,
+    /// Browser UI theme color (from `<meta name="theme-color">`)
+    pub theme_color: Option<String>,
     /// Page author
     pub author: Option<String>,
     /// Publication date
     pub published: Option<String>,
+    /// Last-modified date (from `article:modified_time`)
+    pub modified: Option<String>,
+    /// Site name (from `og:site_name`)
+    pub site_name: Option<String>,
+    /// Open Graph object type (from `og:type`, e.g. `article`, `website`)
+    pub og_type: Option<String>,
+    /// Content locale (from `og:locale`)
+    pub locale: Option<String>,
+    /// Article section or category (from `article:section`)
+    pub section: Option<String>,
+    /// Tags, in document order (from repeated `article:tag` meta tags, or
+    /// comma-separated `keywords` when `article:tag` is absent)
+    pub tags: Vec<String>,
+    /// oEmbed response `type` (`photo`/`video`/`link`/`rich`). Only present
+    /// when [`MetadataExtractor::with_oembed_fetcher`] was configured and a
+    /// discovered endpoint resolved successfully.
+    pub oembed_type: Option<String>,
+    /// oEmbed response `html`, an embeddable snippet for the linked
+    /// resource. Only present under the same conditions as `oembed_type`.
+    pub oembed_html: Option<String>,
+    /// Additional fields captured via [`MetadataRule`]s, in rule-match order.
+    /// Empty unless [`MetadataExtractor::with_metadata_fields`] was used.
+    pub extra: Vec<(String, MetadataValue)>,
 }
 
 impl PageMetadata {
@@ -61,6 +95,70 @@ impl PageMetadata {
     }
 }
 
+/// A value captured via a configurable [`MetadataRule`]
+///
+/// Most rules capture a single value, but tags that legitimately repeat
+/// (e.g. `article:tag`) collect every match into a `List` in document order
+/// instead of silently keeping only the first or last one seen.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetadataValue {
+    /// A single captured value.
+    Single(String),
+    /// Multiple captured values, in document order.
+    List(Vec<String>),
+}
+
+/// A rule mapping an HTML metadata source to a front-matter key
+///
+/// Lets callers capture additional `<meta name="...">`/`<meta property="...">`
+/// tags (e.g. `keywords`, `og:type`, `og:site_name`, `article:tag`,
+/// `twitter:*`) or `<link rel="...">` hrefs into front matter without code
+/// changes, via [`MetadataExtractor::with_metadata_fields`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetadataRule {
+    /// The `name`/`property` value of a `<meta>` tag, or the `rel` value of
+    /// a `<link>` tag when `from_link` is `true`.
+    pub source: String,
+    /// Whether `source` names a `<link rel>` instead of a `<meta>` tag.
+    pub from_link: bool,
+    /// Front matter key the matched value(s) are written under.
+    pub target: String,
+}
+
+impl MetadataRule {
+    /// Create a rule matching a `<meta name="...">` or `<meta property="...">` tag
+    pub fn meta(source: impl Into<String>, target: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            from_link: false,
+            target: target.into(),
+        }
+    }
+
+    /// Create a rule matching a `<link rel="...">` tag's `href`
+    pub fn link(source: impl Into<String>, target: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            from_link: true,
+            target: target.into(),
+        }
+    }
+}
+
+/// Fetches the JSON payload of a discovered oEmbed endpoint
+/// ([oembed.com](https://oembed.com))
+///
+/// Implemented by the caller, not this crate: a converter embedded in NGINX
+/// already has an HTTP client, timeout budget, and SSRF-safe egress policy,
+/// and duplicating that here would mean maintaining a second one out of
+/// sync with the first. Pass an implementation to
+/// [`MetadataExtractor::with_oembed_fetcher`] to enable discovery.
+pub trait OembedFetcher {
+    /// Fetch `url` (an oEmbed endpoint resolved from a discovered
+    /// `<link>` tag) and return its raw JSON response body
+    fn fetch(&self, url: &str) -> Result<String, ConversionError>;
+}
+
 /// Metadata extractor with URL resolution
 ///
 /// The `MetadataExtractor` extracts metadata from HTML DOM trees and resolves
@@ -76,15 +174,31 @@ impl PageMetadata {
 /// # Metadata Sources
 ///
 /// The extractor checks multiple sources with the following priority:
-/// - Title: Open Graph og:title > Twitter twitter:title > <title> tag
-/// - Description: Open Graph og:description > meta description
-/// - Image: Open Graph og:image > Twitter twitter:image
+/// - Title: Open Graph og:title > Twitter twitter:title > <title> tag > JSON-LD headline > oEmbed title
+/// - Description: Open Graph og:description > meta description > JSON-LD description
+/// - Image: Open Graph og:image > Twitter twitter:image > JSON-LD image > oEmbed thumbnail_url
+/// - Video: Open Graph og:video > og:video:url
+/// - Favicon: link rel="icon" > rel="shortcut icon" > /favicon.ico
+/// - Theme color: meta theme-color tag
 /// - URL: Canonical link > Open Graph og:url > base_url
-/// - Author: meta author tag
-/// - Published: article:published_time meta tag
+/// - Author: meta author tag > JSON-LD author.name > oEmbed author_name
+/// - Published: article:published_time meta tag > JSON-LD datePublished
+///
+/// JSON-LD (`<script type="application/ld+json">`) is parsed after meta tags
+/// and only fills fields meta tags left empty, so it never overrides an
+/// Open Graph title but reliably supplies author/published, which many sites
+/// only emit via structured data. See [`Self::extract_json_ld`].
+///
+/// oEmbed discovery (`<link rel="alternate" type="application/json+oembed">`)
+/// runs last and is opt-in via [`Self::with_oembed_fetcher`]; it fills title,
+/// author, and image the same way JSON-LD does, and always sets the new
+/// `oembed_type`/`oembed_html` fields when an endpoint resolves. See
+/// [`Self::extract_oembed`].
 pub struct MetadataExtractor {
     base_url: Option<String>,
     resolve_urls: bool,
+    metadata_fields: Vec<MetadataRule>,
+    oembed_fetcher: Option<Rc<dyn OembedFetcher>>,
 }
 
 impl MetadataExtractor {
@@ -98,9 +212,33 @@ impl MetadataExtractor {
         Self {
             base_url,
             resolve_urls,
+            metadata_fields: Vec::new(),
+            oembed_fetcher: None,
         }
     }
 
+    /// Configure additional `<meta>`/`<link>` tags to capture into
+    /// [`PageMetadata::extra`]
+    ///
+    /// Rules are matched in addition to, not instead of, the hardcoded
+    /// title/description/image/author/published extraction.
+    pub fn with_metadata_fields(mut self, metadata_fields: Vec<MetadataRule>) -> Self {
+        self.metadata_fields = metadata_fields;
+        self
+    }
+
+    /// Opt in to oEmbed discovery by supplying a fetcher for discovered
+    /// endpoint URLs
+    ///
+    /// `MetadataExtractor` has no built-in HTTP client — an NGINX
+    /// integration already has one, with its own timeout and SSRF policy —
+    /// so the network fetch only happens when a caller provides one here.
+    /// Left unset, `extract` skips oEmbed discovery entirely.
+    pub fn with_oembed_fetcher(mut self, fetcher: Rc<dyn OembedFetcher>) -> Self {
+        self.oembed_fetcher = Some(fetcher);
+        self
+    }
+
     /// Extract metadata from DOM tree
     ///
     /// # Arguments
@@ -125,15 +263,33 @@ impl MetadataExtractor {
     pub fn extract(&self, dom: &RcDom) -> Result<PageMetadata, ConversionError> {
         let mut metadata = PageMetadata::new();
 
+        // A `<base href>` in the document overrides base_url as the root for
+        // resolving every other relative URL extracted below (but not the
+        // base_url fallback for metadata.url itself, which represents the
+        // document's own URL rather than its resolution root).
+        let effective_base = self.effective_base_url(dom);
+
         // Extract title from <title> tag first (fallback)
         metadata.title = self.find_title(dom);
 
         // Extract from meta tags (will override title if og:title found)
-        self.extract_meta_tags(dom, &mut metadata)?;
+        self.extract_meta_tags(dom, &mut metadata, effective_base.as_deref())?;
+
+        // Fill any field meta tags left empty from JSON-LD structured data
+        self.extract_json_ld(dom, &mut metadata, effective_base.as_deref())?;
+
+        // Discover and fetch oEmbed endpoints, if a fetcher was configured
+        self.extract_oembed(dom, &mut metadata, effective_base.as_deref());
+
+        // No <link rel="icon">/"shortcut icon"> found: fall back to the
+        // well-known /favicon.ico path rather than leaving favicon empty
+        if metadata.favicon.is_none() {
+            metadata.favicon = Some(self.resolve_against("/favicon.ico", effective_base.as_deref()));
+        }
 
         // Extract canonical URL
         if let Some(canonical) = self.find_canonical(dom) {
-            metadata.url = Some(self.resolve_url(&canonical));
+            metadata.url = Some(self.resolve_against(&canonical, effective_base.as_deref()));
         } else {
             // Use base_url as fallback
             metadata.url = self.base_url.clone();
@@ -142,6 +298,82 @@ impl MetadataExtractor {
         Ok(metadata)
     }
 
+    /// Resolve the effective base URL for this document
+    ///
+    /// Browsers resolve relative URLs against the first in-document
+    /// `<base href>` when present, falling back to the document URL
+    /// otherwise. This mirrors that: if a `<base href>` exists, its value
+    /// (itself resolved against `base_url`, since it may be relative) becomes
+    /// the resolution root; otherwise `base_url` is used unchanged.
+    pub fn effective_base_url(&self, dom: &RcDom) -> Option<String> {
+        match self.find_base_href(dom) {
+            Some(href) => Some(self.resolve_against(&href, self.base_url.as_deref())),
+            None => self.base_url.clone(),
+        }
+    }
+
+    /// Render a `<!-- Saved from ... -->` provenance comment recording the
+    /// source URL and retrieval time
+    ///
+    /// The URL is sanitized first, the way monolith does it: the fragment is
+    /// dropped, and for `http(s)` URLs any `user:password@` userinfo is
+    /// cleared so credentials embedded in `base_url` never leak into the
+    /// saved artifact. A non-HTTP `base_url` (or none at all) emits a
+    /// "local source" variant with no URL, since there's nothing meaningful
+    /// — and potentially sensitive — to print instead.
+    ///
+    /// `retrieved_at` is a parameter rather than read from the clock here so
+    /// callers (and tests) control the timestamp.
+    pub fn provenance_comment(&self, retrieved_at: chrono::DateTime<chrono::Utc>) -> String {
+        let timestamp = retrieved_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        let version = env!("CARGO_PKG_VERSION");
+
+        match self
+            .base_url
+            .as_deref()
+            .and_then(Self::sanitize_provenance_url)
+        {
+            Some(url) => format!(
+                "<!-- Saved from {url} at {timestamp} using nginx-markdown-for-agents v{version} -->"
+            ),
+            None => format!(
+                "<!-- Saved from a local source at {timestamp} using nginx-markdown-for-agents v{version} -->"
+            ),
+        }
+    }
+
+    /// Sanitize a URL for provenance output: drop the fragment, and for
+    /// `http(s)` URLs clear `user:password@` userinfo from the authority
+    ///
+    /// Returns `None` for any non-`http(s)` URL, which [`Self::provenance_comment`]
+    /// treats as a local source with nothing safe or meaningful to print.
+    fn sanitize_provenance_url(url: &str) -> Option<String> {
+        let scheme_len = if url.starts_with("https://") {
+            8
+        } else if url.starts_with("http://") {
+            7
+        } else {
+            return None;
+        };
+        let scheme = &url[..scheme_len];
+        let rest = &url[scheme_len..];
+
+        let authority_end = rest.find('/').unwrap_or(rest.len());
+        let (authority, path_rest) = rest.split_at(authority_end);
+
+        let host_and_port = match authority.rfind('@') {
+            Some(pos) => &authority[pos + 1..],
+            None => authority,
+        };
+
+        let path_and_query = match path_rest.find('#') {
+            Some(pos) => &path_rest[..pos],
+            None => path_rest,
+        };
+
+        Some(format!("{scheme}{host_and_port}{path_and_query}"))
+    }
+
     /// Find title from <title> tag
     fn find_title(&self, dom: &RcDom) -> Option<String> {
         self.find_element_text(dom, "title")
@@ -157,8 +389,9 @@ impl MetadataExtractor {
         &self,
         dom: &RcDom,
         metadata: &mut PageMetadata,
+        base: Option<&str>,
     ) -> Result<(), ConversionError> {
-        self.traverse_for_meta(&dom.document, metadata)?;
+        self.traverse_for_meta(&dom.document, metadata, base)?;
         Ok(())
     }
 
@@ -167,6 +400,7 @@ impl MetadataExtractor {
         &self,
         node: &Handle,
         metadata: &mut PageMetadata,
+        base: Option<&str>,
     ) -> Result<(), ConversionError> {
         match node.data {
             NodeData::Element {
@@ -175,18 +409,20 @@ impl MetadataExtractor {
                 ..
             } => {
                 if name.local.as_ref() == "meta" {
-                    self.process_meta_tag(&attrs.borrow(), metadata)?;
+                    self.process_meta_tag(&attrs.borrow(), metadata, base)?;
+                } else if name.local.as_ref() == "link" {
+                    self.process_link_tag(&attrs.borrow(), metadata, base);
                 }
 
                 // Recurse into children
                 for child in node.children.borrow().iter() {
-                    self.traverse_for_meta(child, metadata)?;
+                    self.traverse_for_meta(child, metadata, base)?;
                 }
             }
             NodeData::Document => {
                 // Recurse into children
                 for child in node.children.borrow().iter() {
-                    self.traverse_for_meta(child, metadata)?;
+                    self.traverse_for_meta(child, metadata, base)?;
                 }
             }
             _ => {}
@@ -200,6 +436,7 @@ impl MetadataExtractor {
         &self,
         attrs: &Ref<Vec<html5ever::Attribute>>,
         metadata: &mut PageMetadata,
+        base: Option<&str>,
     ) -> Result<(), ConversionError> {
         let property = self.get_attr(attrs, "property");
         let name = self.get_attr(attrs, "name");
@@ -210,8 +447,17 @@ impl MetadataExtractor {
         }
 
         let content = content.unwrap();
+        let is_property = property.is_some();
         let key = property.or(name);
 
+        if let Some(key_str) = key.as_deref() {
+            for rule in &self.metadata_fields {
+                if !rule.from_link && rule.source == key_str {
+                    Self::record_extra(metadata, &rule.target, content.clone());
+                }
+            }
+        }
+
         match key.as_deref() {
             // Title (Open Graph and Twitter have priority over <title>)
             Some("og:title") | Some("twitter:title") => {
@@ -226,10 +472,23 @@ impl MetadataExtractor {
             // Image (resolve relative URLs)
             Some("og:image") | Some("twitter:image") => {
                 if metadata.image.is_none() {
-                    let resolved = self.resolve_url(&content);
+                    let resolved = self.resolve_against(&content, base);
                     metadata.image = Some(resolved);
                 }
             }
+            // Video (resolve relative URLs)
+            Some("og:video") | Some("og:video:url") => {
+                if metadata.video.is_none() {
+                    let resolved = self.resolve_against(&content, base);
+                    metadata.video = Some(resolved);
+                }
+            }
+            // Browser UI theme color
+            Some("theme-color") => {
+                if metadata.theme_color.is_none() {
+                    metadata.theme_color = Some(content);
+                }
+            }
             // URL
             Some("og:url") => {
                 if metadata.url.is_none() {
@@ -242,18 +501,398 @@ impl MetadataExtractor {
                     metadata.author = Some(content);
                 }
             }
-            // Published date
-            Some("article:published_time") => {
-                if metadata.published.is_none() {
+            // Published date (Open Graph article time takes priority over
+            // the plain `<meta name="date">` some CMSes emit instead)
+            Some("article:published_time") | Some("date") => {
+                if metadata.published.is_none() || is_property {
                     metadata.published = Some(content);
                 }
             }
+            // Last-modified date
+            Some("article:modified_time") => {
+                if metadata.modified.is_none() {
+                    metadata.modified = Some(content);
+                }
+            }
+            // Site name
+            Some("og:site_name") => {
+                if metadata.site_name.is_none() {
+                    metadata.site_name = Some(content);
+                }
+            }
+            // Open Graph object type
+            Some("og:type") => {
+                if metadata.og_type.is_none() {
+                    metadata.og_type = Some(content);
+                }
+            }
+            // Content locale
+            Some("og:locale") => {
+                if metadata.locale.is_none() {
+                    metadata.locale = Some(content);
+                }
+            }
+            // Article section/category
+            Some("article:section") => {
+                if metadata.section.is_none() {
+                    metadata.section = Some(content);
+                }
+            }
+            // Tags (repeated article:tag meta tags collect in document order)
+            Some("article:tag") => {
+                metadata.tags.push(content);
+            }
+            // Keywords (comma-separated fallback when article:tag is absent)
+            Some("keywords") => {
+                if metadata.tags.is_empty() {
+                    metadata.tags = content
+                        .split(',')
+                        .map(|tag| tag.trim().to_string())
+                        .filter(|tag| !tag.is_empty())
+                        .collect();
+                }
+            }
             _ => {}
         }
 
         Ok(())
     }
 
+    /// Process a single link tag against any configured `from_link` rules
+    /// and the built-in favicon rels
+    fn process_link_tag(
+        &self,
+        attrs: &Ref<Vec<html5ever::Attribute>>,
+        metadata: &mut PageMetadata,
+        base: Option<&str>,
+    ) {
+        let Some(rel) = self.get_attr(attrs, "rel") else {
+            return;
+        };
+        let Some(href) = self.get_attr(attrs, "href") else {
+            return;
+        };
+
+        if metadata.favicon.is_none() && (rel == "icon" || rel == "shortcut icon") {
+            metadata.favicon = Some(self.resolve_against(&href, base));
+        }
+
+        for rule in &self.metadata_fields {
+            if rule.from_link && rule.source == rel {
+                let resolved = self.resolve_against(&href, base);
+                Self::record_extra(metadata, &rule.target, resolved);
+            }
+        }
+    }
+
+    /// Record a rule match into [`PageMetadata::extra`]
+    ///
+    /// A target key seen once is stored as [`MetadataValue::Single`]; a
+    /// second match for the same target promotes it to a
+    /// [`MetadataValue::List`] so repeated tags like `article:tag` collect
+    /// into a sequence instead of overwriting each other.
+    fn record_extra(metadata: &mut PageMetadata, target: &str, value: String) {
+        if let Some((_, existing)) = metadata.extra.iter_mut().find(|(key, _)| key == target) {
+            match existing {
+                MetadataValue::Single(first) => {
+                    *existing = MetadataValue::List(vec![first.clone(), value]);
+                }
+                MetadataValue::List(values) => values.push(value),
+            }
+        } else {
+            metadata
+                .extra
+                .push((target.to_string(), MetadataValue::Single(value)));
+        }
+    }
+
+    /// `@type` values this module recognizes as article-like JSON-LD objects
+    const JSON_LD_ARTICLE_TYPES: &[&str] = &["Article", "NewsArticle", "BlogPosting", "WebPage"];
+
+    /// Extract metadata from `<script type="application/ld+json">` schema.org
+    /// blocks, filling any field meta-tag extraction left empty
+    ///
+    /// See the module-level "Metadata Sources" precedence table; a block
+    /// with malformed JSON, or none of the recognized `@type`s, is skipped
+    /// without affecting the rest of extraction.
+    fn extract_json_ld(
+        &self,
+        dom: &RcDom,
+        metadata: &mut PageMetadata,
+        base: Option<&str>,
+    ) -> Result<(), ConversionError> {
+        self.traverse_for_json_ld(&dom.document, metadata, base);
+        Ok(())
+    }
+
+    /// Traverse the DOM tree looking for JSON-LD `<script>` blocks
+    fn traverse_for_json_ld(&self, node: &Handle, metadata: &mut PageMetadata, base: Option<&str>) {
+        match node.data {
+            NodeData::Element {
+                ref name,
+                ref attrs,
+                ..
+            } => {
+                if name.local.as_ref() == "script"
+                    && self.get_attr(&attrs.borrow(), "type").as_deref()
+                        == Some("application/ld+json")
+                {
+                    let mut text = String::new();
+                    self.extract_text_content(node, &mut text);
+                    self.apply_json_ld(&text, metadata, base);
+                }
+
+                for child in node.children.borrow().iter() {
+                    self.traverse_for_json_ld(child, metadata, base);
+                }
+            }
+            NodeData::Document => {
+                for child in node.children.borrow().iter() {
+                    self.traverse_for_json_ld(child, metadata, base);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Parse a single JSON-LD block's text and apply every recognized
+    /// candidate object within it
+    ///
+    /// Malformed JSON is silently skipped, per this module's contract that
+    /// one bad block must not fail extraction of the rest of the document.
+    fn apply_json_ld(&self, json_text: &str, metadata: &mut PageMetadata, base: Option<&str>) {
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(json_text) else {
+            return;
+        };
+
+        for object in Self::json_ld_objects(&value) {
+            self.apply_json_ld_object(object, metadata, base);
+        }
+    }
+
+    /// Flatten a parsed JSON-LD document into the candidate objects worth
+    /// inspecting: the document itself, each entry of a top-level `@graph`
+    /// array, or each entry of a bare top-level array of JSON-LD objects
+    fn json_ld_objects(value: &serde_json::Value) -> Vec<&serde_json::Value> {
+        match value {
+            serde_json::Value::Array(items) => items.iter().collect(),
+            serde_json::Value::Object(map) => match map.get("@graph") {
+                Some(serde_json::Value::Array(items)) => items.iter().collect(),
+                _ => vec![value],
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    /// Fill any still-empty `title`/`description`/`author`/`published`/
+    /// `modified`/`image` field from one JSON-LD object, if its `@type` is
+    /// article-like
+    fn apply_json_ld_object(
+        &self,
+        object: &serde_json::Value,
+        metadata: &mut PageMetadata,
+        base: Option<&str>,
+    ) {
+        if !Self::json_ld_type_matches(object) {
+            return;
+        }
+
+        if metadata.title.is_none()
+            && let Some(headline) = object.get("headline").and_then(serde_json::Value::as_str)
+        {
+            metadata.title = Some(headline.to_string());
+        }
+
+        if metadata.description.is_none()
+            && let Some(description) = object.get("description").and_then(serde_json::Value::as_str)
+        {
+            metadata.description = Some(description.to_string());
+        }
+
+        if metadata.author.is_none()
+            && let Some(author) = Self::json_ld_author_name(object.get("author"))
+        {
+            metadata.author = Some(author);
+        }
+
+        if metadata.published.is_none()
+            && let Some(published) = object.get("datePublished").and_then(serde_json::Value::as_str)
+        {
+            metadata.published = Some(published.to_string());
+        }
+
+        if metadata.modified.is_none()
+            && let Some(modified) = object.get("dateModified").and_then(serde_json::Value::as_str)
+        {
+            metadata.modified = Some(modified.to_string());
+        }
+
+        if metadata.image.is_none()
+            && let Some(image) = Self::json_ld_image_url(object.get("image"))
+        {
+            metadata.image = Some(self.resolve_against(&image, base));
+        }
+    }
+
+    /// Whether a JSON-LD object's `@type` (a single string or an array of
+    /// strings) includes one of [`Self::JSON_LD_ARTICLE_TYPES`]
+    fn json_ld_type_matches(object: &serde_json::Value) -> bool {
+        match object.get("@type") {
+            Some(serde_json::Value::String(t)) => {
+                Self::JSON_LD_ARTICLE_TYPES.contains(&t.as_str())
+            }
+            Some(serde_json::Value::Array(items)) => items.iter().any(|item| {
+                item.as_str()
+                    .is_some_and(|t| Self::JSON_LD_ARTICLE_TYPES.contains(&t))
+            }),
+            _ => false,
+        }
+    }
+
+    /// Resolve JSON-LD's `author`, which schema.org allows as a plain name
+    /// string, a `Person`/`Organization` object's `name`, or an array of
+    /// either (the first with a name wins)
+    fn json_ld_author_name(author: Option<&serde_json::Value>) -> Option<String> {
+        match author? {
+            serde_json::Value::String(name) => Some(name.clone()),
+            serde_json::Value::Object(map) => {
+                map.get("name").and_then(serde_json::Value::as_str).map(str::to_string)
+            }
+            serde_json::Value::Array(items) => {
+                items.iter().find_map(|item| Self::json_ld_author_name(Some(item)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Resolve JSON-LD's `image`, which schema.org allows as a plain URL
+    /// string, an `ImageObject`'s `url`, or an array of either (the first
+    /// with a URL wins)
+    fn json_ld_image_url(image: Option<&serde_json::Value>) -> Option<String> {
+        match image? {
+            serde_json::Value::String(url) => Some(url.clone()),
+            serde_json::Value::Object(map) => {
+                map.get("url").and_then(serde_json::Value::as_str).map(str::to_string)
+            }
+            serde_json::Value::Array(items) => {
+                items.iter().find_map(|item| Self::json_ld_image_url(Some(item)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Discover `<link rel="alternate" type="application/json+oembed">`
+    /// endpoints and merge their response into `metadata`
+    ///
+    /// No-op unless [`Self::with_oembed_fetcher`] was configured. The XML
+    /// variant (`text/xml+oembed`) is discoverable by the same sites but
+    /// isn't fetched here, since this module only parses JSON.
+    fn extract_oembed(&self, dom: &RcDom, metadata: &mut PageMetadata, base: Option<&str>) {
+        if self.oembed_fetcher.is_none() {
+            return;
+        }
+        let mut fetched: HashMap<String, String> = HashMap::new();
+        self.traverse_for_oembed(&dom.document, metadata, base, &mut fetched);
+    }
+
+    /// Traverse the DOM tree looking for oEmbed discovery `<link>` tags
+    fn traverse_for_oembed(
+        &self,
+        node: &Handle,
+        metadata: &mut PageMetadata,
+        base: Option<&str>,
+        fetched: &mut HashMap<String, String>,
+    ) {
+        match node.data {
+            NodeData::Element {
+                ref name,
+                ref attrs,
+                ..
+            } => {
+                if name.local.as_ref() == "link" {
+                    let attrs = attrs.borrow();
+                    let is_oembed_link =
+                        self.get_attr(&attrs, "type").as_deref() == Some("application/json+oembed");
+                    if is_oembed_link
+                        && let Some(href) = self.get_attr(&attrs, "href")
+                    {
+                        let endpoint = self.resolve_against(&href, base);
+                        self.apply_oembed_endpoint(&endpoint, metadata, base, fetched);
+                    }
+                }
+
+                for child in node.children.borrow().iter() {
+                    self.traverse_for_oembed(child, metadata, base, fetched);
+                }
+            }
+            NodeData::Document => {
+                for child in node.children.borrow().iter() {
+                    self.traverse_for_oembed(child, metadata, base, fetched);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Fetch (or reuse an already-fetched) oEmbed endpoint's JSON and apply
+    /// its fields to `metadata`
+    ///
+    /// Caches by resolved endpoint URL in `fetched` so a document with
+    /// multiple discovery links pointing at the same endpoint only triggers
+    /// one request. A fetch failure or malformed JSON response is skipped
+    /// without affecting the rest of extraction.
+    fn apply_oembed_endpoint(
+        &self,
+        endpoint: &str,
+        metadata: &mut PageMetadata,
+        base: Option<&str>,
+        fetched: &mut HashMap<String, String>,
+    ) {
+        let Some(fetcher) = &self.oembed_fetcher else {
+            return;
+        };
+
+        let json_text = if let Some(cached) = fetched.get(endpoint) {
+            cached.clone()
+        } else {
+            let Ok(text) = fetcher.fetch(endpoint) else {
+                return;
+            };
+            fetched.insert(endpoint.to_string(), text.clone());
+            text
+        };
+
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(&json_text) else {
+            return;
+        };
+
+        if metadata.title.is_none()
+            && let Some(title) = value.get("title").and_then(serde_json::Value::as_str)
+        {
+            metadata.title = Some(title.to_string());
+        }
+
+        if metadata.author.is_none()
+            && let Some(author) = value.get("author_name").and_then(serde_json::Value::as_str)
+        {
+            metadata.author = Some(author.to_string());
+        }
+
+        if metadata.image.is_none()
+            && let Some(thumbnail) = value.get("thumbnail_url").and_then(serde_json::Value::as_str)
+        {
+            metadata.image = Some(self.resolve_against(thumbnail, base));
+        }
+
+        if let Some(oembed_type) = value.get("type").and_then(serde_json::Value::as_str) {
+            metadata.oembed_type = Some(oembed_type.to_string());
+        }
+
+        if let Some(html) = value.get("html").and_then(serde_json::Value::as_str) {
+            metadata.oembed_html = Some(html.to_string());
+        }
+    }
+
     /// Get attribute value from element
     fn get_attr(&self, attrs: &Ref<Vec<html5ever::Attribute>>, name: &str) -> Option<String> {
         attrs
@@ -343,6 +982,55 @@ impl MetadataExtractor {
         None
     }
 
+    /// Find the `href` of the first `<base href>` element in the document
+    ///
+    /// Per the HTML base URL algorithm, an earlier `<base>` element without an
+    /// `href` attribute does not count; the search continues for the first
+    /// one that has one.
+    fn find_base_href(&self, dom: &RcDom) -> Option<String> {
+        self.find_element_attr(&dom.document, "base", "href")
+    }
+
+    /// Recursively find the named attribute of the first matching element
+    fn find_element_attr(
+        &self,
+        node: &Handle,
+        element_name: &str,
+        attr_name: &str,
+    ) -> Option<String> {
+        match node.data {
+            NodeData::Element {
+                ref name,
+                ref attrs,
+                ..
+            } => {
+                if name.local.as_ref() == element_name
+                    && let Some(value) = self.get_attr(&attrs.borrow(), attr_name)
+                {
+                    return Some(value);
+                }
+
+                // Recurse into children
+                for child in node.children.borrow().iter() {
+                    if let Some(value) = self.find_element_attr(child, element_name, attr_name) {
+                        return Some(value);
+                    }
+                }
+            }
+            NodeData::Document => {
+                // Recurse into children
+                for child in node.children.borrow().iter() {
+                    if let Some(value) = self.find_element_attr(child, element_name, attr_name) {
+                        return Some(value);
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        None
+    }
+
     /// Extract text content from node and its children
     fn extract_text_content(&self, node: &Handle, output: &mut String) {
         match node.data {
@@ -401,8 +1089,23 @@ impl MetadataExtractor {
     ///     extractor.resolve_url("image.jpg"),
     ///     "https://example.com/page/image.jpg"
     /// );
+    ///
+    /// // `../` traversal is collapsed rather than left in the result
+    /// assert_eq!(
+    ///     extractor.resolve_url("../images/logo.png"),
+    ///     "https://example.com/images/logo.png"
+    /// );
     /// ```
     pub fn resolve_url(&self, url: &str) -> String {
+        self.resolve_against(url, self.base_url.as_deref())
+    }
+
+    /// Resolve a URL against an explicit base, honoring the `resolve_urls` flag
+    ///
+    /// Shared by [`Self::resolve_url`] (resolves against `base_url`) and
+    /// [`Self::extract`] (resolves against the effective base, which may come
+    /// from an in-document `<base href>` instead).
+    fn resolve_against(&self, url: &str, base: Option<&str>) -> String {
         // If URL resolution is disabled, return as-is
         if !self.resolve_urls {
             return url.to_string();
@@ -413,7 +1116,7 @@ impl MetadataExtractor {
             return url.to_string();
         }
 
-        // Already absolute URL
+        // Reference carries its own scheme: already absolute
         if url.starts_with("http://") || url.starts_with("https://") {
             return url.to_string();
         }
@@ -424,7 +1127,7 @@ impl MetadataExtractor {
         }
 
         // No base URL available
-        let Some(ref base) = self.base_url else {
+        let Some(base) = base else {
             return url.to_string();
         };
 
@@ -434,15 +1137,7 @@ impl MetadataExtractor {
             return url.to_string();
         }
 
-        // Absolute path (/path)
-        if url.starts_with('/') {
-            return format!("{}{}", self.get_origin(base), url);
-        }
-
-        // Relative path (path or ./path or ../path)
-        // Resolve relative to base_url directory
-        let base_dir = self.get_base_directory(base);
-        format!("{}/{}", base_dir.trim_end_matches('/'), url)
+        Self::transform_reference(base, url)
     }
 
     /// Check if base URL is valid (has scheme and host)
@@ -450,55 +1145,122 @@ impl MetadataExtractor {
         url.starts_with("http://") || url.starts_with("https://")
     }
 
-    /// Extract origin (scheme://host) from URL
+    /// Split an absolute URL into its origin (`scheme://host[:port]`) and
+    /// the remainder (path, optionally followed by `?query`/`#fragment`)
     ///
     /// # Examples
     ///
-    /// - `https://example.com/path` -> `https://example.com`
-    /// - `http://example.com:8080/path` -> `http://example.com:8080`
-    fn get_origin(&self, url: &str) -> String {
-        // Find the third slash (after scheme://)
-        let after_scheme = if let Some(stripped) = url.strip_prefix("https://") {
-            stripped
-        } else if let Some(stripped) = url.strip_prefix("http://") {
-            stripped
+    /// - `https://example.com/path` -> (`https://example.com`, `/path`)
+    /// - `https://example.com` -> (`https://example.com`, ``)
+    fn split_origin(url: &str) -> (&str, &str) {
+        let scheme_len = if url.starts_with("https://") {
+            8
+        } else if url.starts_with("http://") {
+            7
         } else {
-            return url.to_string();
+            return (url, "");
         };
 
-        // Find the first slash after the host
-        if let Some(pos) = after_scheme.find('/') {
-            let scheme_len = if url.starts_with("https://") { 8 } else { 7 };
-            url[..scheme_len + pos].to_string()
-        } else {
-            // No path, return entire URL
-            url.to_string()
+        match url[scheme_len..].find('/') {
+            Some(pos) => url.split_at(scheme_len + pos),
+            None => (url, ""),
         }
     }
 
-    /// Get base directory from URL (for resolving relative paths)
+    /// Split a URI reference (or the path+query+fragment remainder of an
+    /// absolute URL) into its path, query, and fragment components
+    fn split_reference(reference: &str) -> (&str, Option<&str>, Option<&str>) {
+        let (without_fragment, fragment) = match reference.find('#') {
+            Some(i) => (&reference[..i], Some(&reference[i + 1..])),
+            None => (reference, None),
+        };
+        let (path, query) = match without_fragment.find('?') {
+            Some(i) => (&without_fragment[..i], Some(&without_fragment[i + 1..])),
+            None => (without_fragment, None),
+        };
+        (path, query, fragment)
+    }
+
+    /// Merge a reference path with a base path, per RFC 3986 §5.3
     ///
-    /// # Examples
+    /// If the base has no path (or just `/`), the merged result is rooted at
+    /// `/`; otherwise the reference path replaces everything after the
+    /// base's last `/`.
+    fn merge_paths(base_path: &str, reference_path: &str) -> String {
+        match base_path.rfind('/') {
+            Some(pos) => format!("{}{reference_path}", &base_path[..=pos]),
+            None => format!("/{reference_path}"),
+        }
+    }
+
+    /// Resolve a relative reference against an absolute base URL, per RFC
+    /// 3986 §5.3 "Transform References"
     ///
-    /// - `https://example.com/page/subpage` -> `https://example.com/page`
-    /// - `https://example.com/page/` -> `https://example.com/page`
-    /// - `https://example.com` -> `https://example.com`
-    fn get_base_directory(&self, url: &str) -> String {
-        let trimmed = url.trim_end_matches('/');
-
-        // Find the last slash
-        if let Some(pos) = trimmed.rfind('/') {
-            // Check if this is the slash after the scheme (http://)
-            if pos > 0 && trimmed.chars().nth(pos - 1) == Some('/') {
-                // This is scheme://, return entire URL
-                return trimmed.to_string();
-            }
-            // Return everything up to the last slash
-            trimmed[..pos].to_string()
+    /// Only the scheme-relative cases this module actually reaches
+    /// (`resolve_against` already short-circuits references that carry
+    /// their own scheme or authority) are implemented: the reference
+    /// inherits the base's scheme and authority, and its path is either
+    /// taken verbatim (if absolute) or merged with the base's directory
+    /// portion, with `.`/`..` segments removed from the result either way.
+    /// A reference that is only a query and/or fragment keeps the base
+    /// path untouched.
+    fn transform_reference(base: &str, reference: &str) -> String {
+        let (origin, base_rest) = Self::split_origin(base);
+        let (base_path, base_query, _) = Self::split_reference(base_rest);
+        let (ref_path, ref_query, ref_fragment) = Self::split_reference(reference);
+
+        let (target_path, target_query) = if ref_path.is_empty() {
+            (base_path.to_string(), ref_query.or(base_query))
+        } else if ref_path.starts_with('/') {
+            (Self::remove_dot_segments(ref_path), ref_query)
         } else {
-            // No slash found, return as-is
-            trimmed.to_string()
+            (
+                Self::remove_dot_segments(&Self::merge_paths(base_path, ref_path)),
+                ref_query,
+            )
+        };
+
+        let mut result = format!("{origin}{target_path}");
+        if let Some(query) = target_query {
+            result.push('?');
+            result.push_str(query);
+        }
+        if let Some(fragment) = ref_fragment {
+            result.push('#');
+            result.push_str(fragment);
         }
+        result
+    }
+
+    /// Collapse `.`/`..` segments out of a URL path, per RFC 3986 §5.2.4
+    ///
+    /// `..` pops the previous real segment (clamped at the root rather than
+    /// escaping above it); `.` is dropped. Leading/trailing slashes are
+    /// preserved so `/a/b/` stays a directory-style path after normalization.
+    fn remove_dot_segments(path: &str) -> String {
+        let leading_slash = path.starts_with('/');
+        let trailing_slash = path.len() > 1 && path.ends_with('/');
+
+        let mut stack: Vec<&str> = Vec::new();
+        for segment in path.split('/') {
+            match segment {
+                "" | "." => {}
+                ".." => {
+                    stack.pop();
+                }
+                _ => stack.push(segment),
+            }
+        }
+
+        let mut result = String::new();
+        if leading_slash {
+            result.push('/');
+        }
+        result.push_str(&stack.join("/"));
+        if trailing_slash && !result.ends_with('/') {
+            result.push('/');
+        }
+        result
     }
 }
 
@@ -576,6 +1338,85 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_extract_image_with_base_href_overrides_base_url() {
+        let html = b"<html><head>
+            <base href=\"https://cdn.example.com/assets/\">
+            <meta property=\"og:image\" content=\"photo.jpg\" />
+        </head></html>";
+        let dom = parse_html(html).unwrap();
+        let base_url = Some("https://example.com/page".to_string());
+        let extractor = MetadataExtractor::new(base_url, true);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(
+            metadata.image,
+            Some("https://cdn.example.com/assets/photo.jpg".to_string())
+        );
+    }
+
+    #[test]
+    fn test_relative_base_href_resolves_against_base_url() {
+        let html = b"<html><head>
+            <base href=\"/assets/\">
+            <meta property=\"og:image\" content=\"photo.jpg\" />
+        </head></html>";
+        let dom = parse_html(html).unwrap();
+        let base_url = Some("https://example.com/page".to_string());
+        let extractor = MetadataExtractor::new(base_url, true);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(
+            metadata.image,
+            Some("https://example.com/assets/photo.jpg".to_string())
+        );
+    }
+
+    #[test]
+    fn test_base_href_without_href_attribute_is_skipped() {
+        let html = b"<html><head>
+            <base target=\"_blank\">
+            <base href=\"https://cdn.example.com/assets/\">
+            <meta property=\"og:image\" content=\"photo.jpg\" />
+        </head></html>";
+        let dom = parse_html(html).unwrap();
+        let extractor = MetadataExtractor::new(None, true);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(
+            metadata.image,
+            Some("https://cdn.example.com/assets/photo.jpg".to_string())
+        );
+    }
+
+    #[test]
+    fn test_no_base_href_falls_back_to_base_url() {
+        let html = b"<html><head>
+            <meta property=\"og:image\" content=\"photo.jpg\" />
+        </head></html>";
+        let dom = parse_html(html).unwrap();
+        let base_url = Some("https://example.com/page/".to_string());
+        let extractor = MetadataExtractor::new(base_url, true);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(
+            metadata.image,
+            Some("https://example.com/page/photo.jpg".to_string())
+        );
+    }
+
+    #[test]
+    fn test_effective_base_url_with_base_href() {
+        let html = b"<html><head><base href=\"https://cdn.example.com/assets/\"></head></html>";
+        let dom = parse_html(html).unwrap();
+        let extractor = MetadataExtractor::new(Some("https://example.com/page".to_string()), true);
+
+        assert_eq!(
+            extractor.effective_base_url(&dom),
+            Some("https://cdn.example.com/assets/".to_string())
+        );
+    }
+
     #[test]
     fn test_extract_author() {
         let html = b"<html><head>
@@ -600,6 +1441,60 @@ mod tests {
         assert_eq!(metadata.published, Some("2024-01-15T10:30:00Z".to_string()));
     }
 
+    #[test]
+    fn test_extract_published_date_falls_back_to_meta_date() {
+        let html = b"<html><head>
+            <meta name=\"date\" content=\"2024-01-15\" />
+        </head></html>";
+        let dom = parse_html(html).unwrap();
+        let extractor = MetadataExtractor::new(None, false);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(metadata.published, Some("2024-01-15".to_string()));
+    }
+
+    #[test]
+    fn test_extract_published_date_prefers_article_time_over_meta_date() {
+        let html = b"<html><head>
+            <meta name=\"date\" content=\"2024-01-01\" />
+            <meta property=\"article:published_time\" content=\"2024-01-15T10:30:00Z\" />
+        </head></html>";
+        let dom = parse_html(html).unwrap();
+        let extractor = MetadataExtractor::new(None, false);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(metadata.published, Some("2024-01-15T10:30:00Z".to_string()));
+    }
+
+    #[test]
+    fn test_extract_modified_date() {
+        let html = b"<html><head>
+            <meta property=\"article:modified_time\" content=\"2024-02-01T08:00:00Z\" />
+        </head></html>";
+        let dom = parse_html(html).unwrap();
+        let extractor = MetadataExtractor::new(None, false);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(metadata.modified, Some("2024-02-01T08:00:00Z".to_string()));
+    }
+
+    #[test]
+    fn test_canonical_url_resolves_against_base_href() {
+        let html = b"<html><head>
+            <base href=\"https://cdn.example.com/assets/\">
+            <link rel=\"canonical\" href=\"canonical-page\" />
+        </head></html>";
+        let dom = parse_html(html).unwrap();
+        let base_url = Some("https://example.com/page".to_string());
+        let extractor = MetadataExtractor::new(base_url, true);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(
+            metadata.url,
+            Some("https://cdn.example.com/assets/canonical-page".to_string())
+        );
+    }
+
     #[test]
     fn test_extract_canonical_url() {
         let html = b"<html><head>
@@ -668,6 +1563,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_resolve_relative_path_with_dot_dot_traversal() {
+        let extractor =
+            MetadataExtractor::new(Some("https://example.com/blog/post".to_string()), true);
+
+        assert_eq!(
+            extractor.resolve_url("../images/logo.png"),
+            "https://example.com/images/logo.png"
+        );
+    }
+
+    #[test]
+    fn test_resolve_absolute_path_with_dot_dot_traversal_preserves_query_and_fragment() {
+        let extractor =
+            MetadataExtractor::new(Some("https://example.com/blog/post".to_string()), true);
+
+        assert_eq!(
+            extractor.resolve_url("/a/../b/image.jpg?size=large#top"),
+            "https://example.com/b/image.jpg?size=large#top"
+        );
+    }
+
     #[test]
     fn test_resolve_url_disabled() {
         let extractor = MetadataExtractor::new(Some("https://example.com/page".to_string()), false);
@@ -704,38 +1621,47 @@ mod tests {
     }
 
     #[test]
-    fn test_get_origin() {
-        let extractor = MetadataExtractor::new(None, false);
+    fn test_resolve_relative_path_against_base_with_no_path() {
+        let extractor = MetadataExtractor::new(Some("https://example.com".to_string()), true);
 
         assert_eq!(
-            extractor.get_origin("https://example.com/path/to/page"),
-            "https://example.com"
-        );
-        assert_eq!(
-            extractor.get_origin("http://example.com:8080/path"),
-            "http://example.com:8080"
-        );
-        assert_eq!(
-            extractor.get_origin("https://example.com"),
-            "https://example.com"
+            extractor.resolve_url("image.jpg"),
+            "https://example.com/image.jpg"
         );
     }
 
     #[test]
-    fn test_get_base_directory() {
-        let extractor = MetadataExtractor::new(None, false);
+    fn test_resolve_query_only_reference_keeps_base_path() {
+        let extractor =
+            MetadataExtractor::new(Some("https://example.com/page/subpage".to_string()), true);
 
         assert_eq!(
-            extractor.get_base_directory("https://example.com/page/subpage"),
-            "https://example.com/page"
+            extractor.resolve_url("?sort=asc"),
+            "https://example.com/page/subpage?sort=asc"
         );
+    }
+
+    #[test]
+    fn test_resolve_fragment_only_reference_keeps_base_path_and_query() {
+        let extractor = MetadataExtractor::new(
+            Some("https://example.com/page/subpage?tab=1".to_string()),
+            true,
+        );
+
         assert_eq!(
-            extractor.get_base_directory("https://example.com/page/"),
-            "https://example.com"
+            extractor.resolve_url("#section-2"),
+            "https://example.com/page/subpage?tab=1#section-2"
         );
+    }
+
+    #[test]
+    fn test_resolve_relative_path_with_multiple_dot_dot_traversal() {
+        let extractor =
+            MetadataExtractor::new(Some("https://example.com/a/b/page".to_string()), true);
+
         assert_eq!(
-            extractor.get_base_directory("https://example.com"),
-            "https://example.com"
+            extractor.resolve_url("../../img.jpg"),
+            "https://example.com/img.jpg"
         );
     }
 
@@ -775,6 +1701,82 @@ mod tests {
         assert_eq!(metadata.published, Some("2024-01-15".to_string()));
     }
 
+    #[test]
+    fn test_metadata_rule_captures_single_meta_value() {
+        let html = b"<html><head>
+            <meta name=\"keywords\" content=\"rust, markdown\" />
+        </head></html>";
+        let dom = parse_html(html).unwrap();
+        let extractor = MetadataExtractor::new(None, false)
+            .with_metadata_fields(vec![MetadataRule::meta("keywords", "keywords")]);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(
+            metadata.extra,
+            vec![(
+                "keywords".to_string(),
+                MetadataValue::Single("rust, markdown".to_string())
+            )]
+        );
+    }
+
+    #[test]
+    fn test_metadata_rule_collects_repeated_tags_into_list() {
+        let html = b"<html><head>
+            <meta property=\"article:tag\" content=\"rust\" />
+            <meta property=\"article:tag\" content=\"markdown\" />
+            <meta property=\"article:tag\" content=\"html\" />
+        </head></html>";
+        let dom = parse_html(html).unwrap();
+        let extractor = MetadataExtractor::new(None, false)
+            .with_metadata_fields(vec![MetadataRule::meta("article:tag", "tags")]);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(
+            metadata.extra,
+            vec![(
+                "tags".to_string(),
+                MetadataValue::List(vec![
+                    "rust".to_string(),
+                    "markdown".to_string(),
+                    "html".to_string()
+                ])
+            )]
+        );
+    }
+
+    #[test]
+    fn test_metadata_rule_matches_link_rel() {
+        let html = b"<html><head>
+            <link rel=\"alternate\" href=\"/feed.xml\" />
+        </head></html>";
+        let dom = parse_html(html).unwrap();
+        let base_url = Some("https://example.com/page".to_string());
+        let extractor = MetadataExtractor::new(base_url, true)
+            .with_metadata_fields(vec![MetadataRule::link("alternate", "feed")]);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(
+            metadata.extra,
+            vec![(
+                "feed".to_string(),
+                MetadataValue::Single("https://example.com/feed.xml".to_string())
+            )]
+        );
+    }
+
+    #[test]
+    fn test_metadata_rules_do_not_affect_output_when_unconfigured() {
+        let html = b"<html><head>
+            <meta name=\"keywords\" content=\"rust, markdown\" />
+        </head></html>";
+        let dom = parse_html(html).unwrap();
+        let extractor = MetadataExtractor::new(None, false);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert!(metadata.extra.is_empty());
+    }
+
     #[test]
     fn test_twitter_card_metadata() {
         let html = b"<html><head>
@@ -792,4 +1794,403 @@ mod tests {
             Some("https://cdn.example.com/image.jpg".to_string())
         );
     }
+
+    #[test]
+    fn test_json_ld_fills_fields_meta_tags_left_empty() {
+        let html = br#"<html><head>
+            <script type="application/ld+json">
+            {
+                "@context": "https://schema.org",
+                "@type": "Article",
+                "headline": "JSON-LD Headline",
+                "description": "JSON-LD description",
+                "author": {"@type": "Person", "name": "Jane Author"},
+                "datePublished": "2024-01-15T00:00:00Z",
+                "image": "https://example.com/hero.jpg"
+            }
+            </script>
+        </head></html>"#;
+        let dom = parse_html(html).unwrap();
+        let extractor = MetadataExtractor::new(None, false);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(metadata.title, Some("JSON-LD Headline".to_string()));
+        assert_eq!(metadata.description, Some("JSON-LD description".to_string()));
+        assert_eq!(metadata.author, Some("Jane Author".to_string()));
+        assert_eq!(metadata.published, Some("2024-01-15T00:00:00Z".to_string()));
+        assert_eq!(
+            metadata.image,
+            Some("https://example.com/hero.jpg".to_string())
+        );
+    }
+
+    #[test]
+    fn test_json_ld_fills_date_modified() {
+        let html = br#"<html><head>
+            <script type="application/ld+json">
+            {
+                "@context": "https://schema.org",
+                "@type": "Article",
+                "headline": "JSON-LD Headline",
+                "dateModified": "2024-02-01T08:00:00Z"
+            }
+            </script>
+        </head></html>"#;
+        let dom = parse_html(html).unwrap();
+        let extractor = MetadataExtractor::new(None, false);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(
+            metadata.modified,
+            Some("2024-02-01T08:00:00Z".to_string())
+        );
+    }
+
+    #[test]
+    fn test_json_ld_does_not_override_og_title() {
+        let html = br#"<html><head>
+            <meta property="og:title" content="OG Title" />
+            <script type="application/ld+json">
+            {"@type": "Article", "headline": "JSON-LD Headline"}
+            </script>
+        </head></html>"#;
+        let dom = parse_html(html).unwrap();
+        let extractor = MetadataExtractor::new(None, false);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(metadata.title, Some("OG Title".to_string()));
+    }
+
+    #[test]
+    fn test_json_ld_graph_array_is_searched_for_article_type() {
+        let html = br#"<html><head>
+            <script type="application/ld+json">
+            {
+                "@context": "https://schema.org",
+                "@graph": [
+                    {"@type": "Organization", "name": "Example Corp"},
+                    {"@type": "NewsArticle", "headline": "Graph Headline", "author": "Plain Name"}
+                ]
+            }
+            </script>
+        </head></html>"#;
+        let dom = parse_html(html).unwrap();
+        let extractor = MetadataExtractor::new(None, false);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(metadata.title, Some("Graph Headline".to_string()));
+        assert_eq!(metadata.author, Some("Plain Name".to_string()));
+    }
+
+    #[test]
+    fn test_malformed_json_ld_is_skipped_without_error() {
+        let html = br#"<html><head>
+            <script type="application/ld+json">{ not valid json </script>
+            <title>Fallback Title</title>
+        </head></html>"#;
+        let dom = parse_html(html).unwrap();
+        let extractor = MetadataExtractor::new(None, false);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(metadata.title, Some("Fallback Title".to_string()));
+    }
+
+    #[test]
+    fn test_json_ld_unrecognized_type_is_ignored() {
+        let html = br#"<html><head>
+            <script type="application/ld+json">
+            {"@type": "Person", "name": "Not An Article"}
+            </script>
+        </head></html>"#;
+        let dom = parse_html(html).unwrap();
+        let extractor = MetadataExtractor::new(None, false);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(metadata.title, None);
+    }
+
+    #[test]
+    fn test_extract_open_graph_article_namespace() {
+        let html = b"<html><head>
+            <meta property=\"og:site_name\" content=\"Example News\" />
+            <meta property=\"og:type\" content=\"article\" />
+            <meta property=\"og:locale\" content=\"en_US\" />
+            <meta property=\"article:section\" content=\"Technology\" />
+        </head></html>";
+        let dom = parse_html(html).unwrap();
+        let extractor = MetadataExtractor::new(None, false);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(metadata.site_name, Some("Example News".to_string()));
+        assert_eq!(metadata.og_type, Some("article".to_string()));
+        assert_eq!(metadata.locale, Some("en_US".to_string()));
+        assert_eq!(metadata.section, Some("Technology".to_string()));
+    }
+
+    #[test]
+    fn test_extract_repeated_article_tag_into_tags_list() {
+        let html = b"<html><head>
+            <meta property=\"article:tag\" content=\"rust\" />
+            <meta property=\"article:tag\" content=\"markdown\" />
+        </head></html>";
+        let dom = parse_html(html).unwrap();
+        let extractor = MetadataExtractor::new(None, false);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(metadata.tags, vec!["rust".to_string(), "markdown".to_string()]);
+    }
+
+    #[test]
+    fn test_extract_keywords_falls_back_to_tags_when_no_article_tag() {
+        let html = b"<html><head>
+            <meta name=\"keywords\" content=\"rust, markdown, html\" />
+        </head></html>";
+        let dom = parse_html(html).unwrap();
+        let extractor = MetadataExtractor::new(None, false);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(
+            metadata.tags,
+            vec!["rust".to_string(), "markdown".to_string(), "html".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_extract_og_video_resolves_relative_url() {
+        let html = b"<html><head>
+            <meta property=\"og:video\" content=\"/videos/clip.mp4\" />
+        </head></html>";
+        let dom = parse_html(html).unwrap();
+        let extractor =
+            MetadataExtractor::new(Some("https://example.com/page".to_string()), true);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(
+            metadata.video,
+            Some("https://example.com/videos/clip.mp4".to_string())
+        );
+    }
+
+    #[test]
+    fn test_extract_og_video_url_fallback() {
+        let html = b"<html><head>
+            <meta property=\"og:video:url\" content=\"https://cdn.example.com/clip.mp4\" />
+        </head></html>";
+        let dom = parse_html(html).unwrap();
+        let extractor = MetadataExtractor::new(None, false);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(
+            metadata.video,
+            Some("https://cdn.example.com/clip.mp4".to_string())
+        );
+    }
+
+    #[test]
+    fn test_extract_theme_color() {
+        let html = b"<html><head>
+            <meta name=\"theme-color\" content=\"#123456\" />
+        </head></html>";
+        let dom = parse_html(html).unwrap();
+        let extractor = MetadataExtractor::new(None, false);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(metadata.theme_color, Some("#123456".to_string()));
+    }
+
+    #[test]
+    fn test_extract_favicon_from_icon_link() {
+        let html = b"<html><head>
+            <link rel=\"icon\" href=\"/assets/favicon.png\" />
+        </head></html>";
+        let dom = parse_html(html).unwrap();
+        let extractor =
+            MetadataExtractor::new(Some("https://example.com/page".to_string()), true);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(
+            metadata.favicon,
+            Some("https://example.com/assets/favicon.png".to_string())
+        );
+    }
+
+    #[test]
+    fn test_extract_favicon_from_shortcut_icon_link() {
+        let html = b"<html><head>
+            <link rel=\"shortcut icon\" href=\"/assets/favicon.png\" />
+        </head></html>";
+        let dom = parse_html(html).unwrap();
+        let extractor =
+            MetadataExtractor::new(Some("https://example.com/page".to_string()), true);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(
+            metadata.favicon,
+            Some("https://example.com/assets/favicon.png".to_string())
+        );
+    }
+
+    #[test]
+    fn test_extract_favicon_falls_back_to_well_known_path() {
+        let html = b"<html><head><title>No favicon link</title></head></html>";
+        let dom = parse_html(html).unwrap();
+        let extractor =
+            MetadataExtractor::new(Some("https://example.com/page".to_string()), true);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(
+            metadata.favicon,
+            Some("https://example.com/favicon.ico".to_string())
+        );
+    }
+
+    /// Test fetcher returning a fixed JSON payload and counting calls, so
+    /// tests can assert on both the merged fields and the cache behavior
+    struct StubOembedFetcher {
+        response: String,
+        calls: std::cell::RefCell<u32>,
+    }
+
+    impl OembedFetcher for StubOembedFetcher {
+        fn fetch(&self, _url: &str) -> Result<String, ConversionError> {
+            *self.calls.borrow_mut() += 1;
+            Ok(self.response.clone())
+        }
+    }
+
+    #[test]
+    fn test_oembed_discovery_fills_empty_fields_and_sets_type_and_html() {
+        let html = br#"<html><head>
+            <link rel="alternate" type="application/json+oembed" href="/oembed?url=x" />
+        </head></html>"#;
+        let dom = parse_html(html).unwrap();
+        let fetcher = Rc::new(StubOembedFetcher {
+            response: r#"{
+                "type": "video",
+                "title": "oEmbed Title",
+                "author_name": "oEmbed Author",
+                "thumbnail_url": "/thumb.jpg",
+                "html": "<iframe></iframe>"
+            }"#
+            .to_string(),
+            calls: std::cell::RefCell::new(0),
+        });
+        let extractor = MetadataExtractor::new(Some("https://example.com".to_string()), true)
+            .with_oembed_fetcher(fetcher);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(metadata.title, Some("oEmbed Title".to_string()));
+        assert_eq!(metadata.author, Some("oEmbed Author".to_string()));
+        assert_eq!(
+            metadata.image,
+            Some("https://example.com/thumb.jpg".to_string())
+        );
+        assert_eq!(metadata.oembed_type, Some("video".to_string()));
+        assert_eq!(metadata.oembed_html, Some("<iframe></iframe>".to_string()));
+    }
+
+    #[test]
+    fn test_oembed_does_not_override_og_title() {
+        let html = br#"<html><head>
+            <meta property="og:title" content="OG Title" />
+            <link rel="alternate" type="application/json+oembed" href="/oembed" />
+        </head></html>"#;
+        let dom = parse_html(html).unwrap();
+        let fetcher = Rc::new(StubOembedFetcher {
+            response: r#"{"type": "link", "title": "oEmbed Title"}"#.to_string(),
+            calls: std::cell::RefCell::new(0),
+        });
+        let extractor = MetadataExtractor::new(None, false).with_oembed_fetcher(fetcher);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(metadata.title, Some("OG Title".to_string()));
+    }
+
+    #[test]
+    fn test_oembed_duplicate_endpoint_is_fetched_only_once() {
+        let html = br#"<html><head>
+            <link rel="alternate" type="application/json+oembed" href="/oembed" />
+            <link rel="alternate" type="application/json+oembed" href="/oembed" />
+        </head></html>"#;
+        let dom = parse_html(html).unwrap();
+        let fetcher = Rc::new(StubOembedFetcher {
+            response: r#"{"type": "rich"}"#.to_string(),
+            calls: std::cell::RefCell::new(0),
+        });
+        let extractor = MetadataExtractor::new(None, false).with_oembed_fetcher(fetcher.clone());
+        let _ = extractor.extract(&dom).unwrap();
+
+        assert_eq!(*fetcher.calls.borrow(), 1);
+    }
+
+    #[test]
+    fn test_oembed_discovery_is_skipped_without_a_fetcher_configured() {
+        let html = br#"<html><head>
+            <link rel="alternate" type="application/json+oembed" href="/oembed" />
+            <title>Fallback Title</title>
+        </head></html>"#;
+        let dom = parse_html(html).unwrap();
+        let extractor = MetadataExtractor::new(None, false);
+        let metadata = extractor.extract(&dom).unwrap();
+
+        assert_eq!(metadata.title, Some("Fallback Title".to_string()));
+        assert_eq!(metadata.oembed_type, None);
+    }
+
+    #[test]
+    fn test_provenance_comment_strips_credentials_and_fragment() {
+        let extractor = MetadataExtractor::new(
+            Some("https://user:[email protected]/article#section-2".to_string()),
+            false,
+        );
+        let retrieved_at = chrono::DateTime::parse_from_rfc3339("2024-01-15T12:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+
+        let comment = extractor.provenance_comment(retrieved_at);
+
+        assert_eq!(
+            comment,
+            format!(
+                "<!-- Saved from https://example.com/article at 2024-01-15T12:00:00Z using nginx-markdown-for-agents v{} -->",
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+    }
+
+    #[test]
+    fn test_provenance_comment_local_source_for_non_http_base_url() {
+        let extractor = MetadataExtractor::new(Some("file:///tmp/article.html".to_string()), false);
+        let retrieved_at = chrono::DateTime::parse_from_rfc3339("2024-01-15T12:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+
+        let comment = extractor.provenance_comment(retrieved_at);
+
+        assert_eq!(
+            comment,
+            format!(
+                "<!-- Saved from a local source at 2024-01-15T12:00:00Z using nginx-markdown-for-agents v{} -->",
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+    }
+
+    #[test]
+    fn test_provenance_comment_local_source_for_no_base_url() {
+        let extractor = MetadataExtractor::new(None, false);
+        let retrieved_at = chrono::DateTime::parse_from_rfc3339("2024-01-15T12:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+
+        let comment = extractor.provenance_comment(retrieved_at);
+
+        assert_eq!(
+            comment,
+            format!(
+                "<!-- Saved from a local source at 2024-01-15T12:00:00Z using nginx-markdown-for-agents v{} -->",
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+    }
 }
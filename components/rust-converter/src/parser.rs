@@ -46,23 +46,26 @@
 //! - The parser allocates memory for the DOM tree proportional to document size
 //! - Large documents should be size-limited before parsing (enforced by caller)
 //! - Parsing is single-threaded and synchronous
+//! - [`HtmlParserStream`] avoids buffering the whole input up front by
+//!   feeding html5ever bytes as they arrive; parsing itself is still
+//!   synchronous and happens inside each `push`/`finish` call
 
 use html5ever::parse_document;
-use html5ever::tendril::TendrilSink;
+use html5ever::tendril::{StrTendril, TendrilSink};
+use html5ever::Parser;
 use markup5ever_rcdom::RcDom;
 use std::borrow::Cow;
 
-use crate::charset::detect_charset;
+use crate::charset::{
+    resolve_input_charset, resolve_input_charset_with_codepage, resolve_input_charset_with_detection,
+};
+use crate::decompression::decompress_body;
 use crate::error::ConversionError;
 
 /// Parse HTML bytes into a DOM tree with charset detection
 ///
-/// This function implements the charset detection cascade specified in
-/// Requirements FR-05.1, FR-05.2, and FR-05.3:
-///
-/// 1. Check Content-Type header charset parameter (FR-05.1)
-/// 2. Check HTML meta charset tags (FR-05.2)
-/// 3. Default to UTF-8 (FR-05.3)
+/// This is a convenience wrapper over [`parse_html_with_options`] with no
+/// explicit `input_charset` override, relying on BOM/header/meta detection.
 ///
 /// # Arguments
 ///
@@ -72,16 +75,7 @@ use crate::error::ConversionError;
 /// # Returns
 ///
 /// Returns `Ok(RcDom)` containing the parsed DOM tree on success.
-/// Returns `Err(ConversionError)` if parsing fails or encoding is invalid.
-///
-/// # Errors
-///
-/// This function returns an error in the following cases:
-///
-/// - `ConversionError::EncodingError`: The input is invalid for the detected charset,
-///   or the detected charset is unsupported
-/// - `ConversionError::ParseError`: HTML parsing failed (rare, as html5ever is very permissive)
-/// - `ConversionError::InvalidInput`: Input is empty or null
+/// Returns `Err(ConversionError)` if the input is empty.
 ///
 /// # Examples
 ///
@@ -104,27 +98,115 @@ use crate::error::ConversionError;
 ///     .expect("Failed to parse HTML");
 /// ```
 ///
-/// # Charset Detection Cascade
+/// # See Also
 ///
-/// The function follows a three-level cascade:
+/// - [`parse_html_with_options`]: For parsing with an explicit charset override
+pub fn parse_html_with_charset(
+    html: &[u8],
+    content_type: Option<&str>,
+) -> Result<RcDom, ConversionError> {
+    parse_html_with_options(html, content_type, None).map(|(dom, _had_lossy_decode)| dom)
+}
+
+/// Parse HTML bytes into a DOM tree, with full control over charset resolution
 ///
-/// 1. **Content-Type Header** (Priority 1): If `content_type` parameter contains
-///    a charset parameter, use that charset for parsing.
+/// Resolves the charset using [`crate::charset::resolve_input_charset`]'s
+/// cascade, then transcodes to UTF-8 before handing the document to
+/// html5ever:
 ///
-/// 2. **HTML Meta Tags** (Priority 2): If Content-Type has no charset, scan the
-///    HTML for `<meta charset>` or `<meta http-equiv="Content-Type">` tags.
+/// 1. `input_charset` override, when given (wins over every sniffing signal)
+/// 2. A leading UTF-8/UTF-16/UTF-32 byte-order mark
+/// 3. Content-Type header charset parameter
+/// 4. `<meta charset>` / `<meta http-equiv="Content-Type">` tags
+/// 5. Default to UTF-8
 ///
-/// 3. **Default to UTF-8** (Priority 3): If both fail, assume UTF-8 encoding.
+/// Decoding never fails: an unrecognized charset label falls back to UTF-8,
+/// and malformed byte sequences are replaced with U+FFFD, matching how
+/// browsers handle mislabeled or corrupted real-world HTML.
 ///
-/// # Performance Notes
+/// # Arguments
 ///
-/// - Charset detection scans only the first 1024 bytes of HTML
-/// - Input is decoded/transcoded to UTF-8 before parsing
-/// - Parsing time is roughly linear with document size
-pub fn parse_html_with_charset(
+/// * `html` - A byte slice containing HTML content
+/// * `content_type` - Optional Content-Type header value (e.g., "text/html; charset=UTF-8")
+/// * `input_charset` - Optional explicit charset label that overrides all sniffing
+///
+/// # Returns
+///
+/// Returns `Ok((RcDom, had_lossy_decode))` on success, where `had_lossy_decode`
+/// is `true` if any byte sequence in `html` was not valid under the
+/// detected/explicit charset and was replaced with U+FFFD.
+/// Returns `Err(ConversionError::InvalidInput)` if `html` is empty.
+///
+/// # Examples
+///
+/// ```rust
+/// use nginx_markdown_converter::parser::parse_html_with_options;
+///
+/// // Force a charset, overriding the page's own (wrong) meta tag
+/// let html = b"<html><head><meta charset=\"UTF-8\"></head><body>Caf\xE9</body></html>";
+/// let (dom, had_lossy_decode) = parse_html_with_options(html, None, Some("ISO-8859-1"))
+///     .expect("Failed to parse HTML");
+/// assert!(!had_lossy_decode);
+/// ```
+pub fn parse_html_with_options(
     html: &[u8],
     content_type: Option<&str>,
-) -> Result<RcDom, ConversionError> {
+    input_charset: Option<&str>,
+) -> Result<(RcDom, bool), ConversionError> {
+    parse_html_with_decoding_mode(html, content_type, input_charset, false)
+}
+
+/// Parse HTML bytes into a DOM tree, with control over strict vs. lenient decoding
+///
+/// Identical to [`parse_html_with_options`], except it also controls what
+/// happens when a byte sequence is invalid under the detected/explicit
+/// charset:
+///
+/// - `strict_decoding = false` (the default used by [`parse_html_with_options`]
+///   and every other wrapper in this module): replace invalid sequences with
+///   U+FFFD and keep going, matching how browsers render mislabeled or
+///   corrupted real-world HTML. `had_lossy_decode` reports whether that
+///   replacement happened.
+/// - `strict_decoding = true`: reject the document outright with
+///   [`ConversionError::EncodingError`] on the first invalid sequence,
+///   rather than silently substituting content a caller may want to treat
+///   as a hard failure (e.g. to fall back to a different charset guess, or
+///   to surface a clear upstream-encoding bug instead of garbled Markdown).
+///
+/// # Arguments
+///
+/// * `html` - A byte slice containing HTML content
+/// * `content_type` - Optional Content-Type header value (e.g., "text/html; charset=UTF-8")
+/// * `input_charset` - Optional explicit charset label that overrides all sniffing
+/// * `strict_decoding` - When `true`, an invalid byte sequence is an error instead of a U+FFFD substitution
+///
+/// # Returns
+///
+/// Returns `Ok((RcDom, had_lossy_decode))` on success; `had_lossy_decode` is
+/// always `false` when `strict_decoding` is `true`, since any invalid
+/// sequence would have already returned `Err` instead.
+/// Returns `Err(ConversionError::InvalidInput)` if `html` is empty.
+/// Returns `Err(ConversionError::EncodingError)` if `strict_decoding` is
+/// `true` and a byte sequence is invalid under the detected/explicit
+/// charset.
+///
+/// # Examples
+///
+/// ```rust
+/// use nginx_markdown_converter::parser::parse_html_with_decoding_mode;
+///
+/// // A lone continuation byte is invalid UTF-8; strict mode rejects it
+/// // instead of substituting U+FFFD.
+/// let html = b"<html><body><p>Bad\x80byte</p></body></html>";
+/// let result = parse_html_with_decoding_mode(html, None, None, true);
+/// assert!(matches!(result, Err(nginx_markdown_converter::ConversionError::EncodingError(_))));
+/// ```
+pub fn parse_html_with_decoding_mode(
+    html: &[u8],
+    content_type: Option<&str>,
+    input_charset: Option<&str>,
+    strict_decoding: bool,
+) -> Result<(RcDom, bool), ConversionError> {
     // Validate input is not empty
     if html.is_empty() {
         return Err(ConversionError::InvalidInput(
@@ -132,52 +214,608 @@ pub fn parse_html_with_charset(
         ));
     }
 
-    // Detect charset using the three-level cascade
-    let detected_charset = detect_charset(content_type, html);
+    let (detected_charset, bom_len) = resolve_input_charset(input_charset, content_type, html);
 
     // Decode to UTF-8 before html5ever parsing. html5ever's `from_utf8()` expects UTF-8 bytes,
     // so non-UTF-8 inputs must be transcoded according to the detected charset.
-    let utf8_str = decode_html_to_utf8(html, &detected_charset)?;
+    let (utf8_str, had_lossy_decode) =
+        decode_html_to_utf8_with_mode(&html[bom_len..], &detected_charset, strict_decoding)?;
 
     // Parse the HTML document using html5ever directly from a UTF-8 string
     // sink to avoid `std::io::Read`/Cursor overhead in the hot path.
-    let dom = parse_document(RcDom::default(), Default::default())
-        .one(utf8_str.as_ref());
+    let dom = parse_document(RcDom::default(), Default::default()).one(utf8_str.as_ref());
 
-    Ok(dom)
+    Ok((dom, had_lossy_decode))
 }
 
-fn decode_html_to_utf8<'a>(
+/// Parse HTML bytes into a DOM tree, exposing the charset that was resolved
+///
+/// Identical to [`parse_html_with_options`], except it also returns the
+/// charset label that [`crate::charset::resolve_input_charset`] actually
+/// settled on. The other wrappers in this module discard that label once
+/// decoding succeeds; callers that need to log it, echo it back in a
+/// response header, or decide whether to re-fetch with an explicit
+/// `input_charset` override need this variant instead.
+///
+/// # Arguments
+///
+/// * `html` - A byte slice containing HTML content
+/// * `content_type` - Optional Content-Type header value (e.g., "text/html; charset=UTF-8")
+/// * `input_charset` - Optional explicit charset label that overrides all sniffing
+///
+/// # Returns
+///
+/// Returns `Ok((RcDom, detected_charset, had_lossy_decode))` on success,
+/// where `detected_charset` is the resolved charset's canonical
+/// `encoding_rs` name (e.g. `"UTF-8"`, `"windows-1252"`).
+/// Returns `Err(ConversionError::InvalidInput)` if `html` is empty.
+///
+/// # Examples
+///
+/// ```rust
+/// use nginx_markdown_converter::parser::parse_html_with_detected_charset;
+///
+/// let html = b"<html><head><meta charset=\"ISO-8859-1\"></head><body>Caf\xE9</body></html>";
+/// let (_dom, detected_charset, had_lossy_decode) =
+///     parse_html_with_detected_charset(html, None, None).expect("Failed to parse HTML");
+/// assert_eq!(detected_charset, "windows-1252");
+/// assert!(!had_lossy_decode);
+/// ```
+pub fn parse_html_with_detected_charset(
+    html: &[u8],
+    content_type: Option<&str>,
+    input_charset: Option<&str>,
+) -> Result<(RcDom, String, bool), ConversionError> {
+    if html.is_empty() {
+        return Err(ConversionError::InvalidInput(
+            "HTML input is empty".to_string(),
+        ));
+    }
+
+    let (detected_charset, bom_len) = resolve_input_charset(input_charset, content_type, html);
+    let (utf8_str, had_lossy_decode) =
+        decode_html_to_utf8_with_mode(&html[bom_len..], &detected_charset, false)?;
+    let dom = parse_document(RcDom::default(), Default::default()).one(utf8_str.as_ref());
+
+    Ok((dom, detected_charset, had_lossy_decode))
+}
+
+/// Parse HTML bytes into a DOM tree, with a statistical fallback for
+/// undeclared legacy encodings
+///
+/// Identical to [`parse_html_with_detected_charset`], except its charset
+/// resolution adds a fifth, feature-gated stage: when no BOM, Content-Type
+/// charset, or `<meta charset>` is found and `html` doesn't decode as valid
+/// UTF-8 on its own, [`crate::statistical_charset::detect_charset_statistically_with_hint`]
+/// guesses a legacy single-byte or CJK multibyte encoding (optionally biased
+/// by `tld_hint`, e.g. `"jp"`) instead of defaulting straight to UTF-8. This
+/// only ever changes behavior for input that would otherwise have been
+/// mis-decoded as UTF-8 with replacement characters; a page with a real
+/// declared charset is completely unaffected.
+///
+/// Set `allow_statistical_detection` to `false` to skip that stage, e.g. for
+/// callers who would rather fall back to plain UTF-8 than risk an
+/// unreliable guess. This requires the crate's `stat_charset_detect` feature
+/// regardless of `allow_statistical_detection`; without it, this function
+/// behaves exactly like [`parse_html_with_detected_charset`] and
+/// `was_guessed` is always `false`.
+///
+/// # Arguments
+///
+/// * `html` - A byte slice containing HTML content
+/// * `content_type` - Optional Content-Type header value (e.g., "text/html; charset=UTF-8")
+/// * `input_charset` - Optional explicit charset label that overrides all sniffing
+/// * `allow_statistical_detection` - Whether the statistical fallback stage may run at all
+/// * `tld_hint` - Optional TLD/locale hint (e.g. `"jp"`, `"ru"`) to bias the statistical guess
+///
+/// # Returns
+///
+/// Returns `Ok((RcDom, detected_charset, had_lossy_decode, was_guessed))` on
+/// success; `was_guessed` is `true` only when the statistical fallback
+/// supplied `detected_charset`, so callers can decide whether to trust a
+/// guessed result less than a declared one.
+/// Returns `Err(ConversionError::InvalidInput)` if `html` is empty.
+///
+/// # Examples
+///
+/// ```rust
+/// use nginx_markdown_converter::parser::parse_html_with_charset_detection;
+///
+/// let html = b"<html><body>No declared charset</body></html>";
+/// let (_dom, detected_charset, _had_lossy_decode, was_guessed) =
+///     parse_html_with_charset_detection(html, None, None, true, None)
+///         .expect("Failed to parse HTML");
+/// // Valid UTF-8 on its own, so the statistical stage never runs.
+/// assert_eq!(detected_charset, "UTF-8");
+/// assert!(!was_guessed);
+/// ```
+pub fn parse_html_with_charset_detection(
+    html: &[u8],
+    content_type: Option<&str>,
+    input_charset: Option<&str>,
+    allow_statistical_detection: bool,
+    tld_hint: Option<&str>,
+) -> Result<(RcDom, String, bool, bool), ConversionError> {
+    if html.is_empty() {
+        return Err(ConversionError::InvalidInput(
+            "HTML input is empty".to_string(),
+        ));
+    }
+
+    let (detected_charset, bom_len, was_guessed) = resolve_input_charset_with_detection(
+        input_charset,
+        content_type,
+        html,
+        allow_statistical_detection,
+        tld_hint,
+    );
+    let (utf8_str, had_lossy_decode) =
+        decode_html_to_utf8_with_mode(&html[bom_len..], &detected_charset, false)?;
+    let dom = parse_document(RcDom::default(), Default::default()).one(utf8_str.as_ref());
+
+    Ok((dom, detected_charset, had_lossy_decode, was_guessed))
+}
+
+/// Parse HTML bytes into a DOM tree, with a caller-pinned encoding that
+/// overrides every sniffing signal
+///
+/// Some servers declare the wrong charset entirely (claiming UTF-8 while
+/// actually serving GB2312, for instance), so even correct BOM/header/meta
+/// detection just reproduces the server's mistake and decodes mojibake.
+/// When `force_encoding` is `Some`, it wins outright: BOM sniffing,
+/// `input_charset`, the `Content-Type` header, and `<meta charset>` are all
+/// skipped, and `html` is decoded with exactly that encoding. This mirrors
+/// how tools like Huginn's `force_encoding` and monolith's custom-charset
+/// flag let an operator pin decoding for a known-broken source.
+///
+/// `force_encoding` is validated with
+/// [`encoding_rs::Encoding::for_label_no_replacement`] rather than
+/// [`encoding_rs::Encoding::for_label`], so a caller gets a clear error for
+/// an unrecognized label instead of a silent UTF-8 fallback — and so the
+/// label can never resolve to the `replacement` encoding, which
+/// `for_label` allows but which `encoding_rs` reserves for security-relevant
+/// inputs (see [its docs](https://docs.rs/encoding_rs/)) and which would
+/// turn every byte of `html` into U+FFFD.
+///
+/// When `force_encoding` is `None`, this behaves exactly like
+/// [`parse_html_with_options`].
+///
+/// # Arguments
+///
+/// * `html` - A byte slice containing HTML content
+/// * `content_type` - Optional Content-Type header value, used only when `force_encoding` is `None`
+/// * `input_charset` - Optional explicit charset label, used only when `force_encoding` is `None`
+/// * `force_encoding` - Optional encoding label that overrides every other signal
+///
+/// # Returns
+///
+/// Returns `Ok((RcDom, had_lossy_decode))` on success, same as
+/// [`parse_html_with_options`].
+/// Returns `Err(ConversionError::InvalidInput)` if `html` is empty.
+/// Returns `Err(ConversionError::UnknownEncoding)` if `force_encoding` is
+/// `Some` and not a recognized encoding label.
+///
+/// # Examples
+///
+/// ```rust
+/// use nginx_markdown_converter::parser::parse_html_with_forced_encoding;
+///
+/// // The page lies and claims UTF-8, but is really ISO-8859-1.
+/// let html = b"<html><head><meta charset=\"UTF-8\"></head><body>Caf\xE9</body></html>";
+/// let (_dom, had_lossy_decode) =
+///     parse_html_with_forced_encoding(html, None, None, Some("ISO-8859-1"))
+///         .expect("Failed to parse HTML");
+/// assert!(!had_lossy_decode);
+///
+/// // An unrecognized label is a clear error, not a silent UTF-8 fallback.
+/// let err = parse_html_with_forced_encoding(html, None, None, Some("not-a-real-encoding"));
+/// assert!(matches!(err, Err(nginx_markdown_converter::ConversionError::UnknownEncoding(_))));
+/// ```
+pub fn parse_html_with_forced_encoding(
+    html: &[u8],
+    content_type: Option<&str>,
+    input_charset: Option<&str>,
+    force_encoding: Option<&str>,
+) -> Result<(RcDom, bool), ConversionError> {
+    let Some(label) = force_encoding else {
+        return parse_html_with_options(html, content_type, input_charset);
+    };
+
+    if html.is_empty() {
+        return Err(ConversionError::InvalidInput(
+            "HTML input is empty".to_string(),
+        ));
+    }
+
+    let encoding = encoding_rs::Encoding::for_label_no_replacement(label.as_bytes())
+        .ok_or_else(|| ConversionError::UnknownEncoding(label.to_string()))?;
+
+    let (utf8_str, had_lossy_decode) = decode_html_to_utf8_with_mode(html, encoding.name(), false)?;
+    let dom = parse_document(RcDom::default(), Default::default()).one(utf8_str.as_ref());
+
+    Ok((dom, had_lossy_decode))
+}
+
+/// Parse HTML bytes into a DOM tree, accepting a Windows numeric codepage
+/// as an alternative charset signal
+///
+/// Identical to [`parse_html_with_options`], except `codepage` lets a
+/// caller that only has a numeric Windows codepage (e.g. from a Windows
+/// API or a document format that stores charset that way) express it
+/// without first converting it to an IANA label themselves. See
+/// [`crate::charset::resolve_input_charset_with_codepage`] for the exact
+/// priority rules and which codepages are intentionally left unmapped.
+///
+/// # Arguments
+///
+/// * `html` - A byte slice containing HTML content
+/// * `content_type` - Optional Content-Type header value (e.g., "text/html; charset=UTF-8")
+/// * `input_charset` - Optional explicit charset label; wins over `codepage` if both are given
+/// * `codepage` - Optional Windows numeric codepage (e.g. `1252`, `936`, `932`)
+///
+/// # Returns
+///
+/// Returns `Ok((RcDom, had_lossy_decode))` on success, same as
+/// [`parse_html_with_options`].
+/// Returns `Err(ConversionError::InvalidInput)` if `html` is empty.
+///
+/// # Examples
+///
+/// ```rust
+/// use nginx_markdown_converter::parser::parse_html_with_codepage;
+///
+/// // Codepage 936 is Simplified Chinese GBK.
+/// let (encoded, _, _) = encoding_rs::GBK.encode("\u{4f60}\u{597d}");
+/// let mut html = b"<html><body>".to_vec();
+/// html.extend_from_slice(&encoded);
+/// html.extend_from_slice(b"</body></html>");
+/// let (_dom, had_lossy_decode) =
+///     parse_html_with_codepage(&html, None, None, Some(936)).expect("Failed to parse HTML");
+/// assert!(!had_lossy_decode);
+/// ```
+pub fn parse_html_with_codepage(
+    html: &[u8],
+    content_type: Option<&str>,
+    input_charset: Option<&str>,
+    codepage: Option<u32>,
+) -> Result<(RcDom, bool), ConversionError> {
+    if html.is_empty() {
+        return Err(ConversionError::InvalidInput(
+            "HTML input is empty".to_string(),
+        ));
+    }
+
+    let (detected_charset, bom_len) =
+        resolve_input_charset_with_codepage(input_charset, codepage, content_type, html);
+    let (utf8_str, had_lossy_decode) =
+        decode_html_to_utf8_with_mode(&html[bom_len..], &detected_charset, false)?;
+    let dom = parse_document(RcDom::default(), Default::default()).one(utf8_str.as_ref());
+
+    Ok((dom, had_lossy_decode))
+}
+
+/// Parse HTML bytes into a DOM tree, first undoing a `Content-Encoding`
+///
+/// An NGINX upstream's response body is frequently compressed
+/// (`Content-Encoding: gzip`, `br`, `deflate`, or `zstd`); parsing those
+/// bytes directly would yield garbage or an `EncodingError` from the
+/// charset cascade, which has no idea the input isn't HTML yet. This
+/// decompresses via [`crate::decompression::decompress_body`] before
+/// handing off to [`parse_html_with_options`], so charset sniffing always
+/// sees the real document bytes.
+///
+/// # Arguments
+///
+/// * `html` - A byte slice containing (possibly compressed) HTML content
+/// * `content_type` - Optional Content-Type header value (e.g., "text/html; charset=UTF-8")
+/// * `input_charset` - Optional explicit charset label that overrides all sniffing
+/// * `content_encoding` - Optional `Content-Encoding` header value (e.g., `"gzip"`); tokens are undone in reverse order
+/// * `max_decompressed_size` - Cap on decompressed byte size, to bound decompression-bomb input
+///
+/// # Returns
+///
+/// Returns `Ok((RcDom, had_lossy_decode))` on success, same as
+/// [`parse_html_with_options`].
+/// Returns `Err(ConversionError::EncodingError)` if `content_encoding` names
+/// an unsupported token or a compressed stream is malformed.
+/// Returns `Err(ConversionError::MemoryLimit)` if decompressing would
+/// exceed `max_decompressed_size`.
+/// Returns `Err(ConversionError::InvalidInput)` if `html` (after
+/// decompression) is empty.
+///
+/// # Examples
+///
+/// ```rust
+/// use nginx_markdown_converter::parser::parse_html_with_encoding;
+///
+/// let html = b"<html><body><h1>Hello</h1></body></html>";
+/// let (dom, _had_lossy_decode) =
+///     parse_html_with_encoding(html, None, None, None, 10 * 1024 * 1024)
+///         .expect("Failed to parse HTML");
+/// ```
+///
+/// # See Also
+///
+/// - [`parse_html_with_options`]: For parsing already-uncompressed input
+pub fn parse_html_with_encoding(
+    html: &[u8],
+    content_type: Option<&str>,
+    input_charset: Option<&str>,
+    content_encoding: Option<&str>,
+    max_decompressed_size: usize,
+) -> Result<(RcDom, bool), ConversionError> {
+    let decompressed = decompress_body(html, content_encoding, max_decompressed_size)?;
+    parse_html_with_options(decompressed.as_ref(), content_type, input_charset)
+}
+
+/// Incremental HTML parser that accepts bytes as they arrive
+///
+/// [`parse_html_with_options`] requires the whole document in one `&[u8]`
+/// before handing it to html5ever's `TendrilSink::one`, which forces a
+/// caller to buffer the full upstream body first. `HtmlParserStream` instead
+/// drives html5ever's push-based `TendrilSink::process`/`finish` directly,
+/// transcoding and feeding bytes through [`HtmlParserStream::push`] as each
+/// chunk arrives, so an NGINX body filter can start parsing a response
+/// before it has finished downloading. This mirrors the push-driven
+/// tokenizer model used by Servo's `ServoParser`.
+///
+/// Unlike [`parse_html_with_options`], the charset isn't sniffed here — BOM
+/// and `<meta charset>` detection both need to look ahead at bytes the
+/// caller may not have received yet. Resolve the charset once up front (via
+/// [`crate::charset::resolve_input_charset`] against the `Content-Type`
+/// header and the first chunk) and pass it to [`HtmlParserStream::new`].
+///
+/// # Chunk Boundaries
+///
+/// A multi-byte character can land on a chunk boundary (e.g. the 2nd byte of
+/// a 3-byte UTF-8 sequence, or a UTF-32 code unit split across two `push`
+/// calls). Non-UTF-32 charsets are decoded with a stateful
+/// `encoding_rs::Decoder`, which carries an incomplete sequence across calls
+/// internally. UTF-32, which `encoding_rs` doesn't implement (matching
+/// `decode_html_to_utf8`'s own hand-rolled UTF-32 path), keeps its own small
+/// carry buffer of up to 3 leftover bytes between calls.
+///
+/// This complements, rather than replaces, [`crate::incremental::IncrementalConverter`]:
+/// that type re-parses and re-converts the full buffered-so-far document on
+/// every chunk to approximate incremental *conversion*; `HtmlParserStream`
+/// instead incrementally feeds html5ever's own parser and only builds the
+/// `RcDom` once, on [`HtmlParserStream::finish`].
+pub struct HtmlParserStream {
+    parser: Parser<RcDom>,
+    decode_state: DecodeState,
+    had_lossy_decode: bool,
+}
+
+enum DecodeState {
+    EncodingRs(Box<encoding_rs::Decoder>),
+    Utf32 { little_endian: bool, carry: Vec<u8> },
+}
+
+impl HtmlParserStream {
+    /// Create a new incremental parser for a document in `detected_charset`
+    ///
+    /// `detected_charset` should already be resolved (e.g. via
+    /// [`crate::charset::resolve_input_charset`]); an unrecognized label
+    /// falls back to UTF-8, matching [`parse_html_with_options`].
+    pub fn new(detected_charset: &str) -> Self {
+        let decode_state = match detected_charset {
+            "UTF-32LE" => DecodeState::Utf32 {
+                little_endian: true,
+                carry: Vec::new(),
+            },
+            "UTF-32BE" => DecodeState::Utf32 {
+                little_endian: false,
+                carry: Vec::new(),
+            },
+            _ => {
+                let encoding = encoding_rs::Encoding::for_label(detected_charset.as_bytes())
+                    .unwrap_or(encoding_rs::UTF_8);
+                DecodeState::EncodingRs(Box::new(encoding.new_decoder_without_bom_handling()))
+            }
+        };
+
+        Self {
+            parser: parse_document(RcDom::default(), Default::default()),
+            decode_state,
+            had_lossy_decode: false,
+        }
+    }
+
+    /// Feed the next chunk of (possibly non-UTF-8) bytes to the parser
+    ///
+    /// Chunks may be any size and don't need to end on a character boundary;
+    /// an incomplete trailing multi-byte sequence is carried over and
+    /// completed by the next `push` call, or by [`HtmlParserStream::finish`]
+    /// if no more chunks arrive.
+    pub fn push(&mut self, chunk: &[u8]) {
+        if chunk.is_empty() {
+            return;
+        }
+        let decoded = self.decode_chunk(chunk, false);
+        if !decoded.is_empty() {
+            self.parser.process(StrTendril::from(decoded.as_str()));
+        }
+    }
+
+    /// Flush any carried partial sequence and build the final `RcDom`
+    ///
+    /// Returns the parsed DOM along with `had_lossy_decode`, `true` if any
+    /// byte sequence across all pushed chunks was invalid under the
+    /// configured charset and was replaced with U+FFFD.
+    pub fn finish(mut self) -> (RcDom, bool) {
+        let decoded = self.decode_chunk(&[], true);
+        if !decoded.is_empty() {
+            self.parser.process(StrTendril::from(decoded.as_str()));
+        }
+        let had_lossy_decode = self.had_lossy_decode;
+        (self.parser.finish(), had_lossy_decode)
+    }
+
+    fn decode_chunk(&mut self, chunk: &[u8], last: bool) -> String {
+        match &mut self.decode_state {
+            DecodeState::EncodingRs(decoder) => {
+                let mut output = String::new();
+                let mut remaining = chunk;
+                loop {
+                    let needed = decoder
+                        .max_utf8_buffer_length(remaining.len())
+                        .unwrap_or(remaining.len() * 4 + 32);
+                    output.reserve(needed);
+                    let (result, read, had_errors) =
+                        decoder.decode_to_string(remaining, &mut output, last);
+                    if had_errors {
+                        self.had_lossy_decode = true;
+                    }
+                    remaining = &remaining[read..];
+                    if result == encoding_rs::CoderResult::InputEmpty {
+                        break;
+                    }
+                }
+                output
+            }
+            DecodeState::Utf32 {
+                little_endian,
+                carry,
+            } => {
+                carry.extend_from_slice(chunk);
+
+                let mut output = String::with_capacity(carry.len() / 4);
+                let mut consumed = 0;
+                while carry.len() - consumed >= 4 {
+                    let unit: [u8; 4] = carry[consumed..consumed + 4]
+                        .try_into()
+                        .expect("slice of length 4");
+                    let code_point = if *little_endian {
+                        u32::from_le_bytes(unit)
+                    } else {
+                        u32::from_be_bytes(unit)
+                    };
+                    match char::from_u32(code_point) {
+                        Some(ch) => output.push(ch),
+                        None => {
+                            self.had_lossy_decode = true;
+                            output.push('\u{FFFD}');
+                        }
+                    }
+                    consumed += 4;
+                }
+                carry.drain(..consumed);
+
+                // A trailing partial unit only matters once no more chunks
+                // are coming; otherwise it's still waiting on its remaining
+                // bytes from the next `push` call.
+                if last && !carry.is_empty() {
+                    self.had_lossy_decode = true;
+                    output.push('\u{FFFD}');
+                    carry.clear();
+                }
+
+                output
+            }
+        }
+    }
+}
+
+fn decode_html_to_utf8<'a>(html: &'a [u8], detected_charset: &str) -> (Cow<'a, str>, bool) {
+    decode_html_to_utf8_with_mode(html, detected_charset, false)
+        .expect("lenient decoding never returns an error")
+}
+
+/// Decode `html` to UTF-8, optionally rejecting invalid byte sequences
+///
+/// See [`parse_html_with_decoding_mode`] for what `strict_decoding` changes.
+/// Lenient mode (`strict_decoding = false`) always returns `Ok`.
+fn decode_html_to_utf8_with_mode<'a>(
     html: &'a [u8],
     detected_charset: &str,
-) -> Result<Cow<'a, str>, ConversionError> {
-    if detected_charset.eq_ignore_ascii_case("UTF-8") {
-        return std::str::from_utf8(html).map(Cow::Borrowed).map_err(|e| {
-            ConversionError::EncodingError(format!(
-                "Invalid UTF-8 at byte position {}: {} (detected charset: {})",
-                e.valid_up_to(),
-                e,
-                detected_charset
-            ))
-        });
+    strict_decoding: bool,
+) -> Result<(Cow<'a, str>, bool), ConversionError> {
+    // UTF-32 isn't part of the Encoding Standard `encoding_rs` implements (no
+    // browser decodes it off the wire either), so it's handled directly
+    // rather than falling through to `Encoding::for_label`.
+    if let Some(decoded) = decode_utf32(html, detected_charset, strict_decoding)? {
+        return Ok(decoded);
     }
 
+    // An unrecognized charset label is treated the same as a real upstream
+    // that sent a typo'd or made-up charset: fall back to UTF-8 rather than
+    // rejecting the whole document.
     let encoding =
-        encoding_rs::Encoding::for_label(detected_charset.as_bytes()).ok_or_else(|| {
-            ConversionError::EncodingError(format!(
-                "Unsupported charset '{}' for HTML parsing",
-                detected_charset
-            ))
-        })?;
-
-    encoding
-        .decode_without_bom_handling_and_without_replacement(html)
-        .ok_or_else(|| {
-            ConversionError::EncodingError(format!(
-                "Invalid byte sequence for charset '{}'",
-                detected_charset
-            ))
-        })
+        encoding_rs::Encoding::for_label(detected_charset.as_bytes()).unwrap_or(encoding_rs::UTF_8);
+
+    // BOM handling already happened via `resolve_input_charset`/the caller
+    // skipping `bom_len`, so decode without re-sniffing a BOM here.
+    if strict_decoding {
+        let decoded = encoding
+            .decode_without_bom_handling_and_without_replacement(html)
+            .ok_or_else(|| {
+                ConversionError::EncodingError(format!(
+                    "invalid byte sequence for charset {}",
+                    encoding.name()
+                ))
+            })?;
+        return Ok((decoded, false));
+    }
+
+    // Malformed byte sequences are replaced with U+FFFD instead of failing
+    // the parse; `had_errors` reports whether that replacement actually
+    // happened.
+    Ok(encoding.decode_without_bom_handling(html))
+}
+
+/// Decode `html` as UTF-32LE/BE, returning `None` for any other charset
+///
+/// Each 4-byte unit is read in the given endianness and converted to a
+/// `char`. In lenient mode, an invalid scalar value or a trailing partial
+/// unit is replaced with U+FFFD, matching `encoding_rs`'s lossy-decode
+/// convention so `had_lossy_decode` means the same thing regardless of which
+/// path decoded the document. In strict mode, either condition is reported
+/// as [`ConversionError::EncodingError`] instead.
+fn decode_utf32(
+    html: &[u8],
+    detected_charset: &str,
+    strict_decoding: bool,
+) -> Result<Option<(Cow<'static, str>, bool)>, ConversionError> {
+    let little_endian = match detected_charset {
+        "UTF-32LE" => true,
+        "UTF-32BE" => false,
+        _ => return Ok(None),
+    };
+
+    let mut output = String::with_capacity(html.len() / 4);
+    let mut had_lossy_decode = false;
+
+    for chunk in html.chunks(4) {
+        let Ok(unit): Result<[u8; 4], _> = chunk.try_into() else {
+            if strict_decoding {
+                return Err(ConversionError::EncodingError(
+                    "truncated UTF-32 code unit".to_string(),
+                ));
+            }
+            had_lossy_decode = true;
+            output.push('\u{FFFD}');
+            break;
+        };
+        let code_point = if little_endian {
+            u32::from_le_bytes(unit)
+        } else {
+            u32::from_be_bytes(unit)
+        };
+        match char::from_u32(code_point) {
+            Some(ch) => output.push(ch),
+            None => {
+                if strict_decoding {
+                    return Err(ConversionError::EncodingError(format!(
+                        "invalid UTF-32 scalar value 0x{code_point:X}"
+                    )));
+                }
+                had_lossy_decode = true;
+                output.push('\u{FFFD}');
+            }
+        }
+    }
+
+    Ok(Some((Cow::Owned(output), had_lossy_decode)))
 }
 
 /// Parse HTML bytes into a DOM tree
@@ -192,7 +830,7 @@ fn decode_html_to_utf8<'a>(
 /// # Returns
 ///
 /// Returns `Ok(RcDom)` containing the parsed DOM tree on success.
-/// Returns `Err(ConversionError)` if parsing fails or encoding is invalid.
+/// Returns `Err(ConversionError)` if the input is empty.
 ///
 /// # Examples
 ///
@@ -244,15 +882,186 @@ mod tests {
     }
 
     #[test]
-    fn test_parse_invalid_utf8() {
-        // Invalid UTF-8 sequence
-        let html = b"\xFF\xFE<html><body>Invalid</body></html>";
-        let result = parse_html(html);
-        assert!(result.is_err(), "Should reject invalid UTF-8");
-        match result {
-            Err(ConversionError::EncodingError(_)) => (),
-            _ => panic!("Expected EncodingError"),
-        }
+    fn test_parse_malformed_utf8_is_replaced_not_rejected() {
+        // A lone continuation byte is invalid UTF-8 and isn't a recognized BOM,
+        // so it's replaced with U+FFFD rather than failing the parse.
+        let html = b"<html><body><p>Bad\x80byte</p></body></html>";
+        let dom = parse_html(html).expect("Malformed UTF-8 should decode, not error");
+
+        let markdown = MarkdownConverter::new()
+            .convert(&dom)
+            .expect("Conversion should succeed");
+        assert!(
+            markdown.contains('\u{FFFD}'),
+            "Expected replacement character in output, got: {markdown:?}"
+        );
+    }
+
+    #[test]
+    fn test_parse_html_with_detected_charset_reports_meta_charset() {
+        let html = b"<html><head><meta charset=\"ISO-8859-1\"></head><body>Caf\xe9</body></html>";
+        let (_dom, detected_charset, had_lossy_decode) =
+            parse_html_with_detected_charset(html, None, None).expect("Should parse HTML");
+        assert_eq!(detected_charset, "windows-1252");
+        assert!(!had_lossy_decode);
+    }
+
+    #[test]
+    fn test_parse_html_with_detected_charset_reports_utf8_default() {
+        let html = b"<html><body>No charset specified</body></html>";
+        let (_dom, detected_charset, had_lossy_decode) =
+            parse_html_with_detected_charset(html, None, None).expect("Should parse HTML");
+        assert_eq!(detected_charset, "UTF-8");
+        assert!(!had_lossy_decode);
+    }
+
+    #[test]
+    fn test_parse_html_with_detected_charset_honors_content_type_override() {
+        let html = b"<html><head><meta charset=\"UTF-8\"></head><body>Caf\xe9</body></html>";
+        let (_dom, detected_charset, had_lossy_decode) =
+            parse_html_with_detected_charset(html, Some("text/html; charset=ISO-8859-1"), None)
+                .expect("Should parse HTML");
+        assert_eq!(detected_charset, "windows-1252");
+        assert!(!had_lossy_decode);
+    }
+
+    #[test]
+    fn test_parse_html_with_detected_charset_rejects_empty_input() {
+        let result = parse_html_with_detected_charset(b"", None, None);
+        assert!(matches!(result, Err(ConversionError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_parse_html_with_charset_detection_rejects_empty_input() {
+        let result = parse_html_with_charset_detection(b"", None, None, true, None);
+        assert!(matches!(result, Err(ConversionError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_parse_html_with_charset_detection_declared_charset_is_never_guessed() {
+        let html = b"<html><head><meta charset=\"ISO-8859-1\"></head><body>Caf\xe9</body></html>";
+        let (_dom, detected_charset, had_lossy_decode, was_guessed) =
+            parse_html_with_charset_detection(html, None, None, true, None)
+                .expect("Should parse HTML");
+        assert_eq!(detected_charset, "windows-1252");
+        assert!(!had_lossy_decode);
+        assert!(!was_guessed);
+    }
+
+    #[test]
+    fn test_parse_html_with_charset_detection_disabled_defaults_to_utf8() {
+        let mut html = b"<html><body>Caf".to_vec();
+        html.extend_from_slice(&encoding_rs::WINDOWS_1252.encode("\u{e9}").0);
+        html.extend_from_slice(b" au lait</body></html>");
+        let (_dom, detected_charset, had_lossy_decode, was_guessed) =
+            parse_html_with_charset_detection(&html, None, None, false, None)
+                .expect("Should parse HTML");
+        assert_eq!(detected_charset, "UTF-8");
+        assert!(had_lossy_decode);
+        assert!(!was_guessed);
+    }
+
+    #[cfg(feature = "stat_charset_detect")]
+    #[test]
+    fn test_parse_html_with_charset_detection_guesses_undeclared_legacy_encoding() {
+        let mut html = b"<html><body>Caf".to_vec();
+        html.extend_from_slice(&encoding_rs::WINDOWS_1252.encode("\u{e9}").0);
+        html.extend_from_slice(b" au lait</body></html>");
+        let (_dom, detected_charset, had_lossy_decode, was_guessed) =
+            parse_html_with_charset_detection(&html, None, None, true, None)
+                .expect("Should parse HTML");
+        assert_eq!(detected_charset, "windows-1252");
+        assert!(!had_lossy_decode);
+        assert!(was_guessed);
+    }
+
+    #[test]
+    fn test_parse_html_with_forced_encoding_overrides_lying_meta_tag() {
+        let html = b"<html><head><meta charset=\"UTF-8\"></head><body>Caf\xe9</body></html>";
+        let (dom, had_lossy_decode) =
+            parse_html_with_forced_encoding(html, None, None, Some("ISO-8859-1"))
+                .expect("Should parse HTML");
+        assert!(!had_lossy_decode);
+
+        let markdown = MarkdownConverter::new()
+            .convert(&dom)
+            .expect("Conversion should succeed");
+        assert!(markdown.contains("Caf\u{e9}"));
+    }
+
+    #[test]
+    fn test_parse_html_with_forced_encoding_overrides_bom() {
+        let html = b"\xEF\xBB\xBF<html><body>Caf\xe9</body></html>";
+        let (_dom, had_lossy_decode) =
+            parse_html_with_forced_encoding(html, None, None, Some("ISO-8859-1"))
+                .expect("Should parse HTML");
+        assert!(!had_lossy_decode);
+    }
+
+    #[test]
+    fn test_parse_html_with_forced_encoding_rejects_unknown_label() {
+        let html = b"<html><body>Hello</body></html>";
+        let result = parse_html_with_forced_encoding(html, None, None, Some("not-a-real-encoding"));
+        assert!(matches!(result, Err(ConversionError::UnknownEncoding(label)) if label == "not-a-real-encoding"));
+    }
+
+    #[test]
+    fn test_parse_html_with_forced_encoding_rejects_replacement_label() {
+        let html = b"<html><body>Hello</body></html>";
+        let result = parse_html_with_forced_encoding(html, None, None, Some("replacement"));
+        assert!(matches!(result, Err(ConversionError::UnknownEncoding(_))));
+    }
+
+    #[test]
+    fn test_parse_html_with_forced_encoding_none_falls_back_to_options() {
+        let html = b"<html><head><meta charset=\"ISO-8859-1\"></head><body>Caf\xe9</body></html>";
+        let (_dom, had_lossy_decode) =
+            parse_html_with_forced_encoding(html, None, None, None).expect("Should parse HTML");
+        assert!(!had_lossy_decode);
+    }
+
+    #[test]
+    fn test_parse_html_with_forced_encoding_rejects_empty_input() {
+        let result = parse_html_with_forced_encoding(b"", None, None, Some("UTF-8"));
+        assert!(matches!(result, Err(ConversionError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_parse_html_with_codepage_decodes_gbk() {
+        let (encoded, _, _) = encoding_rs::GBK.encode("\u{4f60}\u{597d}");
+        let mut html = b"<html><body>".to_vec();
+        html.extend_from_slice(&encoded);
+        html.extend_from_slice(b"</body></html>");
+        let (_dom, had_lossy_decode) =
+            parse_html_with_codepage(&html, None, None, Some(936)).expect("Should parse HTML");
+        assert!(!had_lossy_decode);
+    }
+
+    #[test]
+    fn test_parse_html_with_codepage_overrides_meta_charset() {
+        let html = b"<html><head><meta charset=\"UTF-8\"></head><body>Caf\xe9</body></html>";
+        let (dom, had_lossy_decode) =
+            parse_html_with_codepage(html, None, None, Some(1252)).expect("Should parse HTML");
+        assert!(!had_lossy_decode);
+
+        let markdown = MarkdownConverter::new()
+            .convert(&dom)
+            .expect("Conversion should succeed");
+        assert!(markdown.contains("Caf\u{e9}"));
+    }
+
+    #[test]
+    fn test_parse_html_with_codepage_unmapped_falls_through_to_meta() {
+        let html = b"<html><head><meta charset=\"ISO-8859-1\"></head><body>Caf\xe9</body></html>";
+        let (_dom, had_lossy_decode) =
+            parse_html_with_codepage(html, None, None, Some(1)).expect("Should parse HTML");
+        assert!(!had_lossy_decode);
+    }
+
+    #[test]
+    fn test_parse_html_with_codepage_rejects_empty_input() {
+        let result = parse_html_with_codepage(b"", None, None, Some(1252));
+        assert!(matches!(result, Err(ConversionError::InvalidInput(_))));
     }
 
     #[test]
@@ -394,17 +1203,181 @@ mod tests {
     }
 
     #[test]
-    fn test_parse_with_unknown_charset_returns_encoding_error() {
+    fn test_parse_with_unknown_charset_defaults_to_utf8() {
         let html = b"<html><body><p>Hello</p></body></html>";
-        let result = parse_html_with_charset(html, Some("text/html; charset=x-unknown-test"));
+        let dom = parse_html_with_charset(html, Some("text/html; charset=x-unknown-test"))
+            .expect("Unrecognized charset label should fall back to UTF-8, not error");
 
-        match result {
-            Err(ConversionError::EncodingError(message)) => {
-                assert!(message.contains("Unsupported charset"));
-            }
-            Ok(_) => panic!("Expected EncodingError for unknown charset, got Ok(_)"),
-            Err(err) => panic!("Expected EncodingError for unknown charset, got: {err}"),
+        let markdown = MarkdownConverter::new()
+            .convert(&dom)
+            .expect("Conversion should succeed");
+        assert!(markdown.contains("Hello"));
+    }
+
+    #[test]
+    fn test_parse_with_utf8_bom_is_stripped() {
+        let html = b"\xEF\xBB\xBF<html><body><p>Hello</p></body></html>";
+        let dom = parse_html(html).expect("Should parse UTF-8 BOM input");
+
+        let markdown = MarkdownConverter::new()
+            .convert(&dom)
+            .expect("Conversion should succeed");
+        assert!(
+            !markdown.contains('\u{FEFF}'),
+            "BOM should not leak into output"
+        );
+        assert!(markdown.contains("Hello"));
+    }
+
+    /// Encode `text` as UTF-32 (little- or big-endian) with a leading BOM,
+    /// for exercising the UTF-32 BOM detection/decode path
+    fn utf32_encode(text: &str, little_endian: bool) -> Vec<u8> {
+        let bom: [u8; 4] = if little_endian {
+            [0xFF, 0xFE, 0x00, 0x00]
+        } else {
+            [0x00, 0x00, 0xFE, 0xFF]
+        };
+        let mut bytes = bom.to_vec();
+        for ch in text.chars() {
+            let code_point = ch as u32;
+            bytes.extend_from_slice(&if little_endian {
+                code_point.to_le_bytes()
+            } else {
+                code_point.to_be_bytes()
+            });
         }
+        bytes
+    }
+
+    #[test]
+    fn test_parse_with_utf32le_bom_is_decoded() {
+        let html = utf32_encode("<html><body><p>Hello</p></body></html>", true);
+        let (dom, had_lossy_decode) =
+            parse_html_with_options(&html, None, None).expect("Should decode UTF-32LE input");
+        assert!(!had_lossy_decode);
+
+        let markdown = MarkdownConverter::new()
+            .convert(&dom)
+            .expect("Conversion should succeed");
+        assert!(markdown.contains("Hello"));
+    }
+
+    #[test]
+    fn test_parse_with_utf32be_bom_is_decoded() {
+        let html = utf32_encode("<html><body><p>Hello</p></body></html>", false);
+        let (dom, had_lossy_decode) =
+            parse_html_with_options(&html, None, None).expect("Should decode UTF-32BE input");
+        assert!(!had_lossy_decode);
+
+        let markdown = MarkdownConverter::new()
+            .convert(&dom)
+            .expect("Conversion should succeed");
+        assert!(markdown.contains("Hello"));
+    }
+
+    #[test]
+    fn test_parse_with_input_charset_override_wins_over_meta() {
+        // Meta tag claims UTF-8, but the body is actually ISO-8859-1 encoded "é".
+        let html = b"<html><head><meta charset=\"UTF-8\"></head><body><p>Caf\xE9</p></body></html>";
+        let (dom, had_lossy_decode) = parse_html_with_options(html, None, Some("ISO-8859-1"))
+            .expect("Should honor the explicit override");
+        assert!(
+            !had_lossy_decode,
+            "ISO-8859-1 override should decode cleanly"
+        );
+
+        let markdown = MarkdownConverter::new()
+            .convert(&dom)
+            .expect("Conversion should succeed");
+        assert!(
+            markdown.contains("Café"),
+            "Expected override charset to be used over meta, got: {markdown:?}"
+        );
+    }
+
+    #[test]
+    fn test_had_lossy_decode_flags_invalid_utf8() {
+        // A lone continuation byte is invalid UTF-8 and triggers replacement.
+        let html = b"<html><body><p>Bad\x80byte</p></body></html>";
+        let (_dom, had_lossy_decode) =
+            parse_html_with_options(html, None, None).expect("Should decode, not error");
+        assert!(
+            had_lossy_decode,
+            "Malformed UTF-8 byte should be reported as lossy"
+        );
+    }
+
+    #[test]
+    fn test_had_lossy_decode_false_for_clean_input() {
+        let html = b"<html><body><p>Hello</p></body></html>";
+        let (_dom, had_lossy_decode) =
+            parse_html_with_options(html, None, None).expect("Should parse cleanly");
+        assert!(
+            !had_lossy_decode,
+            "Clean UTF-8 input should not be reported as lossy"
+        );
+    }
+
+    #[test]
+    fn test_strict_decoding_rejects_invalid_utf8() {
+        let html = b"<html><body><p>Bad\x80byte</p></body></html>";
+        let result = parse_html_with_decoding_mode(html, None, None, true);
+        assert!(matches!(result, Err(ConversionError::EncodingError(_))));
+    }
+
+    #[test]
+    fn test_lenient_decoding_is_the_default_and_unchanged() {
+        // Same malformed input as the strict test, but through the existing
+        // `parse_html_with_options` entry point: still replaces, not errors.
+        let html = b"<html><body><p>Bad\x80byte</p></body></html>";
+        let (dom, had_lossy_decode) =
+            parse_html_with_options(html, None, None).expect("Should decode, not error");
+        assert!(had_lossy_decode);
+
+        let markdown = MarkdownConverter::new()
+            .convert(&dom)
+            .expect("Conversion should succeed");
+        assert!(markdown.contains('\u{FFFD}'));
+    }
+
+    #[test]
+    fn test_strict_decoding_accepts_clean_input() {
+        let html = b"<html><body><p>Hello</p></body></html>";
+        let (dom, had_lossy_decode) = parse_html_with_decoding_mode(html, None, None, true)
+            .expect("Clean UTF-8 should pass strict decoding");
+        assert!(!had_lossy_decode);
+
+        let markdown = MarkdownConverter::new()
+            .convert(&dom)
+            .expect("Conversion should succeed");
+        assert!(markdown.contains("Hello"));
+    }
+
+    #[test]
+    fn test_strict_decoding_rejects_invalid_iso_8859_charset_mismatch() {
+        // windows-1252 leaves bytes 0x81, 0x8D, 0x8F, 0x90, 0x9D undefined;
+        // encoding_rs treats those as invalid under strict decoding.
+        let html = b"<html><body><p>Bad\x81byte</p></body></html>";
+        let result =
+            parse_html_with_decoding_mode(html, Some("text/html; charset=windows-1252"), None, true);
+        assert!(matches!(result, Err(ConversionError::EncodingError(_))));
+    }
+
+    #[test]
+    fn test_strict_decoding_rejects_truncated_utf32() {
+        let mut html = utf32_encode("<html><body><p>Hi</p></body></html>", true);
+        html.truncate(html.len() - 2); // chop mid code-unit
+        let result = parse_html_with_decoding_mode(&html, None, Some("UTF-32LE"), true);
+        assert!(matches!(result, Err(ConversionError::EncodingError(_))));
+    }
+
+    #[test]
+    fn test_lenient_decoding_replaces_truncated_utf32() {
+        let mut html = utf32_encode("<html><body><p>Hi</p></body></html>", true);
+        html.truncate(html.len() - 2);
+        let (_dom, had_lossy_decode) =
+            parse_html_with_options(&html, None, Some("UTF-32LE")).expect("Should decode, not error");
+        assert!(had_lossy_decode);
     }
 
     #[test]
@@ -437,6 +1410,165 @@ mod tests {
         assert!(result.is_ok(), "Should parse emoji");
     }
 
+    #[test]
+    fn test_parse_with_encoding_no_content_encoding_parses_directly() {
+        let html = b"<html><body><h1>Hello</h1></body></html>";
+        let (dom, had_lossy_decode) =
+            parse_html_with_encoding(html, None, None, None, 1024 * 1024)
+                .expect("Should parse uncompressed HTML");
+        assert!(!had_lossy_decode);
+
+        let markdown = MarkdownConverter::new()
+            .convert(&dom)
+            .expect("Conversion should succeed");
+        assert!(markdown.contains("Hello"));
+    }
+
+    #[test]
+    fn test_parse_with_encoding_decompresses_gzip_body() {
+        use std::io::Write;
+
+        let html = b"<html><body><h1>Compressed</h1></body></html>";
+        let mut encoder =
+            flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(html).expect("gzip compression failed");
+        let compressed = encoder.finish().expect("gzip compression failed");
+
+        let (dom, _had_lossy_decode) =
+            parse_html_with_encoding(&compressed, None, None, Some("gzip"), 1024 * 1024)
+                .expect("Should decompress and parse gzip body");
+
+        let markdown = MarkdownConverter::new()
+            .convert(&dom)
+            .expect("Conversion should succeed");
+        assert!(markdown.contains("Compressed"));
+    }
+
+    #[test]
+    fn test_parse_with_encoding_over_cap_is_memory_limit() {
+        use std::io::Write;
+
+        let html = b"<html><body><h1>Hello</h1></body></html>".repeat(100);
+        let mut encoder =
+            flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&html).expect("gzip compression failed");
+        let compressed = encoder.finish().expect("gzip compression failed");
+
+        let result = parse_html_with_encoding(&compressed, None, None, Some("gzip"), 16);
+        assert!(matches!(result, Err(ConversionError::MemoryLimit { .. })));
+    }
+
+    #[test]
+    fn test_html_parser_stream_single_push() {
+        let mut stream = HtmlParserStream::new("UTF-8");
+        stream.push(b"<html><body><h1>Hello</h1></body></html>");
+        let (dom, had_lossy_decode) = stream.finish();
+        assert!(!had_lossy_decode);
+
+        let markdown = MarkdownConverter::new()
+            .convert(&dom)
+            .expect("Conversion should succeed");
+        assert!(markdown.contains("Hello"));
+    }
+
+    #[test]
+    fn test_html_parser_stream_splits_across_many_small_chunks() {
+        let html = b"<html><body><h1>Chunked Hello</h1></body></html>";
+        let mut stream = HtmlParserStream::new("UTF-8");
+        for byte in html {
+            stream.push(std::slice::from_ref(byte));
+        }
+        let (dom, had_lossy_decode) = stream.finish();
+        assert!(!had_lossy_decode);
+
+        let markdown = MarkdownConverter::new()
+            .convert(&dom)
+            .expect("Conversion should succeed");
+        assert!(markdown.contains("Chunked Hello"));
+    }
+
+    #[test]
+    fn test_html_parser_stream_splits_multibyte_utf8_across_chunks() {
+        // "世界" is two 3-byte UTF-8 sequences; split mid-sequence to exercise
+        // the encoding_rs::Decoder's internal carry state.
+        let html = "<html><body><p>\u{4e16}\u{754c}</p></body></html>".as_bytes();
+        let split_at = html
+            .windows(3)
+            .position(|w| w == [0xE4, 0xB8, 0x96])
+            .expect("fixture should contain the expected UTF-8 sequence")
+            + 1;
+
+        let mut stream = HtmlParserStream::new("UTF-8");
+        stream.push(&html[..split_at]);
+        stream.push(&html[split_at..]);
+        let (dom, had_lossy_decode) = stream.finish();
+        assert!(!had_lossy_decode);
+
+        let markdown = MarkdownConverter::new()
+            .convert(&dom)
+            .expect("Conversion should succeed");
+        assert!(markdown.contains('\u{4e16}'));
+        assert!(markdown.contains('\u{754c}'));
+    }
+
+    #[test]
+    fn test_html_parser_stream_transcodes_non_utf8_charset() {
+        // "Café" encoded as ISO-8859-1 (0xE9 is invalid UTF-8 on its own).
+        let html: &[u8] = b"<html><body><p>Caf\xE9</p></body></html>";
+        let mut stream = HtmlParserStream::new("ISO-8859-1");
+        stream.push(&html[..html.len() - 5]);
+        stream.push(&html[html.len() - 5..]);
+        let (dom, had_lossy_decode) = stream.finish();
+        assert!(!had_lossy_decode);
+
+        let markdown = MarkdownConverter::new()
+            .convert(&dom)
+            .expect("Conversion should succeed");
+        assert!(markdown.contains("Café"));
+    }
+
+    #[test]
+    fn test_html_parser_stream_reports_lossy_decode_for_invalid_bytes() {
+        let html: &[u8] = b"<html><body><p>Bad\x80byte</p></body></html>";
+        let mut stream = HtmlParserStream::new("UTF-8");
+        stream.push(html);
+        let (_dom, had_lossy_decode) = stream.finish();
+        assert!(had_lossy_decode);
+    }
+
+    #[test]
+    fn test_html_parser_stream_splits_utf32_code_unit_across_chunks() {
+        let html = utf32_encode("<html><body><p>Hello</p></body></html>", true);
+        // Split 2 bytes into the first UTF-32 code unit past the BOM, so the
+        // carry buffer has to hold a partial unit across the `push` call.
+        let split_at = 4 + 2;
+
+        let mut stream = HtmlParserStream::new("UTF-32LE");
+        stream.push(&html[..split_at]);
+        stream.push(&html[split_at..]);
+        let (dom, had_lossy_decode) = stream.finish();
+        assert!(!had_lossy_decode);
+
+        let markdown = MarkdownConverter::new()
+            .convert(&dom)
+            .expect("Conversion should succeed");
+        assert!(markdown.contains("Hello"));
+    }
+
+    #[test]
+    fn test_html_parser_stream_empty_push_is_noop() {
+        let mut stream = HtmlParserStream::new("UTF-8");
+        stream.push(b"<html><body><p>Hi</p>");
+        stream.push(b"");
+        stream.push(b"</body></html>");
+        let (dom, _had_lossy_decode) = stream.finish();
+
+        let markdown = MarkdownConverter::new()
+            .convert(&dom)
+            .expect("Conversion should succeed");
+        assert!(markdown.contains("Hi"));
+    }
+
     // ============================================================================
     // Property-Based Tests
     // ============================================================================
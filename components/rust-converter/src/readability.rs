@@ -0,0 +1,526 @@
+//! Readability-style main-content extraction
+//!
+//! Implements the Mozilla/arc90 Readability scoring algorithm to isolate a
+//! document's primary article body from navigation, sidebars, ads, and other
+//! boilerplate before Markdown conversion. Opt in via
+//! [`crate::converter::ConversionOptions::readability_mode`]; metadata
+//! extraction for front matter still runs over the full, unpruned document
+//! regardless of this setting.
+//!
+//! # Algorithm
+//!
+//! 1. Walk the tree, skipping `<script>`/`<style>`/`<noscript>` subtrees
+//!    entirely — neither scored nor counted toward surrounding text.
+//! 2. Score every `<p>` and text-bearing `<div>` (more than 25 characters of
+//!    text): a base score by tag, +1 per comma, and +1 per 100 characters of
+//!    text, capped at +3.
+//! 3. Add each candidate's score fully to its parent and half to its
+//!    grandparent, accumulating across candidates so a container with many
+//!    scored children rises to the top.
+//! 4. Multiply each accumulated candidate's score by `(1 - linkDensity)`,
+//!    where link density is the fraction of its text sitting inside `<a>`
+//!    descendants — this demotes link-heavy navigation and boilerplate.
+//! 5. The highest-scoring candidate becomes the article root. Its siblings
+//!    are appended alongside it when their own score exceeds
+//!    `max(10, topScore * 0.2)`, or they share the root's `class` attribute.
+//!
+//! [`extract_main_content`] returns a synthetic container node holding the
+//! selected nodes in document order, ready to hand to
+//! [`crate::converter::MarkdownConverter`] in place of `dom.document`.
+
+use markup5ever_rcdom::{Handle, Node, NodeData, RcDom};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Minimum text length (in characters) for a `<p>`/`<div>` to be scored as a
+/// content candidate
+const MIN_CANDIDATE_TEXT_LEN: usize = 25;
+
+/// A container node accumulating score contributions from scored children
+struct Candidate {
+    handle: Handle,
+    score: f64,
+}
+
+/// Extract the main article content from a parsed document
+///
+/// Returns a synthetic node containing the highest-scoring candidate and any
+/// qualifying siblings, in document order. Falls back to `dom.document`
+/// itself (i.e. the whole document, unchanged) when no `<p>` or `<div>` in
+/// the document has enough text to be scored.
+pub fn extract_main_content(dom: &RcDom) -> Handle {
+    let mut scores: HashMap<usize, Candidate> = HashMap::new();
+    let mut parents: HashMap<usize, Handle> = HashMap::new();
+    score_candidates(&dom.document, None, None, &mut scores, &mut parents);
+
+    for candidate in scores.values_mut() {
+        candidate.score *= 1.0 - link_density(&candidate.handle);
+    }
+
+    let Some(top) = scores
+        .values()
+        .max_by(|a, b| a.score.total_cmp(&b.score))
+    else {
+        return dom.document.clone();
+    };
+    let top_handle = top.handle.clone();
+    let top_score = top.score;
+
+    let Some(parent) = parents.get(&ptr_key(&top_handle)) else {
+        return top_handle;
+    };
+
+    let top_class = element_attr(&top_handle, "class");
+    let threshold = (top_score * 0.2).max(10.0);
+
+    let container = Node::new(NodeData::Document);
+    {
+        let mut kids = container.children.borrow_mut();
+        for sibling in parent.children.borrow().iter() {
+            if Rc::ptr_eq(sibling, &top_handle) {
+                kids.push(sibling.clone());
+                continue;
+            }
+            let passes_score = scores
+                .get(&ptr_key(sibling))
+                .is_some_and(|candidate| candidate.score > threshold);
+            if passes_score || shares_class(sibling, top_class.as_deref()) {
+                kids.push(sibling.clone());
+            }
+        }
+    }
+    container
+}
+
+/// Recursively score every `<p>`/`<div>` candidate, crediting its parent and
+/// grandparent, while also recording each visited node's parent so the
+/// eventual top candidate's siblings can be found afterward
+fn score_candidates(
+    node: &Handle,
+    parent: Option<&Handle>,
+    grandparent: Option<&Handle>,
+    scores: &mut HashMap<usize, Candidate>,
+    parents: &mut HashMap<usize, Handle>,
+) {
+    if let Some(p) = parent {
+        parents.insert(ptr_key(node), p.clone());
+    }
+
+    let tag = match &node.data {
+        NodeData::Document => None,
+        NodeData::Element { name, .. } => {
+            let tag = name.local.as_ref();
+            if is_skip_tag(tag) {
+                return;
+            }
+            Some(tag)
+        }
+        _ => return,
+    };
+
+    let is_candidate = match tag {
+        Some("p") => true,
+        // A `<div>` only stands as its own candidate when it's being used as
+        // a leaf paragraph substitute (no nested `<p>`/`<div>`); otherwise
+        // it's a wrapper whose nested candidates already credit it via the
+        // parent/grandparent propagation below, and scoring it too would
+        // double-count that text.
+        Some("div") => !has_nested_candidate(node),
+        _ => false,
+    };
+
+    if is_candidate {
+        let mut text = String::new();
+        extract_text(node, &mut text);
+        let trimmed = text.trim();
+        let len = trimmed.chars().count();
+        if len > MIN_CANDIDATE_TEXT_LEN {
+            let score = tag_base_score(tag.unwrap_or(""))
+                + comma_count(trimmed) as f64
+                + (len as f64 / 100.0).floor().min(3.0);
+            if let Some(p) = parent {
+                add_score(scores, p, score);
+            }
+            if let Some(gp) = grandparent {
+                add_score(scores, gp, score / 2.0);
+            }
+        }
+    }
+
+    let next_parent = Some(node);
+    for child in node.children.borrow().iter() {
+        score_candidates(child, next_parent, parent, scores, parents);
+    }
+}
+
+/// Whether any descendant of `node` is itself a `<p>` or `<div>` — used to
+/// keep a wrapper `<div>` from being scored as its own candidate on top of
+/// the nested candidates it already gets credited for
+fn has_nested_candidate(node: &Handle) -> bool {
+    node.children.borrow().iter().any(|child| match &child.data {
+        NodeData::Element { name, .. } => {
+            let tag = name.local.as_ref();
+            tag == "p" || tag == "div" || (!is_skip_tag(tag) && has_nested_candidate(child))
+        }
+        _ => false,
+    })
+}
+
+/// Base content score contributed by a candidate's own tag, per the
+/// Mozilla/arc90 scoring table (only the tags this module scores: `<div>`
+/// and `<p>`)
+fn tag_base_score(tag: &str) -> f64 {
+    match tag {
+        "div" => 5.0,
+        _ => 0.0,
+    }
+}
+
+/// Add `delta` to a container's accumulated score, initializing it at `0.0`
+/// on first contribution
+fn add_score(scores: &mut HashMap<usize, Candidate>, handle: &Handle, delta: f64) {
+    scores
+        .entry(ptr_key(handle))
+        .or_insert_with(|| Candidate {
+            handle: handle.clone(),
+            score: 0.0,
+        })
+        .score += delta;
+}
+
+/// Number of commas in a candidate's text, one content-score point each
+fn comma_count(text: &str) -> usize {
+    text.matches(',').count()
+}
+
+/// Fraction of a node's text that sits inside `<a>` descendants
+fn link_density(node: &Handle) -> f64 {
+    let mut total = String::new();
+    extract_text(node, &mut total);
+    let total_len = total.chars().count();
+    if total_len == 0 {
+        return 0.0;
+    }
+
+    let mut link_len = 0usize;
+    accumulate_link_text(node, &mut link_len);
+    link_len as f64 / total_len as f64
+}
+
+/// Sum the text length of every `<a>` descendant of `node`
+fn accumulate_link_text(node: &Handle, link_len: &mut usize) {
+    match &node.data {
+        NodeData::Element { name, .. } if name.local.as_ref() == "a" => {
+            let mut text = String::new();
+            extract_text(node, &mut text);
+            *link_len += text.chars().count();
+        }
+        NodeData::Element { name, .. } if is_skip_tag(name.local.as_ref()) => {}
+        NodeData::Element { .. } | NodeData::Document => {
+            for child in node.children.borrow().iter() {
+                accumulate_link_text(child, link_len);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Collect a node's text content, skipping `<script>`/`<style>`/`<noscript>`
+/// subtrees entirely
+fn extract_text(node: &Handle, output: &mut String) {
+    match &node.data {
+        NodeData::Text { contents } => output.push_str(&contents.borrow()),
+        NodeData::Element { name, .. } if is_skip_tag(name.local.as_ref()) => {}
+        NodeData::Element { .. } | NodeData::Document => {
+            for child in node.children.borrow().iter() {
+                extract_text(child, output);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Whether a sibling's `class` attribute matches the winning candidate's
+fn shares_class(node: &Handle, winner_class: Option<&str>) -> bool {
+    match winner_class {
+        Some(class) if !class.is_empty() => element_attr(node, "class").as_deref() == Some(class),
+        _ => false,
+    }
+}
+
+/// Read a single attribute's value off an element node
+fn element_attr(node: &Handle, attr: &str) -> Option<String> {
+    if let NodeData::Element { ref attrs, .. } = node.data {
+        attrs
+            .borrow()
+            .iter()
+            .find(|a| a.name.local.as_ref() == attr)
+            .map(|a| a.value.to_string())
+    } else {
+        None
+    }
+}
+
+fn is_skip_tag(tag: &str) -> bool {
+    matches!(tag, "script" | "style" | "noscript")
+}
+
+fn ptr_key(handle: &Handle) -> usize {
+    Rc::as_ptr(handle) as usize
+}
+
+/// Tags [`strip_boilerplate`] always drops, independent of any `class`/`id`
+/// pattern match
+const BOILERPLATE_TAGS: &[&str] = &["nav", "header", "footer", "aside", "script", "style"];
+
+/// Default `class`/`id` substrings [`strip_boilerplate`] checks when a
+/// caller hasn't configured
+/// [`crate::converter::ConversionOptions::boilerplate_class_patterns`]
+pub const DEFAULT_BOILERPLATE_CLASS_PATTERNS: &[&str] = &["sidebar", "menu", "cookie", "ad"];
+
+/// Strip navigational and boilerplate chrome from a document before
+/// conversion, analogous to Zed's rustdoc "chrome remover" handler
+///
+/// Opt in via [`crate::converter::ConversionOptions::remove_boilerplate`].
+/// Unlike [`extract_main_content`]'s content-scoring approach, this is a
+/// simple, predictable pattern match: every [`BOILERPLATE_TAGS`] element is
+/// dropped outright, and so is any element whose `class`/`id` contains one
+/// of `class_patterns` as a case-insensitive substring. Mutates `dom` in
+/// place; like [`crate::converter::MarkdownConverter::hoist_block_from_inline`],
+/// displaced children's `parent` pointers are left stale, since nothing
+/// downstream in this crate reads them.
+pub fn strip_boilerplate(dom: &RcDom, class_patterns: &[String]) {
+    prune_boilerplate(&dom.document, class_patterns);
+}
+
+/// Recursively drop boilerplate children of `node`, depth-first so a
+/// surviving child's own boilerplate descendants are pruned too
+fn prune_boilerplate(node: &Handle, class_patterns: &[String]) {
+    let mut children = node.children.borrow_mut();
+    children.retain(|child| !is_boilerplate(child, class_patterns));
+    for child in children.iter() {
+        prune_boilerplate(child, class_patterns);
+    }
+}
+
+/// Whether `node` is a tag [`strip_boilerplate`] always drops, or carries a
+/// `class`/`id` matching one of `class_patterns`
+fn is_boilerplate(node: &Handle, class_patterns: &[String]) -> bool {
+    let NodeData::Element {
+        ref name,
+        ref attrs,
+        ..
+    } = node.data
+    else {
+        return false;
+    };
+
+    if BOILERPLATE_TAGS.contains(&name.local.as_ref()) {
+        return true;
+    }
+
+    let attrs = attrs.borrow();
+    let class_and_id: String = attrs
+        .iter()
+        .filter(|a| matches!(a.name.local.as_ref(), "class" | "id"))
+        .map(|a| a.value.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+
+    class_patterns
+        .iter()
+        .any(|pattern| !pattern.is_empty() && class_and_id.contains(&pattern.to_lowercase()))
+}
+
+/// The first `<main>` or `<article>` element in the document, or the whole
+/// document if neither is present
+///
+/// Used after [`strip_boilerplate`] to prefer the primary-content subtree
+/// when the page marks it explicitly, without the scoring pass
+/// [`extract_main_content`] would otherwise require.
+pub fn preferred_content_root(dom: &RcDom) -> Handle {
+    find_main_or_article(&dom.document).unwrap_or_else(|| dom.document.clone())
+}
+
+fn find_main_or_article(node: &Handle) -> Option<Handle> {
+    if let NodeData::Element { ref name, .. } = node.data
+        && matches!(name.local.as_ref(), "main" | "article")
+    {
+        return Some(node.clone());
+    }
+
+    node.children
+        .borrow()
+        .iter()
+        .find_map(find_main_or_article)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::converter::{ConversionOptions, MarkdownConverter};
+    use crate::parser::parse_html;
+
+    /// Convert with readability mode enabled, so assertions read like the
+    /// rest of the conversion pipeline's output instead of raw DOM structure
+    fn extract_markdown(html: &[u8]) -> String {
+        let dom = parse_html(html).unwrap();
+        let converter = MarkdownConverter::with_options(ConversionOptions {
+            readability_mode: true,
+            ..Default::default()
+        });
+        converter.convert(&dom).unwrap()
+    }
+
+    #[test]
+    fn test_picks_the_article_div_over_a_short_nav() {
+        let html = b"<html><body>
+            <nav><a href=\"/a\">A</a> <a href=\"/b\">B</a></nav>
+            <div class=\"article\">
+                <p>This is the very first paragraph of a long, meaningful article, with plenty of punctuation, clauses, and content to score highly.</p>
+                <p>And a second paragraph that keeps going, adding more substantial text, commas, and narrative detail to the page.</p>
+            </div>
+        </body></html>";
+        let markdown = extract_markdown(html);
+
+        assert!(markdown.contains("very first paragraph"));
+        assert!(!markdown.contains("\"/a\""));
+    }
+
+    #[test]
+    fn test_link_heavy_div_is_demoted() {
+        let html = b"<html><body>
+            <div class=\"links\">
+                <p><a href=\"/1\">Link one, a long anchor with lots of words</a>, <a href=\"/2\">link two, another long anchor</a>, and more link text to pad this out.</p>
+            </div>
+            <div class=\"content\">
+                <p>Plain article text with no links at all, just sentences, commas, and narrative, long enough to score well on its own merits.</p>
+            </div>
+        </body></html>";
+        let markdown = extract_markdown(html);
+
+        assert!(markdown.contains("Plain article text"));
+        assert!(!markdown.contains("Link one"));
+    }
+
+    #[test]
+    fn test_sibling_sharing_winners_class_is_included() {
+        let html = b"<html><body>
+            <div class=\"post\">
+                <p>The opening paragraph of the article, long enough with commas, clauses, and detail to score as a real candidate.</p>
+            </div>
+            <div class=\"post\">
+                <p>A continuation paragraph in a sibling div sharing the same class as the winning candidate above, so it should be kept too.</p>
+            </div>
+            <div class=\"ad\"><p>Buy now!</p></div>
+        </body></html>";
+        let markdown = extract_markdown(html);
+
+        assert!(markdown.contains("opening paragraph"));
+        assert!(markdown.contains("continuation paragraph"));
+        assert!(!markdown.contains("Buy now"));
+    }
+
+    #[test]
+    fn test_falls_back_to_whole_document_when_nothing_scores() {
+        let html = b"<html><body><p>short</p></body></html>";
+        let dom = parse_html(html).unwrap();
+        let root = extract_main_content(&dom);
+
+        assert!(Rc::ptr_eq(&root, &dom.document));
+    }
+
+    #[test]
+    fn test_script_and_style_are_excluded_from_scoring_and_output() {
+        let html = b"<html><body>
+            <div class=\"article\">
+                <script>trackPageView('should not appear anywhere in the output');</script>
+                <style>.article { color: red; }</style>
+                <p>Real article content with enough commas, words, and substance to score as a candidate worth keeping.</p>
+            </div>
+        </body></html>";
+        let markdown = extract_markdown(html);
+
+        assert!(markdown.contains("Real article content"));
+        assert!(!markdown.contains("trackPageView"));
+        assert!(!markdown.contains("color: red"));
+    }
+
+    /// Convert with boilerplate removal enabled, so assertions read like the
+    /// rest of the conversion pipeline's output instead of raw DOM structure
+    fn strip_markdown(html: &[u8]) -> String {
+        let dom = parse_html(html).unwrap();
+        let converter = MarkdownConverter::with_options(ConversionOptions {
+            remove_boilerplate: true,
+            ..Default::default()
+        });
+        converter.convert(&dom).unwrap()
+    }
+
+    #[test]
+    fn test_remove_boilerplate_drops_nav_header_footer_aside() {
+        let html = b"<html><body>
+            <header>Site Header</header>
+            <nav><a href=\"/a\">A</a></nav>
+            <aside>Related links</aside>
+            <p>Main article text.</p>
+            <footer>Site Footer</footer>
+        </body></html>";
+        let markdown = strip_markdown(html);
+
+        assert!(markdown.contains("Main article text"));
+        assert!(!markdown.contains("Site Header"));
+        assert!(!markdown.contains("Site Footer"));
+        assert!(!markdown.contains("Related links"));
+        assert!(!markdown.contains("\"/a\""));
+    }
+
+    #[test]
+    fn test_remove_boilerplate_drops_default_class_patterns() {
+        let html = b"<html><body>
+            <div class=\"sidebar-widget\">Newsletter signup</div>
+            <div class=\"main-menu\">Home About Contact</div>
+            <div class=\"cookie-banner\">We use cookies</div>
+            <div id=\"ad-slot-1\">Buy now!</div>
+            <p>Main article text.</p>
+        </body></html>";
+        let markdown = strip_markdown(html);
+
+        assert!(markdown.contains("Main article text"));
+        assert!(!markdown.contains("Newsletter signup"));
+        assert!(!markdown.contains("Home About Contact"));
+        assert!(!markdown.contains("We use cookies"));
+        assert!(!markdown.contains("Buy now"));
+    }
+
+    #[test]
+    fn test_remove_boilerplate_prefers_main_element() {
+        let html = b"<html><body>
+            <div class=\"sidebar\">Not the main content</div>
+            <main><p>The primary article body.</p></main>
+        </body></html>";
+        let markdown = strip_markdown(html);
+
+        assert!(markdown.contains("The primary article body"));
+        assert!(!markdown.contains("Not the main content"));
+    }
+
+    #[test]
+    fn test_remove_boilerplate_custom_class_patterns() {
+        let html = b"<html><body>
+            <div class=\"promo-banner\">Special offer</div>
+            <p>Main article text.</p>
+        </body></html>";
+        let dom = parse_html(html).unwrap();
+        let converter = MarkdownConverter::with_options(ConversionOptions {
+            remove_boilerplate: true,
+            boilerplate_class_patterns: vec!["promo".to_string()],
+            ..Default::default()
+        });
+        let markdown = converter.convert(&dom).unwrap();
+
+        assert!(markdown.contains("Main article text"));
+        assert!(!markdown.contains("Special offer"));
+    }
+}
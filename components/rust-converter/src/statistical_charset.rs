@@ -0,0 +1,244 @@
+//! Statistical charset auto-detection (feature-gated cascade fallback)
+//!
+//! [`crate::charset::detect_charset`]'s three-level cascade (Content-Type
+//! header, `<meta charset>`, default UTF-8) gives up and assumes UTF-8 once
+//! a page supplies no charset information at all. Most such pages aren't
+//! actually UTF-8 — they're legacy single-byte (windows-1252 and the
+//! ISO-8859 family, windows-1251) or CJK multibyte (Shift_JIS, EUC-JP, GBK,
+//! Big5, EUC-KR) encodings that predate the header/meta convention. This
+//! module adds a fourth, best-effort stage that guesses among those
+//! candidates before falling back to UTF-8.
+//!
+//! # Approach
+//!
+//! A full per-language n-gram frequency model, as used by Firefox's
+//! `chardetng`, needs a sizable trained corpus per candidate encoding and is
+//! out of scope here. Instead, this reuses a signal `encoding_rs` already
+//! computes precisely: whether `html` decodes *cleanly* under a candidate
+//! encoding (no invalid lead/trail byte sequences for multibyte encodings,
+//! no undefined code points for single-byte ones), via
+//! [`encoding_rs::Encoding::decode_without_bom_handling_and_without_replacement`].
+//! Genuinely-encoded text in any of these encodings essentially never
+//! produces an invalid sequence, so "decodes with zero errors" is a strong
+//! signal on its own; where more than one candidate decodes cleanly (or none
+//! do), [`CANDIDATES`]' order breaks the tie, biased toward the most common
+//! encodings first, and a replacement-count comparison ranks the rest.
+//! [`detect_charset_statistically_with_hint`] lets a caller who knows a
+//! page's TLD or locale (e.g. from its hostname) bias that tie-break toward
+//! the encoding conventional for that region instead.
+//!
+//! # Feature Flag
+//!
+//! Gated behind the `stat_charset_detect` feature (see this module's `mod`
+//! declaration in `lib.rs`) since the candidate list means pulling in
+//! `encoding_rs`'s full single-byte and CJK decode tables even for callers
+//! who never need this fallback.
+
+/// Candidate encodings, ordered by how common they are in practice
+///
+/// This ordering is the tie-breaker: when multiple candidates decode `html`
+/// equally well, the earliest one in this list wins.
+const CANDIDATES: &[&encoding_rs::Encoding] = &[
+    encoding_rs::WINDOWS_1252,
+    encoding_rs::ISO_8859_2,
+    encoding_rs::ISO_8859_5,
+    encoding_rs::ISO_8859_7,
+    encoding_rs::WINDOWS_1251,
+    encoding_rs::SHIFT_JIS,
+    encoding_rs::EUC_JP,
+    encoding_rs::GBK,
+    encoding_rs::BIG5,
+    encoding_rs::EUC_KR,
+];
+
+/// Per-TLD bias: when a caller knows the page's country-code TLD (or
+/// locale), that's a much stronger prior than [`CANDIDATES`]' generic
+/// frequency ordering — a `.jp` page failing UTF-8 decoding is far more
+/// likely windows-31j-ish Shift_JIS than windows-1252. Only the TLDs whose
+/// legacy encoding is unambiguous are listed; anything else falls back to
+/// [`CANDIDATES`]' default order.
+const TLD_BIAS: &[(&str, &encoding_rs::Encoding)] = &[
+    ("jp", encoding_rs::SHIFT_JIS),
+    ("cn", encoding_rs::GBK),
+    ("tw", encoding_rs::BIG5),
+    ("hk", encoding_rs::BIG5),
+    ("kr", encoding_rs::EUC_KR),
+    ("ru", encoding_rs::WINDOWS_1251),
+    ("pl", encoding_rs::ISO_8859_2),
+    ("gr", encoding_rs::ISO_8859_7),
+];
+
+/// Guess a charset for `html` by scoring it against [`CANDIDATES`]
+///
+/// Returns `None` if `html` is empty, since there is nothing to score.
+/// Otherwise always returns `Some`, even if every candidate decodes with
+/// errors, since the least-bad candidate is still usually a better guess
+/// than defaulting to UTF-8 for a page that already failed to decode as
+/// UTF-8 (callers are expected to call this only in that situation; see
+/// [`crate::charset::detect_charset`]).
+///
+/// # Examples
+///
+/// ```rust
+/// use nginx_markdown_converter::statistical_charset::detect_charset_statistically;
+///
+/// // "caf\xe9" as windows-1252 ("café"); not valid UTF-8 on its own.
+/// let html = b"<html><body>Caf\xe9</body></html>";
+/// let charset = detect_charset_statistically(html).expect("non-empty input");
+/// assert_eq!(charset, "windows-1252");
+/// ```
+pub fn detect_charset_statistically(html: &[u8]) -> Option<&'static str> {
+    detect_charset_statistically_with_hint(html, None)
+}
+
+/// Like [`detect_charset_statistically`], but lets a caller bias the
+/// candidate order with a TLD or locale hint (e.g. `"jp"`, `"ru"`) when one
+/// is known — typically from the request's hostname or an `Accept-Language`
+/// header. An unrecognized or absent hint falls back to [`CANDIDATES`]'
+/// plain frequency order, matching [`detect_charset_statistically`] exactly.
+///
+/// # Examples
+///
+/// ```rust
+/// use nginx_markdown_converter::statistical_charset::detect_charset_statistically_with_hint;
+///
+/// // Without a hint, ambiguous byte patterns fall back to the generic order.
+/// // With a ".jp" hint, genuinely Shift_JIS-encoded text wins outright.
+/// let (encoded, _, _) = encoding_rs::SHIFT_JIS.encode("\u{3053}\u{3093}\u{306b}\u{3061}\u{306f}");
+/// let charset = detect_charset_statistically_with_hint(&encoded, Some("jp")).expect("non-empty input");
+/// assert_eq!(charset, "Shift_JIS");
+/// ```
+pub fn detect_charset_statistically_with_hint(
+    html: &[u8],
+    tld_hint: Option<&str>,
+) -> Option<&'static str> {
+    if html.is_empty() {
+        return None;
+    }
+
+    let biased = tld_hint.and_then(|hint| {
+        TLD_BIAS
+            .iter()
+            .find(|(tld, _)| tld.eq_ignore_ascii_case(hint))
+            .map(|(_, encoding)| *encoding)
+    });
+
+    biased
+        .into_iter()
+        .chain(
+            CANDIDATES
+                .iter()
+                .copied()
+                .filter(|c| !biased.is_some_and(|b| std::ptr::eq(*c, b))),
+        )
+        .map(|encoding| (encoding, score(encoding, html)))
+        // `min_by_key` keeps the first of equal-scoring candidates, so a TLD
+        // bias (if any) wins ties, then `CANDIDATES`' order takes over.
+        .min_by_key(|(_, errors)| *errors)
+        .map(|(encoding, _)| encoding.name())
+}
+
+/// Lower is better: count of replacement characters `encoding` would
+/// produce decoding `html`, or `0` if it decodes with no errors at all
+fn score(encoding: &'static encoding_rs::Encoding, html: &[u8]) -> usize {
+    if encoding
+        .decode_without_bom_handling_and_without_replacement(html)
+        .is_some()
+    {
+        return 0;
+    }
+
+    let (decoded, _had_errors) = encoding.decode_without_bom_handling(html);
+    decoded.matches('\u{FFFD}').count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_charset_statistically_empty_input_is_none() {
+        assert_eq!(detect_charset_statistically(b""), None);
+    }
+
+    #[test]
+    fn test_detect_charset_statistically_windows_1252() {
+        let (encoded, _, _) = encoding_rs::WINDOWS_1252.encode("Caf\u{e9} au lait");
+        let charset = detect_charset_statistically(&encoded).expect("non-empty input");
+        assert_eq!(charset, "windows-1252");
+    }
+
+    #[test]
+    fn test_detect_charset_statistically_shift_jis() {
+        let (encoded, _, _) = encoding_rs::SHIFT_JIS.encode("\u{3053}\u{3093}\u{306b}\u{3061}\u{306f}");
+        let charset = detect_charset_statistically(&encoded).expect("non-empty input");
+        assert_eq!(charset, "Shift_JIS");
+    }
+
+    #[test]
+    fn test_detect_charset_statistically_euc_jp() {
+        let (encoded, _, _) = encoding_rs::EUC_JP.encode("\u{3053}\u{3093}\u{306b}\u{3061}\u{306f}");
+        let charset = detect_charset_statistically(&encoded).expect("non-empty input");
+        assert_eq!(charset, "EUC-JP");
+    }
+
+    #[test]
+    fn test_detect_charset_statistically_gbk() {
+        let (encoded, _, _) = encoding_rs::GBK.encode("\u{4f60}\u{597d}\u{4e16}\u{754c}");
+        let charset = detect_charset_statistically(&encoded).expect("non-empty input");
+        assert_eq!(charset, "GBK");
+    }
+
+    #[test]
+    fn test_detect_charset_statistically_big5() {
+        let (encoded, _, _) = encoding_rs::BIG5.encode("\u{4f60}\u{597d}\u{4e16}\u{754c}");
+        let charset = detect_charset_statistically(&encoded).expect("non-empty input");
+        assert_eq!(charset, "Big5");
+    }
+
+    #[test]
+    fn test_detect_charset_statistically_euc_kr() {
+        let (encoded, _, _) = encoding_rs::EUC_KR.encode("\u{c548}\u{b155}\u{d558}\u{c138}\u{c694}");
+        let charset = detect_charset_statistically(&encoded).expect("non-empty input");
+        assert_eq!(charset, "EUC-KR");
+    }
+
+    #[test]
+    fn test_detect_charset_statistically_pure_ascii_is_stable() {
+        // Every candidate decodes plain ASCII with zero errors, so the
+        // bias-ordered first candidate (windows-1252) wins the tie.
+        let charset = detect_charset_statistically(b"hello world").expect("non-empty input");
+        assert_eq!(charset, "windows-1252");
+    }
+
+    #[test]
+    fn test_detect_charset_statistically_with_hint_empty_input_is_none() {
+        assert_eq!(detect_charset_statistically_with_hint(b"", Some("jp")), None);
+    }
+
+    #[test]
+    fn test_detect_charset_statistically_with_hint_unknown_hint_matches_unhinted() {
+        let (encoded, _, _) = encoding_rs::WINDOWS_1252.encode("Caf\u{e9} au lait");
+        let charset = detect_charset_statistically_with_hint(&encoded, Some("xx"))
+            .expect("non-empty input");
+        assert_eq!(charset, "windows-1252");
+    }
+
+    #[test]
+    fn test_detect_charset_statistically_with_hint_breaks_ties_toward_tld() {
+        // Plain ASCII decodes cleanly under every candidate, so without a
+        // hint the bias-ordered first candidate (windows-1252) wins; with a
+        // ".kr" hint, EUC-KR should win the same tie instead.
+        let charset = detect_charset_statistically_with_hint(b"hello world", Some("kr"))
+            .expect("non-empty input");
+        assert_eq!(charset, "EUC-KR");
+    }
+
+    #[test]
+    fn test_detect_charset_statistically_with_hint_overrides_close_scores() {
+        let (encoded, _, _) = encoding_rs::SHIFT_JIS.encode("\u{3053}\u{3093}\u{306b}\u{3061}\u{306f}");
+        let charset = detect_charset_statistically_with_hint(&encoded, Some("JP"))
+            .expect("non-empty input");
+        assert_eq!(charset, "Shift_JIS");
+    }
+}
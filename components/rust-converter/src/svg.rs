@@ -0,0 +1,264 @@
+//! Sanitization for inline `<svg>` subtrees
+//!
+//! [`crate::security`] strips `svg` entirely by default, since html5ever parses
+//! its contents into foreign-content namespaces where this crate's
+//! HTML-oriented attribute/element assumptions don't hold. That default is
+//! unaffected by this module. What this module adds is a narrower, opt-in
+//! path: a caller who sets `svg`'s [`crate::security::ElementDisposition`] to
+//! something other than `Strip` gets the subtree preserved as sanitized raw
+//! HTML (an inline diagram) instead of the all-or-nothing choice between
+//! stripping it or trusting it verbatim.
+//!
+//! [`SvgSanitizer`] allowlists the drawing-oriented SVG elements, drops
+//! `<script>`, `<style>`, and `<foreignObject>` wholesale (the foreign-content
+//! constructs that can smuggle script execution or arbitrary HTML), removes
+//! `on*` event handler attributes, drops inline `style` attributes (this
+//! crate has no dedicated CSS sanitizer yet, so the conservative choice is to
+//! drop rather than pass through unsanitized CSS), and rejects `javascript:`/
+//! `vbscript:`/`data:` references in `href`/`xlink:href`. `<use>` and
+//! `<image>` additionally may only reference a same-document fragment
+//! (`#id`): both elements load external resources, which is exactly the SSRF
+//! and data-exfiltration vector [`crate::security`] already guards against
+//! for `href`/`src`, and there is no safe external reference for them in a
+//! sanitized-passthrough context.
+
+use crate::security::{decode_html_entities, recover_url_scheme};
+
+/// SVG elements preserved by [`SvgSanitizer`]
+///
+/// Limited to elements that describe vector drawing and have no meaningful
+/// script-execution surface on their own (their attributes are what's
+/// dangerous, not the tag itself). Foreign-content and scripting elements are
+/// listed separately in [`SVG_DANGEROUS_ELEMENTS`] and always removed.
+const SVG_ALLOWED_ELEMENTS: &[&str] = &[
+    "svg",
+    "g",
+    "defs",
+    "symbol",
+    "use",
+    "image",
+    "path",
+    "rect",
+    "circle",
+    "ellipse",
+    "line",
+    "polyline",
+    "polygon",
+    "text",
+    "tspan",
+    "textPath",
+    "linearGradient",
+    "radialGradient",
+    "stop",
+    "clipPath",
+    "mask",
+    "pattern",
+    "marker",
+    "title",
+    "desc",
+];
+
+/// SVG elements removed along with their children, even under sanitized
+/// passthrough
+///
+/// `script` executes arbitrary JavaScript, `foreignObject` embeds unrestricted
+/// HTML (defeating every HTML-oriented assumption elsewhere in this crate),
+/// and `style` carries CSS this crate has no dedicated sanitizer for yet.
+const SVG_DANGEROUS_ELEMENTS: &[&str] = &["script", "foreignObject", "style"];
+
+/// Elements whose `href`/`xlink:href` may only reference a same-document
+/// fragment (`#id`)
+///
+/// `<use>` and `<image>` load external resources, so
+/// `href="http://evil.example/tracker.svg#x"` would let an inline diagram
+/// exfiltrate data or probe internal hosts exactly like the SSRF vectors
+/// [`crate::security`] already guards against for `href`/`src`.
+const SVG_LOCAL_REFERENCE_ONLY_ELEMENTS: &[&str] = &["use", "image"];
+
+/// Action to take for an SVG element under [`SvgSanitizer`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SvgElementAction {
+    /// Preserve the element, subject to [`SvgSanitizer::is_attribute_allowed`]
+    Allow,
+    /// Drop the element and all its children
+    Remove,
+}
+
+/// Sanitizer for the elements and attributes of a preserved `<svg>` subtree
+///
+/// # Examples
+///
+/// ```
+/// use nginx_markdown_converter::svg::{SvgSanitizer, SvgElementAction};
+///
+/// let sanitizer = SvgSanitizer::new();
+/// assert_eq!(sanitizer.check_element("path"), SvgElementAction::Allow);
+/// assert_eq!(sanitizer.check_element("script"), SvgElementAction::Remove);
+/// assert!(!sanitizer.is_attribute_allowed("rect", "onclick", "alert(1)"));
+/// assert!(sanitizer.is_attribute_allowed("rect", "fill", "red"));
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SvgSanitizer;
+
+impl SvgSanitizer {
+    /// Create a new SVG sanitizer
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Check whether an SVG element should be preserved or removed
+    pub fn check_element(&self, tag_name: &str) -> SvgElementAction {
+        if SVG_DANGEROUS_ELEMENTS.contains(&tag_name) {
+            SvgElementAction::Remove
+        } else {
+            SvgElementAction::Allow
+        }
+    }
+
+    /// Check whether `tag_name` is on the drawing-element allowlist
+    ///
+    /// Unlike [`Self::check_element`], this doesn't distinguish "explicitly
+    /// dangerous" from "simply unrecognized" — it's used by the converter to
+    /// decide whether an unrecognized foreign-content element (a future SVG
+    /// feature, a custom element) should be unwrapped rather than preserved.
+    pub fn is_known_safe_element(&self, tag_name: &str) -> bool {
+        SVG_ALLOWED_ELEMENTS.contains(&tag_name)
+    }
+
+    /// Check if an attribute name is an event handler (`onclick`, `onload`,
+    /// SMIL's `onbegin`/`onend`/`onrepeat`, ...)
+    pub fn is_event_handler(&self, attr_name: &str) -> bool {
+        attr_name.len() > 2 && attr_name[..2].eq_ignore_ascii_case("on")
+    }
+
+    /// Check whether `attr_name`'s value on `tag_name` is an unsafe reference
+    ///
+    /// Only applies to `href`/`xlink:href`; every other attribute is not a
+    /// reference and returns `false`. `javascript:`/`vbscript:`/`data:`
+    /// schemes are rejected everywhere, mirroring
+    /// [`crate::security::SecurityValidator::is_dangerous_url`]'s entity-decoding
+    /// and control-character-stripping scheme recovery. `<use>`/`<image>`
+    /// additionally reject anything but a same-document fragment reference
+    /// (see [`SVG_LOCAL_REFERENCE_ONLY_ELEMENTS`]).
+    pub fn is_unsafe_reference(&self, tag_name: &str, attr_name: &str, attr_value: &str) -> bool {
+        if attr_name != "href" && attr_name != "xlink:href" {
+            return false;
+        }
+
+        let decoded = decode_html_entities(attr_value.trim());
+
+        if SVG_LOCAL_REFERENCE_ONLY_ELEMENTS.contains(&tag_name) {
+            return !decoded.starts_with('#');
+        }
+
+        matches!(
+            recover_url_scheme(&decoded).as_deref(),
+            Some("javascript" | "vbscript" | "data")
+        )
+    }
+
+    /// Check whether an attribute should be preserved on a sanitized SVG element
+    ///
+    /// Drops event handlers and unsafe `href`/`xlink:href` references.
+    /// Everything else (presentation attributes like `d`, `fill`, `stroke`,
+    /// `viewBox`, `points`, ...) is preserved. The `style` attribute is
+    /// *not* filtered here: it's rewritten through
+    /// [`crate::security::SecurityValidator::sanitize_style`] by the
+    /// converter instead of being allowed or dropped wholesale, since a CSS
+    /// declaration-level sanitizer exists there.
+    pub fn is_attribute_allowed(&self, tag_name: &str, attr_name: &str, attr_value: &str) -> bool {
+        if self.is_event_handler(attr_name) {
+            return false;
+        }
+        !self.is_unsafe_reference(tag_name, attr_name, attr_value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_element_allows_drawing_elements() {
+        let sanitizer = SvgSanitizer::new();
+        assert_eq!(sanitizer.check_element("svg"), SvgElementAction::Allow);
+        assert_eq!(sanitizer.check_element("path"), SvgElementAction::Allow);
+        assert_eq!(sanitizer.check_element("circle"), SvgElementAction::Allow);
+        assert_eq!(sanitizer.check_element("use"), SvgElementAction::Allow);
+    }
+
+    #[test]
+    fn test_check_element_removes_foreign_content_and_scripting() {
+        let sanitizer = SvgSanitizer::new();
+        assert_eq!(sanitizer.check_element("script"), SvgElementAction::Remove);
+        assert_eq!(
+            sanitizer.check_element("foreignObject"),
+            SvgElementAction::Remove
+        );
+        assert_eq!(sanitizer.check_element("style"), SvgElementAction::Remove);
+    }
+
+    #[test]
+    fn test_is_known_safe_element() {
+        let sanitizer = SvgSanitizer::new();
+        assert!(sanitizer.is_known_safe_element("path"));
+        assert!(!sanitizer.is_known_safe_element("script"));
+        assert!(!sanitizer.is_known_safe_element("unknownFutureElement"));
+    }
+
+    #[test]
+    fn test_is_event_handler() {
+        let sanitizer = SvgSanitizer::new();
+        assert!(sanitizer.is_event_handler("onclick"));
+        assert!(sanitizer.is_event_handler("onbegin"));
+        assert!(sanitizer.is_event_handler("onend"));
+        assert!(sanitizer.is_event_handler("OnClick"));
+        assert!(!sanitizer.is_event_handler("fill"));
+        assert!(!sanitizer.is_event_handler("on"));
+    }
+
+    #[test]
+    fn test_is_unsafe_reference_blocks_script_schemes_everywhere() {
+        let sanitizer = SvgSanitizer::new();
+        assert!(sanitizer.is_unsafe_reference("a", "href", "javascript:alert(1)"));
+        assert!(sanitizer.is_unsafe_reference("a", "xlink:href", "vbscript:msgbox(1)"));
+        assert!(sanitizer.is_unsafe_reference("a", "href", "data:text/html,<script>"));
+    }
+
+    #[test]
+    fn test_is_unsafe_reference_allows_remote_urls_on_ordinary_elements() {
+        let sanitizer = SvgSanitizer::new();
+        assert!(!sanitizer.is_unsafe_reference("a", "href", "https://example.com"));
+    }
+
+    #[test]
+    fn test_is_unsafe_reference_requires_local_fragment_for_use_and_image() {
+        let sanitizer = SvgSanitizer::new();
+        assert!(sanitizer.is_unsafe_reference("use", "href", "https://evil.example/x.svg#y"));
+        assert!(sanitizer.is_unsafe_reference("image", "xlink:href", "/local/path.svg"));
+        assert!(!sanitizer.is_unsafe_reference("use", "href", "#local-id"));
+    }
+
+    #[test]
+    fn test_is_unsafe_reference_ignores_non_reference_attributes() {
+        let sanitizer = SvgSanitizer::new();
+        assert!(!sanitizer.is_unsafe_reference("rect", "fill", "javascript:alert(1)"));
+    }
+
+    #[test]
+    fn test_is_attribute_allowed_drops_event_handlers() {
+        let sanitizer = SvgSanitizer::new();
+        assert!(!sanitizer.is_attribute_allowed("rect", "onclick", "alert(1)"));
+        assert!(sanitizer.is_attribute_allowed("rect", "fill", "red"));
+        // `style` is handled separately by the converter via `sanitize_style`,
+        // not filtered here
+        assert!(sanitizer.is_attribute_allowed("rect", "style", "fill: red"));
+    }
+
+    #[test]
+    fn test_is_attribute_allowed_drops_unsafe_references() {
+        let sanitizer = SvgSanitizer::new();
+        assert!(!sanitizer.is_attribute_allowed("use", "href", "http://evil.example/x.svg"));
+        assert!(sanitizer.is_attribute_allowed("use", "href", "#local-id"));
+    }
+}
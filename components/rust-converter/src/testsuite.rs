@@ -0,0 +1,654 @@
+//! Corpus-driven differential and round-trip testing support
+//!
+//! This module loads golden `.html` / `.expected.md` fixture pairs from a
+//! directory tree, runs them through [`crate::parser::parse_html`] and
+//! [`crate::converter::MarkdownConverter`], and reports pass/fail counts.
+//! Known-divergent fixtures can be carried in an ignore list (one
+//! repo-relative path per line, keyed by the `.html` fixture path) so a
+//! regression elsewhere in the corpus doesn't get masked by an
+//! already-accepted divergence.
+//!
+//! It also provides a round-trip invariant check: the Markdown produced from
+//! a fixture is rendered back to HTML by a CommonMark renderer and compared
+//! against the original HTML structurally (heading text/levels, list item
+//! texts, code block contents/languages) rather than byte-for-byte, since
+//! whitespace and formatting differences are expected and not regressions.
+//!
+//! The same [`run_golden_suite`] entry point backs both the `testsuite`
+//! binary (for CI/local ad-hoc runs) and the property tests in
+//! `tests/testsuite_corpus_test.rs`, so dropping a new `.html`/`.expected.md`
+//! pair into the corpus directory picks it up automatically without touching
+//! any code.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use markup5ever_rcdom::{Handle, NodeData, RcDom};
+
+use crate::converter::MarkdownConverter;
+use crate::error::ConversionError;
+use crate::parser::parse_html;
+
+/// A single golden fixture: an `.html` input paired with its expected
+/// `.expected.md` output.
+///
+/// `relative_path` is the fixture's path relative to the corpus root, using
+/// `/`-separated components regardless of host platform, and is the key
+/// used for ignore-list matching.
+#[derive(Debug, Clone)]
+pub struct GoldenCase {
+    /// Corpus-relative path of the `.html` fixture (the ignore-list key)
+    pub relative_path: String,
+    /// Absolute path to the `.html` fixture
+    pub html_path: PathBuf,
+    /// Absolute path to the paired `.expected.md` fixture
+    pub expected_path: PathBuf,
+}
+
+/// Outcome of running a single golden case through the suite
+#[derive(Debug)]
+pub enum CaseOutcome {
+    /// Converter output matched the expected Markdown exactly
+    Passed,
+    /// Converter output diverged from the expected Markdown
+    Failed {
+        /// Markdown actually produced by the converter
+        actual: String,
+        /// Markdown read from the `.expected.md` fixture
+        expected: String,
+    },
+    /// The case is listed in the ignore file, so it ran but was excluded
+    /// from the pass/fail tally
+    Ignored,
+    /// Parsing or conversion returned an error before output could be compared
+    Errored(ConversionError),
+}
+
+/// Result of running one [`GoldenCase`] through the suite
+#[derive(Debug)]
+pub struct CaseResult {
+    /// The case that was run
+    pub case: GoldenCase,
+    /// What happened when it was run
+    pub outcome: CaseOutcome,
+}
+
+/// Aggregate result of running an entire corpus
+#[derive(Debug, Default)]
+pub struct SuiteReport {
+    /// Per-case results, in discovery order
+    pub results: Vec<CaseResult>,
+}
+
+impl SuiteReport {
+    /// Number of cases that passed
+    pub fn passed(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|r| matches!(r.outcome, CaseOutcome::Passed))
+            .count()
+    }
+
+    /// Number of cases that failed (excludes ignored and errored cases)
+    pub fn failed(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|r| matches!(r.outcome, CaseOutcome::Failed { .. }))
+            .count()
+    }
+
+    /// Number of cases skipped via the ignore list
+    pub fn ignored(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|r| matches!(r.outcome, CaseOutcome::Ignored))
+            .count()
+    }
+
+    /// Number of cases that errored before a comparison could be made
+    pub fn errored(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|r| matches!(r.outcome, CaseOutcome::Errored(_)))
+            .count()
+    }
+
+    /// True if no case failed or errored (ignored cases do not block success)
+    pub fn is_clean(&self) -> bool {
+        self.failed() == 0 && self.errored() == 0
+    }
+}
+
+impl fmt::Display for SuiteReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} passed, {} failed, {} ignored, {} errored ({} total)",
+            self.passed(),
+            self.failed(),
+            self.ignored(),
+            self.errored(),
+            self.results.len()
+        )
+    }
+}
+
+/// Recursively discover `.html` / `.expected.md` fixture pairs under `corpus_dir`
+///
+/// An `.html` file without a sibling `<name>.expected.md` is skipped rather
+/// than treated as an error, so a corpus directory can hold supporting files
+/// (e.g. a README) without breaking discovery.
+pub fn discover_golden_cases(corpus_dir: &Path) -> std::io::Result<Vec<GoldenCase>> {
+    let mut cases = Vec::new();
+    collect_golden_cases(corpus_dir, corpus_dir, &mut cases)?;
+    cases.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+    Ok(cases)
+}
+
+fn collect_golden_cases(
+    corpus_dir: &Path,
+    dir: &Path,
+    cases: &mut Vec<GoldenCase>,
+) -> std::io::Result<()> {
+    if !dir.exists() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_dir() {
+            collect_golden_cases(corpus_dir, &path, cases)?;
+            continue;
+        }
+        if path.extension().and_then(|ext| ext.to_str()) != Some("html") {
+            continue;
+        }
+        let expected_path = path.with_extension("expected.md");
+        if !expected_path.exists() {
+            continue;
+        }
+        let relative_path = path
+            .strip_prefix(corpus_dir)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .replace(std::path::MAIN_SEPARATOR, "/");
+        cases.push(GoldenCase {
+            relative_path,
+            html_path: path,
+            expected_path,
+        });
+    }
+    Ok(())
+}
+
+/// Load an ignore list of corpus-relative paths, one per line
+///
+/// Blank lines and lines starting with `#` are ignored, matching the repo's
+/// preference for plain, comment-friendly text config over a structured
+/// format.
+pub fn load_ignore_list(path: &Path) -> std::io::Result<HashSet<String>> {
+    if !path.exists() {
+        return Ok(HashSet::new());
+    }
+    let contents = fs::read_to_string(path)?;
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Run every golden case discovered under `corpus_dir` and report outcomes
+///
+/// `ignored` carries relative paths (as produced by [`discover_golden_cases`])
+/// that are known-divergent and should be excluded from the pass/fail tally;
+/// load it with [`load_ignore_list`].
+pub fn run_golden_suite(
+    corpus_dir: &Path,
+    ignored: &HashSet<String>,
+) -> std::io::Result<SuiteReport> {
+    let cases = discover_golden_cases(corpus_dir)?;
+    let mut results = Vec::with_capacity(cases.len());
+    for case in cases {
+        let outcome = if ignored.contains(&case.relative_path) {
+            CaseOutcome::Ignored
+        } else {
+            run_case(&case)
+        };
+        results.push(CaseResult { case, outcome });
+    }
+    Ok(SuiteReport { results })
+}
+
+fn run_case(case: &GoldenCase) -> CaseOutcome {
+    let html = match fs::read(&case.html_path) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            return CaseOutcome::Errored(ConversionError::InvalidInput(format!(
+                "failed to read {}: {}",
+                case.html_path.display(),
+                err
+            )));
+        }
+    };
+    let expected = match fs::read_to_string(&case.expected_path) {
+        Ok(text) => text,
+        Err(err) => {
+            return CaseOutcome::Errored(ConversionError::InvalidInput(format!(
+                "failed to read {}: {}",
+                case.expected_path.display(),
+                err
+            )));
+        }
+    };
+
+    let actual = match convert_fixture(&html) {
+        Ok(markdown) => markdown,
+        Err(err) => return CaseOutcome::Errored(err),
+    };
+
+    if actual == expected {
+        CaseOutcome::Passed
+    } else {
+        CaseOutcome::Failed { actual, expected }
+    }
+}
+
+fn convert_fixture(html: &[u8]) -> Result<String, ConversionError> {
+    let dom = parse_html(html)?;
+    let converter = MarkdownConverter::new();
+    converter.convert(&dom)
+}
+
+/// A structural fingerprint of an HTML document, used to compare the
+/// original fixture HTML against the HTML rendered back from produced
+/// Markdown without requiring byte equality.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct StructuralSummary {
+    /// `(level, text)` pairs for every `h1`..`h6`, in document order
+    pub headings: Vec<(u8, String)>,
+    /// Text content of every `li`, in document order
+    pub list_items: Vec<String>,
+    /// `(language, code)` pairs for every `pre > code` block, in document order
+    pub code_blocks: Vec<(Option<String>, String)>,
+}
+
+/// Extract a [`StructuralSummary`] from raw HTML bytes
+pub fn extract_structural_summary(html: &[u8]) -> Result<StructuralSummary, ConversionError> {
+    let dom = parse_html(html)?;
+    let mut summary = StructuralSummary::default();
+    walk_structural_summary(&dom.document, &mut summary, None);
+    Ok(summary)
+}
+
+fn walk_structural_summary(
+    handle: &Handle,
+    summary: &mut StructuralSummary,
+    parent_tag: Option<&str>,
+) {
+    let node = handle;
+    if let NodeData::Element { ref name, .. } = node.data {
+        let tag = name.local.as_ref();
+        match tag {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = tag.as_bytes()[1] - b'0';
+                let text = collect_text(node);
+                summary.headings.push((level, text));
+            }
+            "li" => {
+                let text = collect_text(node);
+                summary.list_items.push(text);
+            }
+            "code" if parent_tag == Some("pre") => {
+                let language = code_language(node);
+                let code = collect_text(node);
+                summary.code_blocks.push((language, code));
+            }
+            _ => {}
+        }
+        for child in node.children.borrow().iter() {
+            walk_structural_summary(child, summary, Some(tag));
+        }
+        return;
+    }
+    for child in node.children.borrow().iter() {
+        walk_structural_summary(child, summary, parent_tag);
+    }
+}
+
+fn code_language(code_node: &Handle) -> Option<String> {
+    if let NodeData::Element { ref attrs, .. } = code_node.data {
+        for attr in attrs.borrow().iter() {
+            if attr.name.local.as_ref() == "class" {
+                for class in attr.value.split_whitespace() {
+                    if let Some(lang) = class.strip_prefix("language-") {
+                        return Some(lang.to_string());
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+fn collect_text(handle: &Handle) -> String {
+    let mut text = String::new();
+    collect_text_into(handle, &mut text);
+    text.trim().to_string()
+}
+
+fn collect_text_into(handle: &Handle, out: &mut String) {
+    match handle.data {
+        NodeData::Text { ref contents } => {
+            out.push_str(&contents.borrow());
+        }
+        _ => {
+            for child in handle.children.borrow().iter() {
+                collect_text_into(child, out);
+            }
+        }
+    }
+}
+
+/// Render Markdown back to HTML
+///
+/// This intentionally does not reuse [`crate::converter::MarkdownConverter`]
+/// (that would only prove the converter agrees with itself). It's a small,
+/// independent renderer covering just the constructs
+/// [`extract_structural_summary`] compares: ATX headings, list items, and
+/// fenced code blocks. It is not a full CommonMark implementation and isn't
+/// meant to be one — only enough fidelity to make the round-trip check
+/// meaningful.
+pub fn render_markdown_to_html(markdown: &str) -> String {
+    let mut html = String::new();
+    let mut in_list = false;
+    let mut lines = markdown.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        if let Some(fence_lang) = line.trim_start().strip_prefix("```") {
+            let language = fence_lang.trim();
+            let mut code = String::new();
+            for code_line in lines.by_ref() {
+                if code_line.trim_start().starts_with("```") {
+                    break;
+                }
+                code.push_str(code_line);
+                code.push('\n');
+            }
+            if in_list {
+                html.push_str("</ul>\n");
+                in_list = false;
+            }
+            html.push_str("<pre><code");
+            if !language.is_empty() {
+                html.push_str(&format!(" class=\"language-{language}\""));
+            }
+            html.push('>');
+            html.push_str(&escape_html(&code));
+            html.push_str("</code></pre>\n");
+            continue;
+        }
+
+        let trimmed = line.trim_start();
+        if let Some(level) = atx_heading_level(trimmed) {
+            if in_list {
+                html.push_str("</ul>\n");
+                in_list = false;
+            }
+            let text = trimmed[level as usize..].trim();
+            html.push_str(&format!(
+                "<h{level}>{}</h{level}>\n",
+                escape_html(text)
+            ));
+            continue;
+        }
+
+        if let Some(item_text) = list_item_text(trimmed) {
+            if !in_list {
+                html.push_str("<ul>\n");
+                in_list = true;
+            }
+            html.push_str(&format!("<li>{}</li>\n", escape_html(item_text)));
+            continue;
+        }
+
+        if in_list {
+            html.push_str("</ul>\n");
+            in_list = false;
+        }
+        if !trimmed.is_empty() {
+            html.push_str(&format!("<p>{}</p>\n", escape_html(trimmed)));
+        }
+    }
+
+    if in_list {
+        html.push_str("</ul>\n");
+    }
+
+    html
+}
+
+/// Heading level of an ATX heading line (`#` through `######` followed by a
+/// space), or `None` if `line` isn't one
+fn atx_heading_level(line: &str) -> Option<u8> {
+    let hashes = line.bytes().take_while(|&b| b == b'#').count();
+    if hashes == 0 || hashes > 6 {
+        return None;
+    }
+    if line.as_bytes().get(hashes).is_some_and(|&b| b == b' ') {
+        Some(hashes as u8)
+    } else {
+        None
+    }
+}
+
+/// Item text of an unordered (`-`/`*`/`+`) or ordered (`N.`) list item line,
+/// or `None` if `line` isn't one
+fn list_item_text(line: &str) -> Option<&str> {
+    if let Some(rest) = line
+        .strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))
+        .or_else(|| line.strip_prefix("+ "))
+    {
+        return Some(rest.trim());
+    }
+    let digits = line.bytes().take_while(u8::is_ascii_digit).count();
+    if digits > 0 {
+        let rest = &line[digits..];
+        if let Some(rest) = rest.strip_prefix(". ") {
+            return Some(rest.trim());
+        }
+    }
+    None
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Assert that `markdown` round-trips to HTML structurally equivalent to
+/// `original_html`
+///
+/// Equivalence means: the same heading levels and text, the same list item
+/// texts, and the same code block contents and languages, in the same
+/// order. Whitespace and HTML formatting differences are not compared, so
+/// this is intentionally looser than byte equality.
+///
+/// Returns `Ok(())` when equivalent, or `Err(String)` describing the first
+/// divergence found.
+pub fn assert_roundtrip_equivalent(original_html: &[u8], markdown: &str) -> Result<(), String> {
+    let original = extract_structural_summary(original_html)
+        .map_err(|err| format!("failed to parse original HTML: {err}"))?;
+    let rendered_html = render_markdown_to_html(markdown);
+    let rendered = extract_structural_summary(rendered_html.as_bytes())
+        .map_err(|err| format!("failed to parse round-tripped HTML: {err}"))?;
+
+    if original.headings != rendered.headings {
+        return Err(format!(
+            "heading mismatch: original {:?} vs round-tripped {:?}",
+            original.headings, rendered.headings
+        ));
+    }
+    if original.list_items != rendered.list_items {
+        return Err(format!(
+            "list item mismatch: original {:?} vs round-tripped {:?}",
+            original.list_items, rendered.list_items
+        ));
+    }
+    if original.code_blocks != rendered.code_blocks {
+        return Err(format!(
+            "code block mismatch: original {:?} vs round-tripped {:?}",
+            original.code_blocks, rendered.code_blocks
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::io::Write;
+
+    fn write_fixture(dir: &Path, name: &str, html: &str, expected_md: &str) {
+        fs::write(dir.join(format!("{name}.html")), html).unwrap();
+        fs::write(dir.join(format!("{name}.expected.md")), expected_md).unwrap();
+    }
+
+    fn temp_corpus_dir(label: &str) -> PathBuf {
+        let mut dir = std::env::temp_dir();
+        dir.push(format!(
+            "nginx-markdown-converter-testsuite-{label}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_discover_golden_cases_finds_paired_fixtures() {
+        let dir = temp_corpus_dir("discover");
+        write_fixture(&dir, "simple", "<h1>Hi</h1>", "# Hi\n");
+        fs::write(dir.join("orphan.html"), "<p>no pair</p>").unwrap();
+
+        let cases = discover_golden_cases(&dir).unwrap();
+        assert_eq!(cases.len(), 1);
+        assert_eq!(cases[0].relative_path, "simple.html");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_run_golden_suite_reports_pass_and_fail() {
+        let dir = temp_corpus_dir("pass-fail");
+        write_fixture(&dir, "heading", "<h1>Hello</h1>", "# Hello\n");
+        write_fixture(&dir, "wrong", "<h1>Hello</h1>", "# Goodbye\n");
+
+        let report = run_golden_suite(&dir, &HashSet::new()).unwrap();
+        assert_eq!(report.passed(), 1);
+        assert_eq!(report.failed(), 1);
+        assert_eq!(report.ignored(), 0);
+        assert!(!report.is_clean());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_ignore_list_excludes_known_divergent_case_from_tally() {
+        let dir = temp_corpus_dir("ignore");
+        write_fixture(&dir, "wrong", "<h1>Hello</h1>", "# Goodbye\n");
+
+        let mut ignored = HashSet::new();
+        ignored.insert("wrong.html".to_string());
+
+        let report = run_golden_suite(&dir, &ignored).unwrap();
+        assert_eq!(report.ignored(), 1);
+        assert_eq!(report.failed(), 0);
+        assert!(report.is_clean());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_ignore_list_skips_blank_and_comment_lines() {
+        let dir = temp_corpus_dir("ignore-file");
+        let ignore_path = dir.join("ignore.txt");
+        let mut file = fs::File::create(&ignore_path).unwrap();
+        writeln!(file, "# known divergences").unwrap();
+        writeln!(file).unwrap();
+        writeln!(file, "nested/case.html").unwrap();
+
+        let ignored = load_ignore_list(&ignore_path).unwrap();
+        assert_eq!(ignored.len(), 1);
+        assert!(ignored.contains("nested/case.html"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_ignore_list_missing_file_is_empty() {
+        let ignored = load_ignore_list(Path::new("/nonexistent/ignore.txt")).unwrap();
+        assert!(ignored.is_empty());
+    }
+
+    #[test]
+    fn test_structural_summary_extracts_headings_list_items_and_code() {
+        let html = b"<h1>Title</h1><ul><li>One</li><li>Two</li></ul>\
+            <pre><code class=\"language-rust\">fn main() {}</code></pre>";
+        let summary = extract_structural_summary(html).unwrap();
+        assert_eq!(summary.headings, vec![(1, "Title".to_string())]);
+        assert_eq!(
+            summary.list_items,
+            vec!["One".to_string(), "Two".to_string()]
+        );
+        assert_eq!(
+            summary.code_blocks,
+            vec![(Some("rust".to_string()), "fn main() {}".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_roundtrip_equivalence_ignores_whitespace_differences() {
+        let html = b"<h1>Hello   World</h1><ul><li>Item</li></ul>";
+        let dom = parse_html(html).unwrap();
+        let converter = MarkdownConverter::new();
+        let markdown = converter.convert(&dom).unwrap();
+
+        assert!(
+            assert_roundtrip_equivalent(html, &markdown).is_ok(),
+            "round-trip should consider heading/list text equivalent regardless of \
+             Markdown formatting whitespace"
+        );
+    }
+
+    #[test]
+    fn test_roundtrip_equivalence_detects_heading_divergence() {
+        let html = b"<h1>Hello</h1>";
+        let result = assert_roundtrip_equivalent(html, "## Hello\n");
+        assert!(
+            result.is_err(),
+            "heading level divergence should be detected"
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn prop_golden_corpus_roundtrips_structurally(seed in 0u8..3) {
+            let html: &[u8] = match seed {
+                0 => b"<h1>Alpha</h1><p>Body text.</p>",
+                1 => b"<h2>Beta</h2><ul><li>First</li><li>Second</li></ul>",
+                _ => b"<h3>Gamma</h3><pre><code class=\"language-python\">x = 1</code></pre>",
+            };
+            let dom = parse_html(html).expect("fixture HTML must parse");
+            let converter = MarkdownConverter::new();
+            let markdown = converter.convert(&dom).expect("fixture HTML must convert");
+            prop_assert!(assert_roundtrip_equivalent(html, &markdown).is_ok());
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Pluggable, language-aware typography cleaning for ordinary prose text
+//!
+//! [`ConversionOptions::text_cleaner`](crate::converter::ConversionOptions::text_cleaner)
+//! lets a caller plug in a [`TextCleaner`] that [`MarkdownConverter::normalize_text`]
+//! (crate::converter::MarkdownConverter::normalize_text) runs over every
+//! ordinary text run it normalizes. Since `normalize_text` is never used for
+//! inline code, fenced code blocks, or URLs, a cleaner configured this way
+//! never touches those contexts either - code and links stay byte-exact
+//! regardless of the cleaner in use.
+//!
+//! [`MarkdownConverter::with_cleaner`](crate::converter::MarkdownConverter::with_cleaner)
+//! is the intended way to configure one, mirroring the chainable `with_*`
+//! builder methods [`crate::security::SanitizationPolicy`] already uses for
+//! optional, incremental configuration on top of a primary options struct.
+
+/// A pluggable typography cleaner run over ordinary prose text
+///
+/// There is no default (no-op) implementor: a `None` value for
+/// [`ConversionOptions::text_cleaner`](crate::converter::ConversionOptions::text_cleaner)
+/// already means "don't clean", so `normalize_text` only calls [`Self::clean`]
+/// when a cleaner has actually been configured.
+pub trait TextCleaner: std::fmt::Debug + Send + Sync {
+    /// Clean a run of already whitespace-collapsed prose text
+    fn clean(&self, text: &str) -> String;
+}
+
+/// Non-breaking space (U+00A0), the traditional French typographic spacing
+/// character
+const NBSP: char = '\u{00A0}';
+
+/// Narrow non-breaking space (U+202F), the modern (post-1990s) French
+/// typographic convention for `;:!?`, preferred by recent style guides
+/// because a full NBSP reads as too wide before a single punctuation mark
+const NARROW_NBSP: char = '\u{202F}';
+
+/// Applies French ("Imprimerie Nationale") typographic conventions to prose
+/// text: a non-breaking space before `;`, `:`, `!`, `?`, and inside `«` `»`
+/// guillemets, plus converting straight double quotes (`"..."`) into
+/// guillemets with that same inner spacing - French's primary quotation
+/// mark, in place of the English-style double quote. A straight single
+/// quote (`'...'`) is folded to a curly quote instead, matching the nested
+/// (secondary) quotation convention.
+///
+/// Unlike [`crate::converter::ConversionOptions::smart_punctuation`], which
+/// only curls quotes and normalizes dashes/ellipses, this also inserts the
+/// spacing French typesetting requires around punctuation that would
+/// otherwise be ambiguous to read when wrapped onto a line boundary.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrenchCleaner {
+    /// Use the narrow non-breaking space (U+202F) instead of the full
+    /// non-breaking space (U+00A0) before `;:!?`. Guillemets always use a
+    /// full NBSP either way, matching common usage. Defaults to `false`.
+    pub narrow_nbsp: bool,
+}
+
+impl FrenchCleaner {
+    fn punctuation_nbsp(&self) -> char {
+        if self.narrow_nbsp { NARROW_NBSP } else { NBSP }
+    }
+}
+
+impl TextCleaner for FrenchCleaner {
+    fn clean(&self, text: &str) -> String {
+        let nbsp = self.punctuation_nbsp();
+        let chars: Vec<char> = text.chars().collect();
+        let mut out = String::with_capacity(text.len());
+
+        for (i, &ch) in chars.iter().enumerate() {
+            match ch {
+                ';' | ':' | '!' | '?' => {
+                    // Don't double up a space already inserted for a
+                    // preceding punctuation mark (e.g. "?!") or duplicate
+                    // one the source text already had.
+                    if !matches!(out.chars().next_back(), Some(c) if c.is_whitespace()) {
+                        out.push(nbsp);
+                    }
+                    out.push(ch);
+                }
+                '«' => {
+                    out.push(ch);
+                    if !matches!(chars.get(i + 1), Some(c) if c.is_whitespace()) {
+                        out.push(NBSP);
+                    }
+                }
+                '»' => {
+                    if !matches!(out.chars().next_back(), Some(c) if c.is_whitespace()) {
+                        out.push(NBSP);
+                    }
+                    out.push(ch);
+                }
+                '"' => {
+                    if Self::is_opening_quote_context(out.chars().next_back()) {
+                        out.push('«');
+                        if !matches!(chars.get(i + 1), Some(c) if c.is_whitespace()) {
+                            out.push(NBSP);
+                        }
+                    } else {
+                        if !matches!(out.chars().next_back(), Some(c) if c.is_whitespace()) {
+                            out.push(NBSP);
+                        }
+                        out.push('»');
+                    }
+                }
+                '\'' => out.push(if Self::is_opening_quote_context(out.chars().next_back()) {
+                    '‘'
+                } else {
+                    '’'
+                }),
+                _ => out.push(ch),
+            }
+        }
+
+        out
+    }
+}
+
+impl FrenchCleaner {
+    fn is_opening_quote_context(prev: Option<char>) -> bool {
+        match prev {
+            None => true,
+            Some(c) => c.is_whitespace() || matches!(c, '(' | '[' | '{' | '“' | '‘' | NBSP),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_french_cleaner_inserts_nbsp_before_punctuation() {
+        let cleaner = FrenchCleaner::default();
+        let result = cleaner.clean("Bonjour ! Ca va ?");
+        assert_eq!(result, "Bonjour\u{00A0}! Ca va\u{00A0}?");
+    }
+
+    #[test]
+    fn test_french_cleaner_narrow_nbsp_option() {
+        let cleaner = FrenchCleaner { narrow_nbsp: true };
+        let result = cleaner.clean("Vraiment ?");
+        assert_eq!(result, "Vraiment\u{202F}?");
+    }
+
+    #[test]
+    fn test_french_cleaner_spaces_guillemets() {
+        let cleaner = FrenchCleaner::default();
+        let result = cleaner.clean("«Bonjour»");
+        assert_eq!(result, "«\u{00A0}Bonjour\u{00A0}»");
+    }
+
+    #[test]
+    fn test_french_cleaner_converts_straight_double_quotes_to_guillemets() {
+        let cleaner = FrenchCleaner::default();
+        let result = cleaner.clean("\"text\" and 'text'");
+        assert_eq!(result, "«\u{00A0}text\u{00A0}» and ‘text’");
+    }
+
+    #[test]
+    fn test_french_cleaner_does_not_double_space_existing_whitespace() {
+        let cleaner = FrenchCleaner::default();
+        let result = cleaner.clean("Bonjour \u{00A0}!");
+        assert_eq!(result, "Bonjour \u{00A0}!");
+    }
+}
@@ -0,0 +1,638 @@
+//! Streaming, DOM-free HTML-to-Markdown conversion via html5ever's `TokenSink`
+//!
+//! [`MarkdownConverter`](crate::converter::MarkdownConverter)'s `convert`/
+//! `convert_with_context`/`convert_streaming` all operate on a fully-parsed
+//! [`markup5ever_rcdom::RcDom`]: `convert_streaming`'s output is incremental,
+//! but building that DOM in the first place still costs memory proportional
+//! to document size. [`TokenStreamConverter`] avoids building a tree at all:
+//! it implements [`TokenSink`] directly, so html5ever's [`Tokenizer`] drives
+//! it one start-tag/end-tag/character token at a time, and it tracks only an
+//! explicit stack of open block/inline contexts (list nesting, blockquote
+//! depth, whether inside `<pre>`/`<code>`) in place of DOM parent pointers.
+//! Peak memory is therefore O(nesting depth), not O(document size) — this is
+//! the "dedicated follow-up" [`crate::incremental`]'s module documentation
+//! pointed at: a true incremental tokenizer driving html5ever's `TokenSink`
+//! directly, rather than [`crate::incremental::IncrementalConverter`]'s
+//! re-parse-the-growing-prefix approximation.
+//!
+//! # Scope
+//!
+//! This is a narrower, purpose-built fast path, not a drop-in replacement for
+//! [`MarkdownConverter`](crate::converter::MarkdownConverter): it covers the
+//! block/inline constructs that benefit most from avoiding a DOM build
+//! (headings, paragraphs, lists, blockquotes, `strong`/`em`, `pre`/`code`,
+//! `br`/`hr`) plus the same
+//! [`crate::security::SecurityValidator::check_element`] hard-block list the
+//! DOM path checks first and unconditionally. Links, images, tables, and
+//! front-matter/metadata extraction need attribute handling and
+//! cross-references `TokenSink`'s per-token view doesn't give a natural home
+//! to without reintroducing a tree; use
+//! [`MarkdownConverter`](crate::converter::MarkdownConverter) when those are
+//! needed.
+//!
+//! `<script>`/`<style>`/`<title>` elements are skipped wholesale (all tokens
+//! between the start and matching end tag are discarded) rather than
+//! tokenized in html5ever's dedicated raw-text state: since their content is
+//! always discarded by [`crate::security::SecurityValidator::check_element`]
+//! anyway, the only risk from not switching tokenizer state is losing track
+//! of the `</script>` boundary itself on a contrived
+//! `"<" + "/script>"`-style payload — at worst that drops more of the
+//! document into the same skip, never leaking script/style content into the
+//! Markdown output.
+//!
+//! # Edge cases
+//!
+//! - **Unbalanced/unclosed tags**: an end tag searches the open-context stack
+//!   for a matching start tag; if found, everything above it (unclosed
+//!   descendants) is popped and closed out defensively along with it; if not
+//!   found, the end tag is a no-op rather than corrupting an unrelated
+//!   ancestor's state.
+//! - **Nested list indentation**: computed from how many [`OpenContext::List`]
+//!   entries are currently on the stack, not from a DOM ancestor chain.
+//! - **`pre`/`code` text**: passed through verbatim (no whitespace collapsing)
+//!   while either is on the stack.
+
+use std::cell::RefCell;
+use std::fmt;
+
+use html5ever::tendril::StrTendril;
+use html5ever::tokenizer::{Tag, TagKind, Token, TokenSink, TokenSinkResult, Tokenizer, TokenizerOpts};
+use html5ever::buffer_queue::BufferQueue;
+
+use crate::converter::ConversionContext;
+use crate::error::ConversionError;
+use crate::security::{SanitizeAction, SecurityValidator};
+
+/// A single open block/inline construct tracked by [`TokenStreamConverter`]
+/// while tokens arrive, replacing the DOM parent chain
+/// [`MarkdownConverter`](crate::converter::MarkdownConverter) otherwise walks
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum OpenContext {
+    /// `<h1>`-`<h6>`; carries the heading level so closing it can tell
+    /// headings apart from paragraphs without a second lookup
+    Heading(u8),
+    /// `<p>`
+    Paragraph,
+    /// `<ul>`/`<ol>`; `item_index` is bumped by [`TokenStreamConverter::next_list_marker`]
+    /// for each `<li>` directly inside this list
+    List { ordered: bool, item_index: u32 },
+    /// `<li>`
+    ListItem,
+    /// `<blockquote>`
+    Blockquote,
+    /// `<pre>`
+    Pre,
+    /// `<code>`
+    Code,
+    /// `<strong>`/`<b>`
+    Strong,
+    /// `<em>`/`<i>`
+    Emphasis,
+    /// An element with no Markdown-specific handling; its children are still
+    /// processed, it just contributes no markup of its own
+    Other(String),
+    /// `<script>`/`<style>`/`<title>`, or anything
+    /// [`SecurityValidator::check_element`] hard-blocks: every token until
+    /// the matching end tag (inclusive) is discarded
+    Skip(String),
+}
+
+impl OpenContext {
+    /// Whether `tag_name`'s end tag should close this open context
+    fn matches_end_tag(&self, tag_name: &str) -> bool {
+        match self {
+            OpenContext::Heading(_) => {
+                matches!(tag_name, "h1" | "h2" | "h3" | "h4" | "h5" | "h6")
+            }
+            OpenContext::Paragraph => tag_name == "p",
+            OpenContext::List { .. } => matches!(tag_name, "ul" | "ol"),
+            OpenContext::ListItem => tag_name == "li",
+            OpenContext::Blockquote => tag_name == "blockquote",
+            OpenContext::Pre => tag_name == "pre",
+            OpenContext::Code => tag_name == "code",
+            OpenContext::Strong => matches!(tag_name, "strong" | "b"),
+            OpenContext::Emphasis => matches!(tag_name, "em" | "i"),
+            OpenContext::Other(name) | OpenContext::Skip(name) => name == tag_name,
+        }
+    }
+}
+
+/// Elements whose content is tokenized by html5ever as ordinary markup but
+/// whose body this converter never wants to render. `script`/`style` are
+/// already covered by [`SecurityValidator::check_element`]'s hard-block list
+/// (checked first, above); `title` isn't in that list (it's meaningful
+/// metadata in the DOM path), but has no Markdown representation on this
+/// streaming fast path, so its text is skipped the same way.
+const STREAMING_SKIP_ELEMENTS: &[&str] = &["script", "style", "title"];
+
+/// Implements html5ever's [`TokenSink`] to convert HTML to Markdown without
+/// ever materializing a DOM tree
+///
+/// See the [module documentation](self) for scope and edge-case handling.
+/// Use [`convert_streaming_tokens`] rather than constructing this directly.
+struct TokenStreamConverter<'a, W: fmt::Write> {
+    security_validator: &'a SecurityValidator,
+    ctx: RefCell<&'a mut ConversionContext>,
+    output: RefCell<&'a mut W>,
+    stack: RefCell<Vec<OpenContext>>,
+    /// Whether anything has been written to `output` yet; `W: fmt::Write`
+    /// has no general read-back API, so this tracks "is the next block the
+    /// first thing written" without needing to inspect the sink itself.
+    wrote_any: RefCell<bool>,
+    /// First error encountered; once set, every later token is a no-op so
+    /// the tokenizer can still run to completion (required, since
+    /// `process_token` has no way to abort the tokenizer early) without
+    /// doing further work or masking the original error.
+    error: RefCell<Option<ConversionError>>,
+}
+
+impl<'a, W: fmt::Write> TokenStreamConverter<'a, W> {
+    fn new(
+        security_validator: &'a SecurityValidator,
+        ctx: &'a mut ConversionContext,
+        output: &'a mut W,
+    ) -> Self {
+        Self {
+            security_validator,
+            ctx: RefCell::new(ctx),
+            output: RefCell::new(output),
+            stack: RefCell::new(Vec::new()),
+            wrote_any: RefCell::new(false),
+            error: RefCell::new(None),
+        }
+    }
+
+    /// Bump the node count and check the deadline, same checkpoint frequency
+    /// as [`crate::converter::MarkdownConverter`]'s DOM traversal: every
+    /// token counts, the timeout is only actually checked every 100th
+    fn checkpoint(&self) -> Result<(), ConversionError> {
+        self.ctx.borrow_mut().increment_and_check()
+    }
+
+    fn has_failed(&self) -> bool {
+        self.error.borrow().is_some()
+    }
+
+    fn fail(&self, err: ConversionError) {
+        let mut slot = self.error.borrow_mut();
+        if slot.is_none() {
+            *slot = Some(err);
+        }
+    }
+
+    fn push_str(&self, s: &str) {
+        if self.has_failed() || s.is_empty() {
+            return;
+        }
+        if self.output.borrow_mut().write_str(s).is_err() {
+            self.fail(ConversionError::InternalError(
+                "streaming Markdown sink write failed".to_string(),
+            ));
+            return;
+        }
+        *self.wrote_any.borrow_mut() = true;
+    }
+
+    fn in_pre(&self) -> bool {
+        self.stack.borrow().iter().any(|c| *c == OpenContext::Pre)
+    }
+
+    fn in_code(&self) -> bool {
+        self.stack.borrow().iter().any(|c| *c == OpenContext::Code)
+    }
+
+    fn in_skip(&self) -> bool {
+        self.stack
+            .borrow()
+            .iter()
+            .any(|c| matches!(c, OpenContext::Skip(_)))
+    }
+
+    /// Count of [`OpenContext::List`] entries currently open, used to indent
+    /// a `<li>` by nesting depth instead of by DOM ancestry
+    fn list_depth(&self) -> usize {
+        self.stack
+            .borrow()
+            .iter()
+            .filter(|c| matches!(c, OpenContext::List { .. }))
+            .count()
+    }
+
+    /// Bump and return the marker for the next `<li>` in the innermost open
+    /// list, defaulting to an unordered marker if `<li>` appears outside any
+    /// `<ul>`/`<ol>` (malformed input html5ever still tokenizes happily)
+    fn next_list_marker(&self) -> String {
+        let mut stack = self.stack.borrow_mut();
+        match stack
+            .iter_mut()
+            .rev()
+            .find(|c| matches!(c, OpenContext::List { .. }))
+        {
+            Some(OpenContext::List { ordered, item_index }) => {
+                *item_index += 1;
+                if *ordered {
+                    format!("{}. ", item_index)
+                } else {
+                    "- ".to_string()
+                }
+            }
+            _ => "- ".to_string(),
+        }
+    }
+
+    /// Ensure the next block-level construct starts on its own blank line,
+    /// unless output is still empty
+    fn write_block_separator(&self) {
+        if *self.wrote_any.borrow() {
+            self.push_str("\n\n");
+        }
+    }
+
+    fn start_tag(&self, tag_name: &str, self_closing: bool) {
+        if self.checkpoint_and_fail_early() {
+            return;
+        }
+
+        // Checked first, unconditionally, mirroring the DOM path's
+        // defense-in-depth order (`SecurityValidator` before any
+        // Markdown-specific handling).
+        if self.security_validator.check_element(tag_name) == SanitizeAction::Remove {
+            self.stack
+                .borrow_mut()
+                .push(OpenContext::Skip(tag_name.to_string()));
+            return;
+        }
+
+        if self.in_skip() {
+            return;
+        }
+
+        if STREAMING_SKIP_ELEMENTS.contains(&tag_name) {
+            self.stack
+                .borrow_mut()
+                .push(OpenContext::Skip(tag_name.to_string()));
+            return;
+        }
+
+        match tag_name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = tag_name.as_bytes()[1] - b'0';
+                self.write_block_separator();
+                self.push_str(&"#".repeat(level as usize));
+                self.push_str(" ");
+                self.stack.borrow_mut().push(OpenContext::Heading(level));
+            }
+            "p" => {
+                self.write_block_separator();
+                self.stack.borrow_mut().push(OpenContext::Paragraph);
+            }
+            "ul" => {
+                self.stack.borrow_mut().push(OpenContext::List {
+                    ordered: false,
+                    item_index: 0,
+                });
+            }
+            "ol" => {
+                self.stack.borrow_mut().push(OpenContext::List {
+                    ordered: true,
+                    item_index: 0,
+                });
+            }
+            "li" => {
+                self.write_block_separator();
+                let indent = self.list_depth().saturating_sub(1);
+                self.push_str(&"  ".repeat(indent));
+                let marker = self.next_list_marker();
+                self.push_str(&marker);
+                self.stack.borrow_mut().push(OpenContext::ListItem);
+            }
+            "blockquote" => {
+                self.write_block_separator();
+                self.push_str("> ");
+                self.stack.borrow_mut().push(OpenContext::Blockquote);
+            }
+            "pre" => {
+                self.write_block_separator();
+                self.push_str("```\n");
+                self.stack.borrow_mut().push(OpenContext::Pre);
+            }
+            "code" => {
+                if !self.in_pre() {
+                    self.push_str("`");
+                }
+                self.stack.borrow_mut().push(OpenContext::Code);
+            }
+            "strong" | "b" => {
+                self.push_str("**");
+                self.stack.borrow_mut().push(OpenContext::Strong);
+            }
+            "em" | "i" => {
+                self.push_str("_");
+                self.stack.borrow_mut().push(OpenContext::Emphasis);
+            }
+            "br" => {
+                self.push_str("  \n");
+            }
+            "hr" => {
+                self.write_block_separator();
+                self.push_str("---");
+            }
+            _ => {
+                if !self_closing {
+                    self.stack
+                        .borrow_mut()
+                        .push(OpenContext::Other(tag_name.to_string()));
+                }
+            }
+        }
+    }
+
+    fn end_tag(&self, tag_name: &str) {
+        if self.checkpoint_and_fail_early() {
+            return;
+        }
+
+        let popped = {
+            let mut stack = self.stack.borrow_mut();
+            match stack.iter().rposition(|c| c.matches_end_tag(tag_name)) {
+                // A stray end tag with no matching open context is ignored
+                // rather than closing out an unrelated ancestor.
+                None => return,
+                Some(pos) => stack.split_off(pos),
+            }
+        };
+
+        // Close innermost-first: anything left unclosed above the match
+        // (an unbalanced/missing end tag for a descendant) is popped and
+        // closed out defensively along with it.
+        for ctx in popped.into_iter().rev() {
+            self.close_context(&ctx);
+        }
+    }
+
+    fn close_context(&self, ctx: &OpenContext) {
+        match ctx {
+            OpenContext::Heading(_) | OpenContext::Paragraph => self.push_str("\n"),
+            OpenContext::List { .. } | OpenContext::ListItem | OpenContext::Blockquote => {}
+            OpenContext::Pre => self.push_str("\n```"),
+            OpenContext::Code => {
+                if !self.in_pre() {
+                    self.push_str("`");
+                }
+            }
+            OpenContext::Strong => self.push_str("**"),
+            OpenContext::Emphasis => self.push_str("_"),
+            OpenContext::Other(_) | OpenContext::Skip(_) => {}
+        }
+    }
+
+    fn handle_text(&self, text: &str) {
+        if self.checkpoint_and_fail_early() || self.in_skip() {
+            return;
+        }
+
+        if self.in_pre() || self.in_code() {
+            self.push_str(text);
+            return;
+        }
+
+        // Collapse runs of whitespace the same as ordinary flowed text,
+        // since the tokenizer hands character tokens through untouched
+        // (including the source's original line wrapping).
+        let collapsed = text.split_ascii_whitespace().collect::<Vec<_>>().join(" ");
+        self.push_str(&collapsed);
+    }
+
+    /// Run the per-token checkpoint, recording any timeout as this
+    /// conversion's error; returns `true` if the caller should skip the rest
+    /// of its handling (already failed, here or earlier)
+    fn checkpoint_and_fail_early(&self) -> bool {
+        if self.has_failed() {
+            return true;
+        }
+        if let Err(e) = self.checkpoint() {
+            self.fail(e);
+            return true;
+        }
+        false
+    }
+}
+
+impl<'a, W: fmt::Write> TokenSink for TokenStreamConverter<'a, W> {
+    type Handle = ();
+
+    fn process_token(&mut self, token: Token, _line_number: u64) -> TokenSinkResult<Self::Handle> {
+        if self.has_failed() {
+            return TokenSinkResult::Continue;
+        }
+
+        match token {
+            Token::TagToken(Tag {
+                kind: TagKind::StartTag,
+                name,
+                self_closing,
+                ..
+            }) => self.start_tag(name.as_ref(), self_closing),
+            Token::TagToken(Tag {
+                kind: TagKind::EndTag,
+                name,
+                ..
+            }) => self.end_tag(name.as_ref()),
+            Token::CharacterTokens(text) => self.handle_text(&text),
+            Token::NullCharacterToken
+            | Token::CommentToken(_)
+            | Token::DoctypeToken(_)
+            | Token::ParseError(_)
+            | Token::EOFToken => {
+                let _ = self.checkpoint_and_fail_early();
+            }
+        }
+
+        TokenSinkResult::Continue
+    }
+}
+
+/// Convert `html` to Markdown via [`TokenStreamConverter`], writing
+/// incrementally into `sink` as tokens arrive rather than returning one
+/// finished `String`
+///
+/// `security_validator` is applied the same way the DOM path applies it:
+/// checked first and unconditionally, before any Markdown-specific element
+/// handling. `ctx` is bumped once per token and the deadline is checked
+/// every 100th, same checkpoint frequency as
+/// [`crate::converter::MarkdownConverter::convert_streaming`].
+///
+/// See the [module documentation](self) for which elements this path
+/// understands.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use nginx_markdown_converter::converter::ConversionContext;
+/// use nginx_markdown_converter::security::SecurityValidator;
+/// use nginx_markdown_converter::token_converter::convert_streaming_tokens_into;
+///
+/// let mut ctx = ConversionContext::new(Duration::ZERO);
+/// let validator = SecurityValidator::new();
+/// let mut markdown = String::new();
+/// convert_streaming_tokens_into(
+///     "<h1>Title</h1><p>Hello <strong>world</strong></p>",
+///     &mut ctx,
+///     &validator,
+///     &mut markdown,
+/// )
+/// .expect("conversion failed");
+/// assert!(markdown.contains("# Title"));
+/// assert!(markdown.contains("**world**"));
+/// ```
+pub fn convert_streaming_tokens_into<W: fmt::Write>(
+    html: &str,
+    ctx: &mut ConversionContext,
+    security_validator: &SecurityValidator,
+    sink: &mut W,
+) -> Result<(), ConversionError> {
+    let token_sink = TokenStreamConverter::new(security_validator, ctx, sink);
+
+    let mut queue = BufferQueue::default();
+    queue.push_back(StrTendril::from(html));
+
+    let mut tokenizer = Tokenizer::new(token_sink, TokenizerOpts::default());
+    let _ = tokenizer.feed(&mut queue);
+    tokenizer.end();
+
+    match tokenizer.sink.error.into_inner() {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+/// Convenience wrapper over [`convert_streaming_tokens_into`] that returns
+/// the converted Markdown as an owned `String` instead of writing into a
+/// caller-supplied sink
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use nginx_markdown_converter::converter::ConversionContext;
+/// use nginx_markdown_converter::security::SecurityValidator;
+/// use nginx_markdown_converter::token_converter::convert_streaming_tokens;
+///
+/// let mut ctx = ConversionContext::new(Duration::ZERO);
+/// let validator = SecurityValidator::new();
+/// let markdown = convert_streaming_tokens("<p>Hi</p>", &mut ctx, &validator)
+///     .expect("conversion failed");
+/// assert!(markdown.contains("Hi"));
+/// ```
+pub fn convert_streaming_tokens(
+    html: &str,
+    ctx: &mut ConversionContext,
+    security_validator: &SecurityValidator,
+) -> Result<String, ConversionError> {
+    let mut output = String::with_capacity(html.len() / 2);
+    convert_streaming_tokens_into(html, ctx, security_validator, &mut output)?;
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn convert(html: &str) -> String {
+        let mut ctx = ConversionContext::new(Duration::ZERO);
+        let validator = SecurityValidator::new();
+        convert_streaming_tokens(html, &mut ctx, &validator).expect("conversion failed")
+    }
+
+    #[test]
+    fn test_heading_and_paragraph() {
+        let result = convert("<h1>Title</h1><p>Body text</p>");
+        assert!(result.contains("# Title"));
+        assert!(result.contains("Body text"));
+    }
+
+    #[test]
+    fn test_strong_and_emphasis() {
+        let result = convert("<p>a <strong>b</strong> <em>c</em></p>");
+        assert!(result.contains("**b**"));
+        assert!(result.contains("_c_"));
+    }
+
+    #[test]
+    fn test_nested_list_indentation_by_depth() {
+        let result = convert("<ul><li>one<ul><li>nested</li></ul></li></ul>");
+        assert!(result.contains("- one"));
+        assert!(result.contains("  - nested"));
+    }
+
+    #[test]
+    fn test_ordered_list_numbers_increment() {
+        let result = convert("<ol><li>first</li><li>second</li></ol>");
+        assert!(result.contains("1. first"));
+        assert!(result.contains("2. second"));
+    }
+
+    #[test]
+    fn test_pre_code_preserves_whitespace_verbatim() {
+        let result = convert("<pre><code>fn main() {\n    loop();\n}</code></pre>");
+        assert!(result.contains("fn main() {\n    loop();\n}"));
+        assert!(result.contains("```"));
+    }
+
+    #[test]
+    fn test_ordinary_text_whitespace_is_collapsed() {
+        let result = convert("<p>a   b\n\tc</p>");
+        assert!(result.contains("a b c"));
+    }
+
+    #[test]
+    fn test_unclosed_tag_is_closed_defensively_by_ancestor_end_tag() {
+        // `<em>` is never closed; `</p>` should still close both rather than
+        // leaving an open `_` dangling or corrupting later output.
+        let result = convert("<p>a <em>b</p><p>c</p>");
+        assert!(result.contains('_'));
+        assert!(result.contains('c'));
+    }
+
+    #[test]
+    fn test_stray_end_tag_is_ignored() {
+        let result = convert("<p>a</strong>b</p>");
+        assert!(result.contains("a"));
+        assert!(result.contains("b"));
+        assert!(!result.contains("**"));
+    }
+
+    #[test]
+    fn test_script_content_is_skipped_wholesale() {
+        let result = convert("<p>before</p><script>if (a<b) { alert(1) }</script><p>after</p>");
+        assert!(result.contains("before"));
+        assert!(result.contains("after"));
+        assert!(!result.contains("alert"));
+    }
+
+    #[test]
+    fn test_dangerous_element_is_skipped_via_security_validator() {
+        let result = convert("<p>before</p><iframe>nope</iframe><p>after</p>");
+        assert!(result.contains("before"));
+        assert!(result.contains("after"));
+        assert!(!result.contains("nope"));
+    }
+
+    #[test]
+    fn test_timeout_is_detected_via_checkpointed_ctx() {
+        // The timeout is only actually checked every 100th token (matching
+        // `ConversionContext::increment_and_check`'s checkpoint frequency),
+        // so prime the counter to just below that boundary rather than
+        // relying on a handful of tokens from a tiny document to reach it.
+        let mut ctx = ConversionContext::new(Duration::from_millis(1));
+        for _ in 0..99 {
+            let _ = ctx.increment_and_check();
+        }
+        std::thread::sleep(Duration::from_millis(5));
+        let validator = SecurityValidator::new();
+        let result = convert_streaming_tokens("<p>text</p>", &mut ctx, &validator);
+        assert!(result.is_err());
+    }
+}
@@ -35,6 +35,17 @@ impl TokenEstimator {
         let char_count = markdown.chars().count();
         (char_count as f32 / self.chars_per_token).ceil() as u32
     }
+
+    /// Estimate token count from a character count tallied elsewhere, e.g.
+    /// summed across fragments as they arrive from a streaming conversion
+    /// rather than collected into one string
+    ///
+    /// Uses the same `char_count / chars_per_token` formula as
+    /// [`Self::estimate`], so `estimate_from_char_count(s.chars().count())`
+    /// and `estimate(s)` always agree.
+    pub fn estimate_from_char_count(&self, char_count: u64) -> u32 {
+        (char_count as f32 / self.chars_per_token).ceil() as u32
+    }
 }
 
 impl Default for TokenEstimator {
@@ -43,6 +54,153 @@ impl Default for TokenEstimator {
     }
 }
 
+/// Which character-count bucket [`ScriptAwareEstimator`] assigns a char to
+///
+/// Real tokenizers produce noticeably more tokens per character for CJK text
+/// than for Latin/ASCII text, so a single global `chars_per_token` ratio (as
+/// used by [`TokenEstimator`]) badly misestimates bilingual or CJK-heavy
+/// documents. This is a coarse, Unicode-block-based approximation of that
+/// split, not a script detector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScriptBucket {
+    /// ASCII letters/digits and other Latin-script text
+    Latin,
+    /// CJK Unified Ideographs, Hiragana, Katakana, and Hangul syllables
+    Cjk,
+    /// Whitespace and ASCII punctuation, which tokenize far more densely
+    /// than prose (e.g. a run of Markdown table pipes or list markers)
+    WhitespacePunctuation,
+    /// Anything not covered by the buckets above (other scripts, symbols,
+    /// emoji, combining marks)
+    Other,
+}
+
+impl ScriptBucket {
+    fn classify(c: char) -> Self {
+        if c.is_whitespace() || c.is_ascii_punctuation() {
+            Self::WhitespacePunctuation
+        } else if Self::is_cjk(c) {
+            Self::Cjk
+        } else if c.is_ascii() {
+            Self::Latin
+        } else {
+            Self::Other
+        }
+    }
+
+    /// CJK Unified Ideographs (and Extension A), Hiragana, Katakana, and the
+    /// Hangul Syllables block — deliberately not exhaustive of every CJK
+    /// block (e.g. Bopomofo, Hangul Jamo), matching the scope named in the
+    /// original request.
+    fn is_cjk(c: char) -> bool {
+        matches!(c,
+            '\u{3400}'..='\u{4DBF}'   // CJK Unified Ideographs Extension A
+            | '\u{4E00}'..='\u{9FFF}' // CJK Unified Ideographs
+            | '\u{3040}'..='\u{309F}' // Hiragana
+            | '\u{30A0}'..='\u{30FF}' // Katakana
+            | '\u{AC00}'..='\u{D7A3}' // Hangul Syllables
+        )
+    }
+}
+
+/// Token estimator that applies a different chars-per-token ratio per script
+///
+/// [`TokenEstimator`] applies one `chars_per_token` ratio to an entire
+/// string, which is a reasonable approximation for English-only text but
+/// badly misestimates mixed CJK/Latin documents: English runs around 4
+/// chars/token while CJK text is closer to 1-1.5 chars/token. This walks the
+/// input once, classifies each char into a [`ScriptBucket`], accumulates a
+/// per-bucket character count, and sums each bucket's independently-ceilinged
+/// `count / ratio`.
+pub struct ScriptAwareEstimator {
+    latin_ratio: f32,
+    cjk_ratio: f32,
+    whitespace_ratio: f32,
+    other_ratio: f32,
+}
+
+impl ScriptAwareEstimator {
+    /// Create a new estimator with default per-script ratios (4.0 Latin, 1.3
+    /// CJK, 6.0 whitespace/punctuation, 4.0 other)
+    pub fn new() -> Self {
+        Self {
+            latin_ratio: 4.0,
+            cjk_ratio: 1.3,
+            whitespace_ratio: 6.0,
+            other_ratio: 4.0,
+        }
+    }
+
+    /// Create a new estimator with custom per-script ratios
+    ///
+    /// # Arguments
+    ///
+    /// * `latin_ratio` - Chars per token for ASCII/Latin-script text
+    /// * `cjk_ratio` - Chars per token for CJK Unified Ideographs,
+    ///   Hiragana, Katakana, and Hangul
+    /// * `whitespace_ratio` - Chars per token for whitespace and ASCII
+    ///   punctuation
+    /// * `other_ratio` - Chars per token for anything not covered above
+    pub fn with_script_ratios(
+        latin_ratio: f32,
+        cjk_ratio: f32,
+        whitespace_ratio: f32,
+        other_ratio: f32,
+    ) -> Self {
+        Self {
+            latin_ratio,
+            cjk_ratio,
+            whitespace_ratio,
+            other_ratio,
+        }
+    }
+
+    /// Estimate token count for given Markdown text, using a per-script
+    /// ratio for each character rather than one ratio for the whole string
+    ///
+    /// # Arguments
+    ///
+    /// * `markdown` - Markdown text to estimate
+    ///
+    /// # Returns
+    ///
+    /// Estimated token count
+    pub fn estimate(&self, markdown: &str) -> u32 {
+        let mut latin_count = 0u32;
+        let mut cjk_count = 0u32;
+        let mut whitespace_count = 0u32;
+        let mut other_count = 0u32;
+
+        for c in markdown.chars() {
+            match ScriptBucket::classify(c) {
+                ScriptBucket::Latin => latin_count += 1,
+                ScriptBucket::Cjk => cjk_count += 1,
+                ScriptBucket::WhitespacePunctuation => whitespace_count += 1,
+                ScriptBucket::Other => other_count += 1,
+            }
+        }
+
+        Self::bucket_tokens(latin_count, self.latin_ratio)
+            + Self::bucket_tokens(cjk_count, self.cjk_ratio)
+            + Self::bucket_tokens(whitespace_count, self.whitespace_ratio)
+            + Self::bucket_tokens(other_count, self.other_ratio)
+    }
+
+    fn bucket_tokens(char_count: u32, ratio: f32) -> u32 {
+        if char_count == 0 {
+            0
+        } else {
+            (char_count as f32 / ratio).ceil() as u32
+        }
+    }
+}
+
+impl Default for ScriptAwareEstimator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -163,6 +321,30 @@ mod tests {
         assert_eq!(result, 1);
     }
 
+    #[test]
+    fn test_estimate_from_char_count_matches_estimate() {
+        let estimator = TokenEstimator::new();
+        let text = "The quick brown fox jumps over the lazy dog.";
+        assert_eq!(
+            estimator.estimate_from_char_count(text.chars().count() as u64),
+            estimator.estimate(text)
+        );
+    }
+
+    #[test]
+    fn test_estimate_from_char_count_sums_across_chunks() {
+        let estimator = TokenEstimator::new();
+        let whole = "first fragmentsecond fragmentthird fragment";
+        let chunked_count = "first fragment".chars().count()
+            + "second fragment".chars().count()
+            + "third fragment".chars().count();
+
+        assert_eq!(
+            estimator.estimate_from_char_count(chunked_count as u64),
+            estimator.estimate(whole)
+        );
+    }
+
     proptest! {
         #[test]
         fn prop_estimate_matches_default_formula(chars in prop::collection::vec(any::<char>(), 0..256)) {
@@ -192,4 +374,89 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_script_aware_pure_latin_matches_latin_ratio() {
+        let estimator = ScriptAwareEstimator::new();
+        // "test" is 4 Latin chars / 4.0 = 1 token
+        assert_eq!(estimator.estimate("test"), 1);
+    }
+
+    #[test]
+    fn test_script_aware_pure_cjk_uses_cjk_ratio() {
+        let estimator = ScriptAwareEstimator::new();
+        // 4 CJK chars / 1.3 = 3.08 -> ceil = 4 tokens
+        assert_eq!(estimator.estimate("世界你好"), 4);
+    }
+
+    #[test]
+    fn test_script_aware_mixed_cjk_latin_sums_buckets_independently() {
+        let estimator = ScriptAwareEstimator::new();
+        // "Hello " = 5 Latin chars + 1 whitespace; "世界" = 2 CJK chars
+        // Latin: 5 / 4.0 = 1.25 -> ceil = 2
+        // Whitespace: 1 / 6.0 = 0.17 -> ceil = 1
+        // CJK: 2 / 1.3 = 1.54 -> ceil = 2
+        // total = 5
+        assert_eq!(estimator.estimate("Hello 世界"), 5);
+    }
+
+    #[test]
+    fn test_script_aware_vs_uniform_estimator_diverge_on_cjk() {
+        let uniform = TokenEstimator::new();
+        let script_aware = ScriptAwareEstimator::new();
+        let cjk_text = "你好世界你好世界你好世界你好";
+
+        // The uniform estimator underestimates CJK density relative to the
+        // script-aware estimator's dedicated (lower) CJK ratio.
+        assert!(script_aware.estimate(cjk_text) > uniform.estimate(cjk_text));
+    }
+
+    #[test]
+    fn test_script_aware_custom_ratios() {
+        let estimator = ScriptAwareEstimator::with_script_ratios(2.0, 1.0, 10.0, 1.0);
+        // 4 Latin chars / 2.0 = 2 tokens
+        assert_eq!(estimator.estimate("test"), 2);
+        // 4 CJK chars / 1.0 = 4 tokens
+        assert_eq!(estimator.estimate("世界你好"), 4);
+    }
+
+    #[test]
+    fn test_script_aware_empty_string() {
+        let estimator = ScriptAwareEstimator::new();
+        assert_eq!(estimator.estimate(""), 0);
+    }
+
+    #[test]
+    fn test_script_aware_default_trait() {
+        let estimator = ScriptAwareEstimator::default();
+        assert_eq!(estimator.estimate("test"), 1);
+    }
+
+    #[test]
+    fn test_script_aware_punctuation_uses_whitespace_ratio() {
+        let estimator = ScriptAwareEstimator::new();
+        // 6 punctuation chars / 6.0 = 1 token
+        assert_eq!(estimator.estimate("!!!???"), 1);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_script_aware_estimate_is_monotonic_under_appending(
+            lhs in prop::collection::vec(any::<char>(), 0..128),
+            rhs in prop::collection::vec(any::<char>(), 0..128),
+        ) {
+            let lhs: String = lhs.into_iter().collect();
+            let rhs: String = rhs.into_iter().collect();
+            let combined = format!("{lhs}{rhs}");
+
+            let estimator = ScriptAwareEstimator::new();
+            let lhs_tokens = estimator.estimate(&lhs);
+            let combined_tokens = estimator.estimate(&combined);
+
+            prop_assert!(
+                combined_tokens >= lhs_tokens,
+                "Appending content must not reduce estimated token count"
+            );
+        }
+    }
 }
@@ -12,14 +12,15 @@
 //! 4. Both disabled
 
 use nginx_markdown_converter::ffi::{
-    ERROR_SUCCESS, MarkdownOptions, MarkdownResult, markdown_converter_new,
+    ERROR_SUCCESS, EXT_NORMALIZE_PUNCTUATION, MARKDOWN_ABI_VERSION, MarkdownOptions,
+    MarkdownResult, RENDERER_DEFAULT, markdown_converter_new,
 };
 use proptest::prelude::*;
 use std::ptr;
 use std::slice;
 
 fn ffi_markdown_convert(
-    handle: *mut nginx_markdown_converter::ffi::MarkdownConverterHandle,
+    handle: u64,
     html: *const u8,
     html_len: usize,
     options: *const MarkdownOptions,
@@ -34,10 +35,8 @@ fn ffi_markdown_result_free(result: *mut MarkdownResult) {
     unsafe { nginx_markdown_converter::ffi::markdown_result_free(result) }
 }
 
-fn ffi_markdown_converter_free(
-    handle: *mut nginx_markdown_converter::ffi::MarkdownConverterHandle,
-) {
-    unsafe { nginx_markdown_converter::ffi::markdown_converter_free(handle) }
+fn ffi_markdown_converter_free(handle: u64) {
+    nginx_markdown_converter::ffi::markdown_converter_free(handle)
 }
 
 /// Helper function to create test HTML
@@ -89,10 +88,16 @@ fn empty_result() -> MarkdownResult {
         markdown_len: 0,
         etag: ptr::null_mut(),
         etag_len: 0,
+        toc: ptr::null_mut(),
+        toc_len: 0,
         token_estimate: 0,
+        had_lossy_decode: 0,
         error_code: 0,
         error_message: ptr::null_mut(),
         error_len: 0,
+        content_encoding: ptr::null_mut(),
+        content_encoding_len: 0,
+        uncompressed_len: 0,
     }
 }
 
@@ -103,14 +108,24 @@ fn convert_with_feature_toggles(
     base_url: &[u8],
 ) -> (String, u32) {
     let converter = markdown_converter_new();
-    assert!(!converter.is_null(), "Failed to create converter");
+    assert_ne!(converter, 0, "Failed to create converter");
 
     let options = MarkdownOptions {
+        abi_version: MARKDOWN_ABI_VERSION,
         flavor: 0,
         timeout_ms: 5000,
+        max_input_bytes: 0,
+        max_output_bytes: 0,
+        max_decompressed_bytes: 0,
         generate_etag: 0,
         estimate_tokens: u8::from(estimate_tokens),
         front_matter: u8::from(front_matter),
+        preserve_tables: 1,
+        generate_toc: 0,
+        heading_anchors: 0,
+        renderer: RENDERER_DEFAULT,
+        heading_offset: 0,
+        extensions: 0,
         content_type: ptr::null(),
         content_type_len: 0,
         base_url: if front_matter {
@@ -119,6 +134,12 @@ fn convert_with_feature_toggles(
             ptr::null()
         },
         base_url_len: if front_matter { base_url.len() } else { 0 },
+        accept_encoding: ptr::null(),
+        accept_encoding_len: 0,
+        input_charset: ptr::null(),
+        input_charset_len: 0,
+        content_encoding: ptr::null(),
+        content_encoding_len: 0,
     };
 
     let mut result = empty_result();
@@ -170,19 +191,35 @@ unsafe fn result_markdown_to_string(result: &MarkdownResult) -> String {
 fn test_both_features_enabled() {
     let html = create_test_html();
     let converter = markdown_converter_new();
-    assert!(!converter.is_null(), "Failed to create converter");
+    assert_ne!(converter, 0, "Failed to create converter");
 
     let base_url = "https://example.com/page".as_bytes();
     let options = MarkdownOptions {
+        abi_version: MARKDOWN_ABI_VERSION,
         flavor: 0,
         timeout_ms: 5000,
+        max_input_bytes: 0,
+        max_output_bytes: 0,
+        max_decompressed_bytes: 0,
         generate_etag: 0,
         estimate_tokens: 1, // Enable token estimation
         front_matter: 1,    // Enable front matter
+        preserve_tables: 1,
+        generate_toc: 0,
+        heading_anchors: 0,
+        renderer: RENDERER_DEFAULT,
+        heading_offset: 0,
+        extensions: 0,
         content_type: ptr::null(),
         content_type_len: 0,
         base_url: base_url.as_ptr(),
         base_url_len: base_url.len(),
+        accept_encoding: ptr::null(),
+        accept_encoding_len: 0,
+        input_charset: ptr::null(),
+        input_charset_len: 0,
+        content_encoding: ptr::null(),
+        content_encoding_len: 0,
     };
 
     let mut result = MarkdownResult {
@@ -190,10 +227,16 @@ fn test_both_features_enabled() {
         markdown_len: 0,
         etag: ptr::null_mut(),
         etag_len: 0,
+        toc: ptr::null_mut(),
+        toc_len: 0,
         token_estimate: 0,
+        had_lossy_decode: 0,
         error_code: 0,
         error_message: ptr::null_mut(),
         error_len: 0,
+        content_encoding: ptr::null_mut(),
+        content_encoding_len: 0,
+        uncompressed_len: 0,
     };
 
     unsafe {
@@ -248,18 +291,34 @@ fn test_both_features_enabled() {
 fn test_token_estimation_only() {
     let html = create_test_html();
     let converter = markdown_converter_new();
-    assert!(!converter.is_null(), "Failed to create converter");
+    assert_ne!(converter, 0, "Failed to create converter");
 
     let options = MarkdownOptions {
+        abi_version: MARKDOWN_ABI_VERSION,
         flavor: 0,
         timeout_ms: 5000,
+        max_input_bytes: 0,
+        max_output_bytes: 0,
+        max_decompressed_bytes: 0,
         generate_etag: 0,
         estimate_tokens: 1, // Enable token estimation
         front_matter: 0,    // Disable front matter
+        preserve_tables: 1,
+        generate_toc: 0,
+        heading_anchors: 0,
+        renderer: RENDERER_DEFAULT,
+        heading_offset: 0,
+        extensions: 0,
         content_type: ptr::null(),
         content_type_len: 0,
         base_url: ptr::null(),
         base_url_len: 0,
+        accept_encoding: ptr::null(),
+        accept_encoding_len: 0,
+        input_charset: ptr::null(),
+        input_charset_len: 0,
+        content_encoding: ptr::null(),
+        content_encoding_len: 0,
     };
 
     let mut result = MarkdownResult {
@@ -267,10 +326,16 @@ fn test_token_estimation_only() {
         markdown_len: 0,
         etag: ptr::null_mut(),
         etag_len: 0,
+        toc: ptr::null_mut(),
+        toc_len: 0,
         token_estimate: 0,
+        had_lossy_decode: 0,
         error_code: 0,
         error_message: ptr::null_mut(),
         error_len: 0,
+        content_encoding: ptr::null_mut(),
+        content_encoding_len: 0,
+        uncompressed_len: 0,
     };
 
     unsafe {
@@ -321,19 +386,35 @@ fn test_token_estimation_only() {
 fn test_front_matter_only() {
     let html = create_test_html();
     let converter = markdown_converter_new();
-    assert!(!converter.is_null(), "Failed to create converter");
+    assert_ne!(converter, 0, "Failed to create converter");
 
     let base_url = "https://example.com/page".as_bytes();
     let options = MarkdownOptions {
+        abi_version: MARKDOWN_ABI_VERSION,
         flavor: 0,
         timeout_ms: 5000,
+        max_input_bytes: 0,
+        max_output_bytes: 0,
+        max_decompressed_bytes: 0,
         generate_etag: 0,
         estimate_tokens: 0, // Disable token estimation
         front_matter: 1,    // Enable front matter
+        preserve_tables: 1,
+        generate_toc: 0,
+        heading_anchors: 0,
+        renderer: RENDERER_DEFAULT,
+        heading_offset: 0,
+        extensions: 0,
         content_type: ptr::null(),
         content_type_len: 0,
         base_url: base_url.as_ptr(),
         base_url_len: base_url.len(),
+        accept_encoding: ptr::null(),
+        accept_encoding_len: 0,
+        input_charset: ptr::null(),
+        input_charset_len: 0,
+        content_encoding: ptr::null(),
+        content_encoding_len: 0,
     };
 
     let mut result = MarkdownResult {
@@ -341,10 +422,16 @@ fn test_front_matter_only() {
         markdown_len: 0,
         etag: ptr::null_mut(),
         etag_len: 0,
+        toc: ptr::null_mut(),
+        toc_len: 0,
         token_estimate: 0,
+        had_lossy_decode: 0,
         error_code: 0,
         error_message: ptr::null_mut(),
         error_len: 0,
+        content_encoding: ptr::null_mut(),
+        content_encoding_len: 0,
+        uncompressed_len: 0,
     };
 
     unsafe {
@@ -396,18 +483,34 @@ fn test_front_matter_only() {
 fn test_both_features_disabled() {
     let html = create_test_html();
     let converter = markdown_converter_new();
-    assert!(!converter.is_null(), "Failed to create converter");
+    assert_ne!(converter, 0, "Failed to create converter");
 
     let options = MarkdownOptions {
+        abi_version: MARKDOWN_ABI_VERSION,
         flavor: 0,
         timeout_ms: 5000,
+        max_input_bytes: 0,
+        max_output_bytes: 0,
+        max_decompressed_bytes: 0,
         generate_etag: 0,
         estimate_tokens: 0, // Disable token estimation
         front_matter: 0,    // Disable front matter
+        preserve_tables: 1,
+        generate_toc: 0,
+        heading_anchors: 0,
+        renderer: RENDERER_DEFAULT,
+        heading_offset: 0,
+        extensions: 0,
         content_type: ptr::null(),
         content_type_len: 0,
         base_url: ptr::null(),
         base_url_len: 0,
+        accept_encoding: ptr::null(),
+        accept_encoding_len: 0,
+        input_charset: ptr::null(),
+        input_charset_len: 0,
+        content_encoding: ptr::null(),
+        content_encoding_len: 0,
     };
 
     let mut result = MarkdownResult {
@@ -415,10 +518,16 @@ fn test_both_features_disabled() {
         markdown_len: 0,
         etag: ptr::null_mut(),
         etag_len: 0,
+        toc: ptr::null_mut(),
+        toc_len: 0,
         token_estimate: 0,
+        had_lossy_decode: 0,
         error_code: 0,
         error_message: ptr::null_mut(),
         error_len: 0,
+        content_encoding: ptr::null_mut(),
+        content_encoding_len: 0,
+        uncompressed_len: 0,
     };
 
     unsafe {
@@ -477,14 +586,24 @@ fn test_feature_independence_comprehensive() {
 
     for (estimate_tokens, front_matter, label) in test_cases.iter() {
         let converter = markdown_converter_new();
-        assert!(!converter.is_null(), "Failed to create converter");
+        assert_ne!(converter, 0, "Failed to create converter");
 
         let options = MarkdownOptions {
+            abi_version: MARKDOWN_ABI_VERSION,
             flavor: 0,
             timeout_ms: 5000,
+            max_input_bytes: 0,
+            max_output_bytes: 0,
+            max_decompressed_bytes: 0,
             generate_etag: 0,
             estimate_tokens: *estimate_tokens,
             front_matter: *front_matter,
+            preserve_tables: 1,
+            generate_toc: 0,
+            heading_anchors: 0,
+            renderer: RENDERER_DEFAULT,
+            heading_offset: 0,
+            extensions: 0,
             content_type: ptr::null(),
             content_type_len: 0,
             base_url: if *front_matter == 1 {
@@ -497,6 +616,12 @@ fn test_feature_independence_comprehensive() {
             } else {
                 0
             },
+            accept_encoding: ptr::null(),
+            accept_encoding_len: 0,
+            input_charset: ptr::null(),
+            input_charset_len: 0,
+            content_encoding: ptr::null(),
+            content_encoding_len: 0,
         };
 
         let mut result = MarkdownResult {
@@ -504,10 +629,16 @@ fn test_feature_independence_comprehensive() {
             markdown_len: 0,
             etag: ptr::null_mut(),
             etag_len: 0,
+            toc: ptr::null_mut(),
+            toc_len: 0,
             token_estimate: 0,
+            had_lossy_decode: 0,
             error_code: 0,
             error_message: ptr::null_mut(),
             error_len: 0,
+            content_encoding: ptr::null_mut(),
+            content_encoding_len: 0,
+            uncompressed_len: 0,
         };
 
         unsafe {
@@ -581,19 +712,35 @@ fn test_feature_independence_comprehensive() {
 fn test_no_hidden_dependencies() {
     let html = create_test_html();
     let converter = markdown_converter_new();
-    assert!(!converter.is_null(), "Failed to create converter");
+    assert_ne!(converter, 0, "Failed to create converter");
 
     // Test 1: Enable token estimation, verify it doesn't force front matter
     let options1 = MarkdownOptions {
+        abi_version: MARKDOWN_ABI_VERSION,
         flavor: 0,
         timeout_ms: 5000,
+        max_input_bytes: 0,
+        max_output_bytes: 0,
+        max_decompressed_bytes: 0,
         generate_etag: 0,
         estimate_tokens: 1,
         front_matter: 0,
+        preserve_tables: 1,
+        generate_toc: 0,
+        heading_anchors: 0,
+        renderer: RENDERER_DEFAULT,
+        heading_offset: 0,
+        extensions: 0,
         content_type: ptr::null(),
         content_type_len: 0,
         base_url: ptr::null(),
         base_url_len: 0,
+        accept_encoding: ptr::null(),
+        accept_encoding_len: 0,
+        input_charset: ptr::null(),
+        input_charset_len: 0,
+        content_encoding: ptr::null(),
+        content_encoding_len: 0,
     };
 
     let mut result1 = MarkdownResult {
@@ -601,10 +748,16 @@ fn test_no_hidden_dependencies() {
         markdown_len: 0,
         etag: ptr::null_mut(),
         etag_len: 0,
+        toc: ptr::null_mut(),
+        toc_len: 0,
         token_estimate: 0,
+        had_lossy_decode: 0,
         error_code: 0,
         error_message: ptr::null_mut(),
         error_len: 0,
+        content_encoding: ptr::null_mut(),
+        content_encoding_len: 0,
+        uncompressed_len: 0,
     };
 
     unsafe {
@@ -630,15 +783,31 @@ fn test_no_hidden_dependencies() {
     // Test 2: Enable front matter, verify it doesn't force token estimation
     let base_url = "https://example.com/page".as_bytes();
     let options2 = MarkdownOptions {
+        abi_version: MARKDOWN_ABI_VERSION,
         flavor: 0,
         timeout_ms: 5000,
+        max_input_bytes: 0,
+        max_output_bytes: 0,
+        max_decompressed_bytes: 0,
         generate_etag: 0,
         estimate_tokens: 0,
         front_matter: 1,
+        preserve_tables: 1,
+        generate_toc: 0,
+        heading_anchors: 0,
+        renderer: RENDERER_DEFAULT,
+        heading_offset: 0,
+        extensions: 0,
         content_type: ptr::null(),
         content_type_len: 0,
         base_url: base_url.as_ptr(),
         base_url_len: base_url.len(),
+        accept_encoding: ptr::null(),
+        accept_encoding_len: 0,
+        input_charset: ptr::null(),
+        input_charset_len: 0,
+        content_encoding: ptr::null(),
+        content_encoding_len: 0,
     };
 
     let mut result2 = MarkdownResult {
@@ -646,10 +815,16 @@ fn test_no_hidden_dependencies() {
         markdown_len: 0,
         etag: ptr::null_mut(),
         etag_len: 0,
+        toc: ptr::null_mut(),
+        toc_len: 0,
         token_estimate: 0,
+        had_lossy_decode: 0,
         error_code: 0,
         error_message: ptr::null_mut(),
         error_len: 0,
+        content_encoding: ptr::null_mut(),
+        content_encoding_len: 0,
+        uncompressed_len: 0,
     };
 
     unsafe {
@@ -679,6 +854,97 @@ fn test_no_hidden_dependencies() {
     println!("✓ No hidden dependencies: features are truly independent");
 }
 
+/// Test Case 7: `normalize_punctuation` (via `EXT_NORMALIZE_PUNCTUATION`) is
+/// independent of `estimate_tokens` and `front_matter` - all eight
+/// combinations succeed and each feature's output only reflects its own flag.
+#[test]
+fn test_normalize_punctuation_independent_of_token_and_front_matter() {
+    // Curly quotes, an em dash, and an ellipsis require an owned String
+    // literal (not representable in a raw byte string).
+    let html = format!(
+        "<!DOCTYPE html><html><head><title>Test Page</title>\
+         <meta name=\"description\" content=\"Test description\"></head>\
+         <body><h1>Main Heading</h1>\
+         <p>It\u{2019}s a \u{201C}quoted\u{201D} phrase \u{2014} with an ellipsis\u{2026}</p>\
+         </body></html>"
+    )
+    .into_bytes();
+
+    let base_url = "https://example.com/page".as_bytes();
+
+    for estimate_tokens in [0u8, 1u8] {
+        for front_matter in [0u8, 1u8] {
+            for normalize in [0u32, EXT_NORMALIZE_PUNCTUATION] {
+                let converter = markdown_converter_new();
+                assert_ne!(converter, 0, "Failed to create converter");
+
+                let options = MarkdownOptions {
+                    abi_version: MARKDOWN_ABI_VERSION,
+                    flavor: 0,
+                    timeout_ms: 5000,
+                    max_input_bytes: 0,
+                    max_output_bytes: 0,
+                    max_decompressed_bytes: 0,
+                    generate_etag: 0,
+                    estimate_tokens,
+                    front_matter,
+                    preserve_tables: 1,
+                    generate_toc: 0,
+                    heading_anchors: 0,
+                    renderer: RENDERER_DEFAULT,
+                    heading_offset: 0,
+                    extensions: normalize,
+                    content_type: ptr::null(),
+                    content_type_len: 0,
+                    base_url: if front_matter == 1 {
+                        base_url.as_ptr()
+                    } else {
+                        ptr::null()
+                    },
+                    base_url_len: if front_matter == 1 { base_url.len() } else { 0 },
+                    accept_encoding: ptr::null(),
+                    accept_encoding_len: 0,
+                    input_charset: ptr::null(),
+                    input_charset_len: 0,
+                    content_encoding: ptr::null(),
+                    content_encoding_len: 0,
+                };
+
+                let mut result = empty_result();
+
+                unsafe {
+                    ffi_markdown_convert(converter, html.as_ptr(), html.len(), &options, &mut result);
+                    assert_eq!(result.error_code, ERROR_SUCCESS);
+
+                    let markdown = result_markdown_to_string(&result);
+
+                    assert_eq!(
+                        estimate_tokens == 1,
+                        result.token_estimate > 0,
+                        "estimate_tokens should be unaffected by normalize_punctuation"
+                    );
+                    assert_eq!(
+                        front_matter == 1,
+                        markdown.starts_with("---\n"),
+                        "front_matter should be unaffected by normalize_punctuation"
+                    );
+
+                    let body = markdown_body_without_front_matter(&markdown);
+                    if normalize == EXT_NORMALIZE_PUNCTUATION {
+                        assert!(body.contains("It's a \"quoted\" phrase -- with an ellipsis..."));
+                    } else {
+                        assert!(body.contains('\u{2019}'));
+                        assert!(body.contains('\u{201C}'));
+                    }
+
+                    ffi_markdown_result_free(&mut result);
+                    ffi_markdown_converter_free(converter);
+                }
+            }
+        }
+    }
+}
+
 proptest! {
     /// Property 29: Feature Toggle Independence
     /// Validates: FR-15.6, FR-15.7, FR-15.8
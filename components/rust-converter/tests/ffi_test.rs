@@ -3,13 +3,15 @@
 //! These tests verify that the FFI functions work correctly and handle
 //! memory management properly.
 
+use nginx_markdown_converter::converter::StreamCapacity;
 use nginx_markdown_converter::ffi::*;
 use proptest::prelude::*;
+use std::ffi::c_void;
 use std::ptr;
 use std::slice;
 
 fn ffi_markdown_convert(
-    handle: *mut MarkdownConverterHandle,
+    handle: u64,
     html: *const u8,
     html_len: usize,
     options: *const MarkdownOptions,
@@ -24,34 +26,183 @@ fn ffi_markdown_result_free(result: *mut MarkdownResult) {
     unsafe { nginx_markdown_converter::ffi::markdown_result_free(result) }
 }
 
-fn ffi_markdown_converter_free(handle: *mut MarkdownConverterHandle) {
-    unsafe { nginx_markdown_converter::ffi::markdown_converter_free(handle) }
+fn ffi_markdown_converter_free(handle: u64) {
+    nginx_markdown_converter::ffi::markdown_converter_free(handle)
 }
 
 fn ffi_test_default_options() -> MarkdownOptions {
     MarkdownOptions {
+        abi_version: MARKDOWN_ABI_VERSION,
         flavor: 0,
         timeout_ms: 5000,
+        max_input_bytes: 0,
+        max_output_bytes: 0,
+        max_decompressed_bytes: 0,
         generate_etag: 0,
         estimate_tokens: 0,
         front_matter: 0,
+        preserve_tables: 1,
+        generate_toc: 0,
+        heading_anchors: 0,
+        renderer: RENDERER_DEFAULT,
+        heading_offset: 0,
+        extensions: 0,
         content_type: ptr::null(),
         content_type_len: 0,
         base_url: ptr::null(),
         base_url_len: 0,
+        accept_encoding: ptr::null(),
+        accept_encoding_len: 0,
+        input_charset: ptr::null(),
+        input_charset_len: 0,
+        content_encoding: ptr::null(),
+        content_encoding_len: 0,
     }
 }
 
+fn ffi_markdown_convert_streaming(
+    handle: u64,
+    html: *const u8,
+    html_len: usize,
+    options: *const MarkdownOptions,
+    capacity: *mut StreamCapacity,
+    sink: Option<MarkdownStreamSink>,
+    completion: Option<MarkdownStreamCompletion>,
+    user_data: *mut c_void,
+) -> u32 {
+    unsafe {
+        nginx_markdown_converter::ffi::markdown_convert_streaming(
+            handle, html, html_len, options, capacity, sink, completion, user_data,
+        )
+    }
+}
+
+fn ffi_stream_capacity_new(initial: usize) -> *mut StreamCapacity {
+    markdown_stream_capacity_new(initial)
+}
+
+fn ffi_set_log_callback(
+    handle: u64,
+    level_filter: u32,
+    callback: Option<MarkdownLogCallback>,
+    user_data: *mut c_void,
+) -> u32 {
+    unsafe {
+        nginx_markdown_converter::ffi::markdown_set_log_callback(
+            handle,
+            level_filter,
+            callback,
+            user_data,
+        )
+    }
+}
+
+/// Log callback that appends `(level, message)` to the `Vec` pointed to by
+/// `user_data`.
+unsafe extern "C" fn collect_log_records(
+    level: u32,
+    msg: *const u8,
+    msg_len: usize,
+    user_data: *mut c_void,
+) {
+    // SAFETY: test call sites pass a live `&mut Vec<(u32, String)>` as `user_data`.
+    let records = unsafe { &mut *(user_data as *mut Vec<(u32, String)>) };
+    // SAFETY: `msg`/`msg_len` describe a valid UTF-8 byte slice for the duration of this call.
+    let slice = unsafe { slice::from_raw_parts(msg, msg_len) };
+    records.push((level, String::from_utf8_lossy(slice).into_owned()));
+}
+
+fn ffi_stream_capacity_free(capacity: *mut StreamCapacity) {
+    unsafe { nginx_markdown_converter::ffi::markdown_stream_capacity_free(capacity) }
+}
+
+/// Streaming sink that appends every fragment to the `Vec<u8>` pointed to by
+/// `user_data`, always requesting more (returns 0).
+unsafe extern "C" fn collect_fragments(
+    chunk: *const u8,
+    chunk_len: usize,
+    user_data: *mut c_void,
+) -> i32 {
+    // SAFETY: test call sites pass a live `&mut Vec<u8>` as `user_data`.
+    let buf = unsafe { &mut *(user_data as *mut Vec<u8>) };
+    // SAFETY: `chunk`/`chunk_len` describe a valid UTF-8 byte slice for the duration of this call.
+    let slice = unsafe { slice::from_raw_parts(chunk, chunk_len) };
+    buf.extend_from_slice(slice);
+    0
+}
+
+/// Streaming sink that collects only the first fragment, then requests a stop.
+unsafe extern "C" fn collect_first_fragment_then_stop(
+    chunk: *const u8,
+    chunk_len: usize,
+    user_data: *mut c_void,
+) -> i32 {
+    let buf = unsafe { &mut *(user_data as *mut Vec<u8>) };
+    let slice = unsafe { slice::from_raw_parts(chunk, chunk_len) };
+    buf.extend_from_slice(slice);
+    1
+}
+
+/// `user_data` shape shared by [`collect_fragments_and_completion`] and
+/// [`collect_completion`]: `sink` and `completion` are invoked with the
+/// same `user_data` pointer, so a test that wants both the streamed
+/// fragments and the completion report needs one struct holding both.
+#[derive(Default)]
+struct StreamingOutput {
+    markdown: Vec<u8>,
+    completion: Option<(Option<String>, u32)>,
+}
+
+unsafe extern "C" fn collect_fragments_and_completion(
+    chunk: *const u8,
+    chunk_len: usize,
+    user_data: *mut c_void,
+) -> i32 {
+    // SAFETY: test call sites pass a live `&mut StreamingOutput` as `user_data`.
+    let out = unsafe { &mut *(user_data as *mut StreamingOutput) };
+    // SAFETY: `chunk`/`chunk_len` describe a valid UTF-8 byte slice for the duration of this call.
+    let slice = unsafe { slice::from_raw_parts(chunk, chunk_len) };
+    out.markdown.extend_from_slice(slice);
+    0
+}
+
+/// Completion callback that records `(etag, token_estimate)` into the
+/// [`StreamingOutput`] pointed to by `user_data`; `etag` is recorded as
+/// `None` when the callback receives a NULL pointer.
+unsafe extern "C" fn collect_completion(
+    etag: *const u8,
+    etag_len: usize,
+    token_estimate: u32,
+    user_data: *mut c_void,
+) {
+    // SAFETY: test call sites pass a live `&mut StreamingOutput` as `user_data`.
+    let out = unsafe { &mut *(user_data as *mut StreamingOutput) };
+    let etag = if etag.is_null() {
+        None
+    } else {
+        // SAFETY: `etag`/`etag_len` describe a valid UTF-8 byte slice for the duration of this call.
+        let slice = unsafe { slice::from_raw_parts(etag, etag_len) };
+        Some(String::from_utf8(slice.to_vec()).expect("etag must be valid utf-8"))
+    };
+    out.completion = Some((etag, token_estimate));
+}
+
 fn ffi_test_empty_result() -> MarkdownResult {
     MarkdownResult {
         markdown: ptr::null_mut(),
         markdown_len: 0,
         etag: ptr::null_mut(),
         etag_len: 0,
+        toc: ptr::null_mut(),
+        toc_len: 0,
         token_estimate: 0,
+        had_lossy_decode: 0,
         error_code: 0,
         error_message: ptr::null_mut(),
         error_len: 0,
+        content_encoding: ptr::null_mut(),
+        content_encoding_len: 0,
+        uncompressed_len: 0,
     }
 }
 
@@ -59,7 +210,7 @@ fn ffi_test_empty_result() -> MarkdownResult {
 fn test_converter_lifecycle() {
     // Create converter
     let converter = markdown_converter_new();
-    assert!(!converter.is_null(), "Converter should not be NULL");
+    assert_ne!(converter, 0, "Converter should not be NULL");
 
     // Free converter
     ffi_markdown_converter_free(converter);
@@ -69,22 +220,38 @@ fn test_converter_lifecycle() {
 fn test_basic_conversion() {
     // Create converter
     let converter = markdown_converter_new();
-    assert!(!converter.is_null(), "Converter should not be NULL");
+    assert_ne!(converter, 0, "Converter should not be NULL");
 
     // Prepare input
     let html = b"<h1>Hello World</h1><p>This is a test.</p>";
 
     // Prepare options
     let options = MarkdownOptions {
+        abi_version: MARKDOWN_ABI_VERSION,
         flavor: 0, // CommonMark
         timeout_ms: 5000,
+        max_input_bytes: 0,
+        max_output_bytes: 0,
+        max_decompressed_bytes: 0,
         generate_etag: 1,
         estimate_tokens: 1,
         front_matter: 0,
+        preserve_tables: 1,
+        generate_toc: 0,
+        heading_anchors: 0,
+        renderer: RENDERER_DEFAULT,
+        heading_offset: 0,
+        extensions: 0,
         content_type: ptr::null(),
         content_type_len: 0,
         base_url: ptr::null(),
         base_url_len: 0,
+        accept_encoding: ptr::null(),
+        accept_encoding_len: 0,
+        input_charset: ptr::null(),
+        input_charset_len: 0,
+        content_encoding: ptr::null(),
+        content_encoding_len: 0,
     };
 
     // Perform conversion
@@ -93,10 +260,16 @@ fn test_basic_conversion() {
         markdown_len: 0,
         etag: ptr::null_mut(),
         etag_len: 0,
+        toc: ptr::null_mut(),
+        toc_len: 0,
         token_estimate: 0,
+        had_lossy_decode: 0,
         error_code: 0,
         error_message: ptr::null_mut(),
         error_len: 0,
+        content_encoding: ptr::null_mut(),
+        content_encoding_len: 0,
+        uncompressed_len: 0,
     };
 
     ffi_markdown_convert(converter, html.as_ptr(), html.len(), &options, &mut result);
@@ -151,7 +324,7 @@ proptest! {
         estimate_enabled in any::<bool>(),
     ) {
         let converter = markdown_converter_new();
-        prop_assert!(!converter.is_null(), "Converter handle should be created");
+        prop_assert_ne!(converter, 0, "Converter handle should be created");
 
         let html = format!("<h1>{}</h1><p>{}</p>", heading, paragraph);
 
@@ -197,19 +370,35 @@ proptest! {
 fn test_null_pointer_handling() {
     // Create converter
     let converter = markdown_converter_new();
-    assert!(!converter.is_null(), "Converter should not be NULL");
+    assert_ne!(converter, 0, "Converter should not be NULL");
 
     let html = b"<p>Test</p>";
     let options = MarkdownOptions {
+        abi_version: MARKDOWN_ABI_VERSION,
         flavor: 0,
         timeout_ms: 5000,
+        max_input_bytes: 0,
+        max_output_bytes: 0,
+        max_decompressed_bytes: 0,
         generate_etag: 0,
         estimate_tokens: 0,
         front_matter: 0,
+        preserve_tables: 1,
+        generate_toc: 0,
+        heading_anchors: 0,
+        renderer: RENDERER_DEFAULT,
+        heading_offset: 0,
+        extensions: 0,
         content_type: ptr::null(),
         content_type_len: 0,
         base_url: ptr::null(),
         base_url_len: 0,
+        accept_encoding: ptr::null(),
+        accept_encoding_len: 0,
+        input_charset: ptr::null(),
+        input_charset_len: 0,
+        content_encoding: ptr::null(),
+        content_encoding_len: 0,
     };
 
     let mut result = MarkdownResult {
@@ -217,21 +406,24 @@ fn test_null_pointer_handling() {
         markdown_len: 0,
         etag: ptr::null_mut(),
         etag_len: 0,
+        toc: ptr::null_mut(),
+        toc_len: 0,
         token_estimate: 0,
+        had_lossy_decode: 0,
         error_code: 0,
         error_message: ptr::null_mut(),
         error_len: 0,
+        content_encoding: ptr::null_mut(),
+        content_encoding_len: 0,
+        uncompressed_len: 0,
     };
 
     // Test NULL converter handle
-    ffi_markdown_convert(
-        ptr::null_mut(),
-        html.as_ptr(),
-        html.len(),
-        &options,
-        &mut result,
+    ffi_markdown_convert(0, html.as_ptr(), html.len(), &options, &mut result);
+    assert_eq!(
+        result.error_code, ERROR_INVALID_INPUT,
+        "NULL handle should yield the documented ERROR_INVALID_INPUT code"
     );
-    assert_ne!(result.error_code, 0, "Should return error for NULL handle");
     assert!(!result.error_message.is_null(), "Should have error message");
     ffi_markdown_result_free(&mut result);
 
@@ -241,15 +433,24 @@ fn test_null_pointer_handling() {
         markdown_len: 0,
         etag: ptr::null_mut(),
         etag_len: 0,
+        toc: ptr::null_mut(),
+        toc_len: 0,
         token_estimate: 0,
+        had_lossy_decode: 0,
         error_code: 0,
         error_message: ptr::null_mut(),
         error_len: 0,
+        content_encoding: ptr::null_mut(),
+        content_encoding_len: 0,
+        uncompressed_len: 0,
     };
 
     // Test NULL HTML pointer
     ffi_markdown_convert(converter, ptr::null(), 0, &options, &mut result);
-    assert_ne!(result.error_code, 0, "Should return error for NULL HTML");
+    assert_eq!(
+        result.error_code, ERROR_INVALID_INPUT,
+        "NULL HTML pointer should yield the documented ERROR_INVALID_INPUT code"
+    );
     assert!(!result.error_message.is_null(), "Should have error message");
     ffi_markdown_result_free(&mut result);
 
@@ -259,10 +460,16 @@ fn test_null_pointer_handling() {
         markdown_len: 0,
         etag: ptr::null_mut(),
         etag_len: 0,
+        toc: ptr::null_mut(),
+        toc_len: 0,
         token_estimate: 0,
+        had_lossy_decode: 0,
         error_code: 0,
         error_message: ptr::null_mut(),
         error_len: 0,
+        content_encoding: ptr::null_mut(),
+        content_encoding_len: 0,
+        uncompressed_len: 0,
     };
 
     // Test NULL options pointer
@@ -273,7 +480,10 @@ fn test_null_pointer_handling() {
         ptr::null(),
         &mut result,
     );
-    assert_ne!(result.error_code, 0, "Should return error for NULL options");
+    assert_eq!(
+        result.error_code, ERROR_INVALID_INPUT,
+        "NULL options pointer should yield the documented ERROR_INVALID_INPUT code"
+    );
     assert!(!result.error_message.is_null(), "Should have error message");
     ffi_markdown_result_free(&mut result);
 
@@ -285,18 +495,34 @@ fn test_null_pointer_handling() {
 fn test_multiple_conversions() {
     // Create converter
     let converter = markdown_converter_new();
-    assert!(!converter.is_null(), "Converter should not be NULL");
+    assert_ne!(converter, 0, "Converter should not be NULL");
 
     let options = MarkdownOptions {
+        abi_version: MARKDOWN_ABI_VERSION,
         flavor: 0,
         timeout_ms: 5000,
+        max_input_bytes: 0,
+        max_output_bytes: 0,
+        max_decompressed_bytes: 0,
         generate_etag: 0,
         estimate_tokens: 0,
         front_matter: 0,
+        preserve_tables: 1,
+        generate_toc: 0,
+        heading_anchors: 0,
+        renderer: RENDERER_DEFAULT,
+        heading_offset: 0,
+        extensions: 0,
         content_type: ptr::null(),
         content_type_len: 0,
         base_url: ptr::null(),
         base_url_len: 0,
+        accept_encoding: ptr::null(),
+        accept_encoding_len: 0,
+        input_charset: ptr::null(),
+        input_charset_len: 0,
+        content_encoding: ptr::null(),
+        content_encoding_len: 0,
     };
 
     // Perform multiple conversions
@@ -309,10 +535,16 @@ fn test_multiple_conversions() {
             markdown_len: 0,
             etag: ptr::null_mut(),
             etag_len: 0,
+            toc: ptr::null_mut(),
+            toc_len: 0,
             token_estimate: 0,
+            had_lossy_decode: 0,
             error_code: 0,
             error_message: ptr::null_mut(),
             error_len: 0,
+            content_encoding: ptr::null_mut(),
+            content_encoding_len: 0,
+            uncompressed_len: 0,
         };
 
         ffi_markdown_convert(
@@ -342,19 +574,35 @@ fn test_multiple_conversions() {
 fn test_idempotent_free() {
     // Create converter
     let converter = markdown_converter_new();
-    assert!(!converter.is_null(), "Converter should not be NULL");
+    assert_ne!(converter, 0, "Converter should not be NULL");
 
     let html = b"<p>Test</p>";
     let options = MarkdownOptions {
+        abi_version: MARKDOWN_ABI_VERSION,
         flavor: 0,
         timeout_ms: 5000,
+        max_input_bytes: 0,
+        max_output_bytes: 0,
+        max_decompressed_bytes: 0,
         generate_etag: 0,
         estimate_tokens: 0,
         front_matter: 0,
+        preserve_tables: 1,
+        generate_toc: 0,
+        heading_anchors: 0,
+        renderer: RENDERER_DEFAULT,
+        heading_offset: 0,
+        extensions: 0,
         content_type: ptr::null(),
         content_type_len: 0,
         base_url: ptr::null(),
         base_url_len: 0,
+        accept_encoding: ptr::null(),
+        accept_encoding_len: 0,
+        input_charset: ptr::null(),
+        input_charset_len: 0,
+        content_encoding: ptr::null(),
+        content_encoding_len: 0,
     };
 
     let mut result = MarkdownResult {
@@ -362,10 +610,16 @@ fn test_idempotent_free() {
         markdown_len: 0,
         etag: ptr::null_mut(),
         etag_len: 0,
+        toc: ptr::null_mut(),
+        toc_len: 0,
         token_estimate: 0,
+        had_lossy_decode: 0,
         error_code: 0,
         error_message: ptr::null_mut(),
         error_len: 0,
+        content_encoding: ptr::null_mut(),
+        content_encoding_len: 0,
+        uncompressed_len: 0,
     };
 
     ffi_markdown_convert(converter, html.as_ptr(), html.len(), &options, &mut result);
@@ -385,21 +639,37 @@ fn test_idempotent_free() {
 fn test_content_type_charset_detection() {
     // Create converter
     let converter = markdown_converter_new();
-    assert!(!converter.is_null(), "Converter should not be NULL");
+    assert_ne!(converter, 0, "Converter should not be NULL");
 
     let html = b"<p>Test</p>";
     let content_type = b"text/html; charset=UTF-8";
 
     let options = MarkdownOptions {
+        abi_version: MARKDOWN_ABI_VERSION,
         flavor: 0,
         timeout_ms: 5000,
+        max_input_bytes: 0,
+        max_output_bytes: 0,
+        max_decompressed_bytes: 0,
         generate_etag: 0,
         estimate_tokens: 0,
         front_matter: 0,
+        preserve_tables: 1,
+        generate_toc: 0,
+        heading_anchors: 0,
+        renderer: RENDERER_DEFAULT,
+        heading_offset: 0,
+        extensions: 0,
         content_type: content_type.as_ptr(),
         content_type_len: content_type.len(),
         base_url: ptr::null(),
         base_url_len: 0,
+        accept_encoding: ptr::null(),
+        accept_encoding_len: 0,
+        input_charset: ptr::null(),
+        input_charset_len: 0,
+        content_encoding: ptr::null(),
+        content_encoding_len: 0,
     };
 
     let mut result = MarkdownResult {
@@ -407,10 +677,16 @@ fn test_content_type_charset_detection() {
         markdown_len: 0,
         etag: ptr::null_mut(),
         etag_len: 0,
+        toc: ptr::null_mut(),
+        toc_len: 0,
         token_estimate: 0,
+        had_lossy_decode: 0,
         error_code: 0,
         error_message: ptr::null_mut(),
         error_len: 0,
+        content_encoding: ptr::null_mut(),
+        content_encoding_len: 0,
+        uncompressed_len: 0,
     };
 
     ffi_markdown_convert(converter, html.as_ptr(), html.len(), &options, &mut result);
@@ -432,20 +708,36 @@ fn test_content_type_charset_detection() {
 fn test_gfm_flavor() {
     // Create converter
     let converter = markdown_converter_new();
-    assert!(!converter.is_null(), "Converter should not be NULL");
+    assert_ne!(converter, 0, "Converter should not be NULL");
 
     let html = b"<table><tr><th>Header</th></tr><tr><td>Cell</td></tr></table>";
 
     let options = MarkdownOptions {
+        abi_version: MARKDOWN_ABI_VERSION,
         flavor: 1, // GFM
         timeout_ms: 5000,
+        max_input_bytes: 0,
+        max_output_bytes: 0,
+        max_decompressed_bytes: 0,
         generate_etag: 0,
         estimate_tokens: 0,
         front_matter: 0,
+        preserve_tables: 1,
+        generate_toc: 0,
+        heading_anchors: 0,
+        renderer: RENDERER_DEFAULT,
+        heading_offset: 0,
+        extensions: 0,
         content_type: ptr::null(),
         content_type_len: 0,
         base_url: ptr::null(),
         base_url_len: 0,
+        accept_encoding: ptr::null(),
+        accept_encoding_len: 0,
+        input_charset: ptr::null(),
+        input_charset_len: 0,
+        content_encoding: ptr::null(),
+        content_encoding_len: 0,
     };
 
     let mut result = MarkdownResult {
@@ -453,10 +745,16 @@ fn test_gfm_flavor() {
         markdown_len: 0,
         etag: ptr::null_mut(),
         etag_len: 0,
+        toc: ptr::null_mut(),
+        toc_len: 0,
         token_estimate: 0,
+        had_lossy_decode: 0,
         error_code: 0,
         error_message: ptr::null_mut(),
         error_len: 0,
+        content_encoding: ptr::null_mut(),
+        content_encoding_len: 0,
+        uncompressed_len: 0,
     };
 
     ffi_markdown_convert(converter, html.as_ptr(), html.len(), &options, &mut result);
@@ -482,6 +780,166 @@ fn test_gfm_flavor() {
     ffi_markdown_converter_free(converter);
 }
 
+#[test]
+fn test_unrecognized_flavor_rejected_with_invalid_input() {
+    let converter = markdown_converter_new();
+    assert_ne!(converter, 0);
+
+    let html = b"<p>hi</p>";
+    let mut options = ffi_test_default_options();
+    options.flavor = 2; // neither FLAVOR_COMMONMARK nor FLAVOR_GFM
+
+    let mut result = ffi_test_empty_result();
+    ffi_markdown_convert(converter, html.as_ptr(), html.len(), &options, &mut result);
+
+    assert_eq!(result.error_code, ERROR_INVALID_INPUT);
+    assert!(result.markdown.is_null());
+    assert!(!result.error_message.is_null());
+
+    ffi_markdown_result_free(&mut result);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_unrecognized_renderer_rejected_with_invalid_input() {
+    let converter = markdown_converter_new();
+    assert_ne!(converter, 0);
+
+    let html = b"<p>hi</p>";
+    let mut options = ffi_test_default_options();
+    options.renderer = 1; // not RENDERER_DEFAULT; no other backend is implemented
+
+    let mut result = ffi_test_empty_result();
+    ffi_markdown_convert(converter, html.as_ptr(), html.len(), &options, &mut result);
+
+    assert_eq!(result.error_code, ERROR_INVALID_INPUT);
+    assert!(result.markdown.is_null());
+    assert!(!result.error_message.is_null());
+
+    ffi_markdown_result_free(&mut result);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_heading_offset_out_of_range_rejected_with_invalid_input() {
+    let converter = markdown_converter_new();
+    assert_ne!(converter, 0);
+
+    let html = b"<h1>hi</h1>";
+    let mut options = ffi_test_default_options();
+    options.heading_offset = 6; // only 0-5 are valid
+
+    let mut result = ffi_test_empty_result();
+    ffi_markdown_convert(converter, html.as_ptr(), html.len(), &options, &mut result);
+
+    assert_eq!(result.error_code, ERROR_INVALID_INPUT);
+    assert!(result.markdown.is_null());
+    assert!(!result.error_message.is_null());
+
+    ffi_markdown_result_free(&mut result);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_heading_offset_shifts_toc_nesting() {
+    let converter = markdown_converter_new();
+    assert_ne!(converter, 0);
+
+    let html = b"<h1>Top</h1><h2>Child</h2>";
+    let mut options = ffi_test_default_options();
+    options.generate_toc = 1;
+    options.heading_offset = 2;
+    let mut result = ffi_test_empty_result();
+    ffi_markdown_convert(converter, html.as_ptr(), html.len(), &options, &mut result);
+
+    assert_eq!(result.error_code, 0);
+    assert!(!result.toc.is_null());
+    let toc = unsafe {
+        let bytes = slice::from_raw_parts(result.toc, result.toc_len);
+        std::str::from_utf8(bytes).expect("valid utf-8").to_owned()
+    };
+    // h1 shifted to level 3 is the shallowest entry (no indent); h2 shifted
+    // to level 4 nests one level (two spaces) beneath it.
+    assert!(toc.contains("- [Top](#top)\n  - [Child](#child)"));
+
+    ffi_markdown_result_free(&mut result);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_inline_toc_splices_into_markdown() {
+    let converter = markdown_converter_new();
+    assert_ne!(converter, 0);
+
+    let html = b"<h1>Top</h1><p>Body</p>";
+    let mut options = ffi_test_default_options();
+    options.extensions = EXT_INLINE_TOC;
+    let mut result = ffi_test_empty_result();
+    ffi_markdown_convert(converter, html.as_ptr(), html.len(), &options, &mut result);
+
+    assert_eq!(result.error_code, 0);
+    let markdown = unsafe {
+        let bytes = slice::from_raw_parts(result.markdown, result.markdown_len);
+        std::str::from_utf8(bytes).expect("valid utf-8").to_owned()
+    };
+    assert!(markdown.contains("- [Top](#top)"));
+    // `MarkdownResult::toc` remains the separate, opt-in output; it was not
+    // requested here via `generate_toc`.
+    assert!(result.toc.is_null());
+
+    ffi_markdown_result_free(&mut result);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_emoji_shortcodes_extension_rewrites_prose_emoji() {
+    let converter = markdown_converter_new();
+    assert_ne!(converter, 0);
+
+    let html = "<p>Great work \u{1F389} team</p>".to_string().into_bytes();
+    let mut options = ffi_test_default_options();
+    options.extensions = EXT_EMOJI_SHORTCODES;
+    let mut result = ffi_test_empty_result();
+    ffi_markdown_convert(converter, html.as_ptr(), html.len(), &options, &mut result);
+
+    assert_eq!(result.error_code, 0);
+    let markdown = unsafe {
+        let bytes = slice::from_raw_parts(result.markdown, result.markdown_len);
+        std::str::from_utf8(bytes).expect("valid utf-8").to_owned()
+    };
+    assert!(markdown.contains("Great work :tada: team"));
+
+    ffi_markdown_result_free(&mut result);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_oversized_input_rejected_with_input_too_large() {
+    let converter = markdown_converter_new();
+    assert_ne!(converter, 0);
+
+    let html = b"<p>this body is longer than the cap below</p>";
+    let mut options = ffi_test_default_options();
+    options.max_input_bytes = 4;
+    options.generate_etag = 1;
+    options.estimate_tokens = 1;
+
+    let mut result = ffi_test_empty_result();
+    ffi_markdown_convert(converter, html.as_ptr(), html.len(), &options, &mut result);
+
+    assert_eq!(result.error_code, ERROR_INPUT_TOO_LARGE);
+    assert!(result.markdown.is_null());
+    assert_eq!(result.markdown_len, 0);
+    assert!(result.etag.is_null());
+    assert_eq!(result.etag_len, 0);
+    assert_eq!(result.token_estimate, 0);
+    assert!(!result.error_message.is_null());
+    assert!(result.error_len > 0);
+
+    ffi_markdown_result_free(&mut result);
+    ffi_markdown_converter_free(converter);
+}
+
 // ============================================================================
 // Additional Tests for Task 9.3: FFI Null Pointer Handling
 // ============================================================================
@@ -491,19 +949,35 @@ fn test_null_result_pointer() {
     // Test that markdown_convert handles NULL result pointer gracefully
     // This should not crash - the function should return early
     let converter = markdown_converter_new();
-    assert!(!converter.is_null(), "Converter should not be NULL");
+    assert_ne!(converter, 0, "Converter should not be NULL");
 
     let html = b"<p>Test</p>";
     let options = MarkdownOptions {
+        abi_version: MARKDOWN_ABI_VERSION,
         flavor: 0,
         timeout_ms: 5000,
+        max_input_bytes: 0,
+        max_output_bytes: 0,
+        max_decompressed_bytes: 0,
         generate_etag: 0,
         estimate_tokens: 0,
         front_matter: 0,
+        preserve_tables: 1,
+        generate_toc: 0,
+        heading_anchors: 0,
+        renderer: RENDERER_DEFAULT,
+        heading_offset: 0,
+        extensions: 0,
         content_type: ptr::null(),
         content_type_len: 0,
         base_url: ptr::null(),
         base_url_len: 0,
+        accept_encoding: ptr::null(),
+        accept_encoding_len: 0,
+        input_charset: ptr::null(),
+        input_charset_len: 0,
+        content_encoding: ptr::null(),
+        content_encoding_len: 0,
     };
 
     // Call with NULL result pointer - should not crash
@@ -522,7 +996,7 @@ fn test_null_result_pointer() {
 #[test]
 fn test_free_null_converter() {
     // Test that markdown_converter_free handles NULL gracefully
-    ffi_markdown_converter_free(ptr::null_mut());
+    ffi_markdown_converter_free(0);
     // If we get here, the function handled NULL gracefully
 }
 
@@ -541,10 +1015,16 @@ fn test_free_empty_result() {
         markdown_len: 0,
         etag: ptr::null_mut(),
         etag_len: 0,
+        toc: ptr::null_mut(),
+        toc_len: 0,
         token_estimate: 0,
+        had_lossy_decode: 0,
         error_code: 0,
         error_message: ptr::null_mut(),
         error_len: 0,
+        content_encoding: ptr::null_mut(),
+        content_encoding_len: 0,
+        uncompressed_len: 0,
     };
 
     // Should handle empty result gracefully
@@ -561,19 +1041,35 @@ fn test_free_empty_result() {
 fn test_memory_cleanup_with_all_fields() {
     // Test that all allocated fields are properly freed
     let converter = markdown_converter_new();
-    assert!(!converter.is_null(), "Converter should not be NULL");
+    assert_ne!(converter, 0, "Converter should not be NULL");
 
     let html = b"<h1>Test</h1><p>Content</p>";
     let options = MarkdownOptions {
+        abi_version: MARKDOWN_ABI_VERSION,
         flavor: 0,
         timeout_ms: 5000,
+        max_input_bytes: 0,
+        max_output_bytes: 0,
+        max_decompressed_bytes: 0,
         generate_etag: 1,   // Enable ETag
         estimate_tokens: 1, // Enable token estimation
         front_matter: 0,
+        preserve_tables: 1,
+        generate_toc: 0,
+        heading_anchors: 0,
+        renderer: RENDERER_DEFAULT,
+        heading_offset: 0,
+        extensions: 0,
         content_type: ptr::null(),
         content_type_len: 0,
         base_url: ptr::null(),
         base_url_len: 0,
+        accept_encoding: ptr::null(),
+        accept_encoding_len: 0,
+        input_charset: ptr::null(),
+        input_charset_len: 0,
+        content_encoding: ptr::null(),
+        content_encoding_len: 0,
     };
 
     let mut result = MarkdownResult {
@@ -581,10 +1077,16 @@ fn test_memory_cleanup_with_all_fields() {
         markdown_len: 0,
         etag: ptr::null_mut(),
         etag_len: 0,
+        toc: ptr::null_mut(),
+        toc_len: 0,
         token_estimate: 0,
+        had_lossy_decode: 0,
         error_code: 0,
         error_message: ptr::null_mut(),
         error_len: 0,
+        content_encoding: ptr::null_mut(),
+        content_encoding_len: 0,
+        uncompressed_len: 0,
     };
 
     ffi_markdown_convert(converter, html.as_ptr(), html.len(), &options, &mut result);
@@ -615,19 +1117,35 @@ fn test_memory_cleanup_with_all_fields() {
 fn test_memory_cleanup_error_case() {
     // Test that error message is properly freed
     let converter = markdown_converter_new();
-    assert!(!converter.is_null(), "Converter should not be NULL");
+    assert_ne!(converter, 0, "Converter should not be NULL");
 
     let _html = b"<p>Test</p>";
     let options = MarkdownOptions {
+        abi_version: MARKDOWN_ABI_VERSION,
         flavor: 0,
         timeout_ms: 5000,
+        max_input_bytes: 0,
+        max_output_bytes: 0,
+        max_decompressed_bytes: 0,
         generate_etag: 0,
         estimate_tokens: 0,
         front_matter: 0,
+        preserve_tables: 1,
+        generate_toc: 0,
+        heading_anchors: 0,
+        renderer: RENDERER_DEFAULT,
+        heading_offset: 0,
+        extensions: 0,
         content_type: ptr::null(),
         content_type_len: 0,
         base_url: ptr::null(),
         base_url_len: 0,
+        accept_encoding: ptr::null(),
+        accept_encoding_len: 0,
+        input_charset: ptr::null(),
+        input_charset_len: 0,
+        content_encoding: ptr::null(),
+        content_encoding_len: 0,
     };
 
     let mut result = MarkdownResult {
@@ -635,10 +1153,16 @@ fn test_memory_cleanup_error_case() {
         markdown_len: 0,
         etag: ptr::null_mut(),
         etag_len: 0,
+        toc: ptr::null_mut(),
+        toc_len: 0,
         token_estimate: 0,
+        had_lossy_decode: 0,
         error_code: 0,
         error_message: ptr::null_mut(),
         error_len: 0,
+        content_encoding: ptr::null_mut(),
+        content_encoding_len: 0,
+        uncompressed_len: 0,
     };
 
     // Trigger error with NULL HTML pointer
@@ -665,24 +1189,45 @@ fn test_memory_cleanup_error_case() {
 }
 
 #[test]
-fn test_panic_catching_invalid_utf8() {
-    // Test that panics during conversion are caught and converted to errors
+fn test_invalid_utf8_decoded_lossily_not_rejected() {
+    // With no declared charset, these bytes sniff as a UTF-16LE BOM (FF FE)
+    // followed by a truncated code unit. Charset decoding is always lenient
+    // here (see `decode_html_to_utf8_with_mode`'s `strict_decoding` param,
+    // which every FFI entry point leaves `false`), so this substitutes
+    // U+FFFD and succeeds rather than erroring - there is no byte sequence
+    // that fails decoding through the public API today.
     let converter = markdown_converter_new();
-    assert!(!converter.is_null(), "Converter should not be NULL");
+    assert_ne!(converter, 0, "Converter should not be NULL");
 
     // Create invalid UTF-8 sequence
     let invalid_utf8 = [0xFF, 0xFE, 0xFD];
 
     let options = MarkdownOptions {
+        abi_version: MARKDOWN_ABI_VERSION,
         flavor: 0,
         timeout_ms: 5000,
+        max_input_bytes: 0,
+        max_output_bytes: 0,
+        max_decompressed_bytes: 0,
         generate_etag: 0,
         estimate_tokens: 0,
         front_matter: 0,
+        preserve_tables: 1,
+        generate_toc: 0,
+        heading_anchors: 0,
+        renderer: RENDERER_DEFAULT,
+        heading_offset: 0,
+        extensions: 0,
         content_type: ptr::null(),
         content_type_len: 0,
         base_url: ptr::null(),
         base_url_len: 0,
+        accept_encoding: ptr::null(),
+        accept_encoding_len: 0,
+        input_charset: ptr::null(),
+        input_charset_len: 0,
+        content_encoding: ptr::null(),
+        content_encoding_len: 0,
     };
 
     let mut result = MarkdownResult {
@@ -690,10 +1235,16 @@ fn test_panic_catching_invalid_utf8() {
         markdown_len: 0,
         etag: ptr::null_mut(),
         etag_len: 0,
+        toc: ptr::null_mut(),
+        toc_len: 0,
         token_estimate: 0,
+        had_lossy_decode: 0,
         error_code: 0,
         error_message: ptr::null_mut(),
         error_len: 0,
+        content_encoding: ptr::null_mut(),
+        content_encoding_len: 0,
+        uncompressed_len: 0,
     };
 
     ffi_markdown_convert(
@@ -704,19 +1255,14 @@ fn test_panic_catching_invalid_utf8() {
         &mut result,
     );
 
-    // Should return error, not panic
-    assert_ne!(
-        result.error_code, 0,
-        "Should return error for invalid UTF-8"
+    assert_eq!(
+        result.error_code, ERROR_SUCCESS,
+        "Malformed bytes should be lossily decoded, not rejected"
+    );
+    assert_eq!(
+        result.had_lossy_decode, 1,
+        "The truncated UTF-16LE code unit should have been replaced with U+FFFD"
     );
-    assert!(!result.error_message.is_null(), "Should have error message");
-
-    // Verify error message is valid UTF-8
-    unsafe {
-        let error_slice = slice::from_raw_parts(result.error_message, result.error_len);
-        let error_str = std::str::from_utf8(error_slice);
-        assert!(error_str.is_ok(), "Error message should be valid UTF-8");
-    }
 
     ffi_markdown_result_free(&mut result);
     ffi_markdown_converter_free(converter);
@@ -727,20 +1273,36 @@ fn test_zero_length_html() {
     // Test conversion with zero-length HTML
     // Note: Zero-length HTML with valid pointer should succeed
     let converter = markdown_converter_new();
-    assert!(!converter.is_null(), "Converter should not be NULL");
+    assert_ne!(converter, 0, "Converter should not be NULL");
 
     // Use a valid pointer to empty slice (not NULL)
     let html = b"";
     let options = MarkdownOptions {
+        abi_version: MARKDOWN_ABI_VERSION,
         flavor: 0,
         timeout_ms: 5000,
+        max_input_bytes: 0,
+        max_output_bytes: 0,
+        max_decompressed_bytes: 0,
         generate_etag: 0,
         estimate_tokens: 0,
         front_matter: 0,
+        preserve_tables: 1,
+        generate_toc: 0,
+        heading_anchors: 0,
+        renderer: RENDERER_DEFAULT,
+        heading_offset: 0,
+        extensions: 0,
         content_type: ptr::null(),
         content_type_len: 0,
         base_url: ptr::null(),
         base_url_len: 0,
+        accept_encoding: ptr::null(),
+        accept_encoding_len: 0,
+        input_charset: ptr::null(),
+        input_charset_len: 0,
+        content_encoding: ptr::null(),
+        content_encoding_len: 0,
     };
 
     let mut result = MarkdownResult {
@@ -748,10 +1310,16 @@ fn test_zero_length_html() {
         markdown_len: 0,
         etag: ptr::null_mut(),
         etag_len: 0,
+        toc: ptr::null_mut(),
+        toc_len: 0,
         token_estimate: 0,
+        had_lossy_decode: 0,
         error_code: 0,
         error_message: ptr::null_mut(),
         error_len: 0,
+        content_encoding: ptr::null_mut(),
+        content_encoding_len: 0,
+        uncompressed_len: 0,
     };
 
     ffi_markdown_convert(converter, html.as_ptr(), html.len(), &options, &mut result);
@@ -780,19 +1348,35 @@ fn test_zero_length_html() {
 fn test_null_content_type_with_zero_length() {
     // Test that NULL content_type with zero length is handled correctly
     let converter = markdown_converter_new();
-    assert!(!converter.is_null(), "Converter should not be NULL");
+    assert_ne!(converter, 0, "Converter should not be NULL");
 
     let _html = b"<p>Test</p>";
     let options = MarkdownOptions {
+        abi_version: MARKDOWN_ABI_VERSION,
         flavor: 0,
         timeout_ms: 5000,
+        max_input_bytes: 0,
+        max_output_bytes: 0,
+        max_decompressed_bytes: 0,
         generate_etag: 0,
         estimate_tokens: 0,
         front_matter: 0,
+        preserve_tables: 1,
+        generate_toc: 0,
+        heading_anchors: 0,
+        renderer: RENDERER_DEFAULT,
+        heading_offset: 0,
+        extensions: 0,
         content_type: ptr::null(), // NULL pointer
         content_type_len: 0,       // Zero length
         base_url: ptr::null(),
         base_url_len: 0,
+        accept_encoding: ptr::null(),
+        accept_encoding_len: 0,
+        input_charset: ptr::null(),
+        input_charset_len: 0,
+        content_encoding: ptr::null(),
+        content_encoding_len: 0,
     };
 
     let mut result = MarkdownResult {
@@ -800,10 +1384,16 @@ fn test_null_content_type_with_zero_length() {
         markdown_len: 0,
         etag: ptr::null_mut(),
         etag_len: 0,
+        toc: ptr::null_mut(),
+        toc_len: 0,
         token_estimate: 0,
+        had_lossy_decode: 0,
         error_code: 0,
         error_message: ptr::null_mut(),
         error_len: 0,
+        content_encoding: ptr::null_mut(),
+        content_encoding_len: 0,
+        uncompressed_len: 0,
     };
 
     ffi_markdown_convert(
@@ -828,19 +1418,35 @@ fn test_null_content_type_with_zero_length() {
 fn test_error_state_consistency() {
     // Test that error state is consistent across all fields
     let converter = markdown_converter_new();
-    assert!(!converter.is_null(), "Converter should not be NULL");
+    assert_ne!(converter, 0, "Converter should not be NULL");
 
     let _html = b"<p>Test</p>";
     let options = MarkdownOptions {
+        abi_version: MARKDOWN_ABI_VERSION,
         flavor: 0,
         timeout_ms: 5000,
+        max_input_bytes: 0,
+        max_output_bytes: 0,
+        max_decompressed_bytes: 0,
         generate_etag: 1,
         estimate_tokens: 1,
         front_matter: 0,
+        preserve_tables: 1,
+        generate_toc: 0,
+        heading_anchors: 0,
+        renderer: RENDERER_DEFAULT,
+        heading_offset: 0,
+        extensions: 0,
         content_type: ptr::null(),
         content_type_len: 0,
         base_url: ptr::null(),
         base_url_len: 0,
+        accept_encoding: ptr::null(),
+        accept_encoding_len: 0,
+        input_charset: ptr::null(),
+        input_charset_len: 0,
+        content_encoding: ptr::null(),
+        content_encoding_len: 0,
     };
 
     let mut result = MarkdownResult {
@@ -848,20 +1454,20 @@ fn test_error_state_consistency() {
         markdown_len: 0,
         etag: ptr::null_mut(),
         etag_len: 0,
+        toc: ptr::null_mut(),
+        toc_len: 0,
         token_estimate: 0,
+        had_lossy_decode: 0,
         error_code: 0,
         error_message: ptr::null_mut(),
         error_len: 0,
+        content_encoding: ptr::null_mut(),
+        content_encoding_len: 0,
+        uncompressed_len: 0,
     };
 
     // Trigger error with NULL converter
-    ffi_markdown_convert(
-        ptr::null_mut(),
-        _html.as_ptr(),
-        _html.len(),
-        &options,
-        &mut result,
-    );
+    ffi_markdown_convert(0, _html.as_ptr(), _html.len(), &options, &mut result);
 
     // Verify error state consistency
     assert_ne!(result.error_code, 0, "Should have error code");
@@ -895,7 +1501,7 @@ proptest! {
     #[test]
     fn prop_random_bytes_do_not_crash_ffi_conversion(input in proptest::collection::vec(any::<u8>(), 0..128)) {
         let converter = markdown_converter_new();
-        prop_assert!(!converter.is_null());
+        prop_assert_ne!(converter, 0);
 
         let options = ffi_test_default_options();
         let mut result = ffi_test_empty_result();
@@ -933,23 +1539,25 @@ proptest! {
         paragraph in "[A-Za-z0-9 ]{1,64}",
     ) {
         let converter = markdown_converter_new();
-        prop_assert!(!converter.is_null());
-
-        let options = ffi_test_default_options();
-        let invalid = [0xFF, 0xFE, 0xFD];
-
-        let mut error_result = ffi_test_empty_result();
-        ffi_markdown_convert(
-            converter,
-            invalid.as_ptr(),
-            invalid.len(),
-            &options,
-            &mut error_result,
+        prop_assert_ne!(converter, 0);
+
+        // `content_encoding` naming an unsupported compression token is one
+        // of the few inputs that reliably produces a specific error code
+        // rather than succeeding: malformed UTF-8 bytes are always decoded
+        // lossily (see `test_invalid_utf8_decoded_lossily_not_rejected`)
+        // rather than rejected, so they can't stand in for "guaranteed
+        // error" here.
+        let mut error_result =
+            convert_with_content_encoding(converter, b"<p>whatever</p>", "compress");
+        prop_assert_eq!(
+            error_result.error_code,
+            ERROR_ENCODING,
+            "Unsupported content_encoding should fail with ERROR_ENCODING"
         );
-        prop_assert_ne!(error_result.error_code, 0, "Invalid UTF-8 should fail");
         prop_assert!(!error_result.error_message.is_null());
         ffi_markdown_result_free(&mut error_result);
 
+        let options = ffi_test_default_options();
         let html = format!("<h1>{}</h1><p>{}</p>", heading, paragraph);
         let mut success_result = ffi_test_empty_result();
         ffi_markdown_convert(
@@ -973,4 +1581,804 @@ proptest! {
         ffi_markdown_result_free(&mut success_result);
         ffi_markdown_converter_free(converter);
     }
+
+    /// Property 30: `flavor` toggles GFM-only output (pipe tables) without
+    /// perturbing `estimate_tokens`/`front_matter`, which react only to their
+    /// own flags.
+    #[test]
+    fn prop_flavor_independent_of_token_and_front_matter_gates_gfm_tables(
+        flavor in prop_oneof![Just(FLAVOR_COMMONMARK), Just(FLAVOR_GFM)],
+        estimate_tokens in any::<bool>(),
+        front_matter in any::<bool>(),
+    ) {
+        let converter = markdown_converter_new();
+        prop_assert_ne!(converter, 0);
+
+        let html = b"<html><head><title>T</title></head><body>\
+            <table><thead><tr><th>A</th><th>B</th></tr></thead>\
+            <tbody><tr><td>1</td><td>2</td></tr></tbody></table>\
+            </body></html>";
+        let base_url = "https://example.com/page".as_bytes();
+
+        let mut options = ffi_test_default_options();
+        options.flavor = flavor;
+        options.preserve_tables = 1;
+        options.estimate_tokens = u8::from(estimate_tokens);
+        options.front_matter = u8::from(front_matter);
+        if front_matter {
+            options.base_url = base_url.as_ptr();
+            options.base_url_len = base_url.len();
+        }
+
+        let mut result = ffi_test_empty_result();
+        ffi_markdown_convert(converter, html.as_ptr(), html.len(), &options, &mut result);
+
+        prop_assert_eq!(result.error_code, 0);
+        let markdown = unsafe {
+            let bytes = slice::from_raw_parts(result.markdown, result.markdown_len);
+            std::str::from_utf8(bytes).expect("valid utf-8").to_string()
+        };
+
+        prop_assert_eq!(estimate_tokens, result.token_estimate > 0);
+        prop_assert_eq!(front_matter, markdown.starts_with("---\n"));
+        prop_assert_eq!(flavor == FLAVOR_GFM, markdown.contains("| A | B |"));
+
+        ffi_markdown_result_free(&mut result);
+        ffi_markdown_converter_free(converter);
+    }
+}
+
+#[test]
+fn test_streaming_conversion_matches_buffered_conversion() {
+    let converter = markdown_converter_new();
+    assert_ne!(converter, 0, "Converter should not be NULL");
+
+    let html = b"<h1>Hello World</h1><p>This is a test.</p>";
+    let options = ffi_test_default_options();
+
+    let mut buffered_result = ffi_test_empty_result();
+    ffi_markdown_convert(
+        converter,
+        html.as_ptr(),
+        html.len(),
+        &options,
+        &mut buffered_result,
+    );
+    assert_eq!(buffered_result.error_code, 0);
+    let buffered_markdown = unsafe {
+        slice::from_raw_parts(buffered_result.markdown, buffered_result.markdown_len).to_vec()
+    };
+    ffi_markdown_result_free(&mut buffered_result);
+
+    let capacity = ffi_stream_capacity_new(usize::MAX);
+    assert!(!capacity.is_null());
+
+    let mut streamed = Vec::new();
+    let error_code = ffi_markdown_convert_streaming(
+        converter,
+        html.as_ptr(),
+        html.len(),
+        &options,
+        capacity,
+        Some(collect_fragments),
+        None,
+        &mut streamed as *mut Vec<u8> as *mut c_void,
+    );
+
+    assert_eq!(error_code, ERROR_SUCCESS);
+    assert_eq!(streamed, buffered_markdown);
+
+    ffi_stream_capacity_free(capacity);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_streaming_conversion_stops_when_sink_requests_it() {
+    let converter = markdown_converter_new();
+    assert_ne!(converter, 0, "Converter should not be NULL");
+
+    let html: String = (0..150).map(|i| format!("<p>Item {i}</p>")).collect();
+    let options = ffi_test_default_options();
+    let capacity = ffi_stream_capacity_new(usize::MAX);
+
+    let mut streamed = Vec::new();
+    let error_code = ffi_markdown_convert_streaming(
+        converter,
+        html.as_ptr(),
+        html.len(),
+        &options,
+        capacity,
+        Some(collect_first_fragment_then_stop),
+        None,
+        &mut streamed as *mut Vec<u8> as *mut c_void,
+    );
+
+    assert_eq!(error_code, ERROR_SUCCESS);
+    let collected = String::from_utf8(streamed).expect("streamed output must be valid utf-8");
+    assert!(!collected.contains("Item 149"));
+
+    ffi_stream_capacity_free(capacity);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_streaming_conversion_rejects_null_sink_and_capacity() {
+    let converter = markdown_converter_new();
+    let html = b"<p>Hello</p>";
+    let options = ffi_test_default_options();
+    let capacity = ffi_stream_capacity_new(usize::MAX);
+    let mut streamed = Vec::new();
+
+    let missing_sink_error = ffi_markdown_convert_streaming(
+        converter,
+        html.as_ptr(),
+        html.len(),
+        &options,
+        capacity,
+        None,
+        None,
+        &mut streamed as *mut Vec<u8> as *mut c_void,
+    );
+    assert_eq!(missing_sink_error, ERROR_INVALID_INPUT);
+
+    let missing_capacity_error = ffi_markdown_convert_streaming(
+        converter,
+        html.as_ptr(),
+        html.len(),
+        &options,
+        ptr::null_mut(),
+        Some(collect_fragments),
+        None,
+        &mut streamed as *mut Vec<u8> as *mut c_void,
+    );
+    assert_eq!(missing_capacity_error, ERROR_INVALID_INPUT);
+
+    ffi_stream_capacity_free(capacity);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_streaming_conversion_completion_reports_etag_and_tokens() {
+    let converter = markdown_converter_new();
+    assert_ne!(converter, 0, "Converter should not be NULL");
+
+    let html = b"<h1>Hello World</h1><p>This is a test.</p>";
+    let mut options = ffi_test_default_options();
+    options.generate_etag = 1;
+    options.estimate_tokens = 1;
+
+    let mut buffered_result = ffi_test_empty_result();
+    ffi_markdown_convert(
+        converter,
+        html.as_ptr(),
+        html.len(),
+        &options,
+        &mut buffered_result,
+    );
+    assert_eq!(buffered_result.error_code, 0);
+    let buffered_markdown = unsafe {
+        slice::from_raw_parts(buffered_result.markdown, buffered_result.markdown_len).to_vec()
+    };
+    let buffered_etag = unsafe {
+        slice::from_raw_parts(buffered_result.etag, buffered_result.etag_len).to_vec()
+    };
+    let buffered_etag = String::from_utf8(buffered_etag).expect("etag must be valid utf-8");
+    let buffered_token_estimate = buffered_result.token_estimate;
+    ffi_markdown_result_free(&mut buffered_result);
+
+    let capacity = ffi_stream_capacity_new(usize::MAX);
+    assert!(!capacity.is_null());
+
+    let mut output = StreamingOutput::default();
+    let error_code = ffi_markdown_convert_streaming(
+        converter,
+        html.as_ptr(),
+        html.len(),
+        &options,
+        capacity,
+        Some(collect_fragments_and_completion),
+        Some(collect_completion),
+        &mut output as *mut StreamingOutput as *mut c_void,
+    );
+
+    assert_eq!(error_code, ERROR_SUCCESS);
+    assert_eq!(output.markdown, buffered_markdown);
+    let (etag, token_estimate) = output
+        .completion
+        .expect("completion callback should have been invoked");
+    assert_eq!(etag, Some(buffered_etag));
+    assert_eq!(token_estimate, buffered_token_estimate);
+
+    ffi_stream_capacity_free(capacity);
+    ffi_markdown_converter_free(converter);
+}
+
+fn convert_with_accept_encoding(
+    converter: u64,
+    html: &[u8],
+    accept_encoding: &str,
+) -> MarkdownResult {
+    let mut options = ffi_test_default_options();
+    options.generate_etag = 1;
+    options.accept_encoding = accept_encoding.as_ptr();
+    options.accept_encoding_len = accept_encoding.len();
+
+    let mut result = ffi_test_empty_result();
+    ffi_markdown_convert(converter, html.as_ptr(), html.len(), &options, &mut result);
+    result
+}
+
+#[test]
+fn test_compression_absent_accept_encoding_is_uncompressed() {
+    let converter = markdown_converter_new();
+    let html = b"<h1>Hello World</h1><p>This is a test.</p>";
+
+    let mut result = convert_with_accept_encoding(converter, html, "");
+    assert_eq!(result.error_code, 0);
+    assert!(result.content_encoding.is_null());
+    assert_eq!(result.content_encoding_len, 0);
+    assert_eq!(result.markdown_len, result.uncompressed_len);
+
+    ffi_markdown_result_free(&mut result);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_compression_gzip_roundtrips_and_labels_content_encoding() {
+    let converter = markdown_converter_new();
+    let html = b"<h1>Hello World</h1><p>This is a test with enough text to compress.</p>";
+
+    let mut result = convert_with_accept_encoding(converter, html, "gzip");
+    assert_eq!(result.error_code, 0);
+    assert!(!result.content_encoding.is_null());
+
+    let label = unsafe {
+        let bytes = slice::from_raw_parts(result.content_encoding, result.content_encoding_len);
+        std::str::from_utf8(bytes).expect("valid utf-8")
+    };
+    assert_eq!(label, "gzip");
+
+    let compressed =
+        unsafe { slice::from_raw_parts(result.markdown, result.markdown_len).to_vec() };
+    let mut decoder = flate2::read::GzDecoder::new(compressed.as_slice());
+    let mut decompressed = Vec::new();
+    std::io::Read::read_to_end(&mut decoder, &mut decompressed).expect("must decompress");
+    assert_eq!(decompressed.len(), result.uncompressed_len);
+
+    ffi_markdown_result_free(&mut result);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_compression_negotiates_highest_quality_encoding() {
+    let converter = markdown_converter_new();
+    let html = b"<p>Negotiate me</p>";
+
+    let mut result = convert_with_accept_encoding(converter, html, "gzip;q=0.5, br;q=0.9");
+    assert_eq!(result.error_code, 0);
+
+    let label = unsafe {
+        let bytes = slice::from_raw_parts(result.content_encoding, result.content_encoding_len);
+        std::str::from_utf8(bytes).expect("valid utf-8")
+    };
+    assert_eq!(label, "br");
+
+    ffi_markdown_result_free(&mut result);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_compression_etag_stable_across_encodings() {
+    let converter = markdown_converter_new();
+    let html = b"<h1>Stable</h1><p>ETag should not depend on the encoding.</p>";
+
+    let mut identity_result = convert_with_accept_encoding(converter, html, "");
+    let mut gzip_result = convert_with_accept_encoding(converter, html, "gzip");
+    let mut zstd_result = convert_with_accept_encoding(converter, html, "zstd");
+
+    let etag_str = |result: &MarkdownResult| unsafe {
+        let bytes = slice::from_raw_parts(result.etag, result.etag_len);
+        std::str::from_utf8(bytes).expect("valid utf-8").to_owned()
+    };
+
+    let identity_etag = etag_str(&identity_result);
+    assert_eq!(identity_etag, etag_str(&gzip_result));
+    assert_eq!(identity_etag, etag_str(&zstd_result));
+
+    ffi_markdown_result_free(&mut identity_result);
+    ffi_markdown_result_free(&mut gzip_result);
+    ffi_markdown_result_free(&mut zstd_result);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_compression_unsupported_encoding_falls_back_to_identity() {
+    let converter = markdown_converter_new();
+    let html = b"<p>Fallback</p>";
+
+    let mut result = convert_with_accept_encoding(converter, html, "compress, sdch");
+    assert_eq!(result.error_code, 0);
+    assert!(result.content_encoding.is_null());
+    assert_eq!(result.markdown_len, result.uncompressed_len);
+
+    ffi_markdown_result_free(&mut result);
+    ffi_markdown_converter_free(converter);
+}
+
+fn convert_with_content_encoding(
+    converter: u64,
+    compressed: &[u8],
+    content_encoding: &str,
+) -> MarkdownResult {
+    let mut options = ffi_test_default_options();
+    options.content_encoding = content_encoding.as_ptr();
+    options.content_encoding_len = content_encoding.len();
+
+    let mut result = ffi_test_empty_result();
+    ffi_markdown_convert(
+        converter,
+        compressed.as_ptr(),
+        compressed.len(),
+        &options,
+        &mut result,
+    );
+    result
+}
+
+#[test]
+fn test_decompression_gzip_content_encoding_is_parsed() {
+    use std::io::Write;
+
+    let converter = markdown_converter_new();
+    let html = b"<h1>Compressed Upstream</h1>";
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(html).expect("gzip compression failed");
+    let compressed = encoder.finish().expect("gzip compression failed");
+
+    let mut result = convert_with_content_encoding(converter, &compressed, "gzip");
+    assert_eq!(result.error_code, 0);
+
+    let markdown =
+        unsafe { slice::from_raw_parts(result.markdown, result.markdown_len).to_vec() };
+    let markdown_str = std::str::from_utf8(&markdown).expect("valid utf-8");
+    assert!(markdown_str.contains("Compressed Upstream"));
+
+    ffi_markdown_result_free(&mut result);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_decompression_unsupported_content_encoding_is_encoding_error() {
+    let converter = markdown_converter_new();
+    let html = b"<p>whatever</p>";
+
+    let mut result = convert_with_content_encoding(converter, html, "compress");
+    assert_eq!(result.error_code, ERROR_ENCODING);
+
+    ffi_markdown_result_free(&mut result);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_decompression_over_max_decompressed_bytes_is_memory_limit() {
+    use std::io::Write;
+
+    let converter = markdown_converter_new();
+    let html = b"<h1>Hello World</h1><p>Enough text to gzip meaningfully.</p>".repeat(50);
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(&html).expect("gzip compression failed");
+    let compressed = encoder.finish().expect("gzip compression failed");
+
+    let mut options = ffi_test_default_options();
+    options.content_encoding = b"gzip".as_ptr();
+    options.content_encoding_len = 4;
+    options.max_decompressed_bytes = 16;
+
+    let mut result = ffi_test_empty_result();
+    ffi_markdown_convert(
+        converter,
+        compressed.as_ptr(),
+        compressed.len(),
+        &options,
+        &mut result,
+    );
+    assert_eq!(result.error_code, ERROR_MEMORY_LIMIT);
+
+    ffi_markdown_result_free(&mut result);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_exceeded_timeout_is_error_timeout() {
+    // Mirrors timeout_test.rs's test_timeout_detection: a large enough
+    // document with a vanishingly small timeout_ms reliably exceeds it
+    // during conversion. May be flaky on extremely fast systems.
+    let converter = markdown_converter_new();
+    let mut html = String::from("<html><body>");
+    for i in 0..10_000 {
+        html.push_str(&format!("<div><p>Paragraph {i}</p></div>"));
+    }
+    html.push_str("</body></html>");
+
+    let mut options = ffi_test_default_options();
+    options.timeout_ms = 1;
+
+    let mut result = ffi_test_empty_result();
+    ffi_markdown_convert(
+        converter,
+        html.as_ptr(),
+        html.len(),
+        &options,
+        &mut result,
+    );
+    assert_eq!(result.error_code, ERROR_TIMEOUT);
+
+    ffi_markdown_result_free(&mut result);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_preserve_tables_flag_enables_gfm_pipe_table_output() {
+    let converter = markdown_converter_new();
+    let html = b"<table><thead><tr><th>A</th><th>B</th></tr></thead><tbody><tr><td>1</td><td>2</td></tr></tbody></table>";
+
+    let mut options = ffi_test_default_options();
+    options.flavor = 1; // GFM
+    options.preserve_tables = 1;
+
+    let mut result = ffi_test_empty_result();
+    ffi_markdown_convert(converter, html.as_ptr(), html.len(), &options, &mut result);
+
+    assert_eq!(result.error_code, 0);
+    let markdown = unsafe {
+        let bytes = slice::from_raw_parts(result.markdown, result.markdown_len);
+        std::str::from_utf8(bytes).expect("valid utf-8").to_owned()
+    };
+    assert!(markdown.contains("| A | B |"));
+    assert!(markdown.contains("| --- | --- |"));
+
+    ffi_markdown_result_free(&mut result);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_preserve_tables_flag_disabled_flattens_table_to_plain_text() {
+    let converter = markdown_converter_new();
+    let html = b"<table><thead><tr><th>A</th><th>B</th></tr></thead><tbody><tr><td>1</td><td>2</td></tr></tbody></table>";
+
+    let mut options = ffi_test_default_options();
+    options.flavor = 1; // GFM
+    options.preserve_tables = 0;
+
+    let mut result = ffi_test_empty_result();
+    ffi_markdown_convert(converter, html.as_ptr(), html.len(), &options, &mut result);
+
+    assert_eq!(result.error_code, 0);
+    let markdown = unsafe {
+        let bytes = slice::from_raw_parts(result.markdown, result.markdown_len);
+        std::str::from_utf8(bytes).expect("valid utf-8").to_owned()
+    };
+    assert!(!markdown.contains('|'));
+    assert!(markdown.contains('A'));
+
+    ffi_markdown_result_free(&mut result);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_preserve_code_language_extension_emits_fence_language() {
+    let converter = markdown_converter_new();
+    let html = b"<pre><code class=\"language-rust\">fn main() {}</code></pre>";
+
+    let mut options = ffi_test_default_options();
+    options.extensions = EXT_PRESERVE_CODE_LANGUAGE;
+
+    let mut result = ffi_test_empty_result();
+    ffi_markdown_convert(converter, html.as_ptr(), html.len(), &options, &mut result);
+
+    assert_eq!(result.error_code, 0);
+    let markdown = unsafe {
+        let bytes = slice::from_raw_parts(result.markdown, result.markdown_len);
+        std::str::from_utf8(bytes).expect("valid utf-8").to_owned()
+    };
+    assert!(markdown.contains("```rust"));
+
+    ffi_markdown_result_free(&mut result);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_preserve_code_language_extension_absent_emits_bare_fence() {
+    let converter = markdown_converter_new();
+    let html = b"<pre><code class=\"language-rust\">fn main() {}</code></pre>";
+
+    let mut options = ffi_test_default_options();
+    options.extensions = 0;
+
+    let mut result = ffi_test_empty_result();
+    ffi_markdown_convert(converter, html.as_ptr(), html.len(), &options, &mut result);
+
+    assert_eq!(result.error_code, 0);
+    let markdown = unsafe {
+        let bytes = slice::from_raw_parts(result.markdown, result.markdown_len);
+        std::str::from_utf8(bytes).expect("valid utf-8").to_owned()
+    };
+    assert!(markdown.contains("```\n"));
+    assert!(!markdown.contains("```rust"));
+
+    ffi_markdown_result_free(&mut result);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_preserve_code_language_extension_emits_fence_language_under_gfm() {
+    let converter = markdown_converter_new();
+    let html = b"<pre><code class=\"highlight-source-ruby\">puts 1</code></pre>";
+
+    let mut options = ffi_test_default_options();
+    options.flavor = 1; // GFM
+    options.extensions = EXT_PRESERVE_CODE_LANGUAGE;
+
+    let mut result = ffi_test_empty_result();
+    ffi_markdown_convert(converter, html.as_ptr(), html.len(), &options, &mut result);
+
+    assert_eq!(result.error_code, 0);
+    let markdown = unsafe {
+        let bytes = slice::from_raw_parts(result.markdown, result.markdown_len);
+        std::str::from_utf8(bytes).expect("valid utf-8").to_owned()
+    };
+    assert!(markdown.contains("```ruby"));
+
+    ffi_markdown_result_free(&mut result);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_preserve_code_language_extension_absent_emits_bare_fence_under_gfm() {
+    let converter = markdown_converter_new();
+    let html = b"<pre><code class=\"highlight-source-ruby\">puts 1</code></pre>";
+
+    let mut options = ffi_test_default_options();
+    options.flavor = 1; // GFM
+    options.extensions = 0;
+
+    let mut result = ffi_test_empty_result();
+    ffi_markdown_convert(converter, html.as_ptr(), html.len(), &options, &mut result);
+
+    assert_eq!(result.error_code, 0);
+    let markdown = unsafe {
+        let bytes = slice::from_raw_parts(result.markdown, result.markdown_len);
+        std::str::from_utf8(bytes).expect("valid utf-8").to_owned()
+    };
+    assert!(markdown.contains("```\n"));
+    assert!(!markdown.contains("```ruby"));
+
+    ffi_markdown_result_free(&mut result);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_normalize_punctuation_extension_folds_typographic_characters() {
+    let converter = markdown_converter_new();
+    let html = "<p>She said \u{201C}hi\u{201D} \u{2014} it\u{2019}s a test\u{2026}</p>".as_bytes();
+
+    let mut options = ffi_test_default_options();
+    options.extensions = EXT_NORMALIZE_PUNCTUATION;
+
+    let mut result = ffi_test_empty_result();
+    ffi_markdown_convert(converter, html.as_ptr(), html.len(), &options, &mut result);
+
+    assert_eq!(result.error_code, 0);
+    let markdown = unsafe {
+        let bytes = slice::from_raw_parts(result.markdown, result.markdown_len);
+        std::str::from_utf8(bytes).expect("valid utf-8").to_owned()
+    };
+    assert!(markdown.contains("She said \"hi\" -- it's a test..."));
+
+    ffi_markdown_result_free(&mut result);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_normalize_punctuation_extension_absent_keeps_typographic_characters() {
+    let converter = markdown_converter_new();
+    let html = "<p>She said \u{201C}hi\u{201D}</p>".as_bytes();
+
+    let mut options = ffi_test_default_options();
+    options.extensions = 0;
+
+    let mut result = ffi_test_empty_result();
+    ffi_markdown_convert(converter, html.as_ptr(), html.len(), &options, &mut result);
+
+    assert_eq!(result.error_code, 0);
+    let markdown = unsafe {
+        let bytes = slice::from_raw_parts(result.markdown, result.markdown_len);
+        std::str::from_utf8(bytes).expect("valid utf-8").to_owned()
+    };
+    assert!(markdown.contains('\u{201C}'));
+
+    ffi_markdown_result_free(&mut result);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_readability_mode_extension_drops_boilerplate_siblings() {
+    let converter = markdown_converter_new();
+    let html = b"<html><body>
+        <nav><a href=\"/a\">A</a> <a href=\"/b\">B</a> <a href=\"/c\">C</a></nav>
+        <div class=\"article\">
+            <p>This is the main article body, with plenty of punctuation, clauses, and substantial content to score highly as the primary candidate.</p>
+            <p>A second paragraph continuing the article with more narrative detail, commas, and text to keep the score well above any sidebar.</p>
+        </div>
+    </body></html>";
+
+    let mut options = ffi_test_default_options();
+    options.extensions = EXT_READABILITY_MODE;
+
+    let mut result = ffi_test_empty_result();
+    ffi_markdown_convert(converter, html.as_ptr(), html.len(), &options, &mut result);
+
+    assert_eq!(result.error_code, 0);
+    let markdown = unsafe {
+        let bytes = slice::from_raw_parts(result.markdown, result.markdown_len);
+        std::str::from_utf8(bytes).expect("valid utf-8").to_owned()
+    };
+    assert!(markdown.contains("main article body"));
+    assert!(!markdown.contains("\"/a\""));
+
+    ffi_markdown_result_free(&mut result);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_readability_mode_extension_absent_keeps_full_document() {
+    let converter = markdown_converter_new();
+    let html = b"<html><body>
+        <nav><a href=\"/a\">A</a></nav>
+        <div class=\"article\"><p>Article body text here for the main content area of the page.</p></div>
+    </body></html>";
+
+    let mut options = ffi_test_default_options();
+    options.extensions = 0;
+
+    let mut result = ffi_test_empty_result();
+    ffi_markdown_convert(converter, html.as_ptr(), html.len(), &options, &mut result);
+
+    assert_eq!(result.error_code, 0);
+    let markdown = unsafe {
+        let bytes = slice::from_raw_parts(result.markdown, result.markdown_len);
+        std::str::from_utf8(bytes).expect("valid utf-8").to_owned()
+    };
+    assert!(markdown.contains("Article body text"));
+    assert!(markdown.contains("[A](/a)"));
+
+    ffi_markdown_result_free(&mut result);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_statistical_charset_detection_extension_absent_defaults_to_utf8() {
+    let converter = markdown_converter_new();
+    // No BOM, no Content-Type/meta charset, and not valid UTF-8: without the
+    // extension bit, this must fall back to the UTF-8 default rather than
+    // guessing, same as before the bit existed.
+    let (encoded, _, _) = encoding_rs::WINDOWS_1252.encode("Caf\u{e9} au lait");
+    let mut html = b"<html><body>".to_vec();
+    html.extend_from_slice(&encoded);
+    html.extend_from_slice(b"</body></html>");
+
+    let mut options = ffi_test_default_options();
+    options.extensions = 0;
+
+    let mut result = ffi_test_empty_result();
+    ffi_markdown_convert(
+        converter,
+        html.as_ptr(),
+        html.len(),
+        &options,
+        &mut result,
+    );
+
+    assert_eq!(result.error_code, 0);
+    let markdown = unsafe {
+        let bytes = slice::from_raw_parts(result.markdown, result.markdown_len);
+        std::str::from_utf8(bytes).expect("valid utf-8").to_owned()
+    };
+    assert!(markdown.contains('\u{fffd}'));
+
+    ffi_markdown_result_free(&mut result);
+    ffi_markdown_converter_free(converter);
+}
+
+#[cfg(feature = "stat_charset_detect")]
+#[test]
+fn test_statistical_charset_detection_extension_guesses_legacy_encoding() {
+    let converter = markdown_converter_new();
+    let (encoded, _, _) = encoding_rs::WINDOWS_1252.encode("Caf\u{e9} au lait");
+    let mut html = b"<html><body>".to_vec();
+    html.extend_from_slice(&encoded);
+    html.extend_from_slice(b"</body></html>");
+
+    let mut options = ffi_test_default_options();
+    options.extensions = EXT_STATISTICAL_CHARSET_DETECTION;
+
+    let mut result = ffi_test_empty_result();
+    ffi_markdown_convert(
+        converter,
+        html.as_ptr(),
+        html.len(),
+        &options,
+        &mut result,
+    );
+
+    assert_eq!(result.error_code, 0);
+    let markdown = unsafe {
+        let bytes = slice::from_raw_parts(result.markdown, result.markdown_len);
+        std::str::from_utf8(bytes).expect("valid utf-8").to_owned()
+    };
+    assert!(markdown.contains("Caf\u{e9} au lait"));
+
+    ffi_markdown_result_free(&mut result);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_log_callback_receives_input_too_large_record() {
+    let converter = markdown_converter_new();
+    let mut records: Vec<(u32, String)> = Vec::new();
+    let set_error = ffi_set_log_callback(
+        converter,
+        LOG_LEVEL_DEBUG,
+        Some(collect_log_records),
+        &mut records as *mut Vec<(u32, String)> as *mut c_void,
+    );
+    assert_eq!(set_error, ERROR_SUCCESS);
+
+    let html = b"<p>hello world</p>";
+    let mut options = ffi_test_default_options();
+    options.max_input_bytes = 4;
+
+    let mut result = ffi_test_empty_result();
+    ffi_markdown_convert(converter, html.as_ptr(), html.len(), &options, &mut result);
+
+    assert_eq!(result.error_code, ERROR_INPUT_TOO_LARGE);
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].0, LOG_LEVEL_ERROR);
+    assert!(records[0].1.contains("max_input_bytes"));
+
+    ffi_markdown_result_free(&mut result);
+    ffi_markdown_converter_free(converter);
+}
+
+#[test]
+fn test_log_callback_filters_by_level_and_can_be_cleared() {
+    let converter = markdown_converter_new();
+    let mut records: Vec<(u32, String)> = Vec::new();
+    ffi_set_log_callback(
+        converter,
+        LOG_LEVEL_ERROR,
+        Some(collect_log_records),
+        &mut records as *mut Vec<(u32, String)> as *mut c_void,
+    );
+
+    // A lossy-decode warning is filtered out by a LOG_LEVEL_ERROR-only filter.
+    let html = b"<p>hello</p>";
+    let mut options = ffi_test_default_options();
+    options.input_charset = b"ISO-8859-1".as_ptr();
+    options.input_charset_len = "ISO-8859-1".len();
+
+    let mut result = ffi_test_empty_result();
+    ffi_markdown_convert(converter, html.as_ptr(), html.len(), &options, &mut result);
+    assert_eq!(result.error_code, ERROR_SUCCESS);
+    assert!(records.is_empty());
+    ffi_markdown_result_free(&mut result);
+
+    let clear_error = ffi_set_log_callback(converter, LOG_LEVEL_DEBUG, None, ptr::null_mut());
+    assert_eq!(clear_error, ERROR_SUCCESS);
+
+    let invalid_handle_error =
+        ffi_set_log_callback(0, LOG_LEVEL_DEBUG, Some(collect_log_records), ptr::null_mut());
+    assert_eq!(invalid_handle_error, ERROR_INVALID_INPUT);
+
+    ffi_markdown_converter_free(converter);
 }
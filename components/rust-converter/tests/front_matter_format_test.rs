@@ -0,0 +1,296 @@
+//! Tests for TOML and JSON front matter generation
+//!
+//! Covers the `FrontMatterFormat::Toml` and `FrontMatterFormat::Json`
+//! variants added alongside the default YAML format. See
+//! `yaml_front_matter_test.rs` for the YAML coverage these mirror.
+
+use nginx_markdown_converter::converter::{
+    ConversionOptions, FrontMatterFormat, MarkdownConverter,
+};
+use nginx_markdown_converter::parser::parse_html;
+
+/// Test basic TOML front matter generation with title and URL
+#[test]
+fn test_toml_front_matter_basic() {
+    let html = b"<html><head><title>Test Page</title><link rel=\"canonical\" href=\"https://example.com/page\"></head><body><p>Content</p></body></html>";
+    let dom = parse_html(html).expect("Parse failed");
+
+    let options = ConversionOptions {
+        include_front_matter: true,
+        extract_metadata: true,
+        front_matter_format: FrontMatterFormat::Toml,
+        base_url: Some("https://example.com/page".to_string()),
+        ..Default::default()
+    };
+    let converter = MarkdownConverter::with_options(options);
+    let result = converter.convert(&dom).expect("Conversion failed");
+
+    assert!(result.starts_with("+++\n"));
+    assert!(result.contains("+++\n\n"));
+    assert!(result.contains("title = \"Test Page\""));
+    assert!(result.contains("url = \"https://example.com/page\""));
+    assert!(result.contains("Content"));
+}
+
+/// Test TOML front matter with all metadata fields
+#[test]
+fn test_toml_front_matter_complete() {
+    let html = b"<html><head>
+        <title>Complete Page</title>
+        <meta name=\"description\" content=\"A test description\">
+        <meta property=\"og:image\" content=\"https://example.com/image.png\">
+        <meta name=\"author\" content=\"John Doe\">
+        <meta property=\"article:published_time\" content=\"2024-01-15\">
+        <link rel=\"canonical\" href=\"https://example.com/complete\">
+    </head><body><p>Content</p></body></html>";
+    let dom = parse_html(html).expect("Parse failed");
+
+    let options = ConversionOptions {
+        include_front_matter: true,
+        extract_metadata: true,
+        front_matter_format: FrontMatterFormat::Toml,
+        base_url: Some("https://example.com/complete".to_string()),
+        ..Default::default()
+    };
+    let converter = MarkdownConverter::with_options(options);
+    let result = converter.convert(&dom).expect("Conversion failed");
+
+    assert!(result.contains("title = \"Complete Page\""));
+    assert!(result.contains("url = \"https://example.com/complete\""));
+    assert!(result.contains("description = \"A test description\""));
+    assert!(result.contains("image = \"https://example.com/image.png\""));
+    assert!(result.contains("author = \"John Doe\""));
+    assert!(result.contains("published = \"2024-01-15\""));
+}
+
+/// Test TOML front matter escaping rules (quotes, backslash, newline, tab)
+#[test]
+fn test_toml_front_matter_escaping() {
+    let html = b"<html><head>
+        <title>Title with \"quotes\" and backslash\\ and\ttab</title>
+        <link rel=\"canonical\" href=\"https://example.com/page\">
+    </head><body><p>Content</p></body></html>";
+    let dom = parse_html(html).expect("Parse failed");
+
+    let options = ConversionOptions {
+        include_front_matter: true,
+        extract_metadata: true,
+        front_matter_format: FrontMatterFormat::Toml,
+        base_url: Some("https://example.com/page".to_string()),
+        ..Default::default()
+    };
+    let converter = MarkdownConverter::with_options(options);
+    let result = converter.convert(&dom).expect("Conversion failed");
+
+    assert!(result.contains("Title with \\\"quotes\\\" and backslash\\\\ and\\ttab"));
+}
+
+/// Test TOML front matter with empty fields omitted
+#[test]
+fn test_toml_front_matter_minimal() {
+    let html = b"<html><head><title>Minimal</title></head><body><p>Content</p></body></html>";
+    let dom = parse_html(html).expect("Parse failed");
+
+    let options = ConversionOptions {
+        include_front_matter: true,
+        extract_metadata: true,
+        front_matter_format: FrontMatterFormat::Toml,
+        base_url: Some("https://example.com/page".to_string()),
+        ..Default::default()
+    };
+    let converter = MarkdownConverter::with_options(options);
+    let result = converter.convert(&dom).expect("Conversion failed");
+
+    assert!(result.contains("title = \"Minimal\""));
+    assert!(!result.contains("description ="));
+    assert!(!result.contains("author ="));
+}
+
+/// Test TOML front matter block structure (blank line after closing delimiter)
+#[test]
+fn test_toml_front_matter_format() {
+    let html = b"<html><head>
+        <title>Format Test</title>
+        <link rel=\"canonical\" href=\"https://example.com/page\">
+    </head><body><p>Content</p></body></html>";
+    let dom = parse_html(html).expect("Parse failed");
+
+    let options = ConversionOptions {
+        include_front_matter: true,
+        extract_metadata: true,
+        front_matter_format: FrontMatterFormat::Toml,
+        base_url: Some("https://example.com/page".to_string()),
+        ..Default::default()
+    };
+    let converter = MarkdownConverter::with_options(options);
+    let result = converter.convert(&dom).expect("Conversion failed");
+
+    let lines: Vec<&str> = result.lines().collect();
+    assert_eq!(lines[0], "+++");
+
+    let closing_idx = lines
+        .iter()
+        .skip(1)
+        .position(|&line| line == "+++")
+        .unwrap()
+        + 1;
+    assert!(closing_idx > 0);
+
+    if closing_idx + 1 < lines.len() {
+        assert_eq!(lines[closing_idx + 1], "");
+    }
+}
+
+/// Test basic JSON front matter generation with title and URL
+#[test]
+fn test_json_front_matter_basic() {
+    let html = b"<html><head><title>Test Page</title><link rel=\"canonical\" href=\"https://example.com/page\"></head><body><p>Content</p></body></html>";
+    let dom = parse_html(html).expect("Parse failed");
+
+    let options = ConversionOptions {
+        include_front_matter: true,
+        extract_metadata: true,
+        front_matter_format: FrontMatterFormat::Json,
+        base_url: Some("https://example.com/page".to_string()),
+        ..Default::default()
+    };
+    let converter = MarkdownConverter::with_options(options);
+    let result = converter.convert(&dom).expect("Conversion failed");
+
+    assert!(result.starts_with("---json\n{\n"));
+    assert!(result.contains("\n}\n---\n\n"));
+    assert!(result.contains("\"title\": \"Test Page\""));
+    assert!(result.contains("\"url\": \"https://example.com/page\""));
+    assert!(result.contains("Content"));
+}
+
+/// Test JSON front matter with all metadata fields and no trailing comma
+#[test]
+fn test_json_front_matter_complete() {
+    let html = b"<html><head>
+        <title>Complete Page</title>
+        <meta name=\"description\" content=\"A test description\">
+        <meta property=\"og:image\" content=\"https://example.com/image.png\">
+        <meta name=\"author\" content=\"John Doe\">
+        <meta property=\"article:published_time\" content=\"2024-01-15\">
+        <link rel=\"canonical\" href=\"https://example.com/complete\">
+    </head><body><p>Content</p></body></html>";
+    let dom = parse_html(html).expect("Parse failed");
+
+    let options = ConversionOptions {
+        include_front_matter: true,
+        extract_metadata: true,
+        front_matter_format: FrontMatterFormat::Json,
+        base_url: Some("https://example.com/complete".to_string()),
+        ..Default::default()
+    };
+    let converter = MarkdownConverter::with_options(options);
+    let result = converter.convert(&dom).expect("Conversion failed");
+
+    assert!(result.contains("\"title\": \"Complete Page\""));
+    assert!(result.contains("\"url\": \"https://example.com/complete\""));
+    assert!(result.contains("\"description\": \"A test description\""));
+    assert!(result.contains("\"image\": \"https://example.com/image.png\""));
+    assert!(result.contains("\"author\": \"John Doe\""));
+    // Last field written must not be followed by a trailing comma.
+    assert!(result.contains("\"published\": \"2024-01-15\"\n}"));
+    assert!(!result.contains(",\n}"));
+}
+
+/// Test JSON front matter escaping rules (quotes, backslash, newline, tab, control chars)
+#[test]
+fn test_json_front_matter_escaping() {
+    let html = b"<html><head>
+        <title>Title with \"quotes\" and backslash\\ and\ttab</title>
+        <link rel=\"canonical\" href=\"https://example.com/page\">
+    </head><body><p>Content</p></body></html>";
+    let dom = parse_html(html).expect("Parse failed");
+
+    let options = ConversionOptions {
+        include_front_matter: true,
+        extract_metadata: true,
+        front_matter_format: FrontMatterFormat::Json,
+        base_url: Some("https://example.com/page".to_string()),
+        ..Default::default()
+    };
+    let converter = MarkdownConverter::with_options(options);
+    let result = converter.convert(&dom).expect("Conversion failed");
+
+    assert!(result.contains("Title with \\\"quotes\\\" and backslash\\\\ and\\ttab"));
+}
+
+/// Test JSON front matter with empty fields omitted
+#[test]
+fn test_json_front_matter_minimal() {
+    let html = b"<html><head><title>Minimal</title></head><body><p>Content</p></body></html>";
+    let dom = parse_html(html).expect("Parse failed");
+
+    let options = ConversionOptions {
+        include_front_matter: true,
+        extract_metadata: true,
+        front_matter_format: FrontMatterFormat::Json,
+        base_url: Some("https://example.com/page".to_string()),
+        ..Default::default()
+    };
+    let converter = MarkdownConverter::with_options(options);
+    let result = converter.convert(&dom).expect("Conversion failed");
+
+    assert!(result.contains("\"title\": \"Minimal\""));
+    assert!(!result.contains("\"description\":"));
+    assert!(!result.contains("\"author\":"));
+}
+
+/// Test JSON front matter block structure (blank line after closing delimiter)
+#[test]
+fn test_json_front_matter_format() {
+    let html = b"<html><head>
+        <title>Format Test</title>
+        <link rel=\"canonical\" href=\"https://example.com/page\">
+    </head><body><p>Content</p></body></html>";
+    let dom = parse_html(html).expect("Parse failed");
+
+    let options = ConversionOptions {
+        include_front_matter: true,
+        extract_metadata: true,
+        front_matter_format: FrontMatterFormat::Json,
+        base_url: Some("https://example.com/page".to_string()),
+        ..Default::default()
+    };
+    let converter = MarkdownConverter::with_options(options);
+    let result = converter.convert(&dom).expect("Conversion failed");
+
+    let lines: Vec<&str> = result.lines().collect();
+    assert_eq!(lines[0], "---json");
+
+    let closing_idx = lines
+        .iter()
+        .skip(1)
+        .position(|&line| line == "---")
+        .unwrap()
+        + 1;
+    assert!(closing_idx > 0);
+
+    if closing_idx + 1 < lines.len() {
+        assert_eq!(lines[closing_idx + 1], "");
+    }
+}
+
+/// Test the default front matter format is still YAML (backward compatibility)
+#[test]
+fn test_front_matter_format_defaults_to_yaml() {
+    let html = b"<html><head><title>Test</title></head><body><p>Content</p></body></html>";
+    let dom = parse_html(html).expect("Parse failed");
+
+    let options = ConversionOptions {
+        include_front_matter: true,
+        extract_metadata: true,
+        ..Default::default()
+    };
+    assert_eq!(options.front_matter_format, FrontMatterFormat::Yaml);
+
+    let converter = MarkdownConverter::with_options(options);
+    let result = converter.convert(&dom).expect("Conversion failed");
+
+    assert!(result.starts_with("---\n"));
+    assert!(result.contains("title: \"Test\""));
+}
@@ -60,6 +60,7 @@
             <meta property=\"og:image\" content=\"https://example.com/image.png\">
             <meta name=\"author\" content=\"John Doe\">
             <meta property=\"article:published_time\" content=\"2024-01-15\">
+            <meta property=\"article:modified_time\" content=\"2024-02-01\">
             <link rel=\"canonical\" href=\"https://example.com/complete\">
         </head><body><p>Content</p></body></html>";
         let dom = parse_html(html).expect("Parse failed");
@@ -80,6 +81,7 @@
         assert!(result.contains("image: \"https://example.com/image.png\""));
         assert!(result.contains("author: \"John Doe\""));
         assert!(result.contains("published: \"2024-01-15\""));
+        assert!(result.contains("modified: \"2024-02-01\""));
     }
 
     /// Test YAML front matter with special characters requiring escaping
@@ -384,7 +386,8 @@
                         || line.starts_with("description: ")
                         || line.starts_with("image: ")
                         || line.starts_with("author: ")
-                        || line.starts_with("published: "),
+                        || line.starts_with("published: ")
+                        || line.starts_with("modified: "),
                     "Unexpected front matter key line: {line}"
                 );
                 prop_assert!(line.contains(": \""), "Front matter values must use quoted YAML strings: {line}");